@@ -206,14 +206,20 @@ async fn main() -> anyhow::Result<()> {
 
     let mut queries_task = {
         let config = Arc::new(cli.api_config);
+        let schema_cache = Arc::new(graphql_api::schema_cache::ModuleSchemaCache::new(
+            config.schema_cache_capacity,
+            registry.sub_registry_with_prefix("schema_cache"),
+        ));
         let graphql_service = graphql_api::Service::new(
             subscription,
             &mut registry,
             pool.clone(),
             config.clone(),
             nodes_status_receiver,
+            schema_cache.clone(),
         );
-        let rest_service = rest_api::Service::new(pool.clone(), config, &mut registry);
+        let rest_service =
+            rest_api::Service::new(pool.clone(), config, schema_cache, &mut registry);
         let tcp_listener =
             TcpListener::bind(cli.listen).await.context("Parsing TCP listener address failed")?;
         let stop_signal = cancel_token.child_token();
@@ -1,5 +1,6 @@
 use async_graphql::connection::{self, CursorType};
-use std::cmp::min;
+use futures::{Stream, TryStreamExt};
+use std::cmp::{max, min};
 
 use crate::graphql_api::{ApiError, ApiResult};
 
@@ -55,6 +56,29 @@ pub fn connection_from_slice<T: AsRef<[A]>, A: async_graphql::OutputType + Clone
     Ok(connection)
 }
 
+/// Construct a GraphQL Cursor Connection response from a stream of database
+/// rows, where the cursor is the ID of the row.
+/// Also returns the smallest and the largest ID in the page, or `None` if the
+/// page is empty, for determining whether there are previous and next pages.
+pub async fn connection_from_id_stream<A: async_graphql::OutputType>(
+    mut rows: impl Stream<Item = Result<A, sqlx::Error>> + Unpin,
+    id: impl Fn(&A) -> i64,
+) -> ApiResult<(connection::Connection<String, A>, Option<(i64, i64)>)> {
+    let mut connection = connection::Connection::new(false, false);
+    let mut page_ids: Option<(i64, i64)> = None;
+    while let Some(row) = rows.try_next().await? {
+        let row_id = id(&row);
+        page_ids = Some(match page_ids {
+            None => (row_id, row_id),
+            Some((page_min_id, page_max_id)) => {
+                (min(page_min_id, row_id), max(page_max_id, row_id))
+            }
+        });
+        connection.edges.push(connection::Edge::new(row_id.to_string(), row));
+    }
+    Ok((connection, page_ids))
+}
+
 /// Bounds for the Cursor in a GraphQL Cursor Connection, used as the fallback
 /// when no explicit range is provided as `after`/`before`.
 pub trait ConnectionBounds {
@@ -2,24 +2,42 @@
 //! - Enable GraphiQL through flag instead of always.
 
 mod account;
+mod account_credential;
 mod account_metrics;
+mod account_portfolio;
 mod baker;
 mod baker_and_delegator_types;
 mod baker_metrics;
 mod block;
 mod block_metrics;
+mod chain_update;
+mod cis2_balance_change;
 mod contract;
+mod contract_call;
+mod contract_upgrade;
+mod credential_registry;
 mod db;
+mod governance;
+mod identity_registry;
 mod module_reference_event;
 pub mod node_status;
 mod passive_delegation;
 mod plt;
+mod plt_account_statement;
+mod plt_token_list;
+mod plt_token_metrics;
 mod plt_transfer_metrics;
+mod price;
 mod reward_metrics;
+pub mod schema_cache;
 mod search_result;
+mod smart_contract_wallet;
+mod sponsored_action;
 mod stable_coin;
 mod suspended_validators;
 mod token;
+mod token_collection;
+mod token_distribution;
 mod transaction;
 mod transaction_metrics;
 
@@ -38,6 +56,7 @@ use async_graphql::{
     SimpleObject, Subscription, Union,
 };
 use async_graphql_axum::GraphQLSubscription;
+use bigdecimal::BigDecimal;
 use block::Block;
 
 use chrono::{Duration, TimeDelta, Utc};
@@ -64,6 +83,14 @@ pub struct ApiServiceConfig {
     /// Account(s) that should not be considered in circulation.
     #[arg(long, env = "CCDSCAN_API_CONFIG_NON_CIRCULATING_ACCOUNTS", value_delimiter = ',')]
     pub non_circulating_account: Vec<sdk_types::AccountAddress>,
+    /// Account(s) such as the treasury of an issuer or bridges, marked and
+    /// left out of the holder distribution statistics of tokens.
+    #[arg(
+        long,
+        env = "CCDSCAN_API_CONFIG_TOKEN_DISTRIBUTION_EXCLUDED_ACCOUNTS",
+        value_delimiter = ','
+    )]
+    token_distribution_excluded_account: Vec<sdk_types::AccountAddress>,
     /// The most transactions which can be queried at once.
     #[arg(long, env = "CCDSCAN_API_CONFIG_TRANSACTION_CONNECTION_LIMIT", default_value = "100")]
     transaction_connection_limit: u64,
@@ -176,6 +203,41 @@ pub struct ApiServiceConfig {
         default_value = "100"
     )]
     plt_account_amount_connection_limit: u64,
+    /// The most parsed module schemas kept in memory for decoding contract
+    /// parameters and events.
+    #[arg(long, env = "CCDSCAN_API_CONFIG_SCHEMA_CACHE_CAPACITY", default_value = "1000")]
+    pub schema_cache_capacity: usize,
+    /// Bearer token authorizing uploads of module schemas through the REST
+    /// API. Uploads are disabled when not provided.
+    #[arg(long, env = "CCDSCAN_API_SCHEMA_UPLOAD_TOKEN")]
+    pub schema_upload_token: Option<String>,
+    /// Bearer token authorizing verifications of module sources through the
+    /// REST API. Verifications are disabled when not provided.
+    #[arg(long, env = "CCDSCAN_API_MODULE_VERIFICATION_TOKEN")]
+    pub module_verification_token: Option<String>,
+    /// Hex encoded Ed25519 public keys of the attesters trusted to attest
+    /// module sources.
+    #[arg(long, env = "CCDSCAN_API_MODULE_VERIFICATION_ATTESTERS", value_delimiter = ',')]
+    pub module_verification_attesters: Vec<String>,
+    /// Docker executable used for building module sources when verifying
+    /// without an attestation. Local builds are disabled when not provided.
+    #[arg(long, env = "CCDSCAN_API_MODULE_VERIFICATION_DOCKER")]
+    pub module_verification_docker: Option<std::path::PathBuf>,
+    /// Docker images allowed for building module sources, where a trailing
+    /// `*` matches any suffix. Images are never pulled by the builds, so they
+    /// must be available locally.
+    #[arg(
+        long,
+        env = "CCDSCAN_API_MODULE_VERIFICATION_IMAGES",
+        value_delimiter = ',',
+        default_value = "docker.io/concordium/verifiable-sc:*"
+    )]
+    pub module_verification_images: Vec<String>,
+    /// Protocol level tokens considered stablecoins, each provided as
+    /// `TOKEN_ID:CURRENCY` with the code of the fiat currency the token is
+    /// pegged to, for instance `EURR:EUR`.
+    #[arg(long = "stablecoin", env = "CCDSCAN_API_CONFIG_STABLECOINS", value_delimiter = ',')]
+    stablecoins: Vec<stable_coin::StableCoinPeg>,
 }
 
 #[derive(MergedObject, Default)]
@@ -186,12 +248,14 @@ pub struct Query(
     baker::QueryBaker,
     block::QueryBlocks,
     stable_coin::QueryStableCoins,
+    price::QueryPrices,
     transaction::QueryTransactions,
     account::QueryAccounts,
     module_reference_event::QueryModuleReferenceEvent,
     contract::QueryContract,
     node_status::QueryNodeStatus,
     token::QueryToken,
+    token_collection::QueryTokenCollection,
     account_metrics::QueryAccountMetrics,
     baker_metrics::QueryBakerMetrics,
     reward_metrics::QueryRewardMetrics,
@@ -199,9 +263,16 @@ pub struct Query(
     transaction_metrics::QueryTransactionMetrics,
     plt_transfer_metrics::QueryGlobalPltMetrics,
     plt_transfer_metrics::QueryPltTransferMetricsByTokenId,
+    plt_token_metrics::QueryPltTokenMetrics,
     plt::QueryPltEvent,
     plt::QueryPlt,
     plt::QueryPltAccountAmount,
+    chain_update::QueryChainUpdates,
+    governance::QueryGovernance,
+    identity_registry::QueryIdentityRegistry,
+    account_credential::QueryAccountCredentials,
+    credential_registry::QueryCredentialRegistries,
+    smart_contract_wallet::QuerySmartContractWallets,
 );
 
 pub struct Service {
@@ -219,6 +290,7 @@ impl Service {
         pool: PgPool,
         config: Arc<ApiServiceConfig>,
         receiver: Receiver<Option<Vec<NodeStatus>>>,
+        schema_cache: Arc<schema_cache::ModuleSchemaCache>,
     ) -> Self {
         let schema = Schema::build(Query::default(), EmptyMutation, subscription)
             .extension(async_graphql::extensions::Tracing)
@@ -227,6 +299,7 @@ impl Service {
             .data(receiver)
             .data(pool)
             .data(config)
+            .data(schema_cache)
             .finish();
         Self {
             schema,
@@ -460,6 +533,8 @@ pub enum InternalError {
     NoDatabasePool(async_graphql::Error),
     #[error("Internal error (NoServiceConfig): {}", .0.message)]
     NoServiceConfig(async_graphql::Error),
+    #[error("Internal error (NoSchemaCache): {}", .0.message)]
+    NoSchemaCache(async_graphql::Error),
     #[error("Internal error: {}", .0.message)]
     NoReceiver(async_graphql::Error),
     #[error("Internal error (FailedDatabaseQuery): {0}")]
@@ -487,6 +562,8 @@ pub enum ApiError {
     DurationOutOfRange(Arc<Box<dyn Error + Send + Sync>>),
     #[error("The \"first\" and \"last\" parameters cannot exist at the same time")]
     QueryConnectionFirstLast,
+    #[error("Exactly one of the \"height\" and \"time\" parameters must be given")]
+    QueryHeightTime,
     #[error("Internal server error")]
     InternalServerError(#[from] InternalError),
     #[error("Invalid integer: {0}")]
@@ -499,6 +576,8 @@ pub enum ApiError {
     Unavailable(String),
     #[error("Invalid ID format: {0}")]
     InvalidIdFormat(String),
+    #[error("Invalid token quantity: {0}")]
+    InvalidTokenQuantity(f64),
 }
 
 impl From<sqlx::Error> for InternalError {
@@ -526,6 +605,19 @@ pub fn get_config<'a>(ctx: &Context<'a>) -> ApiResult<&'a ApiServiceConfig> {
     Ok(config.as_ref())
 }
 
+/// Get the cache of parsed module schemas from the context.
+pub fn get_schema_cache<'a>(ctx: &Context<'a>) -> ApiResult<&'a schema_cache::ModuleSchemaCache> {
+    let cache =
+        ctx.data::<Arc<schema_cache::ModuleSchemaCache>>().map_err(InternalError::NoSchemaCache)?;
+    Ok(cache.as_ref())
+}
+
+/// Convert an amount in the smallest unit of a token into whole tokens.
+pub fn token_units(amount: &BigDecimal, decimals: i32) -> BigDecimal {
+    let (value, _) = amount.with_scale(0).into_bigint_and_exponent();
+    BigDecimal::new(value, i64::from(decimals))
+}
+
 #[derive(Default)]
 struct BaseQuery;
 
@@ -948,6 +1040,73 @@ pub enum AccountStatementEntryType {
     TransactionFeeReward,
 }
 
+/// The kind of change to the balance of a protocol level token for an
+/// account.
+#[derive(Debug, Enum, Clone, Copy, Display, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "plt_account_statement_entry_type")]
+pub enum PltAccountStatementEntryType {
+    Mint,
+    Burn,
+    TransferIn,
+    TransferOut,
+}
+
+/// The kind of change to the balance of a CIS-2 token for an account.
+#[derive(Debug, Enum, Clone, Copy, Display, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "cis2_balance_change_type")]
+pub enum Cis2BalanceChangeType {
+    Mint,
+    Burn,
+    TransferIn,
+    TransferOut,
+}
+
+/// List of accounts maintained by the token module of a protocol level token.
+#[derive(Debug, Enum, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "plt_token_list")]
+pub enum PltTokenList {
+    Allow,
+    Deny,
+}
+
+/// The kind of change to a credential in a CIS-4 credential registry.
+#[derive(Debug, Enum, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "cis4_credential_event_type")]
+pub enum Cis4CredentialEventType {
+    Registered,
+    Revoked,
+    /// A revoked credential got restored, which is not part of the CIS-4
+    /// standard, but logged by the credential registry provided by
+    /// Concordium.
+    Restored,
+    MetadataUpdated,
+}
+
+/// The kind of change to the balance of a public key in a CIS-5 smart contract
+/// wallet.
+#[derive(Debug, Enum, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "cis5_balance_change_type")]
+pub enum Cis5BalanceChangeType {
+    /// Funds deposited to the public key from an account or contract.
+    Deposit,
+    /// Funds withdrawn from the public key to an account or contract.
+    Withdraw,
+    /// Funds transferred to the public key from another public key.
+    TransferIn,
+    /// Funds transferred from the public key to another public key.
+    TransferOut,
+}
+
+/// How the source code of a smart contract module was verified.
+#[derive(Debug, Enum, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "module_verification_method")]
+pub enum ModuleVerificationMethod {
+    /// The source archive was built by this service, producing the module.
+    LocalBuild,
+    /// The source archive was attested by a trusted attester.
+    Attestation,
+}
+
 /// A sort direction, either ascending or descending.
 #[derive(Debug, Clone, Copy)]
 enum OrderDir {
@@ -1,6 +1,9 @@
 use super::{
-    baker::Baker, get_config, get_pool, token::AccountToken, AccountStatementEntryType, ApiError,
-    ApiResult, ConnectionQuery, InternalError, OrderDir,
+    account_credential::AccountCredential, account_portfolio::AccountPortfolio, baker::Baker,
+    cis2_balance_change::Cis2BalanceChange, get_config, get_pool,
+    identity_registry::IdentityProvider, plt_account_statement::PltAccountStatementEntry,
+    plt_token_list::PltTokenListMembership, sponsored_action::SponsoredAction, token::AccountToken,
+    AccountStatementEntryType, ApiError, ApiResult, ConnectionQuery, InternalError, OrderDir,
 };
 use crate::{
     address::AccountAddress,
@@ -1227,11 +1230,142 @@ impl Account {
         Ok(connection)
     }
 
+    /// The CCD, CIS-2 token and protocol level token balances of the account.
+    async fn portfolio(&self, ctx: &Context<'_>) -> ApiResult<AccountPortfolio> {
+        AccountPortfolio::query(get_pool(ctx)?, get_config(ctx)?, self.index).await
+    }
+
     async fn release_schedule(&self) -> AccountReleaseSchedule {
         AccountReleaseSchedule {
             account_index: self.index,
         }
     }
+
+    /// The identity provider which issued the identity behind the credential
+    /// creating the account.
+    async fn identity_provider(&self, ctx: &Context<'_>) -> ApiResult<Option<IdentityProvider>> {
+        let pool = get_pool(ctx)?;
+        let identity_provider_id = sqlx::query_scalar!(
+            "SELECT identity_provider_id
+            FROM account_credentials
+            WHERE account_index = $1 AND credential_index = 0",
+            self.index
+        )
+        .fetch_optional(pool)
+        .await?;
+        let Some(identity_provider_id) = identity_provider_id else {
+            return Ok(None);
+        };
+        IdentityProvider::query_by_id(pool, identity_provider_id).await
+    }
+
+    /// Number of credentials needed to sign on behalf of the account.
+    async fn signature_threshold(&self, ctx: &Context<'_>) -> ApiResult<i16> {
+        let threshold = sqlx::query_scalar!(
+            "SELECT signature_threshold FROM accounts WHERE index = $1",
+            self.index
+        )
+        .fetch_one(get_pool(ctx)?)
+        .await?;
+        Ok(threshold)
+    }
+
+    /// The current credentials of the account, ordered by credential index.
+    async fn credentials(&self, ctx: &Context<'_>) -> ApiResult<Vec<AccountCredential>> {
+        AccountCredential::query_by_account(get_pool(ctx)?, self.index).await
+    }
+
+    /// The CIS-3 sponsored actions signed by the account and paid for by a
+    /// sponsor, with the most recent action first.
+    async fn sponsored_actions(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Returns the first _n_ elements from the list.")] first: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come after the specified cursor.")]
+        after: Option<String>,
+        #[graphql(desc = "Returns the last _n_ elements from the list.")] last: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come before the specified cursor.")]
+        before: Option<String>,
+    ) -> ApiResult<connection::Connection<String, SponsoredAction>> {
+        let config = get_config(ctx)?;
+        let query = ConnectionQuery::<DescendingI64>::new(
+            first,
+            after,
+            last,
+            before,
+            config.transaction_connection_limit,
+        )?;
+        SponsoredAction::query_by_signer(get_pool(ctx)?, self.index, query).await
+    }
+
+    /// The allow and deny lists of protocol level tokens the account is
+    /// currently on.
+    async fn plt_list_memberships(
+        &self,
+        ctx: &Context<'_>,
+    ) -> ApiResult<Vec<PltTokenListMembership>> {
+        PltTokenListMembership::query_by_account(get_pool(ctx)?, self.index).await
+    }
+
+    /// The changes to the balances of protocol level tokens held by the
+    /// account, optionally only for one token.
+    async fn plt_account_statement(
+        &self,
+        ctx: &Context<'_>,
+        token_id: Option<String>,
+        #[graphql(desc = "Returns the first _n_ elements from the list.")] first: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come after the specified cursor.")]
+        after: Option<String>,
+        #[graphql(desc = "Returns the last _n_ elements from the list.")] last: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come before the specified cursor.")]
+        before: Option<String>,
+    ) -> ApiResult<connection::Connection<String, PltAccountStatementEntry>> {
+        let config = get_config(ctx)?;
+        let query = ConnectionQuery::<DescendingI64>::new(
+            first,
+            after,
+            last,
+            before,
+            config.account_statements_connection_limit,
+        )?;
+        PltAccountStatementEntry::query_by_account(
+            get_pool(ctx)?,
+            self.index,
+            token_id.as_deref(),
+            query,
+        )
+        .await
+    }
+
+    /// The changes to the balances of CIS-2 tokens held by the account,
+    /// optionally only for one token.
+    async fn token_balance_history(
+        &self,
+        ctx: &Context<'_>,
+        token_address: Option<String>,
+        #[graphql(desc = "Returns the first _n_ elements from the list.")] first: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come after the specified cursor.")]
+        after: Option<String>,
+        #[graphql(desc = "Returns the last _n_ elements from the list.")] last: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come before the specified cursor.")]
+        before: Option<String>,
+    ) -> ApiResult<connection::Connection<String, Cis2BalanceChange>> {
+        let config = get_config(ctx)?;
+        let query = ConnectionQuery::<DescendingI64>::new(
+            first,
+            after,
+            last,
+            before,
+            config.account_statements_connection_limit,
+        )?;
+        Cis2BalanceChange::query_by_account(
+            get_pool(ctx)?,
+            self.index,
+            token_address.as_deref(),
+            query,
+        )
+        .await
+    }
 }
 
 struct AccountReleaseSchedule {
@@ -0,0 +1,200 @@
+use super::{
+    account::Account,
+    get_config, get_pool,
+    identity_registry::{AnonymityRevoker, IdentityProvider},
+    ApiError, ApiResult, InternalError,
+};
+use crate::{
+    connection::{connection_from_id_stream, ConnectionQuery},
+    scalar_types::DateTime,
+};
+use async_graphql::{connection, ComplexObject, Context, Object, SimpleObject};
+use chrono::{Months, NaiveDate, Utc};
+use sqlx::PgPool;
+
+#[derive(Default)]
+pub struct QueryAccountCredentials;
+
+#[Object]
+impl QueryAccountCredentials {
+    /// Accounts holding a credential which expires within the provided
+    /// period, ordered by account index.
+    #[allow(clippy::too_many_arguments)]
+    async fn accounts_with_expiring_credentials(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Start of the period, defaults to the current time.")]
+        expires_after: Option<DateTime>,
+        #[graphql(desc = "End of the period.")] expires_before: DateTime,
+        #[graphql(desc = "Returns the first _n_ elements from the list.")] first: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come after the specified cursor.")]
+        after: Option<String>,
+        #[graphql(desc = "Returns the last _n_ elements from the list.")] last: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come before the specified cursor.")]
+        before: Option<String>,
+    ) -> ApiResult<connection::Connection<String, Account>> {
+        let config = get_config(ctx)?;
+        let pool = get_pool(ctx)?;
+        let query = ConnectionQuery::<i64>::new(
+            first,
+            after,
+            last,
+            before,
+            config.account_connection_limit,
+        )?;
+        let expires_after = expires_after.unwrap_or_else(Utc::now);
+
+        // A credential expires at the start of the month following `valid_to`, so
+        // the period is translated into a range of `valid_to` months to make use of
+        // the index.
+        let row_stream = sqlx::query_as!(
+            Account,
+            "SELECT * FROM (
+                SELECT
+                    index,
+                    transaction_index,
+                    address,
+                    amount,
+                    delegated_stake,
+                    num_txs,
+                    delegated_restake_earnings,
+                    delegated_target_baker_id
+                FROM accounts
+                WHERE EXISTS (
+                        SELECT FROM account_credentials
+                        WHERE account_credentials.account_index = accounts.index
+                            AND valid_to >= date_trunc('month', $5 AT TIME ZONE 'UTC')::DATE
+                            AND valid_to <= (date_trunc('month', $6 AT TIME ZONE 'UTC')
+                                - INTERVAL '1 month')::DATE
+                    )
+                    AND index > $1 AND index < $2
+                ORDER BY
+                    (CASE WHEN $3 THEN index END) DESC,
+                    (CASE WHEN NOT $3 THEN index END) ASC
+                LIMIT $4
+            ) ORDER BY index ASC",
+            query.from,
+            query.to,
+            query.is_last,
+            query.limit,
+            expires_after,
+            expires_before
+        )
+        .fetch(pool);
+
+        let (mut connection, page_indices) =
+            connection_from_id_stream(row_stream, |account| account.index).await?;
+        if let Some((page_min_id, page_max_id)) = page_indices {
+            let result = sqlx::query!(
+                "SELECT MAX(account_index) as max_id, MIN(account_index) as min_id
+                FROM account_credentials
+                WHERE valid_to >= date_trunc('month', $1 AT TIME ZONE 'UTC')::DATE
+                    AND valid_to <= (date_trunc('month', $2 AT TIME ZONE 'UTC')
+                        - INTERVAL '1 month')::DATE",
+                expires_after,
+                expires_before
+            )
+            .fetch_one(pool)
+            .await?;
+            connection.has_previous_page = result.min_id.is_some_and(|db_min| db_min < page_min_id);
+            connection.has_next_page = result.max_id.is_some_and(|db_max| db_max > page_max_id);
+        }
+        Ok(connection)
+    }
+}
+
+/// A credential of an account, allowing the holder of the credential to sign
+/// transactions on behalf of the account.
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct AccountCredential {
+    /// The hex encoded credential registration ID.
+    credential_registration_id: String,
+    /// Index of the credential on the account. The credential creating the
+    /// account has index 0.
+    credential_index: i16,
+    /// Whether this is an initial credential, which is created by the
+    /// identity provider and has no anonymity revokers.
+    is_initial: bool,
+    /// Number of anonymity revokers needed to reveal the identity behind the
+    /// credential. Null for initial credentials.
+    anonymity_revoker_threshold: Option<i16>,
+    /// Number of keys needed to sign on behalf of the credential.
+    key_threshold: i16,
+    /// Hex encoded Ed25519 verify keys of the credential, ordered by key
+    /// index.
+    keys: Vec<String>,
+    #[graphql(skip)]
+    identity_provider_id: i64,
+    #[graphql(skip)]
+    anonymity_revoker_ids: Vec<i64>,
+    #[graphql(skip)]
+    valid_from: NaiveDate,
+    #[graphql(skip)]
+    valid_to: NaiveDate,
+}
+
+impl AccountCredential {
+    /// The current credentials of an account, ordered by credential index.
+    pub async fn query_by_account(pool: &PgPool, account_index: i64) -> ApiResult<Vec<Self>> {
+        let credentials = sqlx::query_as!(
+            AccountCredential,
+            "SELECT
+                credential_registration_id,
+                credential_index,
+                is_initial,
+                anonymity_revoker_threshold,
+                key_threshold,
+                keys,
+                identity_provider_id,
+                anonymity_revoker_ids,
+                valid_from,
+                valid_to
+            FROM account_credentials
+            WHERE account_index = $1
+            ORDER BY credential_index ASC",
+            account_index
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(credentials)
+    }
+}
+
+#[ComplexObject]
+impl AccountCredential {
+    /// The identity provider which issued the identity behind the credential.
+    async fn identity_provider(&self, ctx: &Context<'_>) -> ApiResult<IdentityProvider> {
+        IdentityProvider::query_by_id(get_pool(ctx)?, self.identity_provider_id)
+            .await?
+            .ok_or(ApiError::NotFound)
+    }
+
+    /// The anonymity revokers able to reveal the identity behind the
+    /// credential. Empty for initial credentials.
+    async fn anonymity_revokers(&self, ctx: &Context<'_>) -> ApiResult<Vec<AnonymityRevoker>> {
+        AnonymityRevoker::query_by_ids(get_pool(ctx)?, &self.anonymity_revoker_ids).await
+    }
+
+    /// Start of the month where the credential got created.
+    async fn valid_from(&self) -> ApiResult<DateTime> { start_of_day(self.valid_from) }
+
+    /// The time where the credential expires, which is the end of the last
+    /// month where the credential is valid.
+    async fn expiry_time(&self) -> ApiResult<DateTime> {
+        let expiry = self.valid_to.checked_add_months(Months::new(1)).ok_or_else(|| {
+            ApiError::from(InternalError::InternalError(format!(
+                "Credential validity out of range: {}",
+                self.valid_to
+            )))
+        })?;
+        start_of_day(expiry)
+    }
+}
+
+/// Midnight UTC at the provided date.
+fn start_of_day(date: NaiveDate) -> ApiResult<DateTime> {
+    date.and_hms_opt(0, 0, 0).map(|time| time.and_utc()).ok_or_else(|| {
+        InternalError::InternalError(format!("Invalid credential validity date: {}", date)).into()
+    })
+}
@@ -0,0 +1,235 @@
+//! The portfolio of an account, combining the CCD, CIS-2 token and protocol
+//! level token (PLT) balances of the account in a single object.
+//!
+//! Balances are normalized using the decimals of each asset: CCD has 6
+//! decimals, PLTs have the decimals of the token and CIS-2 tokens have the
+//! decimals from their metadata, when fetched by the indexer. Values in US
+//! dollars are given for the assets with a price recorded by the indexer and
+//! for the PLTs registered as stablecoins.
+
+use super::{price, stable_coin::whole_tokens, ApiResult, ApiServiceConfig};
+use crate::scalar_types::{Amount, BigInteger};
+use async_graphql::SimpleObject;
+use bigdecimal::BigDecimal;
+use sqlx::PgPool;
+
+/// Number of decimals of CCD amounts in microCCD.
+const CCD_DECIMALS: i32 = 6;
+
+/// The balances of every asset held by an account.
+#[derive(SimpleObject)]
+pub struct AccountPortfolio {
+    ccd:                  CcdPortfolioBalance,
+    /// The CIS-2 tokens with a non-zero balance, most recently changed first.
+    cis2_tokens:          Vec<Cis2PortfolioBalance>,
+    /// Whether the account holds more CIS-2 tokens than included in
+    /// `cis2Tokens`, which is limited by the configuration of the API.
+    has_more_cis2_tokens: bool,
+    /// The protocol level tokens with a non-zero balance.
+    plt_tokens:           Vec<PltPortfolioBalance>,
+}
+
+/// The CCD balance of an account, split by what the CCD can be used for.
+#[derive(SimpleObject)]
+pub struct CcdPortfolioBalance {
+    /// The total amount of CCD held by the account, in microCCD.
+    total:           Amount,
+    /// The amount of CCD which can be transferred, in microCCD. Locked and
+    /// staked CCD overlap, since the account can stake CCD which are locked.
+    spendable:       Amount,
+    /// The amount of CCD locked in release schedules, in microCCD.
+    locked:          Amount,
+    /// The amount of CCD staked as a validator or delegator, in microCCD.
+    staked:          Amount,
+    /// The amount of CCD removed from the stake and in cooldown until
+    /// released at the first payday after the end of the cooldown, in
+    /// microCCD. Stake in cooldown counts as staked for `spendable`.
+    in_cooldown:     Amount,
+    /// The total amount in whole CCD.
+    balance:         f64,
+    /// The value of the total amount in US dollars, `null` when unknown.
+    value_in_dollar: Option<f64>,
+}
+
+/// The balance of a CIS-2 token held by an account.
+#[derive(SimpleObject)]
+pub struct Cis2PortfolioBalance {
+    token_address:      String,
+    contract_index:     i64,
+    contract_sub_index: i64,
+    token_id:           String,
+    /// Name of the token from its metadata.
+    name:               Option<String>,
+    /// Symbol of the token from its metadata.
+    symbol:             Option<String>,
+    /// The balance in the smallest unit of the token.
+    raw_balance:        BigInteger,
+    /// Decimals of the token from its metadata, `null` when the metadata is
+    /// not fetched or does not specify the decimals.
+    decimals:           Option<i32>,
+    /// The balance in whole units of the token, using zero decimals when the
+    /// decimals are unknown.
+    balance:            f64,
+    /// The value of the balance in US dollars, `null` when unknown.
+    value_in_dollar:    Option<f64>,
+}
+
+/// The balance of a protocol level token held by an account.
+#[derive(SimpleObject)]
+pub struct PltPortfolioBalance {
+    token_id:        String,
+    name:            String,
+    /// The balance in the smallest unit of the token.
+    raw_balance:     BigInteger,
+    decimals:        i32,
+    /// The balance in whole units of the token.
+    balance:         f64,
+    /// The value of the balance in US dollars, `null` when unknown.
+    value_in_dollar: Option<f64>,
+}
+
+impl AccountPortfolio {
+    pub async fn query(
+        pool: &PgPool,
+        config: &ApiServiceConfig,
+        account_index: i64,
+    ) -> ApiResult<Self> {
+        let ccd = sqlx::query!(
+            r#"SELECT
+                accounts.amount,
+                COALESCE(bakers.staked, accounts.delegated_stake) AS "staked!",
+                (SELECT SUM(amount)::BIGINT
+                    FROM scheduled_releases
+                    WHERE account_index = accounts.index
+                        AND release_time > NOW()
+                ) AS locked,
+                (SELECT SUM(amount)::BIGINT
+                    FROM account_cooldowns
+                    WHERE account_index = accounts.index
+                        AND end_time > NOW()
+                ) AS in_cooldown
+            FROM accounts
+                LEFT JOIN bakers ON bakers.id = accounts.index
+            WHERE accounts.index = $1"#,
+            account_index
+        )
+        .fetch_one(pool)
+        .await?;
+        let locked = ccd.locked.unwrap_or(0);
+        let in_cooldown = ccd.in_cooldown.unwrap_or(0);
+        let spendable = (ccd.amount - locked.max(ccd.staked + in_cooldown)).max(0);
+        let balance = whole_tokens(&BigDecimal::from(ccd.amount), CCD_DECIMALS);
+
+        let limit = i64::try_from(config.contract_connection_limit)?;
+        let mut cis2_rows = sqlx::query!(
+            r#"SELECT
+                tokens.token_address,
+                tokens.contract_index,
+                tokens.contract_sub_index,
+                tokens.token_id,
+                tokens.metadata ->> 'name' AS name,
+                tokens.metadata ->> 'symbol' AS symbol,
+                tokens.metadata -> 'decimals' AS decimals,
+                account_tokens.balance
+            FROM account_tokens
+                JOIN tokens ON tokens.index = account_tokens.token_index
+            WHERE account_tokens.account_index = $1
+                AND account_tokens.balance != 0
+            ORDER BY account_tokens.change_seq DESC
+            LIMIT $2"#,
+            account_index,
+            limit + 1
+        )
+        .fetch_all(pool)
+        .await?;
+        let has_more_cis2_tokens = cis2_rows.len() as i64 > limit;
+        cis2_rows.truncate(usize::try_from(limit)?);
+
+        let plt_rows = sqlx::query!(
+            r#"SELECT
+                plt_tokens.token_id,
+                plt_tokens.name,
+                plt_tokens.decimal,
+                plt_accounts.amount AS "amount!"
+            FROM plt_accounts
+                JOIN plt_tokens ON plt_tokens.index = plt_accounts.token_index
+            WHERE plt_accounts.account_index = $1
+                AND plt_accounts.amount > 0
+            ORDER BY plt_tokens.index"#,
+            account_index
+        )
+        .fetch_all(pool)
+        .await?;
+
+        // CIS-2 tokens are priced by their token address.
+        let mut assets = vec!["CCD".to_string()];
+        assets.extend(cis2_rows.iter().map(|row| row.token_address.clone()));
+        assets.extend(plt_rows.iter().map(|row| row.token_id.clone()));
+        assets.extend(config.stablecoins.iter().map(|peg| peg.currency.clone()));
+        let prices = price::latest_dollar_prices(pool, &assets).await?;
+
+        let cis2_tokens = cis2_rows
+            .into_iter()
+            .map(|row| {
+                let decimals = row
+                    .decimals
+                    .and_then(|decimals| decimals.as_u64())
+                    .and_then(|decimals| i32::try_from(decimals).ok());
+                let balance = whole_tokens(&row.balance, decimals.unwrap_or(0));
+                // Prices are per whole token, which is only known with the decimals.
+                let value_in_dollar = decimals
+                    .and_then(|_| price::dollar_value(&prices, &row.token_address, None))
+                    .map(|value| value * balance);
+                Cis2PortfolioBalance {
+                    token_address: row.token_address,
+                    contract_index: row.contract_index,
+                    contract_sub_index: row.contract_sub_index,
+                    token_id: row.token_id,
+                    name: row.name,
+                    symbol: row.symbol,
+                    balance,
+                    raw_balance: BigInteger::from(row.balance),
+                    decimals,
+                    value_in_dollar,
+                }
+            })
+            .collect();
+
+        let plt_tokens = plt_rows
+            .into_iter()
+            .map(|row| {
+                let balance = whole_tokens(&row.amount, row.decimal);
+                let value_in_dollar = price::dollar_value(
+                    &prices,
+                    &row.token_id,
+                    config.stablecoin_peg(&row.token_id),
+                )
+                .map(|value| value * balance);
+                PltPortfolioBalance {
+                    token_id: row.token_id,
+                    name: row.name,
+                    raw_balance: BigInteger::from(row.amount),
+                    decimals: row.decimal,
+                    balance,
+                    value_in_dollar,
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            ccd: CcdPortfolioBalance {
+                total: ccd.amount.try_into()?,
+                spendable: spendable.try_into()?,
+                locked: locked.try_into()?,
+                staked: ccd.staked.try_into()?,
+                in_cooldown: in_cooldown.try_into()?,
+                balance,
+                value_in_dollar: price::dollar_value(&prices, "CCD", None)
+                    .map(|value| value * balance),
+            },
+            cis2_tokens,
+            has_more_cis2_tokens,
+            plt_tokens,
+        })
+    }
+}
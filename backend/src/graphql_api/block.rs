@@ -204,6 +204,31 @@ impl Block {
         Ok(result.count.unwrap_or(0))
     }
 
+    /// The transaction fees paid in this block, in CCD and in EUR using the
+    /// MicroCcdPerEuro exchange rate in force at the block.
+    async fn transaction_fees<'a>(&self, ctx: &Context<'a>) -> ApiResult<BlockTransactionFees> {
+        let fees = sqlx::query!(
+            r#"SELECT
+                blocks.cumulative_ccd_fees - COALESCE(parent.cumulative_ccd_fees, 0)
+                    AS "fees!",
+                (blocks.cumulative_euro_fees - COALESCE(parent.cumulative_euro_fees, 0))::FLOAT8
+                    AS "fees_in_euro!",
+                (1000000 * blocks.micro_ccd_per_euro_denominator
+                    / blocks.micro_ccd_per_euro_numerator)::FLOAT8 AS "euro_per_ccd!"
+            FROM blocks
+                LEFT JOIN blocks parent ON parent.height = blocks.height - 1
+            WHERE blocks.height = $1"#,
+            self.height
+        )
+        .fetch_one(get_pool(ctx)?)
+        .await?;
+        Ok(BlockTransactionFees {
+            fees:         fees.fees.try_into()?,
+            fees_in_euro: fees.fees_in_euro,
+            euro_per_ccd: fees.euro_per_ccd,
+        })
+    }
+
     /// Query the special events (aka. special transaction outcomes) associated
     /// with this block.
     async fn special_events(
@@ -385,6 +410,17 @@ impl Block {
     }
 }
 
+#[derive(SimpleObject)]
+struct BlockTransactionFees {
+    /// Sum of the transaction fees of the block, in microCCD.
+    fees:         Amount,
+    /// Sum of the transaction fees of the block in EUR.
+    fees_in_euro: f64,
+    /// The price of one CCD in EUR given by the MicroCcdPerEuro exchange rate
+    /// in force at the block.
+    euro_per_ccd: f64,
+}
+
 #[derive(SimpleObject)]
 struct BlockStatistics {
     /// Number of seconds between block slot time of this block and previous
@@ -0,0 +1,102 @@
+use super::{get_pool, transaction::Transaction, ApiError, ApiResult, InternalError};
+use crate::{
+    connection::connection_from_slice,
+    scalar_types::{DateTime, TransactionIndex, UnsignedLong},
+    transaction_event::chain_update::{ChainUpdatePayload, ChainUpdateState},
+    transaction_type::UpdateTransactionType,
+};
+use async_graphql::{connection, Context, Object};
+
+#[derive(Default)]
+pub struct QueryChainUpdates;
+
+#[Object]
+impl QueryChainUpdates {
+    /// Chain updates which are enqueued, but not yet in force, ordered by the
+    /// time they take effect.
+    async fn upcoming_chain_updates(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Returns the first _n_ elements from the list.")] first: Option<usize>,
+        #[graphql(desc = "Returns the elements in the list that come after the specified cursor.")]
+        after: Option<String>,
+        #[graphql(desc = "Returns the last _n_ elements from the list.")] last: Option<usize>,
+        #[graphql(desc = "Returns the elements in the list that come before the specified cursor.")]
+        before: Option<String>,
+    ) -> ApiResult<connection::Connection<String, ChainUpdate>> {
+        // The queue only contains updates which are yet to take effect, which is
+        // expected to be a handful at any point in time, so everything is fetched.
+        let updates = sqlx::query_as!(
+            ChainUpdate,
+            r#"SELECT
+                transaction_index,
+                update_type as "update_type: UpdateTransactionType",
+                sequence_number,
+                effective_time,
+                enqueued_time,
+                state as "state: ChainUpdateState",
+                superseded_by_transaction_index,
+                payload
+            FROM chain_update_queue
+            WHERE state = 'Queued'
+            ORDER BY effective_time ASC, transaction_index ASC"#
+        )
+        .fetch_all(get_pool(ctx)?)
+        .await?;
+        connection_from_slice(updates, first, after, last, before)
+    }
+}
+
+/// A chain update enqueued by an update instruction.
+#[derive(Clone)]
+pub struct ChainUpdate {
+    transaction_index: TransactionIndex,
+    update_type: UpdateTransactionType,
+    sequence_number: i64,
+    effective_time: DateTime,
+    enqueued_time: DateTime,
+    state: ChainUpdateState,
+    superseded_by_transaction_index: Option<TransactionIndex>,
+    payload: serde_json::Value,
+}
+
+#[Object]
+impl ChainUpdate {
+    /// The update transaction enqueuing the update.
+    async fn transaction(&self, ctx: &Context<'_>) -> ApiResult<Transaction> {
+        Transaction::query_by_index(get_pool(ctx)?, self.transaction_index)
+            .await?
+            .ok_or(ApiError::NotFound)
+    }
+
+    /// The type of update, each type of update has its own queue on chain.
+    async fn update_type(&self) -> UpdateTransactionType { self.update_type }
+
+    /// The update sequence number of the update instruction.
+    async fn sequence_number(&self) -> ApiResult<UnsignedLong> {
+        Ok(self.sequence_number.try_into()?)
+    }
+
+    /// The time at which the update takes effect.
+    async fn effective_time(&self) -> DateTime { self.effective_time }
+
+    /// The slot time of the block where the update got enqueued.
+    async fn enqueued_time(&self) -> DateTime { self.enqueued_time }
+
+    async fn state(&self) -> ChainUpdateState { self.state }
+
+    /// The update transaction which superseded this update, before it took
+    /// effect.
+    async fn superseded_by(&self, ctx: &Context<'_>) -> ApiResult<Option<Transaction>> {
+        let Some(index) = self.superseded_by_transaction_index else {
+            return Ok(None);
+        };
+        Transaction::query_by_index(get_pool(ctx)?, index).await
+    }
+
+    async fn payload(&self) -> ApiResult<ChainUpdatePayload> {
+        serde_json::from_value(self.payload.clone()).map_err(|err| {
+            InternalError::InternalError(format!("Invalid chain update payload: {}", err)).into()
+        })
+    }
+}
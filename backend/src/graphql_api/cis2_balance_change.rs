@@ -0,0 +1,169 @@
+use super::{
+    get_pool, token::Token, transaction::Transaction, ApiError, ApiResult, Cis2BalanceChangeType,
+    ConnectionQuery,
+};
+use crate::{
+    connection::{connection_from_id_stream, DescendingI64},
+    scalar_types::{BigInteger, BlockHeight, DateTime, TransactionIndex},
+};
+use async_graphql::{connection, types, ComplexObject, Context, SimpleObject};
+use bigdecimal::BigDecimal;
+use sqlx::PgPool;
+
+/// A change to the balance of a CIS-2 token for an account, caused by a mint,
+/// burn or transfer event.
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct Cis2BalanceChange {
+    #[graphql(skip)]
+    id:                 i64,
+    token_address:      String,
+    #[graphql(skip)]
+    token_id:           String,
+    #[graphql(skip)]
+    contract_index:     i64,
+    #[graphql(skip)]
+    contract_sub_index: i64,
+    change_type:        Cis2BalanceChangeType,
+    #[graphql(skip)]
+    amount:             BigDecimal,
+    #[graphql(skip)]
+    account_balance:    BigDecimal,
+    block_height:       BlockHeight,
+    timestamp:          DateTime,
+    #[graphql(skip)]
+    transaction_index:  TransactionIndex,
+}
+
+impl Cis2BalanceChange {
+    /// Query the balance changes of an account, optionally only for one token.
+    pub async fn query_by_account(
+        pool: &PgPool,
+        account_index: i64,
+        token_address: Option<&str>,
+        query: ConnectionQuery<DescendingI64>,
+    ) -> ApiResult<connection::Connection<String, Cis2BalanceChange>> {
+        let row_stream = sqlx::query_as!(
+            Cis2BalanceChange,
+            r#"SELECT * FROM (
+                SELECT
+                    cis2_token_balance_changes.id,
+                    tokens.token_address,
+                    tokens.token_id,
+                    tokens.contract_index,
+                    tokens.contract_sub_index,
+                    cis2_token_balance_changes.change_type
+                        AS "change_type: Cis2BalanceChangeType",
+                    cis2_token_balance_changes.amount,
+                    cis2_token_balance_changes.account_balance,
+                    cis2_token_balance_changes.block_height,
+                    cis2_token_balance_changes.slot_time AS timestamp,
+                    cis2_token_balance_changes.transaction_index
+                FROM cis2_token_balance_changes
+                    JOIN tokens ON tokens.index = cis2_token_balance_changes.token_index
+                WHERE cis2_token_balance_changes.account_index = $5
+                    AND ($6::TEXT IS NULL OR tokens.token_address = $6)
+                    AND cis2_token_balance_changes.id < $1
+                    AND cis2_token_balance_changes.id > $2
+                ORDER BY
+                    (CASE WHEN $3 THEN cis2_token_balance_changes.id END) ASC,
+                    (CASE WHEN NOT $3 THEN cis2_token_balance_changes.id END) DESC
+                LIMIT $4
+            ) ORDER BY id DESC"#,
+            i64::from(query.from),
+            i64::from(query.to),
+            query.is_last,
+            query.limit,
+            account_index,
+            token_address
+        )
+        .fetch(pool);
+
+        let (mut connection, page_ids) =
+            connection_from_id_stream(row_stream, |change| change.id).await?;
+        if let Some((page_min_id, page_max_id)) = page_ids {
+            let result = sqlx::query!(
+                "SELECT MAX(cis2_token_balance_changes.id) as max_id,
+                    MIN(cis2_token_balance_changes.id) as min_id
+                FROM cis2_token_balance_changes
+                    JOIN tokens ON tokens.index = cis2_token_balance_changes.token_index
+                WHERE cis2_token_balance_changes.account_index = $1
+                    AND ($2::TEXT IS NULL OR tokens.token_address = $2)",
+                account_index,
+                token_address
+            )
+            .fetch_one(pool)
+            .await?;
+            connection.has_previous_page = result.max_id.is_some_and(|db_max| db_max > page_max_id);
+            connection.has_next_page = result.min_id.is_some_and(|db_min| db_min < page_min_id);
+        }
+        Ok(connection)
+    }
+
+    /// The balance of a token for an account after the block at the given
+    /// height or, if a time is given instead, after the last block at or
+    /// before the time. The balance is zero before the first change.
+    pub async fn balance_at(
+        pool: &PgPool,
+        account_index: i64,
+        contract_index: i64,
+        contract_sub_index: i64,
+        token_id: &str,
+        height: Option<BlockHeight>,
+        time: Option<DateTime>,
+    ) -> ApiResult<BigDecimal> {
+        if height.is_some() == time.is_some() {
+            return Err(ApiError::QueryHeightTime);
+        }
+        let balance = sqlx::query_scalar!(
+            "SELECT account_balance
+            FROM cis2_token_balance_changes
+            WHERE account_index = $1
+                AND token_index = (
+                    SELECT index FROM tokens
+                    WHERE contract_index = $2 AND contract_sub_index = $3 AND token_id = $4
+                )
+                AND ($5::BIGINT IS NULL OR block_height <= $5)
+                AND ($6::TIMESTAMPTZ IS NULL OR slot_time <= $6)
+            ORDER BY id DESC
+            LIMIT 1",
+            account_index,
+            contract_index,
+            contract_sub_index,
+            token_id,
+            height,
+            time
+        )
+        .fetch_optional(pool)
+        .await?;
+        Ok(balance.unwrap_or_default())
+    }
+}
+
+#[ComplexObject]
+impl Cis2BalanceChange {
+    async fn id(&self) -> types::ID { types::ID::from(self.id) }
+
+    /// The change of the balance, negative when tokens left the account.
+    async fn amount(&self) -> BigInteger { BigInteger::from(self.amount.clone()) }
+
+    /// The balance of the account after the change.
+    async fn account_balance(&self) -> BigInteger { BigInteger::from(self.account_balance.clone()) }
+
+    async fn token(&self, ctx: &Context<'_>) -> ApiResult<Token> {
+        Token::query_by_contract_and_id(
+            get_pool(ctx)?,
+            self.contract_index,
+            self.contract_sub_index,
+            &self.token_id,
+        )
+        .await
+    }
+
+    /// The transaction with the token event causing the change.
+    async fn transaction(&self, ctx: &Context<'_>) -> ApiResult<Transaction> {
+        Transaction::query_by_index(get_pool(ctx)?, self.transaction_index)
+            .await?
+            .ok_or(ApiError::NotFound)
+    }
+}
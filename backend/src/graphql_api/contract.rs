@@ -1,6 +1,9 @@
 use super::{
-    get_config, get_pool, token::TokensCollectionSegment, ApiError, ApiResult,
-    CollectionSegmentInfo, ConnectionQuery, InternalError,
+    contract_call::{ContractCallDirection, ContractCallRelation},
+    contract_upgrade::ContractUpgrade,
+    get_config, get_pool,
+    token::TokensCollectionSegment,
+    ApiError, ApiResult, CollectionSegmentInfo, ConnectionQuery, InternalError,
 };
 use crate::{
     address::{AccountAddress, ContractIndex},
@@ -35,7 +38,8 @@ impl QueryContract {
                 blocks.slot_time as block_slot_time,
                 transactions.block_height,
                 transactions.hash as transaction_hash,
-                accounts.address as creator
+                accounts.address as creator,
+                supported_standards
             FROM contracts
             JOIN transactions ON transaction_index = transactions.index
             JOIN blocks ON transactions.block_height = blocks.height
@@ -69,9 +73,11 @@ impl QueryContract {
             transaction_hash: row.transaction_hash,
             block_slot_time: row.block_slot_time,
             snapshot,
+            supported_standards: row.supported_standards,
         })
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn contracts<'a>(
         &self,
         ctx: &Context<'a>,
@@ -81,6 +87,10 @@ impl QueryContract {
         #[graphql(desc = "Returns the last _n_ elements from the list.")] last: Option<u64>,
         #[graphql(desc = "Returns the elements in the list that come before the specified cursor.")]
         before: Option<String>,
+        #[graphql(
+            desc = "Only include contracts supporting every one of the standards, such as `CIS-2`."
+        )]
+        standards: Option<Vec<String>>,
     ) -> ApiResult<connection::Connection<String, Contract>> {
         let config = get_config(ctx)?;
         let pool = get_pool(ctx)?;
@@ -107,14 +117,16 @@ impl QueryContract {
                     blocks.slot_time as block_slot_time,
                     transactions.block_height,
                     transactions.hash as transaction_hash,
-                    accounts.address as creator
+                    accounts.address as creator,
+                    supported_standards
                 FROM contracts
                     JOIN transactions ON transaction_index = transactions.index
                     JOIN blocks ON transactions.block_height = blocks.height
                     JOIN accounts ON transactions.sender_index = accounts.index
                 WHERE 
                     contracts.index < $1 AND 
-                    contracts.index > $2
+                    contracts.index > $2 AND
+                    ($5::TEXT[] IS NULL OR supported_standards @> $5)
                 ORDER BY
                     (CASE WHEN $4 THEN contracts.index END) ASC,
                     (CASE WHEN NOT $4 THEN contracts.index END) DESC
@@ -124,7 +136,8 @@ impl QueryContract {
             i64::from(query.from),
             i64::from(query.to),
             query.limit,
-            query.is_last
+            query.is_last,
+            standards.as_deref()
         )
         .fetch(pool);
 
@@ -163,6 +176,7 @@ impl QueryContract {
                 transaction_hash: row.transaction_hash,
                 block_slot_time: row.block_slot_time,
                 snapshot,
+                supported_standards: row.supported_standards,
             };
             page_max_index = Some(match page_max_index {
                 None => row.index,
@@ -184,7 +198,9 @@ impl QueryContract {
                 "
                     SELECT MAX(index) as db_max_index, MIN(index) as db_min_index
                     FROM contracts
-                "
+                    WHERE $1::TEXT[] IS NULL OR supported_standards @> $1
+                ",
+                standards.as_deref()
             )
             .fetch_one(pool)
             .await?;
@@ -210,10 +226,21 @@ pub struct Contract {
     pub transaction_hash:           String,
     pub block_slot_time:            DateTime,
     pub snapshot:                   ContractSnapshot,
+    /// Identifiers of the standards supported by the contract, such as
+    /// `CIS-2`, as reported by its CIS-0 `supports` entrypoint.
+    pub supported_standards:        Vec<String>,
 }
 
 #[ComplexObject]
 impl Contract {
+    /// The events of the contract, with the most recent first.
+    ///
+    /// Filtering by `eventFilter` or `parameterFilter` requires the indexer to
+    /// decode contract events. Only events logging an event matching the
+    /// `eventFilter` and with a parameter matching the `parameterFilter` are
+    /// returned, where a JSON object matches if every field of the filter
+    /// matches, such as `{"Transfer": {"to": {"Account": ["<address>"]}}}`.
+    /// The event initializing the contract is excluded when filtering.
     // This function returns events from the `contract_events` table as well as
     // one `init_transaction_event` from when the contract was initialized. The
     // `skip` and `take` parameters are used to paginate the events.
@@ -222,19 +249,24 @@ impl Contract {
         ctx: &Context<'_>,
         skip: Option<u64>,
         take: Option<u64>,
+        event_filter: Option<serde_json::Value>,
+        parameter_filter: Option<serde_json::Value>,
     ) -> ApiResult<ContractEventsCollectionSegment> {
         let config = get_config(ctx)?;
         let pool = get_pool(ctx)?;
         let skip = skip.unwrap_or(0);
         let take = take.unwrap_or(config.contract_events_collection_limit);
+        let decoded_filter = decoded_contract_event_filter(event_filter, parameter_filter);
 
         let total_contract_events_count: u64 = sqlx::query_scalar!(
             "SELECT
                 COUNT(*)
             FROM contract_events
-                WHERE contract_index = $1 AND contract_sub_index = $2",
+                WHERE contract_index = $1 AND contract_sub_index = $2
+                AND ($3::JSONB IS NULL OR decoded @> $3)",
             self.contract_address_index.0 as i64,
-            self.contract_address_sub_index.0 as i64
+            self.contract_address_sub_index.0 as i64,
+            decoded_filter
         )
         .fetch_one(pool)
         .await?
@@ -242,8 +274,16 @@ impl Contract {
         .try_into()?;
         // If `skip` is 0 and at least one event is taken, include the
         // `init_transaction_event`.
-        let include_initial_event =
-            skip <= total_contract_events_count && skip + take >= total_contract_events_count;
+        let include_initial_event = decoded_filter.is_none()
+            && skip <= total_contract_events_count
+            && skip + take >= total_contract_events_count;
+        // Without a filter, the events are paginated using the event index of the
+        // contract, while filtered events are skipped using an offset.
+        let (before_event_index, offset) = if decoded_filter.is_none() {
+            (total_contract_events_count as i64 - skip as i64, 0)
+        } else {
+            (i64::MAX, skip as i64)
+        };
         // Adjust the `take` and `skip` values considering if the
         // `init_transaction_event` is requested to be included or not.
 
@@ -280,13 +320,17 @@ impl Contract {
                 WHERE contract_events.contract_index = $1 AND contract_events.contract_sub_index = \
              $2
                 AND event_index_per_contract < $4
+                AND ($5::JSONB IS NULL OR contract_events.decoded @> $5)
                 ORDER BY event_index_per_contract DESC
+                OFFSET $6
                 LIMIT $3
             ",
             self.contract_address_index.0 as i64,
             self.contract_address_sub_index.0 as i64,
             limit as i64 + 1,
-            total_contract_events_count as i64 - skip as i64
+            before_event_index,
+            decoded_filter,
+            offset
         )
         .fetch_all(pool)
         .await?;
@@ -423,7 +467,7 @@ impl Contract {
             },
             items:       contract_events,
             total_count: total_contract_events_count
-                + initial_contract_event_exists_in_database as u64,
+                + (initial_contract_event_exists_in_database && decoded_filter.is_none()) as u64,
         })
     }
 
@@ -549,6 +593,94 @@ impl Contract {
             items,
         })
     }
+
+    /// The contracts calling this contract, with the most recently discovered
+    /// caller first.
+    async fn callers(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Returns the first _n_ elements from the list.")] first: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come after the specified cursor.")]
+        after: Option<String>,
+        #[graphql(desc = "Returns the last _n_ elements from the list.")] last: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come before the specified cursor.")]
+        before: Option<String>,
+    ) -> ApiResult<connection::Connection<String, ContractCallRelation>> {
+        let config = get_config(ctx)?;
+        let query = ConnectionQuery::<DescendingI64>::new(
+            first,
+            after,
+            last,
+            before,
+            config.contract_connection_limit,
+        )?;
+        ContractCallRelation::query_connection(
+            get_pool(ctx)?,
+            self.contract_address_index.0 as i64,
+            self.contract_address_sub_index.0 as i64,
+            ContractCallDirection::Callers,
+            query,
+        )
+        .await
+    }
+
+    /// The contracts called by this contract, with the most recently discovered
+    /// callee first.
+    async fn callees(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Returns the first _n_ elements from the list.")] first: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come after the specified cursor.")]
+        after: Option<String>,
+        #[graphql(desc = "Returns the last _n_ elements from the list.")] last: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come before the specified cursor.")]
+        before: Option<String>,
+    ) -> ApiResult<connection::Connection<String, ContractCallRelation>> {
+        let config = get_config(ctx)?;
+        let query = ConnectionQuery::<DescendingI64>::new(
+            first,
+            after,
+            last,
+            before,
+            config.contract_connection_limit,
+        )?;
+        ContractCallRelation::query_connection(
+            get_pool(ctx)?,
+            self.contract_address_index.0 as i64,
+            self.contract_address_sub_index.0 as i64,
+            ContractCallDirection::Callees,
+            query,
+        )
+        .await
+    }
+
+    /// The upgrades of this contract, with the most recent upgrade first.
+    async fn upgrades(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Returns the first _n_ elements from the list.")] first: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come after the specified cursor.")]
+        after: Option<String>,
+        #[graphql(desc = "Returns the last _n_ elements from the list.")] last: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come before the specified cursor.")]
+        before: Option<String>,
+    ) -> ApiResult<connection::Connection<String, ContractUpgrade>> {
+        let config = get_config(ctx)?;
+        let query = ConnectionQuery::<DescendingI64>::new(
+            first,
+            after,
+            last,
+            before,
+            config.contract_connection_limit,
+        )?;
+        ContractUpgrade::query_connection(
+            get_pool(ctx)?,
+            self.contract_address_index.0 as i64,
+            self.contract_address_sub_index.0 as i64,
+            query,
+        )
+        .await
+    }
 }
 
 /// A segment of a collection.
@@ -612,3 +744,22 @@ struct ContractEvent {
     transaction_hash: String,
     block_slot_time: DateTime,
 }
+
+/// Build the filter matched against the decoded values of contract events,
+/// `None` if no filter is provided.
+fn decoded_contract_event_filter(
+    event_filter: Option<serde_json::Value>,
+    parameter_filter: Option<serde_json::Value>,
+) -> Option<serde_json::Value> {
+    if event_filter.is_none() && parameter_filter.is_none() {
+        return None;
+    }
+    let mut filter = serde_json::Map::new();
+    if let Some(event_filter) = event_filter {
+        filter.insert("events".to_string(), serde_json::Value::Array(vec![event_filter]));
+    }
+    if let Some(parameter_filter) = parameter_filter {
+        filter.insert("parameter".to_string(), parameter_filter);
+    }
+    Some(serde_json::Value::Object(filter))
+}
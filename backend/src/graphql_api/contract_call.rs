@@ -0,0 +1,252 @@
+use super::{
+    get_pool, transaction::Transaction, ApiError, ApiResult, ConnectionQuery, InternalError,
+};
+use crate::{
+    address::{AccountAddress, Address, ContractAddress},
+    connection::{connection_from_id_stream, DescendingI64},
+    scalar_types::{Amount, Energy, TransactionIndex},
+};
+use async_graphql::{connection, ComplexObject, Context, SimpleObject};
+use sqlx::PgPool;
+use std::collections::HashMap;
+
+/// The contract calls made by a contract update transaction.
+#[derive(SimpleObject)]
+pub struct ContractCallTree {
+    /// Energy cost of the whole transaction, covering every call in the tree.
+    energy_cost: Energy,
+    /// The calls made directly by the transaction, with the nested calls made
+    /// by each of them, in the order they completed.
+    calls:       Vec<ContractCallNode>,
+}
+
+impl ContractCallTree {
+    /// The contract calls made by the transaction, which had the provided
+    /// energy cost.
+    pub async fn query(
+        pool: &PgPool,
+        transaction_index: TransactionIndex,
+        energy_cost: Energy,
+    ) -> ApiResult<Self> {
+        Ok(Self {
+            energy_cost,
+            calls: ContractCallNode::query_tree(pool, transaction_index).await?,
+        })
+    }
+}
+
+/// A contract call made as part of a contract update transaction.
+///
+/// The energy used by each call is not known, as the events reported by the
+/// node for a call, such as `InstanceUpdatedEvent`, carry no energy. Only the
+/// energy cost of the whole transaction is available, on the
+/// `ContractCallTree`.
+#[derive(SimpleObject)]
+pub struct ContractCallNode {
+    /// Index of the trace element reporting the completed call, among the
+    /// events of the transaction.
+    trace_element_index: i64,
+    /// Number of calls between the transaction and this call, 0 for calls made
+    /// directly by the transaction.
+    depth:               i16,
+    /// The account or contract making the call.
+    caller:              Address,
+    /// The contract being called.
+    contract:            ContractAddress,
+    /// The receive name of the entrypoint called.
+    entrypoint:          String,
+    /// Amount of CCD sent with the call.
+    amount:              Amount,
+    /// The calls made by this call, in the order they completed.
+    calls:               Vec<ContractCallNode>,
+}
+
+impl ContractCallNode {
+    /// The calls made directly by the transaction, with the nested calls made
+    /// by each of them.
+    async fn query_tree(
+        pool: &PgPool,
+        transaction_index: TransactionIndex,
+    ) -> ApiResult<Vec<ContractCallNode>> {
+        let rows = sqlx::query!(
+            "SELECT
+                trace_element_index,
+                parent_trace_element_index,
+                depth,
+                accounts.address as \"caller_account_address?\",
+                caller_contract_index,
+                caller_contract_sub_index,
+                contract_index,
+                contract_sub_index,
+                entrypoint,
+                contract_calls.amount
+            FROM contract_calls
+                LEFT JOIN accounts ON accounts.index = caller_account_index
+            WHERE contract_calls.transaction_index = $1
+            ORDER BY trace_element_index ASC",
+            transaction_index
+        )
+        .fetch_all(pool)
+        .await?;
+
+        // Nested calls complete before the calls making them, so the children of a
+        // call are always built before the call itself.
+        let mut children: HashMap<i64, Vec<ContractCallNode>> = HashMap::new();
+        let mut roots = Vec::new();
+        for row in rows {
+            let caller = match (
+                row.caller_account_address,
+                row.caller_contract_index,
+                row.caller_contract_sub_index,
+            ) {
+                (Some(address), _, _) => Address::AccountAddress(AccountAddress::from(address)),
+                (None, Some(index), Some(sub_index)) => {
+                    Address::ContractAddress(ContractAddress::new(index, sub_index)?)
+                }
+                _ => {
+                    return Err(InternalError::InternalError(
+                        "Database invariant violated, contract call without a caller".to_string(),
+                    )
+                    .into())
+                }
+            };
+            let call = ContractCallNode {
+                trace_element_index: row.trace_element_index,
+                depth: row.depth,
+                caller,
+                contract: ContractAddress::new(row.contract_index, row.contract_sub_index)?,
+                entrypoint: row.entrypoint,
+                amount: row.amount.try_into()?,
+                calls: children.remove(&row.trace_element_index).unwrap_or_default(),
+            };
+            match row.parent_trace_element_index {
+                Some(parent) => children.entry(parent).or_default().push(call),
+                None => roots.push(call),
+            }
+        }
+        Ok(roots)
+    }
+}
+
+/// A contract calling another contract, aggregated over every call made.
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct ContractCallRelation {
+    #[graphql(skip)]
+    id: i64,
+    #[graphql(skip)]
+    caller_contract_index: i64,
+    #[graphql(skip)]
+    caller_contract_sub_index: i64,
+    #[graphql(skip)]
+    contract_index: i64,
+    #[graphql(skip)]
+    contract_sub_index: i64,
+    /// Number of calls made.
+    call_count: i64,
+    #[graphql(skip)]
+    first_transaction_index: TransactionIndex,
+    #[graphql(skip)]
+    last_transaction_index: TransactionIndex,
+}
+
+/// The direction of contract call relations, relative to a contract.
+pub enum ContractCallDirection {
+    /// The contracts calling the contract.
+    Callers,
+    /// The contracts called by the contract.
+    Callees,
+}
+
+impl ContractCallRelation {
+    /// The relations between the contract and the contracts calling it or
+    /// called by it, with the most recently discovered relations first.
+    pub async fn query_connection(
+        pool: &PgPool,
+        contract_index: i64,
+        contract_sub_index: i64,
+        direction: ContractCallDirection,
+        query: ConnectionQuery<DescendingI64>,
+    ) -> ApiResult<connection::Connection<String, ContractCallRelation>> {
+        let is_callers = matches!(direction, ContractCallDirection::Callers);
+        let row_stream = sqlx::query_as!(
+            ContractCallRelation,
+            "SELECT * FROM (
+                SELECT
+                    id,
+                    caller_contract_index,
+                    caller_contract_sub_index,
+                    contract_index,
+                    contract_sub_index,
+                    call_count,
+                    first_transaction_index,
+                    last_transaction_index
+                FROM contract_call_edges
+                WHERE (
+                        ($5 AND contract_index = $6 AND contract_sub_index = $7)
+                        OR (NOT $5 AND caller_contract_index = $6 AND caller_contract_sub_index = \
+             $7)
+                    )
+                    AND id < $1 AND id > $2
+                ORDER BY
+                    (CASE WHEN $3 THEN id END) ASC,
+                    (CASE WHEN NOT $3 THEN id END) DESC
+                LIMIT $4
+            ) ORDER BY id DESC",
+            i64::from(query.from),
+            i64::from(query.to),
+            query.is_last,
+            query.limit,
+            is_callers,
+            contract_index,
+            contract_sub_index
+        )
+        .fetch(pool);
+
+        let (mut connection, page_ids) =
+            connection_from_id_stream(row_stream, |relation| relation.id).await?;
+        if let Some((page_min_id, page_max_id)) = page_ids {
+            let result = sqlx::query!(
+                "SELECT MAX(id) as max_id, MIN(id) as min_id
+                FROM contract_call_edges
+                WHERE ($1 AND contract_index = $2 AND contract_sub_index = $3)
+                    OR (NOT $1 AND caller_contract_index = $2 AND caller_contract_sub_index = $3)",
+                is_callers,
+                contract_index,
+                contract_sub_index
+            )
+            .fetch_one(pool)
+            .await?;
+            connection.has_previous_page = result.max_id.is_some_and(|db_max| db_max > page_max_id);
+            connection.has_next_page = result.min_id.is_some_and(|db_min| db_min < page_min_id);
+        }
+        Ok(connection)
+    }
+}
+
+#[ComplexObject]
+impl ContractCallRelation {
+    /// The contract making the calls.
+    async fn caller(&self) -> ApiResult<ContractAddress> {
+        ContractAddress::new(self.caller_contract_index, self.caller_contract_sub_index)
+    }
+
+    /// The contract being called.
+    async fn contract(&self) -> ApiResult<ContractAddress> {
+        ContractAddress::new(self.contract_index, self.contract_sub_index)
+    }
+
+    /// The first transaction where the caller called the contract.
+    async fn first_transaction(&self, ctx: &Context<'_>) -> ApiResult<Transaction> {
+        Transaction::query_by_index(get_pool(ctx)?, self.first_transaction_index)
+            .await?
+            .ok_or(ApiError::NotFound)
+    }
+
+    /// The latest transaction where the caller called the contract.
+    async fn last_transaction(&self, ctx: &Context<'_>) -> ApiResult<Transaction> {
+        Transaction::query_by_index(get_pool(ctx)?, self.last_transaction_index)
+            .await?
+            .ok_or(ApiError::NotFound)
+    }
+}
@@ -0,0 +1,293 @@
+use super::{
+    get_pool, get_schema_cache, transaction::Transaction, ApiError, ApiResult, ConnectionQuery,
+};
+use crate::{
+    connection::{connection_from_id_stream, DescendingI64},
+    scalar_types::{BlockHeight, DateTime, ModuleReference, TransactionIndex},
+};
+use async_graphql::{connection, ComplexObject, Context, Enum, SimpleObject};
+use concordium_rust_sdk::base::contracts_common::{
+    schema::{Type, VersionedModuleSchema},
+    to_bytes,
+};
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+
+/// An upgrade of a contract from one module to another.
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct ContractUpgrade {
+    #[graphql(skip)]
+    id:                    i64,
+    #[graphql(skip)]
+    contract_name:         String,
+    #[graphql(skip)]
+    transaction_index:     TransactionIndex,
+    /// The module of the contract before the upgrade.
+    from_module_reference: ModuleReference,
+    /// The module of the contract after the upgrade.
+    to_module_reference:   ModuleReference,
+    /// Height of the block containing the upgrade.
+    block_height:          BlockHeight,
+    /// Slot time of the block containing the upgrade.
+    block_slot_time:       DateTime,
+}
+
+impl ContractUpgrade {
+    /// The upgrades of the contract, with the most recent upgrade first.
+    pub async fn query_connection(
+        pool: &PgPool,
+        contract_index: i64,
+        contract_sub_index: i64,
+        query: ConnectionQuery<DescendingI64>,
+    ) -> ApiResult<connection::Connection<String, ContractUpgrade>> {
+        let row_stream = sqlx::query_as!(
+            ContractUpgrade,
+            "SELECT * FROM (
+                SELECT
+                    contract_upgrades.id,
+                    contracts.name as contract_name,
+                    contract_upgrades.transaction_index,
+                    from_module_reference,
+                    to_module_reference,
+                    blocks.height as block_height,
+                    blocks.slot_time as block_slot_time
+                FROM contract_upgrades
+                    JOIN contracts ON contracts.index = contract_upgrades.contract_index
+                        AND contracts.sub_index = contract_upgrades.contract_sub_index
+                    JOIN transactions ON transactions.index = contract_upgrades.transaction_index
+                    JOIN blocks ON blocks.height = transactions.block_height
+                WHERE contract_index = $5 AND contract_sub_index = $6
+                    AND contract_upgrades.id < $1 AND contract_upgrades.id > $2
+                ORDER BY
+                    (CASE WHEN $3 THEN contract_upgrades.id END) ASC,
+                    (CASE WHEN NOT $3 THEN contract_upgrades.id END) DESC
+                LIMIT $4
+            ) ORDER BY id DESC",
+            i64::from(query.from),
+            i64::from(query.to),
+            query.is_last,
+            query.limit,
+            contract_index,
+            contract_sub_index
+        )
+        .fetch(pool);
+
+        let (mut connection, page_ids) =
+            connection_from_id_stream(row_stream, |upgrade| upgrade.id).await?;
+        if let Some((page_min_id, page_max_id)) = page_ids {
+            let result = sqlx::query!(
+                "SELECT MAX(id) as max_id, MIN(id) as min_id
+                FROM contract_upgrades
+                WHERE contract_index = $1 AND contract_sub_index = $2",
+                contract_index,
+                contract_sub_index
+            )
+            .fetch_one(pool)
+            .await?;
+            connection.has_previous_page = result.max_id.is_some_and(|db_max| db_max > page_max_id);
+            connection.has_next_page = result.min_id.is_some_and(|db_min| db_min < page_min_id);
+        }
+        Ok(connection)
+    }
+}
+
+#[ComplexObject]
+impl ContractUpgrade {
+    /// The transaction upgrading the contract.
+    async fn transaction(&self, ctx: &Context<'_>) -> ApiResult<Transaction> {
+        Transaction::query_by_index(get_pool(ctx)?, self.transaction_index)
+            .await?
+            .ok_or(ApiError::NotFound)
+    }
+
+    /// The changes to the entrypoints and event schema of the contract, read
+    /// from the schemas embedded in the two modules. Null if either module
+    /// has no embedded schema.
+    async fn schema_diff(&self, ctx: &Context<'_>) -> ApiResult<Option<ContractSchemaDiff>> {
+        let pool = get_pool(ctx)?;
+        let schema_cache = get_schema_cache(ctx)?;
+        let from = schema_cache.get_by_module(pool, &self.from_module_reference).await?;
+        let to = schema_cache.get_by_module(pool, &self.to_module_reference).await?;
+        let (Some(from), Some(to)) = (from, to) else {
+            return Ok(None);
+        };
+        Ok(Some(ContractSchemaDiff::new(&from, &to, &self.contract_name)))
+    }
+}
+
+/// The changes to the schema of a contract between two modules.
+#[derive(SimpleObject)]
+pub struct ContractSchemaDiff {
+    /// The entrypoints which got added, removed or changed, ordered by name.
+    entrypoints:          Vec<EntrypointChange>,
+    /// Whether the schema of the events logged by the contract changed.
+    event_schema_changed: bool,
+}
+
+/// A change to an entrypoint of a contract.
+#[derive(SimpleObject, Debug, PartialEq, Eq)]
+pub struct EntrypointChange {
+    entrypoint:            String,
+    change_type:           EntrypointChangeType,
+    /// JSON template of the parameter before the upgrade. Null if the
+    /// entrypoint got added or the schema has no parameter type.
+    from_parameter_schema: Option<String>,
+    /// JSON template of the parameter after the upgrade. Null if the
+    /// entrypoint got removed or the schema has no parameter type.
+    to_parameter_schema:   Option<String>,
+}
+
+#[derive(Enum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EntrypointChangeType {
+    Added,
+    Removed,
+    /// The entrypoint exists in both modules, but the parameter type changed.
+    ParameterChanged,
+}
+
+/// The entrypoints of a contract in a schema, with their parameter types.
+struct ContractSchema {
+    entrypoints: BTreeMap<String, Option<Type>>,
+    event:       Option<Type>,
+}
+
+impl ContractSchema {
+    fn new(schema: &VersionedModuleSchema, contract_name: &str) -> Self {
+        let (entrypoints, event) = match schema {
+            VersionedModuleSchema::V0(module) => match module.contracts.get(contract_name) {
+                Some(contract) => (
+                    contract
+                        .receive
+                        .iter()
+                        .map(|(name, parameter)| (name.clone(), Some(parameter.clone())))
+                        .collect(),
+                    None,
+                ),
+                None => (BTreeMap::new(), None),
+            },
+            VersionedModuleSchema::V1(module) => match module.contracts.get(contract_name) {
+                Some(contract) => (
+                    contract
+                        .receive
+                        .iter()
+                        .map(|(name, function)| (name.clone(), function.parameter().cloned()))
+                        .collect(),
+                    None,
+                ),
+                None => (BTreeMap::new(), None),
+            },
+            VersionedModuleSchema::V2(module) => match module.contracts.get(contract_name) {
+                Some(contract) => (
+                    contract
+                        .receive
+                        .iter()
+                        .map(|(name, function)| (name.clone(), function.parameter.clone()))
+                        .collect(),
+                    None,
+                ),
+                None => (BTreeMap::new(), None),
+            },
+            VersionedModuleSchema::V3(module) => match module.contracts.get(contract_name) {
+                Some(contract) => (
+                    contract
+                        .receive
+                        .iter()
+                        .map(|(name, function)| (name.clone(), function.parameter.clone()))
+                        .collect(),
+                    contract.event.clone(),
+                ),
+                None => (BTreeMap::new(), None),
+            },
+        };
+        Self {
+            entrypoints,
+            event,
+        }
+    }
+}
+
+impl ContractSchemaDiff {
+    fn new(from: &VersionedModuleSchema, to: &VersionedModuleSchema, contract_name: &str) -> Self {
+        let from = ContractSchema::new(from, contract_name);
+        let to = ContractSchema::new(to, contract_name);
+        Self {
+            entrypoints:          diff_entrypoints(&from.entrypoints, &to.entrypoints),
+            event_schema_changed: !same_type(from.event.as_ref(), to.event.as_ref()),
+        }
+    }
+}
+
+/// Compare types by their serialization, as the schema types are only
+/// compared structurally.
+fn same_type(from: Option<&Type>, to: Option<&Type>) -> bool {
+    from.map(to_bytes) == to.map(to_bytes)
+}
+
+fn json_template(parameter: Option<&Type>) -> Option<String> {
+    parameter.map(|parameter| parameter.to_json_template().to_string())
+}
+
+fn diff_entrypoints(
+    from: &BTreeMap<String, Option<Type>>,
+    to: &BTreeMap<String, Option<Type>>,
+) -> Vec<EntrypointChange> {
+    let mut changes = Vec::new();
+    for (entrypoint, from_parameter) in from.iter() {
+        let change_type = match to.get(entrypoint) {
+            None => EntrypointChangeType::Removed,
+            Some(to_parameter) if !same_type(from_parameter.as_ref(), to_parameter.as_ref()) => {
+                EntrypointChangeType::ParameterChanged
+            }
+            Some(_) => continue,
+        };
+        changes.push(EntrypointChange {
+            entrypoint: entrypoint.clone(),
+            change_type,
+            from_parameter_schema: json_template(from_parameter.as_ref()),
+            to_parameter_schema: to
+                .get(entrypoint)
+                .and_then(|to_parameter| json_template(to_parameter.as_ref())),
+        });
+    }
+    for (entrypoint, to_parameter) in to.iter() {
+        if !from.contains_key(entrypoint) {
+            changes.push(EntrypointChange {
+                entrypoint:            entrypoint.clone(),
+                change_type:           EntrypointChangeType::Added,
+                from_parameter_schema: None,
+                to_parameter_schema:   json_template(to_parameter.as_ref()),
+            });
+        }
+    }
+    changes.sort_by(|a, b| a.entrypoint.cmp(&b.entrypoint));
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_entrypoints() {
+        let from = BTreeMap::from([
+            ("transfer".to_string(), Some(Type::U8)),
+            ("mint".to_string(), Some(Type::U8)),
+            ("view".to_string(), None),
+        ]);
+        let to = BTreeMap::from([
+            ("transfer".to_string(), Some(Type::U16)),
+            ("view".to_string(), None),
+            ("burn".to_string(), Some(Type::Bool)),
+        ]);
+        let changes: Vec<_> = diff_entrypoints(&from, &to)
+            .into_iter()
+            .map(|change| (change.entrypoint, change.change_type))
+            .collect();
+        assert_eq!(changes, vec![
+            ("burn".to_string(), EntrypointChangeType::Added),
+            ("mint".to_string(), EntrypointChangeType::Removed),
+            ("transfer".to_string(), EntrypointChangeType::ParameterChanged),
+        ]);
+    }
+}
@@ -0,0 +1,375 @@
+use super::{
+    get_config, get_pool, transaction::Transaction, ApiError, ApiResult, Cis4CredentialEventType,
+    ConnectionQuery,
+};
+use crate::{
+    address::{ContractAddress, ContractIndex},
+    connection::{connection_from_id_stream, connection_from_slice, DescendingI64},
+    scalar_types::{DateTime, TransactionIndex},
+};
+use async_graphql::{connection, ComplexObject, Context, Enum, Object, SimpleObject};
+use chrono::Utc;
+use sqlx::PgPool;
+
+#[derive(Default)]
+pub struct QueryCredentialRegistries;
+
+#[Object]
+impl QueryCredentialRegistries {
+    async fn cis4_registry(
+        &self,
+        ctx: &Context<'_>,
+        contract_address_index: ContractIndex,
+        contract_address_sub_index: ContractIndex,
+    ) -> ApiResult<Cis4Registry> {
+        sqlx::query_as!(
+            Cis4Registry,
+            "SELECT
+                contract_index,
+                contract_sub_index,
+                transaction_index,
+                issuer_metadata_url,
+                issuer_metadata_hash,
+                credential_type,
+                schema_url
+            FROM cis4_registries
+            WHERE contract_index = $1 AND contract_sub_index = $2",
+            contract_address_index.0 as i64,
+            contract_address_sub_index.0 as i64
+        )
+        .fetch_optional(get_pool(ctx)?)
+        .await?
+        .ok_or(ApiError::NotFound)
+    }
+
+    /// The CIS-4 credential registries, ordered by contract index.
+    async fn cis4_registries(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Returns the first _n_ elements from the list.")] first: Option<usize>,
+        #[graphql(desc = "Returns the elements in the list that come after the specified cursor.")]
+        after: Option<String>,
+        #[graphql(desc = "Returns the last _n_ elements from the list.")] last: Option<usize>,
+        #[graphql(desc = "Returns the elements in the list that come before the specified cursor.")]
+        before: Option<String>,
+    ) -> ApiResult<connection::Connection<String, Cis4Registry>> {
+        // Registries are run by credential issuers, which are few, so every one of
+        // them is fetched.
+        let registries = sqlx::query_as!(
+            Cis4Registry,
+            "SELECT
+                contract_index,
+                contract_sub_index,
+                transaction_index,
+                issuer_metadata_url,
+                issuer_metadata_hash,
+                credential_type,
+                schema_url
+            FROM cis4_registries
+            ORDER BY contract_index ASC, contract_sub_index ASC"
+        )
+        .fetch_all(get_pool(ctx)?)
+        .await?;
+        connection_from_slice(registries, first, after, last, before)
+    }
+}
+
+/// A contract implementing the CIS-4 standard for credential registries.
+#[derive(SimpleObject, Clone)]
+#[graphql(complex)]
+pub struct Cis4Registry {
+    #[graphql(skip)]
+    contract_index:       i64,
+    #[graphql(skip)]
+    contract_sub_index:   i64,
+    #[graphql(skip)]
+    transaction_index:    TransactionIndex,
+    /// URL of the metadata of the issuer. Null until the registry logs it.
+    issuer_metadata_url:  Option<String>,
+    /// Hex encoded SHA256 hash of the issuer metadata, if provided by the
+    /// registry.
+    issuer_metadata_hash: Option<String>,
+    /// The type of the credentials in the registry. Null until the registry
+    /// logs a schema.
+    credential_type:      Option<String>,
+    /// URL of the schema of the credentials in the registry.
+    schema_url:           Option<String>,
+}
+
+#[ComplexObject]
+impl Cis4Registry {
+    async fn contract_address(&self) -> ApiResult<ContractAddress> {
+        ContractAddress::new(self.contract_index, self.contract_sub_index)
+    }
+
+    /// The transaction logging the first CIS-4 event of the registry.
+    async fn transaction(&self, ctx: &Context<'_>) -> ApiResult<Transaction> {
+        Transaction::query_by_index(get_pool(ctx)?, self.transaction_index)
+            .await?
+            .ok_or(ApiError::NotFound)
+    }
+
+    /// The revocation keys able to revoke credentials on behalf of the
+    /// issuer, including removed keys.
+    async fn revocation_keys(&self, ctx: &Context<'_>) -> ApiResult<Vec<Cis4RevocationKey>> {
+        let keys = sqlx::query_as!(
+            Cis4RevocationKey,
+            "SELECT key, active, transaction_index
+            FROM cis4_revocation_keys
+            WHERE contract_index = $1 AND contract_sub_index = $2
+            ORDER BY key ASC",
+            self.contract_index,
+            self.contract_sub_index
+        )
+        .fetch_all(get_pool(ctx)?)
+        .await?;
+        Ok(keys)
+    }
+
+    /// The credential of the holder with the provided hex encoded public key.
+    async fn credential(
+        &self,
+        ctx: &Context<'_>,
+        holder_id: String,
+    ) -> ApiResult<Option<Cis4Credential>> {
+        let credential = sqlx::query_as!(
+            Cis4Credential,
+            "SELECT
+                id,
+                contract_index,
+                contract_sub_index,
+                holder_id,
+                credential_type,
+                schema_url,
+                holder_revocable,
+                valid_from,
+                valid_until,
+                metadata_url,
+                revoked,
+                transaction_index
+            FROM cis4_credentials
+            WHERE contract_index = $1 AND contract_sub_index = $2 AND holder_id = $3",
+            self.contract_index,
+            self.contract_sub_index,
+            holder_id.to_lowercase()
+        )
+        .fetch_optional(get_pool(ctx)?)
+        .await?;
+        Ok(credential)
+    }
+
+    /// The credentials registered in the registry, with the most recently
+    /// registered credential first.
+    async fn credentials(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Returns the first _n_ elements from the list.")] first: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come after the specified cursor.")]
+        after: Option<String>,
+        #[graphql(desc = "Returns the last _n_ elements from the list.")] last: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come before the specified cursor.")]
+        before: Option<String>,
+    ) -> ApiResult<connection::Connection<String, Cis4Credential>> {
+        let config = get_config(ctx)?;
+        let pool = get_pool(ctx)?;
+        let query = ConnectionQuery::<DescendingI64>::new(
+            first,
+            after,
+            last,
+            before,
+            config.contract_connection_limit,
+        )?;
+        let row_stream = sqlx::query_as!(
+            Cis4Credential,
+            "SELECT * FROM (
+                SELECT
+                    id,
+                    contract_index,
+                    contract_sub_index,
+                    holder_id,
+                    credential_type,
+                    schema_url,
+                    holder_revocable,
+                    valid_from,
+                    valid_until,
+                    metadata_url,
+                    revoked,
+                    transaction_index
+                FROM cis4_credentials
+                WHERE contract_index = $5 AND contract_sub_index = $6
+                    AND id < $1 AND id > $2
+                ORDER BY
+                    (CASE WHEN $3 THEN id END) ASC,
+                    (CASE WHEN NOT $3 THEN id END) DESC
+                LIMIT $4
+            ) ORDER BY id DESC",
+            i64::from(query.from),
+            i64::from(query.to),
+            query.is_last,
+            query.limit,
+            self.contract_index,
+            self.contract_sub_index
+        )
+        .fetch(pool);
+
+        let (mut connection, page_ids) =
+            connection_from_id_stream(row_stream, |credential| credential.id).await?;
+        if let Some((page_min_id, page_max_id)) = page_ids {
+            let result = sqlx::query!(
+                "SELECT MAX(id) as max_id, MIN(id) as min_id
+                FROM cis4_credentials
+                WHERE contract_index = $1 AND contract_sub_index = $2",
+                self.contract_index,
+                self.contract_sub_index
+            )
+            .fetch_one(pool)
+            .await?;
+            connection.has_previous_page = result.max_id.is_some_and(|db_max| db_max > page_max_id);
+            connection.has_next_page = result.min_id.is_some_and(|db_min| db_min < page_min_id);
+        }
+        Ok(connection)
+    }
+}
+
+/// A credential registered in a CIS-4 credential registry.
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct Cis4Credential {
+    #[graphql(skip)]
+    id:                 i64,
+    #[graphql(skip)]
+    contract_index:     i64,
+    #[graphql(skip)]
+    contract_sub_index: i64,
+    /// Hex encoded Ed25519 public key identifying the credential holder.
+    holder_id:          String,
+    credential_type:    String,
+    /// URL of the schema of the credential.
+    schema_url:         String,
+    /// Whether the holder is able to revoke the credential. Null if the
+    /// registry did not provide the credential entry.
+    holder_revocable:   Option<bool>,
+    /// Start of the validity window of the credential. Null if the registry
+    /// did not provide the credential entry.
+    valid_from:         Option<DateTime>,
+    /// End of the validity window of the credential. Null when the credential
+    /// never expires.
+    valid_until:        Option<DateTime>,
+    /// URL of the metadata of the credential.
+    metadata_url:       Option<String>,
+    #[graphql(skip)]
+    revoked:            bool,
+    #[graphql(skip)]
+    transaction_index:  TransactionIndex,
+}
+
+/// The status of a credential, as defined by the CIS-4 standard.
+#[derive(Enum, Copy, Clone, PartialEq, Eq)]
+pub enum Cis4CredentialStatus {
+    Active,
+    Revoked,
+    Expired,
+    NotActivated,
+}
+
+#[ComplexObject]
+impl Cis4Credential {
+    /// The current status of the credential, derived from the revocations
+    /// and the validity window.
+    async fn status(&self) -> Cis4CredentialStatus {
+        let now = Utc::now();
+        if self.revoked {
+            Cis4CredentialStatus::Revoked
+        } else if self.valid_from.is_some_and(|valid_from| now < valid_from) {
+            Cis4CredentialStatus::NotActivated
+        } else if self.valid_until.is_some_and(|valid_until| valid_until < now) {
+            Cis4CredentialStatus::Expired
+        } else {
+            Cis4CredentialStatus::Active
+        }
+    }
+
+    /// The registry holding the credential.
+    async fn registry_address(&self) -> ApiResult<ContractAddress> {
+        ContractAddress::new(self.contract_index, self.contract_sub_index)
+    }
+
+    /// The transaction registering the credential.
+    async fn transaction(&self, ctx: &Context<'_>) -> ApiResult<Transaction> {
+        Transaction::query_by_index(get_pool(ctx)?, self.transaction_index)
+            .await?
+            .ok_or(ApiError::NotFound)
+    }
+
+    /// The history of the credential, including its revocations, oldest
+    /// first.
+    async fn events(&self, ctx: &Context<'_>) -> ApiResult<Vec<Cis4CredentialEvent>> {
+        Cis4CredentialEvent::query_by_credential(get_pool(ctx)?, self.id).await
+    }
+}
+
+/// A change to a credential in a CIS-4 credential registry.
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct Cis4CredentialEvent {
+    event_type:        Cis4CredentialEventType,
+    /// The party revoking the credential: `Issuer`, `Holder` or the hex
+    /// encoded revocation key. Only present for revocations.
+    revoker:           Option<String>,
+    /// The reason for revoking the credential, if provided.
+    reason:            Option<String>,
+    #[graphql(skip)]
+    transaction_index: TransactionIndex,
+}
+
+impl Cis4CredentialEvent {
+    async fn query_by_credential(pool: &PgPool, credential_id: i64) -> ApiResult<Vec<Self>> {
+        let events = sqlx::query_as!(
+            Cis4CredentialEvent,
+            r#"SELECT
+                event_type as "event_type: Cis4CredentialEventType",
+                revoker,
+                reason,
+                transaction_index
+            FROM cis4_credential_events
+            WHERE credential_id = $1
+            ORDER BY id ASC"#,
+            credential_id
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(events)
+    }
+}
+
+#[ComplexObject]
+impl Cis4CredentialEvent {
+    async fn transaction(&self, ctx: &Context<'_>) -> ApiResult<Transaction> {
+        Transaction::query_by_index(get_pool(ctx)?, self.transaction_index)
+            .await?
+            .ok_or(ApiError::NotFound)
+    }
+}
+
+/// A key able to revoke credentials on behalf of the issuer of a CIS-4
+/// credential registry.
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct Cis4RevocationKey {
+    /// Hex encoded Ed25519 public key.
+    key:               String,
+    /// Whether the key is registered, false when the key got removed.
+    active:            bool,
+    #[graphql(skip)]
+    transaction_index: TransactionIndex,
+}
+
+#[ComplexObject]
+impl Cis4RevocationKey {
+    /// The latest transaction registering or removing the key.
+    async fn transaction(&self, ctx: &Context<'_>) -> ApiResult<Transaction> {
+        Transaction::query_by_index(get_pool(ctx)?, self.transaction_index)
+            .await?
+            .ok_or(ApiError::NotFound)
+    }
+}
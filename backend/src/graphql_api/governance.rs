@@ -0,0 +1,324 @@
+use super::{
+    block::Block, get_config, get_pool, transaction::Transaction, ApiError, ApiResult,
+    ConnectionQuery, InternalError,
+};
+use crate::{
+    connection::{connection_from_id_stream, connection_from_slice, DescendingI64},
+    scalar_types::BlockHeight,
+    transaction_event::{
+        chain_update::{GovernanceKeyLevel, Level2Authorization},
+        Event,
+    },
+    transaction_reject::TransactionRejectReason,
+    transaction_type::{
+        AccountTransactionType, CredentialDeploymentTransactionType, DbTransactionType,
+        UpdateTransactionType,
+    },
+};
+use async_graphql::{connection, types, ComplexObject, Context, Object, SimpleObject};
+
+#[derive(Default)]
+pub struct QueryGovernance;
+
+#[Object]
+impl QueryGovernance {
+    /// The governance key sets currently authorizing chain updates.
+    async fn governance_keys(&self, ctx: &Context<'_>) -> ApiResult<GovernanceKeys> {
+        let key_sets = sqlx::query_as!(
+            GovernanceKeySet,
+            r#"SELECT
+                id,
+                level as "level: GovernanceKeyLevel",
+                effective_block_height,
+                replaced_block_height,
+                keys,
+                threshold
+            FROM governance_key_sets
+            WHERE replaced_block_height IS NULL"#
+        )
+        .fetch_all(get_pool(ctx)?)
+        .await?;
+        let find_level = |level: GovernanceKeyLevel| {
+            key_sets.iter().find(|key_set| key_set.level == level).cloned().ok_or_else(|| {
+                ApiError::from(InternalError::InternalError(format!(
+                    "No current governance key set found for level {:?}",
+                    level
+                )))
+            })
+        };
+        Ok(GovernanceKeys {
+            root:   find_level(GovernanceKeyLevel::Root)?,
+            level1: find_level(GovernanceKeyLevel::Level1)?,
+            level2: find_level(GovernanceKeyLevel::Level2)?,
+        })
+    }
+
+    async fn governance_key_set(
+        &self,
+        ctx: &Context<'_>,
+        id: types::ID,
+    ) -> ApiResult<GovernanceKeySet> {
+        let id: i64 = id.try_into().map_err(ApiError::InvalidIdInt)?;
+        sqlx::query_as!(
+            GovernanceKeySet,
+            r#"SELECT
+                id,
+                level as "level: GovernanceKeyLevel",
+                effective_block_height,
+                replaced_block_height,
+                keys,
+                threshold
+            FROM governance_key_sets
+            WHERE id = $1"#,
+            id
+        )
+        .fetch_optional(get_pool(ctx)?)
+        .await?
+        .ok_or(ApiError::NotFound)
+    }
+
+    /// Current and historical versions of the governance key set at the
+    /// provided level, starting with the current version.
+    async fn governance_key_sets(
+        &self,
+        ctx: &Context<'_>,
+        level: GovernanceKeyLevel,
+        #[graphql(desc = "Returns the first _n_ elements from the list.")] first: Option<usize>,
+        #[graphql(desc = "Returns the elements in the list that come after the specified cursor.")]
+        after: Option<String>,
+        #[graphql(desc = "Returns the last _n_ elements from the list.")] last: Option<usize>,
+        #[graphql(desc = "Returns the elements in the list that come before the specified cursor.")]
+        before: Option<String>,
+    ) -> ApiResult<connection::Connection<String, GovernanceKeySet>> {
+        // Key sets are only replaced by rare chain updates, so every version is
+        // fetched.
+        let key_sets = sqlx::query_as!(
+            GovernanceKeySet,
+            r#"SELECT
+                id,
+                level as "level: GovernanceKeyLevel",
+                effective_block_height,
+                replaced_block_height,
+                keys,
+                threshold
+            FROM governance_key_sets
+            WHERE level = $1
+            ORDER BY id DESC"#,
+            level as GovernanceKeyLevel
+        )
+        .fetch_all(get_pool(ctx)?)
+        .await?;
+        connection_from_slice(key_sets, first, after, last, before)
+    }
+}
+
+/// The governance key sets authorizing chain updates.
+#[derive(SimpleObject)]
+pub struct GovernanceKeys {
+    /// The root keys.
+    root:   GovernanceKeySet,
+    /// The level 1 keys.
+    level1: GovernanceKeySet,
+    /// The level 2 keys.
+    level2: GovernanceKeySet,
+}
+
+/// A version of a governance key set.
+#[derive(Clone)]
+pub struct GovernanceKeySet {
+    id:                     i64,
+    level:                  GovernanceKeyLevel,
+    effective_block_height: BlockHeight,
+    replaced_block_height:  Option<BlockHeight>,
+    keys:                   Vec<String>,
+    threshold:              Option<i32>,
+}
+
+#[Object]
+impl GovernanceKeySet {
+    async fn id(&self) -> types::ID { types::ID::from(self.id) }
+
+    async fn level(&self) -> GovernanceKeyLevel { self.level }
+
+    /// The first block where this version of the key set is in effect.
+    async fn effective_block(&self, ctx: &Context<'_>) -> ApiResult<Block> {
+        Block::query_by_height(get_pool(ctx)?, self.effective_block_height).await
+    }
+
+    /// The first block where this version of the key set got replaced. Null
+    /// for the current version.
+    async fn replaced_block(&self, ctx: &Context<'_>) -> ApiResult<Option<Block>> {
+        let Some(height) = self.replaced_block_height else {
+            return Ok(None);
+        };
+        Ok(Some(Block::query_by_height(get_pool(ctx)?, height).await?))
+    }
+
+    /// Whether this is the current version of the key set.
+    async fn is_current(&self) -> bool { self.replaced_block_height.is_none() }
+
+    /// The keys of the key set.
+    async fn keys(&self) -> ApiResult<Vec<GovernanceKey>> {
+        self.keys
+            .iter()
+            .enumerate()
+            .map(|(index, verify_key)| {
+                Ok(GovernanceKey {
+                    index:      index.try_into()?,
+                    verify_key: verify_key.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Number of keys needed to authorize an update. Only present for root and
+    /// level 1 key sets, level 2 key sets have a threshold for each access
+    /// structure.
+    async fn threshold(&self) -> Option<i32> { self.threshold }
+
+    /// The access structures for each kind of chain update. Only non-empty for
+    /// level 2 key sets.
+    async fn access_structures(
+        &self,
+        ctx: &Context<'_>,
+    ) -> ApiResult<Vec<GovernanceAccessStructure>> {
+        let access_structures = sqlx::query_as!(
+            GovernanceAccessStructure,
+            r#"SELECT
+                authorization_kind as "authorization: Level2Authorization",
+                key_indices,
+                threshold
+            FROM governance_level2_access_structures
+            WHERE key_set_id = $1
+            ORDER BY authorization_kind"#,
+            self.id
+        )
+        .fetch_all(get_pool(ctx)?)
+        .await?;
+        Ok(access_structures)
+    }
+
+    /// The chain updates authorized using this version of the key set.
+    async fn authorized_updates(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Returns the first _n_ elements from the list.")] first: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come after the specified cursor.")]
+        after: Option<String>,
+        #[graphql(desc = "Returns the last _n_ elements from the list.")] last: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come before the specified cursor.")]
+        before: Option<String>,
+    ) -> ApiResult<connection::Connection<String, Transaction>> {
+        let config = get_config(ctx)?;
+        let pool = get_pool(ctx)?;
+        let query = ConnectionQuery::<DescendingI64>::new(
+            first,
+            after,
+            last,
+            before,
+            config.transaction_connection_limit,
+        )?;
+        let update_types = self.level.authorized_update_types();
+        let payload_variant = self.level.authorized_payload_variant();
+
+        let row_stream = sqlx::query_as!(
+            Transaction,
+            r#"SELECT * FROM (
+                SELECT
+                    index,
+                    block_height,
+                    hash,
+                    ccd_cost,
+                    energy_cost,
+                    sender_index,
+                    type as "tx_type: DbTransactionType",
+                    type_account as "type_account: AccountTransactionType",
+                    type_credential_deployment as "type_credential_deployment: CredentialDeploymentTransactionType",
+                    type_update as "type_update: UpdateTransactionType",
+                    success,
+                    events as "events: sqlx::types::Json<Vec<Event>>",
+                    reject as "reject: sqlx::types::Json<TransactionRejectReason>"
+                FROM transactions
+                WHERE type_update = ANY($5)
+                    AND block_height >= $6
+                    AND ($7::BIGINT IS NULL OR block_height < $7)
+                    AND ($8::TEXT IS NULL OR EXISTS (
+                        SELECT FROM chain_update_queue
+                        WHERE chain_update_queue.transaction_index = transactions.index
+                            AND chain_update_queue.payload ? $8
+                    ))
+                    AND index < $1 AND index > $2
+                ORDER BY
+                    (CASE WHEN $3 THEN index END) ASC,
+                    (CASE WHEN NOT $3 THEN index END) DESC
+                LIMIT $4
+            ) ORDER BY index DESC"#,
+            i64::from(query.from),
+            i64::from(query.to),
+            query.is_last,
+            query.limit,
+            &update_types as &[UpdateTransactionType],
+            self.effective_block_height,
+            self.replaced_block_height,
+            payload_variant
+        )
+        .fetch(pool);
+
+        let (mut connection, page_indices) =
+            connection_from_id_stream(row_stream, |tx| tx.index).await?;
+        if let Some((page_min_id, page_max_id)) = page_indices {
+            let result = sqlx::query!(
+                "SELECT MAX(index) as max_id, MIN(index) as min_id
+                FROM transactions
+                WHERE type_update = ANY($1)
+                    AND block_height >= $2
+                    AND ($3::BIGINT IS NULL OR block_height < $3)
+                    AND ($4::TEXT IS NULL OR EXISTS (
+                        SELECT FROM chain_update_queue
+                        WHERE chain_update_queue.transaction_index = transactions.index
+                            AND chain_update_queue.payload ? $4
+                    ))",
+                &update_types as &[UpdateTransactionType],
+                self.effective_block_height,
+                self.replaced_block_height,
+                payload_variant
+            )
+            .fetch_one(pool)
+            .await?;
+            connection.has_previous_page = result.max_id.is_some_and(|db_max| db_max > page_max_id);
+            connection.has_next_page = result.min_id.is_some_and(|db_min| db_min < page_min_id);
+        }
+        Ok(connection)
+    }
+}
+
+/// A key in a governance key set.
+#[derive(SimpleObject)]
+pub struct GovernanceKey {
+    /// The index of the key in the key set.
+    index:      u16,
+    /// The hex encoded Ed25519 verify key.
+    verify_key: String,
+}
+
+/// The keys authorized to sign a kind of chain update, as part of a level 2
+/// key set.
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct GovernanceAccessStructure {
+    /// The kind of chain updates authorized.
+    authorization: Level2Authorization,
+    /// Indices of the keys in the level 2 key set authorized to sign this kind
+    /// of chain update.
+    key_indices:   Vec<i32>,
+    /// Number of the authorized keys needed to sign this kind of chain update.
+    threshold:     i32,
+}
+
+#[ComplexObject]
+impl GovernanceAccessStructure {
+    /// The types of chain updates authorized by this access structure.
+    async fn authorized_update_types(&self) -> Vec<UpdateTransactionType> {
+        self.authorization.authorized_update_types().to_vec()
+    }
+}
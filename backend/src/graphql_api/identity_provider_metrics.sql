@@ -0,0 +1,52 @@
+-- Counts the accounts created through an identity provider in buckets, by
+-- counting the accounts with an initial credential issued by the identity
+-- provider, created at or before (i.e. <=) the latest transaction before the
+-- start of the bucket and the same number for the latest transaction before the
+-- next bucket. Accounts from genesis have no transaction and are counted before
+-- any transaction.
+WITH
+  thresholds AS (
+    SELECT
+      bucket_time.bucket_start as bucket_time,
+      -- Find the latest transaction index before the start of the bucket
+      (
+        SELECT transactions.index
+        FROM transactions
+        JOIN blocks ON height = transactions.block_height
+        WHERE slot_time < bucket_time.bucket_start
+        ORDER BY slot_time DESC
+        LIMIT 1
+      ) AS tx_start,
+      -- Find the latest transaction index before the end of the bucket
+      (
+        SELECT transactions.index
+        FROM transactions
+        JOIN blocks ON height = transactions.block_height
+        WHERE slot_time < bucket_time.bucket_start + $3::interval
+        ORDER BY slot_time DESC
+        LIMIT 1
+      ) AS tx_end
+    FROM date_bin_series(
+        $3::interval,
+        $2,
+        $1
+    ) AS bucket_time
+  )
+SELECT
+  bucket_time AS "bucket_time!",
+  (
+    SELECT COUNT(*)
+    FROM account_credentials
+    WHERE identity_provider_id = $4
+      AND credential_index = 0
+      AND COALESCE(transaction_index, -1) <= COALESCE(tx_start, -1)
+  ) AS "start_count!",
+  (
+    SELECT COUNT(*)
+    FROM account_credentials
+    WHERE identity_provider_id = $4
+      AND credential_index = 0
+      AND COALESCE(transaction_index, -1) <= COALESCE(tx_end, -1)
+  ) AS "end_count!"
+FROM thresholds
+ORDER BY bucket_time;
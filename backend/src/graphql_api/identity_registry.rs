@@ -0,0 +1,333 @@
+use super::{
+    block::Block, get_pool, transaction::Transaction, ApiError, ApiResult, MetricsPeriod, TimeSpan,
+};
+use crate::{
+    connection::connection_from_slice,
+    scalar_types::{BlockHeight, DateTime, TransactionIndex},
+};
+use async_graphql::{connection, types, Context, Object, SimpleObject};
+use chrono::Utc;
+use sqlx::{postgres::types::PgInterval, PgPool};
+use std::sync::Arc;
+
+#[derive(Default)]
+pub struct QueryIdentityRegistry;
+
+#[Object]
+impl QueryIdentityRegistry {
+    async fn identity_provider(
+        &self,
+        ctx: &Context<'_>,
+        id: types::ID,
+    ) -> ApiResult<IdentityProvider> {
+        let id: i64 = id.try_into().map_err(ApiError::InvalidIdInt)?;
+        IdentityProvider::query_by_id(get_pool(ctx)?, id).await?.ok_or(ApiError::NotFound)
+    }
+
+    /// The identity providers registered on chain, ordered by their identity.
+    async fn identity_providers(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Returns the first _n_ elements from the list.")] first: Option<usize>,
+        #[graphql(desc = "Returns the elements in the list that come after the specified cursor.")]
+        after: Option<String>,
+        #[graphql(desc = "Returns the last _n_ elements from the list.")] last: Option<usize>,
+        #[graphql(desc = "Returns the elements in the list that come before the specified cursor.")]
+        before: Option<String>,
+    ) -> ApiResult<connection::Connection<String, IdentityProvider>> {
+        // Identity providers are only added by rare chain updates, so every one of
+        // them is fetched.
+        let identity_providers = sqlx::query_as!(
+            IdentityProvider,
+            "SELECT
+                id,
+                name,
+                url,
+                description,
+                verify_key,
+                cdi_verify_key,
+                creation_block_height,
+                transaction_index
+            FROM identity_providers
+            ORDER BY id ASC"
+        )
+        .fetch_all(get_pool(ctx)?)
+        .await?;
+        connection_from_slice(identity_providers, first, after, last, before)
+    }
+
+    async fn anonymity_revoker(
+        &self,
+        ctx: &Context<'_>,
+        id: types::ID,
+    ) -> ApiResult<AnonymityRevoker> {
+        let id: i64 = id.try_into().map_err(ApiError::InvalidIdInt)?;
+        sqlx::query_as!(
+            AnonymityRevoker,
+            "SELECT
+                id,
+                name,
+                url,
+                description,
+                public_key,
+                creation_block_height,
+                transaction_index
+            FROM anonymity_revokers
+            WHERE id = $1",
+            id
+        )
+        .fetch_optional(get_pool(ctx)?)
+        .await?
+        .ok_or(ApiError::NotFound)
+    }
+
+    /// The anonymity revokers registered on chain, ordered by their identity.
+    async fn anonymity_revokers(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Returns the first _n_ elements from the list.")] first: Option<usize>,
+        #[graphql(desc = "Returns the elements in the list that come after the specified cursor.")]
+        after: Option<String>,
+        #[graphql(desc = "Returns the last _n_ elements from the list.")] last: Option<usize>,
+        #[graphql(desc = "Returns the elements in the list that come before the specified cursor.")]
+        before: Option<String>,
+    ) -> ApiResult<connection::Connection<String, AnonymityRevoker>> {
+        // Anonymity revokers are only added by rare chain updates, so every one of
+        // them is fetched.
+        let anonymity_revokers = sqlx::query_as!(
+            AnonymityRevoker,
+            "SELECT
+                id,
+                name,
+                url,
+                description,
+                public_key,
+                creation_block_height,
+                transaction_index
+            FROM anonymity_revokers
+            ORDER BY id ASC"
+        )
+        .fetch_all(get_pool(ctx)?)
+        .await?;
+        connection_from_slice(anonymity_revokers, first, after, last, before)
+    }
+}
+
+/// An identity provider, issuing the identities behind the credentials of
+/// accounts.
+#[derive(Clone)]
+pub struct IdentityProvider {
+    id:                    i64,
+    name:                  String,
+    url:                   String,
+    description:           String,
+    verify_key:            String,
+    cdi_verify_key:        String,
+    creation_block_height: BlockHeight,
+    transaction_index:     Option<TransactionIndex>,
+}
+
+impl IdentityProvider {
+    pub async fn query_by_id(pool: &PgPool, id: i64) -> ApiResult<Option<Self>> {
+        let identity_provider = sqlx::query_as!(
+            IdentityProvider,
+            "SELECT
+                id,
+                name,
+                url,
+                description,
+                verify_key,
+                cdi_verify_key,
+                creation_block_height,
+                transaction_index
+            FROM identity_providers
+            WHERE id = $1",
+            id
+        )
+        .fetch_optional(pool)
+        .await?;
+        Ok(identity_provider)
+    }
+}
+
+#[Object]
+impl IdentityProvider {
+    async fn id(&self) -> types::ID { types::ID::from(self.id) }
+
+    /// The identity of the identity provider, as used on chain.
+    async fn ip_identity(&self) -> i64 { self.id }
+
+    async fn name(&self) -> &str { &self.name }
+
+    async fn url(&self) -> &str { &self.url }
+
+    async fn description(&self) -> &str { &self.description }
+
+    /// Hex encoded public key used to verify the signatures of the identity
+    /// provider on identity objects.
+    async fn verify_key(&self) -> &str { &self.verify_key }
+
+    /// Hex encoded Ed25519 public key used to verify initial account creations.
+    async fn cdi_verify_key(&self) -> &str { &self.cdi_verify_key }
+
+    /// The block where the identity provider got registered.
+    async fn creation_block(&self, ctx: &Context<'_>) -> ApiResult<Block> {
+        Block::query_by_height(get_pool(ctx)?, self.creation_block_height).await
+    }
+
+    /// The chain update adding the identity provider. Null for identity
+    /// providers present in genesis.
+    async fn transaction(&self, ctx: &Context<'_>) -> ApiResult<Option<Transaction>> {
+        let Some(index) = self.transaction_index else {
+            return Ok(None);
+        };
+        Transaction::query_by_index(get_pool(ctx)?, index).await
+    }
+
+    /// Number of accounts created using an identity issued by the identity
+    /// provider.
+    async fn accounts_created(&self, ctx: &Context<'_>) -> ApiResult<i64> {
+        let count = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!"
+            FROM account_credentials
+            WHERE identity_provider_id = $1 AND credential_index = 0"#,
+            self.id
+        )
+        .fetch_one(get_pool(ctx)?)
+        .await?;
+        Ok(count)
+    }
+
+    /// Number of accounts created using an identity issued by the identity
+    /// provider over the requested period.
+    async fn accounts_created_metrics(
+        &self,
+        ctx: &Context<'_>,
+        period: MetricsPeriod,
+    ) -> ApiResult<IdentityProviderAccountsMetrics> {
+        let pool = get_pool(ctx)?;
+        let end_time = Utc::now();
+        let before_time = end_time - period.as_duration();
+        let bucket_width = period.bucket_width();
+
+        // The bucket interval, e.g. 6 hours.
+        let bucket_interval: PgInterval =
+            bucket_width.try_into().map_err(|err| ApiError::DurationOutOfRange(Arc::new(err)))?;
+
+        let rows = sqlx::query_file!(
+            "src/graphql_api/identity_provider_metrics.sql",
+            end_time,
+            before_time,
+            bucket_interval,
+            self.id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let x_time = rows.iter().map(|r| r.bucket_time).collect();
+        let y_accounts_created: Vec<i64> =
+            rows.iter().map(|r| r.end_count - r.start_count).collect();
+        let accounts_created = y_accounts_created.iter().sum();
+
+        Ok(IdentityProviderAccountsMetrics {
+            accounts_created,
+            buckets: IdentityProviderAccountsMetricsBuckets {
+                bucket_width: TimeSpan(bucket_width),
+                x_time,
+                y_accounts_created,
+            },
+        })
+    }
+}
+
+#[derive(SimpleObject)]
+struct IdentityProviderAccountsMetrics {
+    /// Total number of accounts created through the identity provider in
+    /// requested period.
+    accounts_created: i64,
+
+    buckets: IdentityProviderAccountsMetricsBuckets,
+}
+
+#[derive(SimpleObject)]
+struct IdentityProviderAccountsMetricsBuckets {
+    /// The width (time interval) of each bucket.
+    bucket_width: TimeSpan,
+
+    /// Start of the bucket time period. Intended x-axis value.
+    #[graphql(name = "x_Time")]
+    x_time: Vec<DateTime>,
+
+    /// Number of accounts created through the identity provider within bucket
+    /// time period. Intended y-axis value.
+    #[graphql(name = "y_AccountsCreated")]
+    y_accounts_created: Vec<i64>,
+}
+
+/// An anonymity revoker, able to reveal the identity behind a credential
+/// together with other anonymity revokers.
+#[derive(Clone)]
+pub struct AnonymityRevoker {
+    id:                    i64,
+    name:                  String,
+    url:                   String,
+    description:           String,
+    public_key:            String,
+    creation_block_height: BlockHeight,
+    transaction_index:     Option<TransactionIndex>,
+}
+
+impl AnonymityRevoker {
+    pub async fn query_by_ids(pool: &PgPool, ids: &[i64]) -> ApiResult<Vec<Self>> {
+        let anonymity_revokers = sqlx::query_as!(
+            AnonymityRevoker,
+            "SELECT
+                id,
+                name,
+                url,
+                description,
+                public_key,
+                creation_block_height,
+                transaction_index
+            FROM anonymity_revokers
+            WHERE id = ANY($1)
+            ORDER BY id ASC",
+            ids
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(anonymity_revokers)
+    }
+}
+
+#[Object]
+impl AnonymityRevoker {
+    async fn id(&self) -> types::ID { types::ID::from(self.id) }
+
+    /// The identity of the anonymity revoker, as used on chain.
+    async fn ar_identity(&self) -> i64 { self.id }
+
+    async fn name(&self) -> &str { &self.name }
+
+    async fn url(&self) -> &str { &self.url }
+
+    async fn description(&self) -> &str { &self.description }
+
+    /// Hex encoded public key used for encrypting data for the anonymity
+    /// revoker.
+    async fn public_key(&self) -> &str { &self.public_key }
+
+    /// The block where the anonymity revoker got registered.
+    async fn creation_block(&self, ctx: &Context<'_>) -> ApiResult<Block> {
+        Block::query_by_height(get_pool(ctx)?, self.creation_block_height).await
+    }
+
+    /// The chain update adding the anonymity revoker. Null for anonymity
+    /// revokers present in genesis.
+    async fn transaction(&self, ctx: &Context<'_>) -> ApiResult<Option<Transaction>> {
+        let Some(index) = self.transaction_index else {
+            return Ok(None);
+        };
+        Transaction::query_by_index(get_pool(ctx)?, index).await
+    }
+}
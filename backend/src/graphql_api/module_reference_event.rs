@@ -1,4 +1,7 @@
-use super::{get_config, get_pool, ApiError, ApiResult, InternalError};
+use super::{
+    get_config, get_pool, get_schema_cache, ApiError, ApiResult, InternalError,
+    ModuleVerificationMethod,
+};
 use crate::{
     address::{AccountAddress, ContractAddress},
     scalar_types::{BlockHeight, DateTime, ModuleReference, TransactionHash, TransactionIndex},
@@ -6,7 +9,6 @@ use crate::{
     transaction_reject::TransactionRejectReason,
 };
 use async_graphql::{ComplexObject, Context, Object, SimpleObject};
-use concordium_rust_sdk::base::contracts_common::schema::VersionedModuleSchema;
 
 #[derive(Default)]
 pub struct QueryModuleReferenceEvent;
@@ -24,7 +26,7 @@ impl QueryModuleReferenceEvent {
             r#"SELECT
                 blocks.height as block_height,
                 smart_contract_modules.transaction_index as transaction_index,
-                schema as display_schema,
+                schema_uploaded_at,
                 blocks.slot_time as block_slot_time,
                 transactions.hash as transaction_hash,
                 accounts.address as sender
@@ -39,12 +41,10 @@ impl QueryModuleReferenceEvent {
         .await?
         .ok_or(ApiError::NotFound)?;
 
-        let display_schema = row
-            .display_schema
-            .as_ref()
-            .map(|s| VersionedModuleSchema::new(s, &None).map(|schema| schema.to_string()))
-            .transpose()
-            .map_err(InternalError::from)?;
+        let display_schema = get_schema_cache(ctx)?
+            .get(pool, &module_reference, row.schema_uploaded_at)
+            .await?
+            .map(|schema| schema.to_string());
 
         Ok(ModuleReferenceEvent {
             module_reference,
@@ -54,6 +54,7 @@ impl QueryModuleReferenceEvent {
             transaction_index: row.transaction_index,
             block_slot_time: row.block_slot_time,
             display_schema,
+            schema_uploaded_at: row.schema_uploaded_at,
         })
     }
 }
@@ -61,13 +62,16 @@ impl QueryModuleReferenceEvent {
 #[derive(SimpleObject)]
 #[graphql(complex)]
 pub struct ModuleReferenceEvent {
-    pub module_reference:  ModuleReference,
-    pub sender:            AccountAddress,
-    pub block_height:      BlockHeight,
-    pub transaction_hash:  TransactionHash,
-    pub transaction_index: TransactionIndex,
-    pub block_slot_time:   DateTime,
-    pub display_schema:    Option<String>,
+    pub module_reference:   ModuleReference,
+    pub sender:             AccountAddress,
+    pub block_height:       BlockHeight,
+    pub transaction_hash:   TransactionHash,
+    pub transaction_index:  TransactionIndex,
+    pub block_slot_time:    DateTime,
+    pub display_schema:     Option<String>,
+    /// Time at which the displayed schema was uploaded and verified against
+    /// the module. Null if the schema is embedded in the module or missing.
+    pub schema_uploaded_at: Option<DateTime>,
 }
 #[ComplexObject]
 impl ModuleReferenceEvent {
@@ -242,6 +246,110 @@ impl ModuleReferenceEvent {
             items,
         })
     }
+
+    /// The build information embedded by a verifiable build. Null if the
+    /// module is not built using a verifiable build.
+    async fn build_info(&self, ctx: &Context<'_>) -> ApiResult<Option<VerifiableBuildInfo>> {
+        let row = sqlx::query!(
+            "SELECT build_archive_hash, build_image, build_command, build_source_link
+            FROM smart_contract_modules
+            WHERE module_reference = $1",
+            self.module_reference
+        )
+        .fetch_one(get_pool(ctx)?)
+        .await?;
+        let (Some(archive_hash), Some(image), Some(build_command)) =
+            (row.build_archive_hash, row.build_image, row.build_command)
+        else {
+            return Ok(None);
+        };
+        Ok(Some(VerifiableBuildInfo {
+            archive_hash,
+            image,
+            build_command,
+            source_link: row.build_source_link,
+        }))
+    }
+
+    /// The verification of the source code of the module. Null if no source
+    /// is verified.
+    async fn source_verification(
+        &self,
+        ctx: &Context<'_>,
+    ) -> ApiResult<Option<ModuleSourceVerification>> {
+        let verification = sqlx::query_as!(
+            ModuleSourceVerification,
+            r#"SELECT
+                module_reference,
+                method as "method: ModuleVerificationMethod",
+                verified_at,
+                attester_key,
+                attestation_signature
+            FROM module_source_verifications
+            WHERE module_reference = $1"#,
+            self.module_reference
+        )
+        .fetch_optional(get_pool(ctx)?)
+        .await?;
+        Ok(verification)
+    }
+}
+
+/// The build information embedded in a module by the verifiable builds of
+/// `cargo concordium`.
+#[derive(SimpleObject)]
+struct VerifiableBuildInfo {
+    /// Hex encoded SHA-256 hash of the source archive.
+    archive_hash:  String,
+    /// The Docker image used for building.
+    image:         String,
+    /// The command used for building, run in the extracted source archive.
+    build_command: Vec<String>,
+    /// Link to where the source archive is published.
+    source_link:   Option<String>,
+}
+
+/// A source archive verified against a module.
+#[derive(SimpleObject)]
+#[graphql(complex)]
+struct ModuleSourceVerification {
+    #[graphql(skip)]
+    module_reference:      ModuleReference,
+    method:                ModuleVerificationMethod,
+    verified_at:           DateTime,
+    /// Hex encoded Ed25519 public key of the attester. Null unless verified
+    /// by attestation.
+    attester_key:          Option<String>,
+    /// Hex encoded signature of the attester on the module reference followed
+    /// by the hash of the source archive. Null unless verified by
+    /// attestation.
+    attestation_signature: Option<String>,
+}
+
+#[ComplexObject]
+impl ModuleSourceVerification {
+    /// The text files of the source archive, ordered by path.
+    async fn files(&self, ctx: &Context<'_>) -> ApiResult<Vec<ModuleSourceFile>> {
+        let files = sqlx::query_as!(
+            ModuleSourceFile,
+            "SELECT path, content
+            FROM module_source_files
+            WHERE module_reference = $1
+            ORDER BY path ASC",
+            self.module_reference
+        )
+        .fetch_all(get_pool(ctx)?)
+        .await?;
+        Ok(files)
+    }
+}
+
+/// A text file in a verified source archive.
+#[derive(SimpleObject)]
+struct ModuleSourceFile {
+    /// Path of the file in the source archive.
+    path:    String,
+    content: String,
 }
 
 #[derive(SimpleObject)]
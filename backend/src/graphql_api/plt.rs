@@ -15,7 +15,11 @@ use crate::{
 };
 
 use super::{
-    block::Block, get_config, get_pool, ApiError, ApiResult, ConnectionQuery, InternalError,
+    block::Block,
+    get_config, get_pool,
+    plt_token_list::{PltTokenListChange, PltTokenListMember, PltTokenPauseChange},
+    token_distribution::TokenHolderDistribution,
+    ApiError, ApiResult, ConnectionQuery, InternalError, PltTokenList,
 };
 
 use futures::TryStreamExt;
@@ -466,6 +470,128 @@ impl PltToken {
 
         Ok(unique_holder)
     }
+
+    /// How the balance of the token is distributed among its holders, with the
+    /// `top` holders with the largest balances.
+    async fn holder_distribution<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(default = 10)] top: u64,
+    ) -> ApiResult<TokenHolderDistribution> {
+        let config = get_config(ctx)?;
+        TokenHolderDistribution::query_plt(
+            get_pool(ctx)?,
+            config,
+            self.index,
+            self.decimal.unwrap_or(0),
+            top.min(config.plt_account_amount_connection_limit),
+        )
+        .await
+    }
+
+    /// Whether the operations of the token are paused by the token module.
+    async fn is_paused<'a>(&self, ctx: &Context<'a>) -> ApiResult<bool> {
+        let paused =
+            sqlx::query_scalar!("SELECT paused FROM plt_tokens WHERE index = $1", self.index)
+                .fetch_one(get_pool(ctx)?)
+                .await?;
+        Ok(paused)
+    }
+
+    /// The accounts currently on the allow list of the token.
+    async fn allow_list<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(desc = "Returns the first _n_ elements from the list.")] first: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come after the specified cursor.")]
+        after: Option<String>,
+        #[graphql(desc = "Returns the last _n_ elements from the list.")] last: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come before the specified cursor.")]
+        before: Option<String>,
+    ) -> ApiResult<connection::Connection<String, PltTokenListMember>> {
+        self.list_members(ctx, PltTokenList::Allow, first, after, last, before).await
+    }
+
+    /// The accounts currently on the deny list of the token.
+    async fn deny_list<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(desc = "Returns the first _n_ elements from the list.")] first: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come after the specified cursor.")]
+        after: Option<String>,
+        #[graphql(desc = "Returns the last _n_ elements from the list.")] last: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come before the specified cursor.")]
+        before: Option<String>,
+    ) -> ApiResult<connection::Connection<String, PltTokenListMember>> {
+        self.list_members(ctx, PltTokenList::Deny, first, after, last, before).await
+    }
+
+    /// The accounts added to and removed from the allow and deny lists of the
+    /// token, latest first.
+    async fn list_history<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(desc = "Returns the first _n_ elements from the list.")] first: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come after the specified cursor.")]
+        after: Option<String>,
+        #[graphql(desc = "Returns the last _n_ elements from the list.")] last: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come before the specified cursor.")]
+        before: Option<String>,
+    ) -> ApiResult<connection::Connection<String, PltTokenListChange>> {
+        let config = get_config(ctx)?;
+        let query = ConnectionQuery::<DescendingI64>::new(
+            first,
+            after,
+            last,
+            before,
+            config.plt_account_amount_connection_limit,
+        )?;
+        PltTokenListChange::query_by_token(get_pool(ctx)?, self.index, query).await
+    }
+
+    /// The pausing and unpausing of the token, latest first.
+    async fn pause_history<'a>(
+        &self,
+        ctx: &Context<'a>,
+        #[graphql(desc = "Returns the first _n_ elements from the list.")] first: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come after the specified cursor.")]
+        after: Option<String>,
+        #[graphql(desc = "Returns the last _n_ elements from the list.")] last: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come before the specified cursor.")]
+        before: Option<String>,
+    ) -> ApiResult<connection::Connection<String, PltTokenPauseChange>> {
+        let config = get_config(ctx)?;
+        let query = ConnectionQuery::<DescendingI64>::new(
+            first,
+            after,
+            last,
+            before,
+            config.plt_account_amount_connection_limit,
+        )?;
+        PltTokenPauseChange::query_by_token(get_pool(ctx)?, self.index, query).await
+    }
+}
+
+impl PltToken {
+    async fn list_members(
+        &self,
+        ctx: &Context<'_>,
+        list: PltTokenList,
+        first: Option<u64>,
+        after: Option<String>,
+        last: Option<u64>,
+        before: Option<String>,
+    ) -> ApiResult<connection::Connection<String, PltTokenListMember>> {
+        let config = get_config(ctx)?;
+        let query = ConnectionQuery::<DescendingI64>::new(
+            first,
+            after,
+            last,
+            before,
+            config.plt_account_amount_connection_limit,
+        )?;
+        PltTokenListMember::query_by_list(get_pool(ctx)?, self.index, list, query).await
+    }
 }
 
 // --------------
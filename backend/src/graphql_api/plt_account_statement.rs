@@ -0,0 +1,121 @@
+use super::{
+    get_pool, transaction::Transaction, ApiError, ApiResult, ConnectionQuery,
+    PltAccountStatementEntryType,
+};
+use crate::{
+    connection::{connection_from_id_stream, DescendingI64},
+    scalar_types::{DateTime, TokenId, TransactionIndex},
+    transaction_event::protocol_level_tokens::TokenAmount,
+};
+use async_graphql::{connection, types, ComplexObject, Context, SimpleObject};
+use bigdecimal::BigDecimal;
+use sqlx::PgPool;
+
+/// A change to the balance of a protocol level token (PLT) for an account,
+/// caused by a mint, burn or transfer.
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct PltAccountStatementEntry {
+    #[graphql(skip)]
+    id:                i64,
+    token_id:          TokenId,
+    timestamp:         DateTime,
+    entry_type:        PltAccountStatementEntryType,
+    #[graphql(skip)]
+    amount:            BigDecimal,
+    #[graphql(skip)]
+    account_balance:   BigDecimal,
+    #[graphql(skip)]
+    decimal:           i32,
+    #[graphql(skip)]
+    transaction_index: TransactionIndex,
+}
+
+impl PltAccountStatementEntry {
+    /// Query the statement entries of an account, optionally only for one
+    /// token.
+    pub async fn query_by_account(
+        pool: &PgPool,
+        account_index: i64,
+        token_id: Option<&str>,
+        query: ConnectionQuery<DescendingI64>,
+    ) -> ApiResult<connection::Connection<String, PltAccountStatementEntry>> {
+        let row_stream = sqlx::query_as!(
+            PltAccountStatementEntry,
+            r#"SELECT * FROM (
+                SELECT
+                    plt_account_statements.id,
+                    plt_tokens.token_id,
+                    plt_account_statements.slot_time AS timestamp,
+                    plt_account_statements.entry_type
+                        AS "entry_type: PltAccountStatementEntryType",
+                    plt_account_statements.amount,
+                    plt_account_statements.account_balance,
+                    plt_tokens.decimal,
+                    plt_account_statements.transaction_index
+                FROM plt_account_statements
+                    JOIN plt_tokens ON plt_tokens.index = plt_account_statements.token_index
+                WHERE plt_account_statements.account_index = $5
+                    AND ($6::TEXT IS NULL OR plt_tokens.token_id = $6)
+                    AND plt_account_statements.id < $1
+                    AND plt_account_statements.id > $2
+                ORDER BY
+                    (CASE WHEN $3 THEN plt_account_statements.id END) ASC,
+                    (CASE WHEN NOT $3 THEN plt_account_statements.id END) DESC
+                LIMIT $4
+            ) ORDER BY id DESC"#,
+            i64::from(query.from),
+            i64::from(query.to),
+            query.is_last,
+            query.limit,
+            account_index,
+            token_id
+        )
+        .fetch(pool);
+
+        let (mut connection, page_ids) =
+            connection_from_id_stream(row_stream, |entry| entry.id).await?;
+        if let Some((page_min_id, page_max_id)) = page_ids {
+            let result = sqlx::query!(
+                "SELECT MAX(plt_account_statements.id) as max_id,
+                    MIN(plt_account_statements.id) as min_id
+                FROM plt_account_statements
+                    JOIN plt_tokens ON plt_tokens.index = plt_account_statements.token_index
+                WHERE plt_account_statements.account_index = $1
+                    AND ($2::TEXT IS NULL OR plt_tokens.token_id = $2)",
+                account_index,
+                token_id
+            )
+            .fetch_one(pool)
+            .await?;
+            connection.has_previous_page = result.max_id.is_some_and(|db_max| db_max > page_max_id);
+            connection.has_next_page = result.min_id.is_some_and(|db_min| db_min < page_min_id);
+        }
+        Ok(connection)
+    }
+
+    fn token_amount(&self, amount: &BigDecimal) -> TokenAmount {
+        TokenAmount {
+            value:    amount.with_scale(0).to_string(),
+            decimals: self.decimal.to_string(),
+        }
+    }
+}
+
+#[ComplexObject]
+impl PltAccountStatementEntry {
+    async fn id(&self) -> types::ID { types::ID::from(self.id) }
+
+    /// The change of the balance, negative when tokens left the account.
+    async fn amount(&self) -> TokenAmount { self.token_amount(&self.amount) }
+
+    /// The balance of the account after the change.
+    async fn account_balance(&self) -> TokenAmount { self.token_amount(&self.account_balance) }
+
+    /// The transaction with the token event causing the change.
+    async fn transaction(&self, ctx: &Context<'_>) -> ApiResult<Transaction> {
+        Transaction::query_by_index(get_pool(ctx)?, self.transaction_index)
+            .await?
+            .ok_or(ApiError::NotFound)
+    }
+}
@@ -0,0 +1,284 @@
+use super::{
+    get_pool, transaction::Transaction, ApiError, ApiResult, ConnectionQuery, PltTokenList,
+};
+use crate::{
+    address::AccountAddress,
+    connection::{connection_from_id_stream, DescendingI64},
+    scalar_types::{DateTime, TokenId, TransactionIndex},
+};
+use async_graphql::{connection, ComplexObject, Context, SimpleObject};
+use sqlx::PgPool;
+
+/// An account on the allow or deny list of a protocol level token (PLT).
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct PltTokenListMember {
+    #[graphql(skip)]
+    plt_event_id:      i64,
+    #[graphql(skip)]
+    account_address:   String,
+    /// The time the account got added to the list.
+    added_at:          DateTime,
+    #[graphql(skip)]
+    transaction_index: TransactionIndex,
+}
+
+impl PltTokenListMember {
+    /// Query the current members of a list of a token, ordered by the time
+    /// they got added, latest first.
+    pub async fn query_by_list(
+        pool: &PgPool,
+        token_index: i64,
+        list: PltTokenList,
+        query: ConnectionQuery<DescendingI64>,
+    ) -> ApiResult<connection::Connection<String, PltTokenListMember>> {
+        let row_stream = sqlx::query_as!(
+            PltTokenListMember,
+            r#"SELECT * FROM (
+                SELECT
+                    plt_token_list_members.plt_event_id,
+                    accounts.address AS account_address,
+                    blocks.slot_time AS added_at,
+                    plt_events.transaction_index
+                FROM plt_token_list_members
+                    JOIN accounts ON accounts.index = plt_token_list_members.account_index
+                    JOIN plt_events ON plt_events.id = plt_token_list_members.plt_event_id
+                    JOIN transactions ON transactions.index = plt_events.transaction_index
+                    JOIN blocks ON blocks.height = transactions.block_height
+                WHERE plt_token_list_members.token_index = $5
+                    AND plt_token_list_members.list = $6
+                    AND plt_token_list_members.plt_event_id < $1
+                    AND plt_token_list_members.plt_event_id > $2
+                ORDER BY
+                    (CASE WHEN $3 THEN plt_token_list_members.plt_event_id END) ASC,
+                    (CASE WHEN NOT $3 THEN plt_token_list_members.plt_event_id END) DESC
+                LIMIT $4
+            ) ORDER BY plt_event_id DESC"#,
+            i64::from(query.from),
+            i64::from(query.to),
+            query.is_last,
+            query.limit,
+            token_index,
+            list as PltTokenList
+        )
+        .fetch(pool);
+
+        let (mut connection, page_ids) =
+            connection_from_id_stream(row_stream, |member| member.plt_event_id).await?;
+        if let Some((page_min_id, page_max_id)) = page_ids {
+            let result = sqlx::query!(
+                "SELECT MAX(plt_event_id) as max_id, MIN(plt_event_id) as min_id
+                FROM plt_token_list_members
+                WHERE token_index = $1 AND list = $2",
+                token_index,
+                list as PltTokenList
+            )
+            .fetch_one(pool)
+            .await?;
+            connection.has_previous_page = result.max_id.is_some_and(|db_max| db_max > page_max_id);
+            connection.has_next_page = result.min_id.is_some_and(|db_min| db_min < page_min_id);
+        }
+        Ok(connection)
+    }
+}
+
+#[ComplexObject]
+impl PltTokenListMember {
+    async fn account_address(&self) -> AccountAddress { self.account_address.clone().into() }
+
+    /// The transaction adding the account to the list.
+    async fn transaction(&self, ctx: &Context<'_>) -> ApiResult<Transaction> {
+        Transaction::query_by_index(get_pool(ctx)?, self.transaction_index)
+            .await?
+            .ok_or(ApiError::NotFound)
+    }
+}
+
+/// An account added to or removed from the allow or deny list of a protocol
+/// level token.
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct PltTokenListChange {
+    #[graphql(skip)]
+    id:                i64,
+    list:              PltTokenList,
+    #[graphql(skip)]
+    account_address:   String,
+    /// Whether the account got added to the list, otherwise it got removed.
+    added:             bool,
+    timestamp:         DateTime,
+    #[graphql(skip)]
+    transaction_index: TransactionIndex,
+}
+
+impl PltTokenListChange {
+    /// Query the changes to the lists of a token, latest first.
+    pub async fn query_by_token(
+        pool: &PgPool,
+        token_index: i64,
+        query: ConnectionQuery<DescendingI64>,
+    ) -> ApiResult<connection::Connection<String, PltTokenListChange>> {
+        let row_stream = sqlx::query_as!(
+            PltTokenListChange,
+            r#"SELECT * FROM (
+                SELECT
+                    plt_token_list_changes.id,
+                    plt_token_list_changes.list AS "list: PltTokenList",
+                    accounts.address AS account_address,
+                    plt_token_list_changes.added,
+                    plt_token_list_changes.slot_time AS timestamp,
+                    plt_token_list_changes.transaction_index
+                FROM plt_token_list_changes
+                    JOIN accounts ON accounts.index = plt_token_list_changes.account_index
+                WHERE plt_token_list_changes.token_index = $5
+                    AND plt_token_list_changes.id < $1
+                    AND plt_token_list_changes.id > $2
+                ORDER BY
+                    (CASE WHEN $3 THEN plt_token_list_changes.id END) ASC,
+                    (CASE WHEN NOT $3 THEN plt_token_list_changes.id END) DESC
+                LIMIT $4
+            ) ORDER BY id DESC"#,
+            i64::from(query.from),
+            i64::from(query.to),
+            query.is_last,
+            query.limit,
+            token_index
+        )
+        .fetch(pool);
+
+        let (mut connection, page_ids) =
+            connection_from_id_stream(row_stream, |change| change.id).await?;
+        if let Some((page_min_id, page_max_id)) = page_ids {
+            let result = sqlx::query!(
+                "SELECT MAX(id) as max_id, MIN(id) as min_id
+                FROM plt_token_list_changes
+                WHERE token_index = $1",
+                token_index
+            )
+            .fetch_one(pool)
+            .await?;
+            connection.has_previous_page = result.max_id.is_some_and(|db_max| db_max > page_max_id);
+            connection.has_next_page = result.min_id.is_some_and(|db_min| db_min < page_min_id);
+        }
+        Ok(connection)
+    }
+}
+
+#[ComplexObject]
+impl PltTokenListChange {
+    async fn account_address(&self) -> AccountAddress { self.account_address.clone().into() }
+
+    /// The transaction changing the list.
+    async fn transaction(&self, ctx: &Context<'_>) -> ApiResult<Transaction> {
+        Transaction::query_by_index(get_pool(ctx)?, self.transaction_index)
+            .await?
+            .ok_or(ApiError::NotFound)
+    }
+}
+
+/// A protocol level token got paused or unpaused by the token module.
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct PltTokenPauseChange {
+    #[graphql(skip)]
+    id:                i64,
+    /// Whether the token got paused, otherwise it got unpaused.
+    paused:            bool,
+    timestamp:         DateTime,
+    #[graphql(skip)]
+    transaction_index: TransactionIndex,
+}
+
+impl PltTokenPauseChange {
+    /// Query the pausing and unpausing of a token, latest first.
+    pub async fn query_by_token(
+        pool: &PgPool,
+        token_index: i64,
+        query: ConnectionQuery<DescendingI64>,
+    ) -> ApiResult<connection::Connection<String, PltTokenPauseChange>> {
+        let row_stream = sqlx::query_as!(
+            PltTokenPauseChange,
+            r#"SELECT * FROM (
+                SELECT
+                    id,
+                    paused,
+                    slot_time AS timestamp,
+                    transaction_index
+                FROM plt_token_pause_changes
+                WHERE token_index = $5
+                    AND id < $1
+                    AND id > $2
+                ORDER BY
+                    (CASE WHEN $3 THEN id END) ASC,
+                    (CASE WHEN NOT $3 THEN id END) DESC
+                LIMIT $4
+            ) ORDER BY id DESC"#,
+            i64::from(query.from),
+            i64::from(query.to),
+            query.is_last,
+            query.limit,
+            token_index
+        )
+        .fetch(pool);
+
+        let (mut connection, page_ids) =
+            connection_from_id_stream(row_stream, |change| change.id).await?;
+        if let Some((page_min_id, page_max_id)) = page_ids {
+            let result = sqlx::query!(
+                "SELECT MAX(id) as max_id, MIN(id) as min_id
+                FROM plt_token_pause_changes
+                WHERE token_index = $1",
+                token_index
+            )
+            .fetch_one(pool)
+            .await?;
+            connection.has_previous_page = result.max_id.is_some_and(|db_max| db_max > page_max_id);
+            connection.has_next_page = result.min_id.is_some_and(|db_min| db_min < page_min_id);
+        }
+        Ok(connection)
+    }
+}
+
+#[ComplexObject]
+impl PltTokenPauseChange {
+    /// The transaction pausing or unpausing the token.
+    async fn transaction(&self, ctx: &Context<'_>) -> ApiResult<Transaction> {
+        Transaction::query_by_index(get_pool(ctx)?, self.transaction_index)
+            .await?
+            .ok_or(ApiError::NotFound)
+    }
+}
+
+/// The membership of an account on the allow or deny list of a protocol level
+/// token.
+#[derive(SimpleObject)]
+pub struct PltTokenListMembership {
+    token_id: TokenId,
+    list:     PltTokenList,
+    /// The time the account got added to the list.
+    added_at: DateTime,
+}
+
+impl PltTokenListMembership {
+    /// Query the lists an account is currently on.
+    pub async fn query_by_account(pool: &PgPool, account_index: i64) -> ApiResult<Vec<Self>> {
+        let memberships = sqlx::query_as!(
+            PltTokenListMembership,
+            r#"SELECT
+                plt_tokens.token_id,
+                plt_token_list_members.list AS "list: PltTokenList",
+                blocks.slot_time AS added_at
+            FROM plt_token_list_members
+                JOIN plt_tokens ON plt_tokens.index = plt_token_list_members.token_index
+                JOIN plt_events ON plt_events.id = plt_token_list_members.plt_event_id
+                JOIN transactions ON transactions.index = plt_events.transaction_index
+                JOIN blocks ON blocks.height = transactions.block_height
+            WHERE plt_token_list_members.account_index = $1
+            ORDER BY plt_tokens.index, plt_token_list_members.list"#,
+            account_index
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(memberships)
+    }
+}
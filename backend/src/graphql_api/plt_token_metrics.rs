@@ -0,0 +1,166 @@
+//! Contains the GraphQL query `pltTokenMetrics` with the supply, minted and
+//! burned amounts and the number of holders of a protocol level token over
+//! time, using the cumulative metrics recorded for every block changing
+//! balances of the token.
+
+use std::sync::Arc;
+
+use async_graphql::{Context, Object, SimpleObject};
+use bigdecimal::{BigDecimal, ToPrimitive};
+use chrono::Utc;
+use sqlx::postgres::types::PgInterval;
+
+use crate::{
+    graphql_api::{get_pool, ApiError, ApiResult, DateTime, MetricsPeriod, TimeSpan},
+    scalar_types::TokenId,
+};
+
+#[derive(Default)]
+pub(crate) struct QueryPltTokenMetrics;
+
+#[derive(SimpleObject)]
+struct PltTokenMetrics {
+    /// Current total supply of the token, as the minted minus the burned
+    /// amount.
+    total_supply: f64,
+    /// Current total amount minted of the token.
+    total_minted: f64,
+    /// Current total amount burned of the token.
+    total_burned: f64,
+    /// Current number of accounts holding a non-zero balance of the token.
+    holder_count: i64,
+    /// Amount minted of the token in the requested period.
+    minted:       f64,
+    /// Amount burned of the token in the requested period.
+    burned:       f64,
+    /// Decimal places of the token.
+    decimal:      i32,
+    buckets:      PltTokenMetricsBuckets,
+}
+
+#[derive(SimpleObject)]
+struct PltTokenMetricsBuckets {
+    /// The width (time interval) of each bucket.
+    bucket_width:   TimeSpan,
+    /// Start of the bucket time period. Intended x-axis value.
+    #[graphql(name = "x_Time")]
+    x_time:         Vec<DateTime>,
+    /// Total supply at the end of the bucket time period. Intended y-axis
+    /// value.
+    #[graphql(name = "y_TotalSupply")]
+    y_total_supply: Vec<f64>,
+    /// Total amount minted at the end of the bucket time period. Intended
+    /// y-axis value.
+    #[graphql(name = "y_TotalMinted")]
+    y_total_minted: Vec<f64>,
+    /// Total amount burned at the end of the bucket time period. Intended
+    /// y-axis value.
+    #[graphql(name = "y_TotalBurned")]
+    y_total_burned: Vec<f64>,
+    /// Number of accounts holding a non-zero balance at the end of the bucket
+    /// time period. Intended y-axis value.
+    #[graphql(name = "y_HolderCount")]
+    y_holder_count: Vec<i64>,
+}
+
+#[Object]
+impl QueryPltTokenMetrics {
+    /// The supply and number of holders of a protocol level token over a
+    /// period. Amounts are in the smallest unit of the token, see `decimal`.
+    async fn plt_token_metrics(
+        &self,
+        ctx: &Context<'_>,
+        token_id: TokenId,
+        period: MetricsPeriod,
+    ) -> ApiResult<PltTokenMetrics> {
+        let pool = get_pool(ctx)?;
+        let end_time = Utc::now();
+        let before_time = end_time - period.as_duration();
+        let bucket_width = period.bucket_width();
+        let bucket_interval: PgInterval =
+            bucket_width.try_into().map_err(|err| ApiError::DurationOutOfRange(Arc::new(err)))?;
+
+        let token = sqlx::query!(
+            "SELECT index, decimal, total_minted, total_burned FROM plt_tokens WHERE token_id = $1",
+            token_id
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+
+        let rows = sqlx::query_file!(
+            "src/graphql_api/plt_token_metrics.sql",
+            end_time,
+            before_time,
+            bucket_interval,
+            token.index
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let holder_count = sqlx::query_scalar!(
+            "SELECT holder_count FROM metrics_plt_token
+            WHERE token_index = $1
+            ORDER BY event_timestamp DESC
+            LIMIT 1",
+            token.index
+        )
+        .fetch_optional(pool)
+        .await?
+        .unwrap_or(0);
+
+        // The metrics before the period, subtracted from the current totals to get the
+        // amounts minted and burned in the period.
+        let before_period = sqlx::query!(
+            "SELECT cumulative_minted, cumulative_burned FROM metrics_plt_token
+            WHERE token_index = $1 AND event_timestamp < $2
+            ORDER BY event_timestamp DESC
+            LIMIT 1",
+            token.index,
+            before_time
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        let to_f64 = |amount: &BigDecimal| amount.to_f64().unwrap_or(0.0);
+        let total_minted = token.total_minted.unwrap_or_default();
+        let total_burned = token.total_burned.unwrap_or_default();
+        let (minted, burned) = match before_period {
+            Some(row) => {
+                (&total_minted - row.cumulative_minted, &total_burned - row.cumulative_burned)
+            }
+            None => (total_minted.clone(), total_burned.clone()),
+        };
+
+        let mut x_time = Vec::with_capacity(rows.len());
+        let mut y_total_supply = Vec::with_capacity(rows.len());
+        let mut y_total_minted = Vec::with_capacity(rows.len());
+        let mut y_total_burned = Vec::with_capacity(rows.len());
+        let mut y_holder_count = Vec::with_capacity(rows.len());
+        for row in rows {
+            x_time.push(row.bucket_time);
+            y_total_supply.push(to_f64(&(&row.cumulative_minted - &row.cumulative_burned)));
+            y_total_minted.push(to_f64(&row.cumulative_minted));
+            y_total_burned.push(to_f64(&row.cumulative_burned));
+            y_holder_count.push(row.holder_count);
+        }
+
+        Ok(PltTokenMetrics {
+            total_supply: to_f64(&(&total_minted - &total_burned)),
+            total_minted: to_f64(&total_minted),
+            total_burned: to_f64(&total_burned),
+            holder_count,
+            minted: to_f64(&minted),
+            burned: to_f64(&burned),
+            decimal: token.decimal,
+            buckets: PltTokenMetricsBuckets {
+                bucket_width: TimeSpan(bucket_width),
+                x_time,
+                y_total_supply,
+                y_total_minted,
+                y_total_burned,
+                y_holder_count,
+            },
+        })
+    }
+}
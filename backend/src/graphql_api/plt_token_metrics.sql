@@ -0,0 +1,29 @@
+-- The supply and holder count of a token at the end of each bucket, using the
+-- latest cumulative metrics before (i.e. <) the start of the next bucket.
+-- Inputs:
+-- $1::timestamptz - end of the period
+-- $2::timestamptz - start of the period
+-- $3::interval    - width of the buckets, e.g. '1 days'
+-- $4::BIGINT      - token_index
+SELECT
+  bucket_time.bucket_start AS "bucket_time!",
+  COALESCE(metrics.cumulative_minted, 0) AS "cumulative_minted!",
+  COALESCE(metrics.cumulative_burned, 0) AS "cumulative_burned!",
+  COALESCE(metrics.holder_count, 0) AS "holder_count!"
+FROM date_bin_series(
+    $3::interval,
+    $2,
+    $1
+  ) AS bucket_time
+  LEFT JOIN LATERAL (
+    SELECT
+      cumulative_minted,
+      cumulative_burned,
+      holder_count
+    FROM metrics_plt_token
+    WHERE token_index = $4::BIGINT
+      AND event_timestamp < bucket_time.bucket_start + $3::interval
+    ORDER BY event_timestamp DESC
+    LIMIT 1
+  ) metrics ON true
+ORDER BY bucket_time.bucket_start;
@@ -0,0 +1,190 @@
+//! Contains the GraphQL queries `priceAt` and `priceChart` with the prices of
+//! CCD and tokens recorded by the indexer from the configured price sources.
+//!
+//! The recorded prices also provide the values in US dollars of the
+//! stablecoins and the account portfolios.
+
+use std::{collections::HashMap, sync::Arc};
+
+use async_graphql::{Context, Object, SimpleObject};
+use chrono::Utc;
+use sqlx::{postgres::types::PgInterval, PgPool};
+
+use crate::graphql_api::{
+    get_pool, stable_coin::StableCoinPeg, ApiError, ApiResult, DateTime, MetricsPeriod, TimeSpan,
+};
+
+#[derive(Default)]
+pub(crate) struct QueryPrices;
+
+#[Object]
+impl QueryPrices {
+    /// The price of an asset at a time, being the last price recorded at or
+    /// before the time. The asset is `CCD`, the token id of a protocol level
+    /// token or the token address of a CIS-2 token.
+    async fn price_at(
+        &self,
+        ctx: &Context<'_>,
+        asset: String,
+        time: DateTime,
+        #[graphql(default = "USD")] currency: String,
+    ) -> ApiResult<Option<Price>> {
+        let price = sqlx::query_as!(
+            Price,
+            "SELECT asset, currency, price, recorded_at, source
+            FROM price_history
+            WHERE asset = $1 AND currency = $2 AND recorded_at <= $3
+            ORDER BY recorded_at DESC
+            LIMIT 1",
+            asset,
+            currency.to_ascii_uppercase(),
+            time
+        )
+        .fetch_optional(get_pool(ctx)?)
+        .await?;
+        Ok(price)
+    }
+
+    /// The prices of an asset over a period.
+    async fn price_chart(
+        &self,
+        ctx: &Context<'_>,
+        asset: String,
+        #[graphql(default = "USD")] currency: String,
+        period: MetricsPeriod,
+    ) -> ApiResult<PriceChart> {
+        let pool = get_pool(ctx)?;
+        let currency = currency.to_ascii_uppercase();
+        let end_time = Utc::now();
+        let before_time = end_time - period.as_duration();
+        let bucket_width = period.bucket_width();
+        let bucket_interval: PgInterval =
+            bucket_width.try_into().map_err(|err| ApiError::DurationOutOfRange(Arc::new(err)))?;
+
+        let rows = sqlx::query_file!(
+            "src/graphql_api/price_chart.sql",
+            end_time,
+            before_time,
+            bucket_interval,
+            asset,
+            currency
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let mut buckets = PriceChartBuckets {
+            bucket_width: TimeSpan(bucket_width),
+            x_time:       Vec::with_capacity(rows.len()),
+            y_close:      Vec::with_capacity(rows.len()),
+            y_low:        Vec::with_capacity(rows.len()),
+            y_high:       Vec::with_capacity(rows.len()),
+        };
+        for row in rows {
+            buckets.x_time.push(row.bucket_time);
+            buckets.y_close.push(row.close);
+            buckets.y_low.push(row.low);
+            buckets.y_high.push(row.high);
+        }
+        Ok(PriceChart {
+            asset,
+            currency,
+            buckets,
+        })
+    }
+}
+
+/// A price of an asset recorded from a price source.
+#[derive(SimpleObject)]
+struct Price {
+    asset:       String,
+    /// ISO 4217 code of the currency of the price.
+    currency:    String,
+    /// Price of one whole unit of the asset.
+    price:       f64,
+    recorded_at: DateTime,
+    /// Name of the price source providing the price.
+    source:      String,
+}
+
+#[derive(SimpleObject)]
+struct PriceChart {
+    asset:    String,
+    currency: String,
+    buckets:  PriceChartBuckets,
+}
+
+#[derive(SimpleObject)]
+struct PriceChartBuckets {
+    /// The width (time interval) of each bucket.
+    bucket_width: TimeSpan,
+    /// Start of the bucket time period. Intended x-axis value.
+    #[graphql(name = "x_Time")]
+    x_time:       Vec<DateTime>,
+    /// The last price recorded before the end of the bucket time period,
+    /// `null` when no price is recorded yet. Intended y-axis value.
+    #[graphql(name = "y_Close")]
+    y_close:      Vec<Option<f64>>,
+    /// The lowest price recorded within the bucket time period, `null` when
+    /// no price is recorded within the bucket. Intended y-axis value.
+    #[graphql(name = "y_Low")]
+    y_low:        Vec<Option<f64>>,
+    /// The highest price recorded within the bucket time period, `null` when
+    /// no price is recorded within the bucket. Intended y-axis value.
+    #[graphql(name = "y_High")]
+    y_high:       Vec<Option<f64>>,
+}
+
+/// The latest recorded prices in US dollars of the assets, leaving out the
+/// assets without a recorded price.
+pub(super) async fn latest_dollar_prices(
+    pool: &PgPool,
+    assets: &[String],
+) -> ApiResult<HashMap<String, f64>> {
+    let prices = sqlx::query!(
+        "SELECT DISTINCT ON (asset) asset, price
+        FROM price_history
+        WHERE asset = ANY($1) AND currency = 'USD'
+        ORDER BY asset, recorded_at DESC",
+        assets
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|row| (row.asset, row.price))
+    .collect();
+    Ok(prices)
+}
+
+/// The value of one whole unit of an asset in US dollars, using the latest
+/// recorded price of the asset. Stablecoins without a recorded price are
+/// valued by their peg, using the recorded price of the currency they are
+/// pegged to, unless pegged to US dollars.
+pub(super) fn dollar_value(
+    prices: &HashMap<String, f64>,
+    asset: &str,
+    peg: Option<&StableCoinPeg>,
+) -> Option<f64> {
+    prices.get(asset).copied().or_else(|| {
+        let peg = peg?;
+        peg.value_in_dollar().or_else(|| prices.get(&peg.currency).copied())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dollar_value() {
+        let prices = HashMap::from([("EUR".to_string(), 1.08), ("EURR".to_string(), 1.07)]);
+        let eurr: StableCoinPeg = "EURR:EUR".parse().unwrap();
+        let eurx: StableCoinPeg = "EURX:EUR".parse().unwrap();
+        let usdr: StableCoinPeg = "USDR:USD".parse().unwrap();
+        let gbpr: StableCoinPeg = "GBPR:GBP".parse().unwrap();
+        assert_eq!(dollar_value(&prices, "EURR", Some(&eurr)), Some(1.07));
+        assert_eq!(dollar_value(&prices, "EURX", Some(&eurx)), Some(1.08));
+        assert_eq!(dollar_value(&prices, "USDR", Some(&usdr)), Some(1.0));
+        assert_eq!(dollar_value(&prices, "GBPR", Some(&gbpr)), None);
+        assert_eq!(dollar_value(&prices, "CCD", None), None);
+    }
+}
@@ -0,0 +1,33 @@
+-- Prices of an asset in buckets, with the last price recorded before the end of each bucket and
+-- the lowest and highest price recorded within each bucket.
+-- Inputs:
+-- $1::timestamptz - end of the period
+-- $2::timestamptz - start of the period
+-- $3::interval    - width of the buckets, e.g. '1 days'
+-- $4::TEXT        - the asset
+-- $5::TEXT        - the currency of the prices
+SELECT
+  bucket_time.bucket_start AS "bucket_time!",
+  (
+    SELECT latest.price
+    FROM price_history AS latest
+    WHERE latest.asset = $4
+      AND latest.currency = $5
+      AND latest.recorded_at < bucket_time.bucket_start + $3::interval
+    ORDER BY latest.recorded_at DESC
+    LIMIT 1
+  ) AS close,
+  MIN(price_history.price) AS low,
+  MAX(price_history.price) AS high
+FROM date_bin_series(
+    $3::interval,
+    $2,
+    $1
+  ) AS bucket_time
+  LEFT JOIN price_history
+    ON price_history.asset = $4
+    AND price_history.currency = $5
+    AND price_history.recorded_at >= bucket_time.bucket_start
+    AND price_history.recorded_at < bucket_time.bucket_start + $3::interval
+GROUP BY bucket_time.bucket_start
+ORDER BY bucket_time.bucket_start;
@@ -0,0 +1,233 @@
+//! Cache of parsed module schemas shared across requests.
+//!
+//! Parsing a module schema is expensive compared to the lookups decoding
+//! parameters and events of a contract, so the parsed schemas are kept in a
+//! bounded cache keyed by module reference. Schemas embedded in a module never
+//! change, while an uploaded schema can be replaced. Entries therefore record
+//! the time the schema got uploaded, and lookups providing another upload time
+//! reload the schema, which also covers uploads through other API instances.
+
+use super::ApiResult;
+use crate::scalar_types::DateTime;
+use concordium_rust_sdk::base::contracts_common::schema::VersionedModuleSchema;
+use prometheus_client::{
+    metrics::{counter::Counter, gauge::Gauge},
+    registry::Registry,
+};
+use sqlx::PgPool;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// Bounded cache of parsed module schemas, evicting the least recently used
+/// module when full.
+#[derive(Debug)]
+pub struct ModuleSchemaCache {
+    /// The most modules kept in the cache.
+    capacity: usize,
+    entries:  Mutex<CacheEntries>,
+    metrics:  CacheMetrics,
+}
+
+#[derive(Debug, Default)]
+struct CacheEntries {
+    /// Cached schemas by module reference.
+    modules:   HashMap<String, CacheEntry>,
+    /// Counter incremented on every access, used for finding the least
+    /// recently used entry.
+    last_used: u64,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    /// The time the schema got uploaded, `None` for embedded schemas.
+    uploaded_at: Option<DateTime>,
+    /// The parsed schema, `None` if the module has no schema.
+    schema:      Option<Arc<VersionedModuleSchema>>,
+    /// Size of the serialized schema in bytes.
+    size:        usize,
+    last_used:   u64,
+}
+
+#[derive(Debug, Default)]
+struct CacheMetrics {
+    hits:    Counter,
+    misses:  Counter,
+    entries: Gauge,
+    /// Approximate memory used, measured by the size of the serialized
+    /// schemas.
+    bytes:   Gauge,
+}
+
+impl ModuleSchemaCache {
+    pub fn new(capacity: usize, registry: &mut Registry) -> Self {
+        let metrics = CacheMetrics::default();
+        registry.register(
+            "hits",
+            "Number of module schemas found in the cache",
+            metrics.hits.clone(),
+        );
+        registry.register(
+            "misses",
+            "Number of module schemas read from the database and parsed",
+            metrics.misses.clone(),
+        );
+        registry.register("entries", "Number of cached module schemas", metrics.entries.clone());
+        registry.register(
+            "bytes",
+            "Total size in bytes of the serialized module schemas in the cache",
+            metrics.bytes.clone(),
+        );
+        Self {
+            capacity,
+            entries: Mutex::default(),
+            metrics,
+        }
+    }
+
+    /// Get the parsed schema of a module, `None` if the module has no valid
+    /// schema.
+    /// The upload time of the schema is used for detecting replaced schemas.
+    pub async fn get(
+        &self,
+        pool: &PgPool,
+        module_reference: &str,
+        uploaded_at: Option<DateTime>,
+    ) -> ApiResult<Option<Arc<VersionedModuleSchema>>> {
+        if let Some(schema) = self.lookup(module_reference, uploaded_at) {
+            self.metrics.hits.inc();
+            return Ok(schema);
+        }
+        self.metrics.misses.inc();
+        let row = sqlx::query!(
+            "SELECT schema, schema_uploaded_at FROM smart_contract_modules WHERE module_reference \
+             = $1",
+            module_reference
+        )
+        .fetch_optional(pool)
+        .await?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let size = row.schema.as_ref().map_or(0, Vec::len);
+        // Schemas failing to parse are treated as missing, as done when decoding
+        // contract events.
+        let schema = row
+            .schema
+            .and_then(|schema| VersionedModuleSchema::new(&schema, &None).ok())
+            .map(Arc::new);
+        self.insert(module_reference, CacheEntry {
+            uploaded_at: row.schema_uploaded_at,
+            schema: schema.clone(),
+            size,
+            last_used: 0,
+        });
+        Ok(schema)
+    }
+
+    /// Get the parsed schema of a module, reading the upload time of the
+    /// schema from the database.
+    pub async fn get_by_module(
+        &self,
+        pool: &PgPool,
+        module_reference: &str,
+    ) -> ApiResult<Option<Arc<VersionedModuleSchema>>> {
+        let uploaded_at = sqlx::query_scalar!(
+            "SELECT schema_uploaded_at FROM smart_contract_modules WHERE module_reference = $1",
+            module_reference
+        )
+        .fetch_optional(pool)
+        .await?
+        .flatten();
+        self.get(pool, module_reference, uploaded_at).await
+    }
+
+    /// Remove the schema of a module from the cache.
+    pub fn invalidate(&self, module_reference: &str) {
+        let mut entries = self.entries.lock().expect("Schema cache lock poisoned");
+        if let Some(entry) = entries.modules.remove(module_reference) {
+            self.metrics.bytes.dec_by(entry.size as i64);
+        }
+        self.metrics.entries.set(entries.modules.len() as i64);
+    }
+
+    fn lookup(
+        &self,
+        module_reference: &str,
+        uploaded_at: Option<DateTime>,
+    ) -> Option<Option<Arc<VersionedModuleSchema>>> {
+        let mut entries = self.entries.lock().expect("Schema cache lock poisoned");
+        entries.last_used += 1;
+        let last_used = entries.last_used;
+        let entry = entries.modules.get_mut(module_reference)?;
+        if entry.uploaded_at != uploaded_at {
+            return None;
+        }
+        entry.last_used = last_used;
+        Some(entry.schema.clone())
+    }
+
+    fn insert(&self, module_reference: &str, mut entry: CacheEntry) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().expect("Schema cache lock poisoned");
+        entries.last_used += 1;
+        entry.last_used = entries.last_used;
+        self.metrics.bytes.inc_by(entry.size as i64);
+        if let Some(replaced) = entries.modules.insert(module_reference.to_string(), entry) {
+            self.metrics.bytes.dec_by(replaced.size as i64);
+        }
+        while entries.modules.len() > self.capacity {
+            let least_recently_used = entries
+                .modules
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(module_reference, _)| module_reference.clone());
+            let Some(least_recently_used) = least_recently_used else {
+                break;
+            };
+            if let Some(evicted) = entries.modules.remove(&least_recently_used) {
+                self.metrics.bytes.dec_by(evicted.size as i64);
+            }
+        }
+        self.metrics.entries.set(entries.modules.len() as i64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use concordium_rust_sdk::base::contracts_common::schema::ModuleV0;
+
+    fn entry(uploaded_at: Option<DateTime>) -> CacheEntry {
+        CacheEntry {
+            uploaded_at,
+            schema: Some(Arc::new(VersionedModuleSchema::V0(ModuleV0 {
+                contracts: Default::default(),
+            }))),
+            size: 10,
+            last_used: 0,
+        }
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let cache = ModuleSchemaCache::new(2, &mut Registry::default());
+        cache.insert("a", entry(None));
+        cache.insert("b", entry(None));
+        assert!(cache.lookup("a", None).is_some());
+        cache.insert("c", entry(None));
+        assert!(cache.lookup("a", None).is_some());
+        assert!(cache.lookup("b", None).is_none());
+        assert!(cache.lookup("c", None).is_some());
+        assert_eq!(cache.metrics.bytes.get(), 20);
+
+        let uploaded_at = Some(chrono::Utc::now());
+        assert!(cache.lookup("a", uploaded_at).is_none());
+        cache.invalidate("a");
+        assert!(cache.lookup("a", None).is_none());
+        assert_eq!(cache.metrics.entries.get(), 1);
+    }
+}
@@ -55,6 +55,7 @@ static HASH_256_REGEX: LazyLock<Regex> =
 
 #[Object]
 impl SearchResult {
+    #[allow(clippy::too_many_arguments)]
     async fn contracts<'a>(
         &self,
         ctx: &Context<'a>,
@@ -64,6 +65,10 @@ impl SearchResult {
         #[graphql(desc = "Returns the last _n_ elements from the list.")] last: Option<u64>,
         #[graphql(desc = "Returns the elements in the list that come before the specified cursor.")]
         before: Option<String>,
+        #[graphql(
+            desc = "Only include contracts supporting every one of the standards, such as `CIS-2`."
+        )]
+        standards: Option<Vec<String>>,
     ) -> ApiResult<connection::Connection<String, contract::Contract>> {
         let contract_index_regex: Regex = Regex::new("^[0-9]+$")
             .map_err(|e| InternalError::InternalError(format!("Invalid regex: {}", e)))?;
@@ -93,16 +98,19 @@ impl SearchResult {
                     blocks.slot_time as block_slot_time,
                     transactions.block_height,
                     transactions.hash as transaction_hash,
-                    accounts.address as creator
+                    accounts.address as creator,
+                    supported_standards
                 FROM contracts
                     JOIN transactions ON transaction_index = transactions.index
                     JOIN blocks ON transactions.block_height = blocks.height
                     JOIN accounts ON transactions.sender_index = accounts.index
-                WHERE 
-                    contracts.index = $5 OR 
-                    starts_with(contracts.index_text, $6) AND      
-                    contracts.index > $1 AND 
-                    contracts.index < $2
+                WHERE (
+                        contracts.index = $5 OR 
+                        starts_with(contracts.index_text, $6) AND      
+                        contracts.index > $1 AND 
+                        contracts.index < $2
+                    )
+                    AND ($7::TEXT[] IS NULL OR supported_standards @> $7)
                 ORDER BY
                     (CASE WHEN $4 THEN contracts.index END) ASC,
                     (CASE WHEN NOT $4 THEN contracts.index END) DESC
@@ -114,7 +122,8 @@ impl SearchResult {
             query.limit,                          // $3
             query.is_last,                        // $4
             lower_case_query.parse::<i64>().ok(), // $5
-            lower_case_query                      // $6
+            lower_case_query,                     // $6
+            standards.as_deref()                  // $7
         )
         .fetch(pool);
 
@@ -149,6 +158,7 @@ impl SearchResult {
                 transaction_hash: row.transaction_hash,
                 block_slot_time: row.block_slot_time,
                 snapshot,
+                supported_standards: row.supported_standards,
             };
 
             connection
@@ -163,12 +173,15 @@ impl SearchResult {
                 "
                     SELECT MAX(index) as db_max_index, MIN(index) as db_min_index
                     FROM contracts
-                    WHERE 
-                        contracts.index = $1 OR 
-                        starts_with(contracts.index_text, $2)
+                    WHERE (
+                            contracts.index = $1 OR 
+                            starts_with(contracts.index_text, $2)
+                        )
+                        AND ($3::TEXT[] IS NULL OR supported_standards @> $3)
                 ",
                 lower_case_query.parse::<i64>().ok(), // $1
-                lower_case_query                      // $2
+                lower_case_query,                     // $2
+                standards.as_deref()                  // $3
             )
             .fetch_one(pool)
             .await?;
@@ -233,6 +246,7 @@ impl SearchResult {
                     blocks.height as block_height,
                     smart_contract_modules.transaction_index as transaction_index,
                     schema as display_schema,
+                    schema_uploaded_at,
                     blocks.slot_time as block_slot_time,
                     transactions.hash as transaction_hash,
                     accounts.address as sender
@@ -305,6 +319,7 @@ impl SearchResult {
                     transaction_index: module.transaction_index,
                     block_slot_time: module.block_slot_time,
                     display_schema,
+                    schema_uploaded_at: module.schema_uploaded_at,
                 },
             ));
         }
@@ -0,0 +1,313 @@
+use super::{
+    get_config, get_pool, token::Token, transaction::Transaction, ApiError, ApiResult,
+    Cis5BalanceChangeType, ConnectionQuery,
+};
+use crate::{
+    address::{AccountAddress, Address, ContractAddress, ContractIndex},
+    connection::{connection_from_id_stream, DescendingI64},
+    scalar_types::{Amount, BigInteger, TransactionIndex},
+};
+use async_graphql::{connection, ComplexObject, Context, Object, SimpleObject};
+
+#[derive(Default)]
+pub struct QuerySmartContractWallets;
+
+#[Object]
+impl QuerySmartContractWallets {
+    /// The public key holder in a CIS-5 smart contract wallet.
+    async fn cis5_public_key_holder(
+        &self,
+        ctx: &Context<'_>,
+        contract_address_index: ContractIndex,
+        contract_address_sub_index: ContractIndex,
+        #[graphql(desc = "Hex encoded Ed25519 public key.")] public_key: String,
+    ) -> ApiResult<Cis5PublicKeyHolder> {
+        sqlx::query_as!(
+            Cis5PublicKeyHolder,
+            "SELECT
+                id,
+                contract_index,
+                contract_sub_index,
+                public_key,
+                ccd_balance,
+                nonce,
+                transaction_index
+            FROM cis5_public_key_holders
+            WHERE contract_index = $1 AND contract_sub_index = $2 AND public_key = $3",
+            contract_address_index.0 as i64,
+            contract_address_sub_index.0 as i64,
+            public_key.to_lowercase()
+        )
+        .fetch_optional(get_pool(ctx)?)
+        .await?
+        .ok_or(ApiError::NotFound)
+    }
+
+    /// The holders of a public key across every CIS-5 smart contract wallet,
+    /// ordered by the contract index of the wallet.
+    async fn cis5_public_key_holders(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Hex encoded Ed25519 public key.")] public_key: String,
+    ) -> ApiResult<Vec<Cis5PublicKeyHolder>> {
+        let holders = sqlx::query_as!(
+            Cis5PublicKeyHolder,
+            "SELECT
+                id,
+                contract_index,
+                contract_sub_index,
+                public_key,
+                ccd_balance,
+                nonce,
+                transaction_index
+            FROM cis5_public_key_holders
+            WHERE public_key = $1
+            ORDER BY contract_index ASC, contract_sub_index ASC",
+            public_key.to_lowercase()
+        )
+        .fetch_all(get_pool(ctx)?)
+        .await?;
+        Ok(holders)
+    }
+}
+
+/// A public key holding CCD and CIS-2 tokens in a CIS-5 smart contract wallet.
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct Cis5PublicKeyHolder {
+    #[graphql(skip)]
+    id:                 i64,
+    #[graphql(skip)]
+    contract_index:     i64,
+    #[graphql(skip)]
+    contract_sub_index: i64,
+    /// Hex encoded Ed25519 public key.
+    public_key:         String,
+    #[graphql(skip)]
+    ccd_balance:        i64,
+    /// The latest nonce logged for the public key by a sponsored action. Null
+    /// if none is logged.
+    nonce:              Option<i64>,
+    #[graphql(skip)]
+    transaction_index:  TransactionIndex,
+}
+
+#[ComplexObject]
+impl Cis5PublicKeyHolder {
+    /// The smart contract wallet holding the funds.
+    async fn wallet_address(&self) -> ApiResult<ContractAddress> {
+        ContractAddress::new(self.contract_index, self.contract_sub_index)
+    }
+
+    /// The balance of CCD held for the public key.
+    async fn ccd_balance(&self) -> ApiResult<Amount> { Ok(self.ccd_balance.try_into()?) }
+
+    /// The transaction logging the first event for the public key.
+    async fn transaction(&self, ctx: &Context<'_>) -> ApiResult<Transaction> {
+        Transaction::query_by_index(get_pool(ctx)?, self.transaction_index)
+            .await?
+            .ok_or(ApiError::NotFound)
+    }
+
+    /// The balances of the CIS-2 tokens held for the public key. Tokens with a
+    /// zero balance are not included.
+    async fn token_balances(&self, ctx: &Context<'_>) -> ApiResult<Vec<Cis5TokenBalance>> {
+        let balances = sqlx::query_as!(
+            Cis5TokenBalance,
+            "SELECT
+                token_contract_index,
+                token_contract_sub_index,
+                token_id,
+                balance
+            FROM cis5_token_balances
+            WHERE holder_id = $1 AND balance != 0
+            ORDER BY token_contract_index ASC, token_contract_sub_index ASC, token_id ASC",
+            self.id
+        )
+        .fetch_all(get_pool(ctx)?)
+        .await?;
+        Ok(balances)
+    }
+
+    /// The history of the balances of the public key, with the most recent
+    /// change first.
+    async fn balance_changes(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Returns the first _n_ elements from the list.")] first: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come after the specified cursor.")]
+        after: Option<String>,
+        #[graphql(desc = "Returns the last _n_ elements from the list.")] last: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come before the specified cursor.")]
+        before: Option<String>,
+    ) -> ApiResult<connection::Connection<String, Cis5BalanceChange>> {
+        let config = get_config(ctx)?;
+        let pool = get_pool(ctx)?;
+        let query = ConnectionQuery::<DescendingI64>::new(
+            first,
+            after,
+            last,
+            before,
+            config.contract_connection_limit,
+        )?;
+        let row_stream = sqlx::query_as!(
+            Cis5BalanceChange,
+            r#"SELECT * FROM (
+                SELECT
+                    cis5_balance_changes.id,
+                    cis5_balance_changes.transaction_index,
+                    change_type as "change_type: Cis5BalanceChangeType",
+                    token_contract_index,
+                    token_contract_sub_index,
+                    token_id,
+                    cis5_balance_changes.amount,
+                    accounts.address as "counterparty_account_address?",
+                    counterparty_contract_index,
+                    counterparty_contract_sub_index,
+                    counterparty_public_key
+                FROM cis5_balance_changes
+                    LEFT JOIN accounts ON accounts.index = counterparty_account_index
+                WHERE holder_id = $5
+                    AND cis5_balance_changes.id < $1
+                    AND cis5_balance_changes.id > $2
+                ORDER BY
+                    (CASE WHEN $3 THEN cis5_balance_changes.id END) ASC,
+                    (CASE WHEN NOT $3 THEN cis5_balance_changes.id END) DESC
+                LIMIT $4
+            ) ORDER BY id DESC"#,
+            i64::from(query.from),
+            i64::from(query.to),
+            query.is_last,
+            query.limit,
+            self.id
+        )
+        .fetch(pool);
+
+        let (mut connection, page_ids) =
+            connection_from_id_stream(row_stream, |change| change.id).await?;
+        if let Some((page_min_id, page_max_id)) = page_ids {
+            let result = sqlx::query!(
+                "SELECT MAX(id) as max_id, MIN(id) as min_id
+                FROM cis5_balance_changes
+                WHERE holder_id = $1",
+                self.id
+            )
+            .fetch_one(pool)
+            .await?;
+            connection.has_previous_page = result.max_id.is_some_and(|db_max| db_max > page_max_id);
+            connection.has_next_page = result.min_id.is_some_and(|db_min| db_min < page_min_id);
+        }
+        Ok(connection)
+    }
+}
+
+/// The balance of a CIS-2 token held for a public key in a CIS-5 smart
+/// contract wallet.
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct Cis5TokenBalance {
+    #[graphql(skip)]
+    token_contract_index:     i64,
+    #[graphql(skip)]
+    token_contract_sub_index: i64,
+    /// Hex encoded token ID.
+    token_id:                 String,
+    #[graphql(skip)]
+    balance:                  bigdecimal::BigDecimal,
+}
+
+#[ComplexObject]
+impl Cis5TokenBalance {
+    /// The CIS-2 contract of the token.
+    async fn token_contract_address(&self) -> ApiResult<ContractAddress> {
+        ContractAddress::new(self.token_contract_index, self.token_contract_sub_index)
+    }
+
+    /// The token, null if the CIS-2 contract of the token is not tracked.
+    async fn token(&self, ctx: &Context<'_>) -> ApiResult<Option<Token>> {
+        match Token::query_by_contract_and_id(
+            get_pool(ctx)?,
+            self.token_contract_index,
+            self.token_contract_sub_index,
+            &self.token_id,
+        )
+        .await
+        {
+            Ok(token) => Ok(Some(token)),
+            Err(ApiError::NotFound) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn balance(&self) -> BigInteger { BigInteger::from(self.balance.clone()) }
+}
+
+/// A change to the balance of a public key in a CIS-5 smart contract wallet.
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct Cis5BalanceChange {
+    #[graphql(skip)]
+    id: i64,
+    #[graphql(skip)]
+    transaction_index: TransactionIndex,
+    change_type: Cis5BalanceChangeType,
+    #[graphql(skip)]
+    token_contract_index: Option<i64>,
+    #[graphql(skip)]
+    token_contract_sub_index: Option<i64>,
+    /// Hex encoded ID of the CIS-2 token changing balance. Null for changes
+    /// to the CCD balance.
+    token_id: Option<String>,
+    #[graphql(skip)]
+    amount: bigdecimal::BigDecimal,
+    #[graphql(skip)]
+    counterparty_account_address: Option<String>,
+    #[graphql(skip)]
+    counterparty_contract_index: Option<i64>,
+    #[graphql(skip)]
+    counterparty_contract_sub_index: Option<i64>,
+    /// The hex encoded public key on the other side of a transfer. Null for
+    /// deposits and withdrawals.
+    counterparty_public_key: Option<String>,
+}
+
+#[ComplexObject]
+impl Cis5BalanceChange {
+    async fn transaction(&self, ctx: &Context<'_>) -> ApiResult<Transaction> {
+        Transaction::query_by_index(get_pool(ctx)?, self.transaction_index)
+            .await?
+            .ok_or(ApiError::NotFound)
+    }
+
+    /// The CIS-2 contract of the token changing balance. Null for changes to
+    /// the CCD balance.
+    async fn token_contract_address(&self) -> ApiResult<Option<ContractAddress>> {
+        match (self.token_contract_index, self.token_contract_sub_index) {
+            (Some(index), Some(sub_index)) => Ok(Some(ContractAddress::new(index, sub_index)?)),
+            _ => Ok(None),
+        }
+    }
+
+    /// The amount deposited, withdrawn or transferred, in microCCD for changes
+    /// to the CCD balance.
+    async fn amount(&self) -> BigInteger { BigInteger::from(self.amount.clone()) }
+
+    /// The account or contract depositing or receiving the funds. Null for
+    /// transfers.
+    async fn counterparty_address(&self) -> ApiResult<Option<Address>> {
+        let address = match (
+            &self.counterparty_account_address,
+            self.counterparty_contract_index,
+            self.counterparty_contract_sub_index,
+        ) {
+            (Some(address), _, _) => {
+                Some(Address::AccountAddress(AccountAddress::from(address.clone())))
+            }
+            (None, Some(index), Some(sub_index)) => {
+                Some(Address::ContractAddress(ContractAddress::new(index, sub_index)?))
+            }
+            _ => None,
+        };
+        Ok(address)
+    }
+}
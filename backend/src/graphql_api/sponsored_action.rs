@@ -0,0 +1,178 @@
+use super::{
+    account::Account, get_pool, transaction::Transaction, ApiError, ApiResult, ConnectionQuery,
+    InternalError,
+};
+use crate::{
+    address::{AccountAddress, Address, ContractAddress},
+    connection::{connection_from_id_stream, DescendingI64},
+    scalar_types::{DateTime, TransactionIndex},
+};
+use async_graphql::{connection, ComplexObject, Context, SimpleObject};
+use sqlx::PgPool;
+
+/// An action signed by an account and executed by a sponsor through the
+/// `permit` entrypoint of a contract supporting CIS-3.
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct SponsoredAction {
+    #[graphql(skip)]
+    id: i64,
+    #[graphql(skip)]
+    transaction_index: TransactionIndex,
+    /// Index of the trace element reporting the call to `permit`, among the
+    /// events of the transaction.
+    trace_element_index: i64,
+    #[graphql(skip)]
+    contract_index: i64,
+    #[graphql(skip)]
+    contract_sub_index: i64,
+    #[graphql(skip)]
+    signer_account_index: Option<i64>,
+    #[graphql(skip)]
+    sponsor_account_address: Option<String>,
+    #[graphql(skip)]
+    sponsor_contract_index: Option<i64>,
+    #[graphql(skip)]
+    sponsor_contract_sub_index: Option<i64>,
+    /// The entrypoint executed on behalf of the signer.
+    entrypoint: String,
+    /// The nonce of the signer in the contract, preventing replays of the
+    /// signed message.
+    nonce: i64,
+    /// Expiry of the signature of the signer.
+    signature_expiry: DateTime,
+}
+
+impl SponsoredAction {
+    /// The sponsored actions executed by a transaction, in the order they were
+    /// executed.
+    pub async fn query_by_transaction(
+        pool: &PgPool,
+        transaction_index: TransactionIndex,
+    ) -> ApiResult<Vec<Self>> {
+        let actions = sqlx::query_as!(
+            SponsoredAction,
+            "SELECT
+                id,
+                sponsored_actions.transaction_index,
+                trace_element_index,
+                contract_index,
+                contract_sub_index,
+                signer_account_index,
+                accounts.address as \"sponsor_account_address?\",
+                sponsor_contract_index,
+                sponsor_contract_sub_index,
+                entrypoint,
+                nonce,
+                signature_expiry
+            FROM sponsored_actions
+                LEFT JOIN accounts ON accounts.index = sponsor_account_index
+            WHERE sponsored_actions.transaction_index = $1
+            ORDER BY trace_element_index ASC",
+            transaction_index
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(actions)
+    }
+
+    /// The sponsored actions signed by an account, with the most recent action
+    /// first.
+    pub async fn query_by_signer(
+        pool: &PgPool,
+        account_index: i64,
+        query: ConnectionQuery<DescendingI64>,
+    ) -> ApiResult<connection::Connection<String, SponsoredAction>> {
+        let row_stream = sqlx::query_as!(
+            SponsoredAction,
+            "SELECT * FROM (
+                SELECT
+                    id,
+                    sponsored_actions.transaction_index,
+                    trace_element_index,
+                    contract_index,
+                    contract_sub_index,
+                    signer_account_index,
+                    accounts.address as \"sponsor_account_address?\",
+                    sponsor_contract_index,
+                    sponsor_contract_sub_index,
+                    entrypoint,
+                    nonce,
+                    signature_expiry
+                FROM sponsored_actions
+                    LEFT JOIN accounts ON accounts.index = sponsor_account_index
+                WHERE signer_account_index = $5 AND id < $1 AND id > $2
+                ORDER BY
+                    (CASE WHEN $3 THEN id END) ASC,
+                    (CASE WHEN NOT $3 THEN id END) DESC
+                LIMIT $4
+            ) ORDER BY id DESC",
+            i64::from(query.from),
+            i64::from(query.to),
+            query.is_last,
+            query.limit,
+            account_index
+        )
+        .fetch(pool);
+
+        let (mut connection, page_ids) =
+            connection_from_id_stream(row_stream, |action| action.id).await?;
+        if let Some((page_min_id, page_max_id)) = page_ids {
+            let result = sqlx::query!(
+                "SELECT MAX(id) as max_id, MIN(id) as min_id
+                FROM sponsored_actions
+                WHERE signer_account_index = $1",
+                account_index
+            )
+            .fetch_one(pool)
+            .await?;
+            connection.has_previous_page = result.max_id.is_some_and(|db_max| db_max > page_max_id);
+            connection.has_next_page = result.min_id.is_some_and(|db_min| db_min < page_min_id);
+        }
+        Ok(connection)
+    }
+}
+
+#[ComplexObject]
+impl SponsoredAction {
+    /// The transaction executing the action.
+    async fn transaction(&self, ctx: &Context<'_>) -> ApiResult<Transaction> {
+        Transaction::query_by_index(get_pool(ctx)?, self.transaction_index)
+            .await?
+            .ok_or(ApiError::NotFound)
+    }
+
+    /// The contract executing the action.
+    async fn contract(&self) -> ApiResult<ContractAddress> {
+        ContractAddress::new(self.contract_index, self.contract_sub_index)
+    }
+
+    /// The account signing the action. Null if the signer is not an account
+    /// on chain.
+    async fn signer(&self, ctx: &Context<'_>) -> ApiResult<Option<Account>> {
+        let Some(signer_account_index) = self.signer_account_index else {
+            return Ok(None);
+        };
+        Account::query_by_index(get_pool(ctx)?, signer_account_index).await
+    }
+
+    /// The account or contract calling `permit`, paying for the action.
+    async fn sponsor(&self) -> ApiResult<Address> {
+        match (
+            &self.sponsor_account_address,
+            self.sponsor_contract_index,
+            self.sponsor_contract_sub_index,
+        ) {
+            (Some(address), _, _) => {
+                Ok(Address::AccountAddress(AccountAddress::from(address.clone())))
+            }
+            (None, Some(index), Some(sub_index)) => {
+                Ok(Address::ContractAddress(ContractAddress::new(index, sub_index)?))
+            }
+            _ => Err(InternalError::InternalError(
+                "Database invariant violated, sponsored action without a sponsor".to_string(),
+            )
+            .into()),
+        }
+    }
+}
@@ -1,112 +1,376 @@
-use crate::address::AccountAddress;
-use async_graphql::{Context, Object, SimpleObject};
-use chrono::{Duration, TimeZone, Utc};
-use serde::Deserialize;
-use std::{
-    collections::{HashMap, HashSet},
-    fs::File,
-    io::BufReader,
+//! Stablecoin views on the protocol level tokens (PLTs).
+//!
+//! Which PLTs are stablecoins, and the fiat currency each of them is pegged
+//! to, is configured through the `--stablecoin` option of the API service. The
+//! supplies, holders and transfers are read from the `plt_*` tables and the
+//! transfer metrics in `metrics_plt_transfer`.
+
+use super::{
+    get_config, get_pool, price, token_units, ApiError, ApiResult, ApiServiceConfig, InternalError,
 };
+use crate::transaction_event::protocol_level_tokens::TokenEventDetails;
+use async_graphql::{ComplexObject, Context, Object, SimpleObject};
+use bigdecimal::BigDecimal;
+use chrono::{Duration, Utc};
+use num_traits::ToPrimitive;
+use sqlx::{types::Json, PgPool};
+use std::{collections::HashMap, str::FromStr};
 
 pub type DateTime = chrono::DateTime<chrono::Utc>;
 
-#[derive(Clone, Deserialize, SimpleObject)]
+/// Number of latest transfers included for a stablecoin when not specified.
+const DEFAULT_LAST_N_TRANSACTIONS: u64 = 20;
+/// Number of latest transfers returned across stablecoins when not specified.
+const DEFAULT_LATEST_TRANSACTIONS: u64 = 10;
+
+/// A PLT registered as a stablecoin, provided as `TOKEN_ID:CURRENCY` where
+/// `CURRENCY` is the ISO 4217 code of the fiat currency the token is pegged
+/// to.
+#[derive(Debug, Clone)]
+pub struct StableCoinPeg {
+    pub token_id: String,
+    pub currency: String,
+}
+
+impl FromStr for StableCoinPeg {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (token_id, currency) = value.split_once(':').ok_or_else(|| {
+            anyhow::anyhow!("Expected stablecoin as TOKEN_ID:CURRENCY, got '{}'", value)
+        })?;
+        let token_id = token_id.trim();
+        let currency = currency.trim();
+        if token_id.is_empty() || currency.len() != 3 || !currency.is_ascii() {
+            anyhow::bail!(
+                "Expected stablecoin as TOKEN_ID:CURRENCY with a three letter currency code, got \
+                 '{}'",
+                value
+            );
+        }
+        Ok(Self {
+            token_id: token_id.to_string(),
+            currency: currency.to_ascii_uppercase(),
+        })
+    }
+}
+
+impl StableCoinPeg {
+    /// The value of one token in US dollars known from the peg alone.
+    pub(super) fn value_in_dollar(&self) -> Option<f64> { (self.currency == "USD").then_some(1.0) }
+}
+
+impl ApiServiceConfig {
+    /// Find the registered stablecoin with a token id. Token ids are compared
+    /// case-insensitively like on chain.
+    pub(super) fn stablecoin_peg(&self, token_id: &str) -> Option<&StableCoinPeg> {
+        self.stablecoins.iter().find(|peg| peg.token_id.eq_ignore_ascii_case(token_id))
+    }
+
+    /// The lowercase token ids of the registered stablecoins.
+    fn stablecoin_token_ids(&self) -> Vec<String> {
+        self.stablecoins.iter().map(|peg| peg.token_id.to_lowercase()).collect()
+    }
+}
+
+/// Convert an amount in the smallest unit of a token into whole tokens, as a
+/// float for the values of the API.
+pub(super) fn whole_tokens(amount: &BigDecimal, decimals: i32) -> f64 {
+    token_units(amount, decimals).to_f64().unwrap_or(0.0)
+}
+
+/// Convert a quantity of whole tokens into the smallest unit of the token.
+fn token_amount(quantity: f64, decimals: i32) -> ApiResult<BigDecimal> {
+    let quantity =
+        BigDecimal::try_from(quantity).map_err(|_| ApiError::InvalidTokenQuantity(quantity))?;
+    Ok(quantity * BigDecimal::new(1.into(), -i64::from(decimals)))
+}
+
+#[derive(SimpleObject)]
+#[graphql(complex)]
 pub struct StableCoin {
+    #[graphql(skip)]
+    token_index:          i64,
     name:                 String,
+    /// The token id of the PLT.
     symbol:               String,
-    decimal:              u8,
-    // Total supply = Created tokens – Burned tokens.
-    total_supply:         i64,
-    // Circulating supply = Tokens available on the market.
-    circulating_supply:   i64,
-    value_in_dollar:      f64,
-    total_unique_holders: Option<i64>,
-    transfers:            Option<Vec<Transfer>>, // Transfers sorted by date
-    holdings:             Option<Vec<HoldingResponse>>,
-    metadata:             Option<Metadata>,
-    transactions:         Option<Vec<TransactionMResponse>>, /* TransactionM type is only for
-                                                              * the mock dataset */
-    issuer:               String, // Keeping this issuer as string as reading from json
+    decimal:              i32,
+    /// Total supply = Minted tokens - Burned tokens.
+    total_supply:         f64,
+    /// Circulating supply = Total supply - Tokens held by the issuer.
+    circulating_supply:   f64,
+    /// ISO 4217 code of the fiat currency the token is pegged to.
+    pegged_currency:      String,
+    /// The value of one token in US dollars from the recorded prices, or from
+    /// the peg when no price is recorded, `null` when unknown.
+    value_in_dollar:      Option<f64>,
+    /// Number of accounts holding a non-zero amount of the token.
+    total_unique_holders: i64,
+    /// URL of the token metadata.
+    metadata_url:         Option<String>,
+    issuer:               String,
+    #[graphql(skip)]
+    holdings_limit:       u64,
+    #[graphql(skip)]
+    min_quantity:         Option<f64>,
+    #[graphql(skip)]
+    last_n_transactions:  u64,
 }
-#[derive(Clone, Deserialize, SimpleObject)]
-pub struct TransactionM {
-    from:             AccountAddress,
-    to:               AccountAddress,
-    asset_name:       String,
-    date_time:        DateTime,
-    amount:           f64,
-    value:            f64,
-    transaction_hash: String,
+
+impl StableCoin {
+    /// Query the registered stablecoins, optionally only the one with the
+    /// given token id.
+    async fn query(
+        pool: &PgPool,
+        config: &ApiServiceConfig,
+        token_id: Option<&str>,
+    ) -> ApiResult<Vec<Self>> {
+        let token_ids = match token_id {
+            Some(token_id) => vec![token_id.to_lowercase()],
+            None => config.stablecoin_token_ids(),
+        };
+        let rows = sqlx::query!(
+            r#"SELECT
+                plt_tokens.index,
+                plt_tokens.token_id,
+                plt_tokens.name,
+                plt_tokens.decimal,
+                plt_tokens.metadata->>'url' AS metadata_url,
+                accounts.address AS issuer,
+                COALESCE(plt_tokens.total_minted, 0) - COALESCE(plt_tokens.total_burned, 0)
+                    AS "total_supply!",
+                COALESCE((SELECT amount
+                    FROM plt_accounts
+                    WHERE plt_accounts.token_index = plt_tokens.index
+                        AND plt_accounts.account_index = plt_tokens.issuer_index), 0)
+                    AS "issuer_amount!",
+                (SELECT COUNT(*)
+                    FROM plt_accounts
+                    WHERE plt_accounts.token_index = plt_tokens.index
+                        AND plt_accounts.amount > 0) AS "holders!"
+            FROM plt_tokens
+                JOIN accounts ON accounts.index = plt_tokens.issuer_index
+            WHERE LOWER(plt_tokens.token_id) = ANY($1)
+            ORDER BY plt_tokens.index"#,
+            &token_ids
+        )
+        .fetch_all(pool)
+        .await?;
+        let mut assets: Vec<String> = rows.iter().map(|row| row.token_id.clone()).collect();
+        assets.extend(config.stablecoins.iter().map(|peg| peg.currency.clone()));
+        let prices = price::latest_dollar_prices(pool, &assets).await?;
+        let stablecoins = rows
+            .into_iter()
+            .filter_map(|row| {
+                let peg = config.stablecoin_peg(&row.token_id)?;
+                let value_in_dollar = price::dollar_value(&prices, &row.token_id, Some(peg));
+                Some(Self {
+                    token_index: row.index,
+                    name: row.name,
+                    symbol: row.token_id,
+                    decimal: row.decimal,
+                    total_supply: whole_tokens(&row.total_supply, row.decimal),
+                    circulating_supply: whole_tokens(
+                        &(&row.total_supply - &row.issuer_amount),
+                        row.decimal,
+                    ),
+                    pegged_currency: peg.currency.clone(),
+                    value_in_dollar,
+                    total_unique_holders: row.holders,
+                    metadata_url: row.metadata_url,
+                    issuer: row.issuer,
+                    holdings_limit: config.plt_account_amount_connection_limit,
+                    min_quantity: None,
+                    last_n_transactions: DEFAULT_LAST_N_TRANSACTIONS
+                        .min(config.plt_token_events_collection_limit),
+                })
+            })
+            .collect();
+        Ok(stablecoins)
+    }
+
+    /// Query a registered stablecoin by token id.
+    async fn query_by_token_id(
+        pool: &PgPool,
+        config: &ApiServiceConfig,
+        token_id: &str,
+    ) -> ApiResult<Option<Self>> {
+        if config.stablecoin_peg(token_id).is_none() {
+            return Ok(None);
+        }
+        Ok(Self::query(pool, config, Some(token_id)).await?.pop())
+    }
 }
-#[derive(Clone, Deserialize, SimpleObject)]
-pub struct TransactionMResponse {
-    from:             String,
-    to:               String,
-    asset_name:       String,
-    date_time:        DateTime,
-    amount:           f64,
-    value:            f64,
-    transaction_hash: String,
+
+#[ComplexObject]
+impl StableCoin {
+    /// The accounts holding the most of the token.
+    async fn holdings(&self, ctx: &Context<'_>) -> ApiResult<Vec<HoldingResponse>> {
+        let pool = get_pool(ctx)?;
+        let min_amount = self
+            .min_quantity
+            .map(|quantity| token_amount(quantity, self.decimal))
+            .transpose()?
+            .unwrap_or_else(|| BigDecimal::from(0));
+        let rows = sqlx::query!(
+            r#"SELECT accounts.address, plt_accounts.amount AS "amount!"
+            FROM plt_accounts
+                JOIN accounts ON accounts.index = plt_accounts.account_index
+            WHERE plt_accounts.token_index = $1
+                AND plt_accounts.amount > 0
+                AND plt_accounts.amount >= $2
+            ORDER BY plt_accounts.amount DESC
+            LIMIT $3"#,
+            self.token_index,
+            min_amount,
+            self.holdings_limit as i64
+        )
+        .fetch_all(pool)
+        .await?;
+        let holdings = rows
+            .into_iter()
+            .map(|row| {
+                let quantity = whole_tokens(&row.amount, self.decimal);
+                let percentage = if self.total_supply > 0.0 {
+                    (quantity / self.total_supply * 100.0) as f32
+                } else {
+                    0.0
+                };
+                HoldingResponse {
+                    address: row.address,
+                    asset_name: self.symbol.clone(),
+                    quantity,
+                    percentage,
+                }
+            })
+            .collect();
+        Ok(holdings)
+    }
+
+    /// The latest transfers of the token.
+    async fn transactions(&self, ctx: &Context<'_>) -> ApiResult<Vec<StableCoinTransfer>> {
+        query_transfers(get_pool(ctx)?, std::slice::from_ref(self), self.last_n_transactions).await
+    }
+}
+
+/// Query the latest transfers of the given stablecoins.
+async fn query_transfers(
+    pool: &PgPool,
+    stablecoins: &[StableCoin],
+    limit: u64,
+) -> ApiResult<Vec<StableCoinTransfer>> {
+    let by_index: HashMap<i64, &StableCoin> =
+        stablecoins.iter().map(|coin| (coin.token_index, coin)).collect();
+    let token_indices: Vec<i64> = by_index.keys().copied().collect();
+    let rows = sqlx::query!(
+        r#"SELECT
+            plt_events.token_index,
+            plt_events.token_event AS "token_event: Json<TokenEventDetails>",
+            transactions.hash,
+            blocks.slot_time
+        FROM plt_events
+            JOIN transactions ON transactions.index = plt_events.transaction_index
+            JOIN blocks ON blocks.height = transactions.block_height
+        WHERE plt_events.event_type = 'Transfer'
+            AND plt_events.token_index = ANY($1)
+        ORDER BY plt_events.id DESC
+        LIMIT $2"#,
+        &token_indices,
+        limit as i64
+    )
+    .fetch_all(pool)
+    .await?;
+    let mut transfers = Vec::with_capacity(rows.len());
+    for row in rows {
+        let TokenEventDetails::Transfer(transfer) = row.token_event.0 else {
+            return Err(InternalError::InternalError(
+                "Transfer event with other token event details".to_string(),
+            )
+            .into());
+        };
+        let Some(coin) = by_index.get(&row.token_index) else {
+            continue;
+        };
+        let amount = BigDecimal::from_str(&transfer.amount.value).map_err(|_| {
+            InternalError::InternalError("Invalid amount of token transfer".to_string())
+        })?;
+        let amount = whole_tokens(&amount, coin.decimal);
+        transfers.push(StableCoinTransfer {
+            from: transfer.from.address.to_string(),
+            to: transfer.to.address.to_string(),
+            asset_name: coin.symbol.clone(),
+            date_time: row.slot_time,
+            amount,
+            value: coin.value_in_dollar.map(|value| amount * value),
+            transaction_hash: row.hash,
+        });
+    }
+    Ok(transfers)
 }
 
-#[derive(Clone, Deserialize, SimpleObject)]
-pub struct LatestTransactionResponse {
+/// Query the cumulative transfer count and amount of each token as of a
+/// point in time.
+async fn cumulative_transfers(
+    pool: &PgPool,
+    token_indices: &[i64],
+    as_of: DateTime,
+) -> ApiResult<HashMap<i64, (i64, BigDecimal)>> {
+    let rows = sqlx::query!(
+        "SELECT DISTINCT ON (token_index)
+            token_index,
+            cumulative_transfer_count,
+            cumulative_transfer_amount
+        FROM metrics_plt_transfer
+        WHERE token_index = ANY($1) AND event_timestamp <= $2
+        ORDER BY token_index, event_timestamp DESC",
+        token_indices,
+        as_of
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            (row.token_index, (row.cumulative_transfer_count, row.cumulative_transfer_amount))
+        })
+        .collect())
+}
+
+/// A transfer of a stablecoin.
+#[derive(Clone, SimpleObject)]
+pub struct StableCoinTransfer {
     from:             String,
     to:               String,
     asset_name:       String,
     date_time:        DateTime,
     amount:           f64,
-    value:            f64,
+    /// The value of the transferred amount in US dollars, `null` when
+    /// unknown.
+    value:            Option<f64>,
     transaction_hash: String,
-    asset_metadata:   Option<Metadata>,
-}
-
-#[derive(Debug, Clone, Deserialize, SimpleObject)]
-pub struct Metadata {
-    icon_url: String,
 }
 
-#[derive(Debug, Clone, Deserialize, SimpleObject)]
+#[derive(Debug, Clone, SimpleObject)]
 pub struct StableCoinOverview {
-    // Total market cap = Total supply * Value in dollar
-    // For Our mockdata we are skipping the calculation of market cap
-    // and just using the total supply as market cap
+    /// Total market cap = Total supply * Value in dollar, for the stablecoins
+    /// with a known value in dollar.
     total_marketcap:            f64,
-    // Number of unique holders = Number of unique addresses holding the token
-    number_of_unique_holders:   usize,
-    no_of_txn:                  usize,
+    /// Number of unique addresses holding any of the stablecoins.
+    number_of_unique_holders:   i64,
+    no_of_txn:                  i64,
+    /// The value in dollar transferred, for the stablecoins with a known value
+    /// in dollar.
     values_transferred:         f64,
-    no_of_txn_last24h:          usize,
+    no_of_txn_last24h:          i64,
     values_transferred_last24h: f64,
 }
 
-#[derive(Clone, Deserialize, SimpleObject)]
-pub struct Transfer {
-    from:       AccountAddress,
-    to:         AccountAddress,
-    asset_name: String,
-    date_time:  DateTime,
-    amount:     f64,
-}
-
-#[derive(Debug, Clone, Deserialize, SimpleObject)]
-pub struct AssetInHold {
-    asset_name: String,
-    quantity:   f64,
-    percentage: f32,
-}
-
-#[derive(Clone, Deserialize, SimpleObject)]
-pub struct Holding {
-    address:  AccountAddress,
-    holdings: Option<Vec<AssetInHold>>,
-}
-
-#[derive(Clone, Deserialize, SimpleObject)]
+#[derive(Clone, SimpleObject)]
 pub struct HoldingResponse {
     address:    String,
     asset_name: String,
     quantity:   f64,
+    /// Percentage of the total supply held by the account.
     percentage: f32,
 }
 
@@ -114,313 +378,230 @@ pub struct HoldingResponse {
 pub struct TransferSummary {
     date_time:         DateTime,
     total_amount:      f64,
-    transaction_count: usize,
+    transaction_count: i64,
 }
 
 #[derive(Debug, Clone, SimpleObject)]
 pub struct TransferSummaryResponse {
     daily_summary:   Vec<TransferSummary>,
-    total_txn_count: usize,
+    total_txn_count: i64,
     total_value:     f64,
 }
 
-impl StableCoin {
-    pub fn top_holders(
-        &self,
-        limit: Option<usize>,
-        min_quantity: Option<f64>,
-    ) -> Option<Vec<HoldingResponse>> {
-        if let Some(holdings) = &self.holdings {
-            let mut filtered_holders = holdings
-                .iter()
-                .filter(|h| {
-                    h.asset_name == self.symbol
-                        && (min_quantity.is_none() || h.quantity >= min_quantity.unwrap())
-                })
-                .cloned()
-                .collect::<Vec<HoldingResponse>>();
-
-            // Sort by quantity in descending order
-            filtered_holders.sort_by(|a, b| b.quantity.partial_cmp(&a.quantity).unwrap());
-
-            // Limit the number of holders if a limit is provided
-            if let Some(l) = limit {
-                filtered_holders.truncate(l);
-            }
-
-            Some(filtered_holders)
-        } else {
-            None
-        }
+/// Summarize the transfers per day from the cumulative transfer count and
+/// amount at the end of each day with transfers, and the cumulative values
+/// before the first day.
+fn daily_transfer_summary(
+    before: (i64, BigDecimal),
+    days: Vec<(DateTime, i64, BigDecimal)>,
+    decimals: i32,
+) -> TransferSummaryResponse {
+    let (initial_count, initial_amount) = before;
+    let (mut previous_count, mut previous_amount) = (initial_count, initial_amount.clone());
+    let mut daily_summary = Vec::with_capacity(days.len());
+    for (date_time, count, amount) in days {
+        daily_summary.push(TransferSummary {
+            date_time,
+            total_amount: whole_tokens(&(&amount - &previous_amount), decimals),
+            transaction_count: count - previous_count,
+        });
+        previous_count = count;
+        previous_amount = amount;
     }
-
-    pub fn last_n_transactions(
-        &self,
-        last_n_transactions: Option<usize>,
-    ) -> Option<Vec<TransactionMResponse>> {
-        let mut transactions = self.transactions.clone()?;
-        transactions.sort_by(|a, b| b.date_time.cmp(&a.date_time));
-        let last_n = last_n_transactions.unwrap_or(20);
-        Some(transactions.into_iter().take(last_n).collect())
+    TransferSummaryResponse {
+        daily_summary,
+        total_txn_count: previous_count - initial_count,
+        total_value: whole_tokens(&(previous_amount - initial_amount), decimals),
     }
 }
 
 #[derive(Default)]
 pub(crate) struct QueryStableCoins;
 
-impl QueryStableCoins {
-    fn load_data() -> Vec<StableCoin> {
-        let file = File::open("stablecoin.json").unwrap();
-        let reader = BufReader::new(file);
-        serde_json::from_reader(reader).unwrap()
-    }
-
-    fn load_transfers() -> Vec<Transfer> {
-        let file = File::open("transfers.json").unwrap();
-        let reader = BufReader::new(file);
-        let mut transfers: Vec<Transfer> = serde_json::from_reader(reader).unwrap();
-
-        // Sort transfers by date in descending order (newest first)
-        transfers.sort_by(|a, b| b.date_time.cmp(&a.date_time));
-        transfers
-    }
-
-    fn load_holdings() -> Vec<Holding> {
-        let file = File::open("walletholdings.json").unwrap();
-        let reader = BufReader::new(file);
-        serde_json::from_reader(reader).unwrap()
-    }
-
-    fn load_transactions() -> Vec<TransactionM> {
-        let file = File::open("transaction.json").unwrap();
-        let reader = BufReader::new(file);
-        serde_json::from_reader(reader).unwrap()
-    }
-
-    fn merge_transfers(
-        mut stablecoins: Vec<StableCoin>,
-        transfers: Vec<Transfer>,
-        holdings: Vec<Holding>,
-        transactions: Vec<TransactionM>,
-    ) -> Vec<StableCoin> {
-        for coin in &mut stablecoins {
-            coin.transfers =
-                Some(transfers.iter().filter(|t| t.asset_name == coin.symbol).cloned().collect());
-
-            let relevant_holdings: Vec<HoldingResponse> = holdings
-                .iter()
-                .filter_map(|holding| {
-                    holding.holdings.as_ref().and_then(|h| {
-                        h.iter().find(|asset| asset.asset_name == coin.symbol).map(|asset| {
-                            HoldingResponse {
-                                address:    holding.address.to_string(),
-                                asset_name: asset.asset_name.clone(),
-                                quantity:   asset.quantity,
-                                percentage: asset.percentage,
-                            }
-                        })
-                    })
-                })
-                .collect();
-
-            coin.holdings = if relevant_holdings.is_empty() {
-                None
-            } else {
-                Some(relevant_holdings.clone())
-            };
-
-            // Compute unique holders
-            let unique_holders: HashSet<String> =
-                relevant_holdings.iter().map(|h| h.address.to_string()).collect();
-
-            coin.total_unique_holders = Some(unique_holders.len() as i64);
-            coin.transactions = Some(
-                transactions
-                    .iter()
-                    .filter(|t| t.asset_name == coin.symbol)
-                    .map(|t| TransactionMResponse {
-                        from:             t.from.to_string(),
-                        to:               t.to.to_string(),
-                        asset_name:       t.asset_name.clone(),
-                        date_time:        t.date_time,
-                        amount:           t.amount,
-                        value:            t.value,
-                        transaction_hash: t.transaction_hash.clone(),
-                    })
-                    .collect(),
-            );
-        }
-        stablecoins
-    }
-}
-
 #[Object]
 impl QueryStableCoins {
     async fn stablecoin<'a>(
         &self,
-        _ctx: &Context<'a>,
+        ctx: &Context<'a>,
         symbol: String,
-        limit: Option<usize>,
+        limit: Option<u64>,
         min_quantity: Option<f64>,
-        last_n_transactions: Option<usize>,
-    ) -> Option<StableCoin> {
-        let mut stablecoins = Self::merge_transfers(
-            Self::load_data(),
-            Self::load_transfers(),
-            Self::load_holdings(),
-            Self::load_transactions(),
-        );
-        let coin = stablecoins.iter_mut().find(|coin: &&mut StableCoin| coin.symbol == symbol)?;
-        coin.holdings = coin.top_holders(limit, min_quantity);
-        coin.transactions = coin.last_n_transactions(last_n_transactions);
-        Some(coin.clone())
+        last_n_transactions: Option<u64>,
+    ) -> ApiResult<Option<StableCoin>> {
+        let pool = get_pool(ctx)?;
+        let config = get_config(ctx)?;
+        let Some(mut coin) = StableCoin::query_by_token_id(pool, config, &symbol).await? else {
+            return Ok(None);
+        };
+        if let Some(limit) = limit {
+            coin.holdings_limit = limit.min(config.plt_account_amount_connection_limit);
+        }
+        coin.min_quantity = min_quantity;
+        if let Some(last_n_transactions) = last_n_transactions {
+            coin.last_n_transactions =
+                last_n_transactions.min(config.plt_token_events_collection_limit);
+        }
+        Ok(Some(coin))
     }
 
-    async fn stablecoins<'a>(&self, _ctx: &Context<'a>) -> Vec<StableCoin> {
-        Self::merge_transfers(
-            Self::load_data(),
-            Self::load_transfers(),
-            Self::load_holdings(),
-            Self::load_transactions(),
-        )
+    async fn stablecoins<'a>(&self, ctx: &Context<'a>) -> ApiResult<Vec<StableCoin>> {
+        StableCoin::query(get_pool(ctx)?, get_config(ctx)?, None).await
     }
 
     async fn stablecoins_by_supply<'a>(
         &self,
-        _ctx: &Context<'a>,
+        ctx: &Context<'a>,
         min_supply: i64,
-    ) -> Vec<StableCoin> {
-        Self::merge_transfers(
-            Self::load_data(),
-            Self::load_transfers(),
-            Self::load_holdings(),
-            Self::load_transactions(),
-        )
-        .into_iter()
-        .filter(|coin| coin.total_supply >= min_supply)
-        .collect()
+    ) -> ApiResult<Vec<StableCoin>> {
+        let stablecoins = StableCoin::query(get_pool(ctx)?, get_config(ctx)?, None).await?;
+        Ok(stablecoins.into_iter().filter(|coin| coin.total_supply >= min_supply as f64).collect())
     }
 
+    /// Daily transfers of a stablecoin over the last days, including today.
     async fn transfer_summary<'a>(
         &self,
-        _ctx: &Context<'a>,
+        ctx: &Context<'a>,
         asset_name: String,
-        days: Option<i64>, // Number of days to fetch
-    ) -> TransferSummaryResponse {
-        let transfers = Self::load_transfers();
-        let now: chrono::DateTime<Utc> = Utc::now();
-        let days = days.unwrap_or(7);
-        let days = if days <= 7 {
-            6
-        } else {
-            days - 1
-        };
-        let last_n_days = now - chrono::Duration::days(days);
-        let mut summary: HashMap<DateTime, (f64, usize)> = HashMap::new();
-        let mut total_value = 0.0;
-        let mut total_txn_count = 0;
-
-        for transfer in transfers.iter().filter(|t| t.asset_name == asset_name) {
-            let transfer_date = transfer.date_time;
-            // Filter transactions within the last `days`
-            if transfer_date >= last_n_days {
-                let date_str = Utc
-                    .from_utc_datetime(&transfer_date.date_naive().and_hms_opt(0, 0, 0).unwrap());
-                let entry = summary.entry(date_str).or_insert((0.0, 0));
-                entry.0 += transfer.amount;
-                entry.1 += 1;
-                total_value += transfer.amount;
-                total_txn_count += 1;
-            }
-        }
+        #[graphql(desc = "Number of days to summarize, 7 by default.")] days: Option<i64>,
+    ) -> ApiResult<TransferSummaryResponse> {
+        let pool = get_pool(ctx)?;
+        let config = get_config(ctx)?;
+        let coin = StableCoin::query_by_token_id(pool, config, &asset_name)
+            .await?
+            .ok_or(ApiError::NotFound)?;
+        let days = days.unwrap_or(7).max(1);
+        let today = Utc::now().date_naive().and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+        let start = today - Duration::days(days - 1);
+
+        let before = sqlx::query!(
+            "SELECT cumulative_transfer_count, cumulative_transfer_amount
+            FROM metrics_plt_transfer
+            WHERE token_index = $1 AND event_timestamp < $2
+            ORDER BY event_timestamp DESC
+            LIMIT 1",
+            coin.token_index,
+            start
+        )
+        .fetch_optional(pool)
+        .await?
+        .map_or((0, BigDecimal::from(0)), |row| {
+            (row.cumulative_transfer_count, row.cumulative_transfer_amount)
+        });
+        // The cumulative values only increase, such that the largest values of a
+        // day are the values at the end of the day.
+        let days = sqlx::query!(
+            r#"SELECT
+                date_trunc('day', event_timestamp, 'UTC') AS "day!",
+                MAX(cumulative_transfer_count) AS "count!",
+                MAX(cumulative_transfer_amount) AS "amount!"
+            FROM metrics_plt_transfer
+            WHERE token_index = $1 AND event_timestamp >= $2
+            GROUP BY 1
+            ORDER BY 1"#,
+            coin.token_index,
+            start
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|row| (row.day, row.count, row.amount))
+        .collect();
+        Ok(daily_transfer_summary(before, days, coin.decimal))
+    }
 
-        let mut summary_vec: Vec<TransferSummary> = summary
-            .into_iter()
-            .map(|(date_time, (total_amount, transaction_count))| TransferSummary {
-                date_time,
-                total_amount,
-                transaction_count,
-            })
-            .collect();
+    async fn stablecoin_overview<'a>(&self, ctx: &Context<'a>) -> ApiResult<StableCoinOverview> {
+        let pool = get_pool(ctx)?;
+        let stablecoins = StableCoin::query(pool, get_config(ctx)?, None).await?;
+        let token_indices: Vec<i64> = stablecoins.iter().map(|coin| coin.token_index).collect();
 
-        // Sort in ascending order (earliest date first)
-        summary_vec.sort_by(|a, b| a.date_time.cmp(&b.date_time));
+        let number_of_unique_holders = sqlx::query_scalar!(
+            r#"SELECT COUNT(DISTINCT account_index) AS "count!"
+            FROM plt_accounts
+            WHERE token_index = ANY($1) AND amount > 0"#,
+            &token_indices
+        )
+        .fetch_one(pool)
+        .await?;
 
-        TransferSummaryResponse {
-            daily_summary: summary_vec,
-            total_txn_count,
-            total_value,
+        let now = Utc::now();
+        let current = cumulative_transfers(pool, &token_indices, now).await?;
+        let day_ago = cumulative_transfers(pool, &token_indices, now - Duration::hours(24)).await?;
+        let mut overview = StableCoinOverview {
+            total_marketcap: 0.0,
+            number_of_unique_holders,
+            no_of_txn: 0,
+            values_transferred: 0.0,
+            no_of_txn_last24h: 0,
+            values_transferred_last24h: 0.0,
+        };
+        for coin in stablecoins.iter() {
+            let value_in_dollar = coin.value_in_dollar.unwrap_or(0.0);
+            overview.total_marketcap += coin.total_supply * value_in_dollar;
+            let Some((count, amount)) = current.get(&coin.token_index) else {
+                continue;
+            };
+            let (count_day_ago, amount_day_ago) =
+                day_ago.get(&coin.token_index).cloned().unwrap_or((0, BigDecimal::from(0)));
+            overview.no_of_txn += count;
+            overview.values_transferred += whole_tokens(amount, coin.decimal) * value_in_dollar;
+            overview.no_of_txn_last24h += count - count_day_ago;
+            overview.values_transferred_last24h +=
+                whole_tokens(&(amount - amount_day_ago), coin.decimal) * value_in_dollar;
         }
+        Ok(overview)
     }
 
-    async fn stablecoin_overview<'a>(&self, _ctx: &Context<'a>) -> StableCoinOverview {
-        let stablecoins = Self::load_data();
-        let transfers = Self::load_transfers();
-        let holdings: Vec<Holding> = Self::load_holdings();
+    /// The latest transfers across the stablecoins.
+    async fn latest_transactions<'a>(
+        &self,
+        ctx: &Context<'a>,
+        limit: Option<u64>,
+    ) -> ApiResult<Vec<StableCoinTransfer>> {
+        let pool = get_pool(ctx)?;
+        let config = get_config(ctx)?;
+        let stablecoins = StableCoin::query(pool, config, None).await?;
+        let limit = limit
+            .unwrap_or(DEFAULT_LATEST_TRANSACTIONS)
+            .min(config.plt_token_events_collection_limit);
+        query_transfers(pool, &stablecoins, limit).await
+    }
+}
 
-        let unique_holders: HashSet<String> = holdings
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_daily_transfer_summary() {
+        let day = |day| {
+            chrono::NaiveDate::from_ymd_opt(2025, 7, day)
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .unwrap()
+                .and_utc()
+        };
+        let summary = daily_transfer_summary(
+            (10, BigDecimal::from(5_000)),
+            vec![(day(1), 12, BigDecimal::from(7_500)), (day(3), 15, BigDecimal::from(8_000))],
+            3,
+        );
+        let daily: Vec<_> = summary
+            .daily_summary
             .iter()
-            .filter_map(|holding| {
-                holding.holdings.as_ref().and_then(|h| {
-                    if h.is_empty() {
-                        None
-                    } else {
-                        Some(holding.address.to_string())
-                    }
-                })
-            })
+            .map(|day| (day.date_time, day.transaction_count, day.total_amount))
             .collect();
-        let now = Utc::now();
-        let last_24h = now - Duration::hours(24);
-        let last_24h_str = last_24h;
-
-        let (values_transferred_last_24h, no_of_txn_last_24h) = transfers
-            .iter()
-            .filter(|t| t.date_time >= last_24h_str)
-            .fold((0.0, 0), |(total_val, count), t| (total_val + t.amount, count + 1));
-
-        StableCoinOverview {
-            total_marketcap:            stablecoins
-                .iter()
-                .map(|coin| coin.total_supply as f64)
-                .sum(),
-            number_of_unique_holders:   unique_holders.len(),
-            no_of_txn:                  transfers.len(),
-            values_transferred:         transfers.iter().map(|t| t.amount).sum(),
-            no_of_txn_last24h:          no_of_txn_last_24h,
-            values_transferred_last24h: values_transferred_last_24h,
-        }
+        assert_eq!(daily, vec![(day(1), 2, 2.5), (day(3), 3, 0.5)]);
+        assert_eq!(summary.total_txn_count, 5);
+        assert_eq!(summary.total_value, 3.0);
     }
 
-    async fn latest_transactions<'a>(
-        &self,
-        _ctx: &Context<'a>,
-        limit: Option<usize>,
-    ) -> Option<Vec<LatestTransactionResponse>> {
-        let transactions = Self::load_transactions();
-        let stablecoins = Self::load_data();
-        let stablecoins_metadata_map: HashMap<String, Option<Metadata>> =
-            stablecoins.into_iter().map(|s| (s.symbol, s.metadata)).collect();
-        let effective_limit = limit.unwrap_or(10); // default to 10 if None
-        let txn_summary: Option<Vec<LatestTransactionResponse>> = Some(
-            transactions
-                .iter()
-                .take(effective_limit)
-                .map(|t| LatestTransactionResponse {
-                    from:             t.from.to_string(),
-                    to:               t.to.to_string(),
-                    asset_name:       t.asset_name.clone(),
-                    date_time:        t.date_time,
-                    amount:           t.amount,
-                    value:            t.value,
-                    transaction_hash: t.transaction_hash.clone(),
-                    asset_metadata:   stablecoins_metadata_map
-                        .get(&t.asset_name.clone())
-                        .unwrap()
-                        .clone(),
-                })
-                .collect(),
-        );
-        txn_summary
+    #[test]
+    fn test_parse_stablecoin_peg() {
+        let peg: StableCoinPeg = "EURR:eur".parse().unwrap();
+        assert_eq!(peg.token_id, "EURR");
+        assert_eq!(peg.currency, "EUR");
+        assert_eq!(peg.value_in_dollar(), None);
+        assert_eq!("USDR:USD".parse::<StableCoinPeg>().unwrap().value_in_dollar(), Some(1.0));
+        assert!("USDR".parse::<StableCoinPeg>().is_err());
+        assert!("USDR:DOLLAR".parse::<StableCoinPeg>().is_err());
     }
 }
@@ -1,11 +1,12 @@
 use super::{
-    account::Account, get_config, get_pool, transaction::Transaction, ApiError, ApiResult,
+    account::Account, cis2_balance_change::Cis2BalanceChange, get_config, get_pool,
+    token_distribution::TokenHolderDistribution, transaction::Transaction, ApiError, ApiResult,
     CollectionSegmentInfo, InternalError,
 };
 use crate::{
     address::ContractIndex,
     connection::{ConnectionQuery, DescendingI64},
-    scalar_types::{BigInteger, TransactionIndex},
+    scalar_types::{BigInteger, BlockHeight, DateTime, TransactionIndex},
     transaction_event::CisEvent,
 };
 use async_graphql::{connection, ComplexObject, Context, Object, SimpleObject};
@@ -108,7 +109,7 @@ pub struct Token {
 }
 
 impl Token {
-    async fn query_by_contract_and_id(
+    pub async fn query_by_contract_and_id(
         pool: &PgPool,
         contract_index: i64,
         contract_sub_index: i64,
@@ -169,6 +170,37 @@ impl Token {
         format!("<{},{}>", self.contract_index, self.contract_sub_index)
     }
 
+    /// How the balance of the token is distributed among its holders, with the
+    /// `top` holders with the largest balances. Balances are in the smallest
+    /// unit of the token.
+    async fn holder_distribution(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 10)] top: u64,
+    ) -> ApiResult<TokenHolderDistribution> {
+        let config = get_config(ctx)?;
+        TokenHolderDistribution::query_cis2(
+            get_pool(ctx)?,
+            config,
+            self.index,
+            top.min(config.token_holder_addresses_collection_limit),
+        )
+        .await
+    }
+
+    /// The metadata fetched from the metadata URL, when the indexer is
+    /// fetching token metadata.
+    async fn metadata(
+        &self,
+        ctx: &Context<'_>,
+    ) -> ApiResult<Option<async_graphql::Json<serde_json::Value>>> {
+        let metadata =
+            sqlx::query_scalar!("SELECT metadata FROM tokens WHERE index = $1", self.index)
+                .fetch_one(get_pool(ctx)?)
+                .await?;
+        Ok(metadata.map(async_graphql::Json))
+    }
+
     async fn accounts(
         &self,
         ctx: &Context<'_>,
@@ -443,6 +475,27 @@ impl AccountToken {
     }
 
     async fn balance(&self) -> BigInteger { BigInteger::from(self.raw_balance.clone()) }
+
+    /// The balance of the account after the block at the given height or, if a
+    /// time is given instead, after the last block at or before the time.
+    async fn balance_at(
+        &self,
+        ctx: &Context<'_>,
+        height: Option<BlockHeight>,
+        time: Option<DateTime>,
+    ) -> ApiResult<BigInteger> {
+        let balance = Cis2BalanceChange::balance_at(
+            get_pool(ctx)?,
+            self.account_id,
+            self.contract_index,
+            self.contract_sub_index,
+            &self.token_id,
+            height,
+            time,
+        )
+        .await?;
+        Ok(BigInteger::from(balance))
+    }
 }
 
 // Interim struct used to fetch AccountToken data from the database.
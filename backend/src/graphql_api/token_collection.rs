@@ -0,0 +1,268 @@
+//! Contains the GraphQL query `tokenCollection`, grouping the CIS-2 tokens of a
+//! contract into a collection, such as an NFT collection.
+//!
+//! The tokens of a collection can be filtered by the attributes in their
+//! metadata, when the indexer fetches the token metadata.
+
+use std::sync::Arc;
+
+use async_graphql::{connection, ComplexObject, Context, InputObject, Object, SimpleObject};
+use chrono::Utc;
+use sqlx::postgres::types::PgInterval;
+
+use crate::{
+    address::{AccountAddress, ContractIndex},
+    connection::{connection_from_id_stream, DescendingI64},
+    graphql_api::{
+        get_config, get_pool, token::Token, ApiError, ApiResult, ConnectionQuery, DateTime,
+        MetricsPeriod, TimeSpan,
+    },
+};
+
+#[derive(Default)]
+pub(crate) struct QueryTokenCollection;
+
+#[Object]
+impl QueryTokenCollection {
+    /// The CIS-2 tokens of a contract, as a collection.
+    async fn token_collection(
+        &self,
+        ctx: &Context<'_>,
+        contract_address_index: ContractIndex,
+        contract_address_sub_index: ContractIndex,
+    ) -> ApiResult<TokenCollection> {
+        let pool = get_pool(ctx)?;
+        let collection = sqlx::query_as!(
+            TokenCollection,
+            r#"SELECT
+                contracts.index AS contract_index,
+                contracts.sub_index AS contract_sub_index,
+                (SELECT COUNT(*) FROM tokens
+                    WHERE tokens.contract_index = contracts.index
+                        AND tokens.contract_sub_index = contracts.sub_index
+                ) AS "token_count!",
+                (SELECT COUNT(DISTINCT account_tokens.account_index)
+                    FROM tokens
+                        JOIN account_tokens ON account_tokens.token_index = tokens.index
+                    WHERE tokens.contract_index = contracts.index
+                        AND tokens.contract_sub_index = contracts.sub_index
+                        AND account_tokens.balance > 0
+                ) AS "owner_count!"
+            FROM contracts
+            WHERE contracts.index = $1 AND contracts.sub_index = $2"#,
+            contract_address_index.0 as i64,
+            contract_address_sub_index.0 as i64
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+        Ok(collection)
+    }
+}
+
+/// The CIS-2 tokens of a contract, such as an NFT collection.
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct TokenCollection {
+    contract_index:     i64,
+    contract_sub_index: i64,
+    /// Number of token IDs of the contract.
+    token_count:        i64,
+    /// Number of accounts holding a non-zero balance of any token of the
+    /// contract.
+    owner_count:        i64,
+}
+
+/// Filter on an attribute in the metadata of a token, matching the `name` and
+/// `value` of an attribute.
+#[derive(InputObject)]
+struct TokenAttributeFilter {
+    name:  String,
+    value: String,
+}
+
+#[ComplexObject]
+impl TokenCollection {
+    /// The tokens of the collection, latest first, optionally only the tokens
+    /// with all the given attributes in their metadata.
+    async fn tokens(
+        &self,
+        ctx: &Context<'_>,
+        attributes: Option<Vec<TokenAttributeFilter>>,
+        #[graphql(desc = "Returns the first _n_ elements from the list.")] first: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come after the specified cursor.")]
+        after: Option<String>,
+        #[graphql(desc = "Returns the last _n_ elements from the list.")] last: Option<u64>,
+        #[graphql(desc = "Returns the elements in the list that come before the specified cursor.")]
+        before: Option<String>,
+    ) -> ApiResult<connection::Connection<String, Token>> {
+        let pool = get_pool(ctx)?;
+        let config = get_config(ctx)?;
+        let query = ConnectionQuery::<DescendingI64>::new(
+            first,
+            after,
+            last,
+            before,
+            config.tokens_connection_limit,
+        )?;
+        let attributes_filter = attributes.map(|attributes| {
+            serde_json::Value::Array(
+                attributes
+                    .into_iter()
+                    .map(|attribute| {
+                        serde_json::json!({"name": attribute.name, "value": attribute.value})
+                    })
+                    .collect(),
+            )
+        });
+        let row_stream = sqlx::query_as!(
+            Token,
+            "SELECT * FROM (
+                SELECT
+                    index,
+                    init_transaction_index,
+                    total_supply as raw_total_supply,
+                    token_id,
+                    contract_index,
+                    contract_sub_index,
+                    token_address,
+                    metadata_url
+                FROM tokens
+                WHERE tokens.contract_index = $5
+                    AND tokens.contract_sub_index = $6
+                    AND ($7::JSONB IS NULL OR tokens.metadata -> 'attributes' @> $7)
+                    AND tokens.index < $1
+                    AND tokens.index > $2
+                ORDER BY
+                    (CASE WHEN $3 THEN tokens.index END) ASC,
+                    (CASE WHEN NOT $3 THEN tokens.index END) DESC
+                LIMIT $4
+            ) ORDER BY index DESC",
+            i64::from(query.from),
+            i64::from(query.to),
+            query.is_last,
+            query.limit,
+            self.contract_index,
+            self.contract_sub_index,
+            attributes_filter
+        )
+        .fetch(pool);
+
+        let (mut connection, page_indices) =
+            connection_from_id_stream(row_stream, |token| token.index).await?;
+        if let Some((page_min_index, page_max_index)) = page_indices {
+            let result = sqlx::query!(
+                "SELECT MAX(index) as max_index, MIN(index) as min_index
+                FROM tokens
+                WHERE contract_index = $1
+                    AND contract_sub_index = $2
+                    AND ($3::JSONB IS NULL OR metadata -> 'attributes' @> $3)",
+                self.contract_index,
+                self.contract_sub_index,
+                attributes_filter
+            )
+            .fetch_one(pool)
+            .await?;
+            connection.has_previous_page =
+                result.max_index.is_some_and(|db_max| db_max > page_max_index);
+            connection.has_next_page =
+                result.min_index.is_some_and(|db_min| db_min < page_min_index);
+        }
+        Ok(connection)
+    }
+
+    /// The account owning a token with a total supply of one, such as an NFT.
+    /// Is null when the token has a different supply, or when it is owned by
+    /// a contract.
+    async fn owner_of(
+        &self,
+        ctx: &Context<'_>,
+        token_id: String,
+    ) -> ApiResult<Option<AccountAddress>> {
+        let token = sqlx::query!(
+            "SELECT
+                tokens.total_supply = 1 AS \"single_supply!\",
+                (SELECT accounts.address
+                    FROM account_tokens
+                        JOIN accounts ON accounts.index = account_tokens.account_index
+                    WHERE account_tokens.token_index = tokens.index
+                        AND account_tokens.balance = 1
+                ) AS owner
+            FROM tokens
+            WHERE contract_index = $1 AND contract_sub_index = $2 AND token_id = $3",
+            self.contract_index,
+            self.contract_sub_index,
+            token_id
+        )
+        .fetch_optional(get_pool(ctx)?)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+        Ok(token.owner.filter(|_| token.single_supply).map(AccountAddress::from))
+    }
+
+    /// The number of mint and burn events of the tokens of the collection over
+    /// a period.
+    async fn activity(
+        &self,
+        ctx: &Context<'_>,
+        period: MetricsPeriod,
+    ) -> ApiResult<TokenCollectionActivity> {
+        let pool = get_pool(ctx)?;
+        let end_time = Utc::now();
+        let before_time = end_time - period.as_duration();
+        let bucket_width = period.bucket_width();
+        let bucket_interval: PgInterval =
+            bucket_width.try_into().map_err(|err| ApiError::DurationOutOfRange(Arc::new(err)))?;
+
+        let rows = sqlx::query_file!(
+            "src/graphql_api/token_collection_activity.sql",
+            end_time,
+            before_time,
+            bucket_interval,
+            self.contract_index,
+            self.contract_sub_index
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let x_time = rows.iter().map(|row| row.bucket_time).collect();
+        let y_minted: Vec<i64> = rows.iter().map(|row| row.minted).collect();
+        let y_burned: Vec<i64> = rows.iter().map(|row| row.burned).collect();
+        Ok(TokenCollectionActivity {
+            minted:  y_minted.iter().sum(),
+            burned:  y_burned.iter().sum(),
+            buckets: TokenCollectionActivityBuckets {
+                bucket_width: TimeSpan(bucket_width),
+                x_time,
+                y_minted,
+                y_burned,
+            },
+        })
+    }
+}
+
+#[derive(SimpleObject)]
+struct TokenCollectionActivity {
+    /// Number of mint events in the requested period.
+    minted:  i64,
+    /// Number of burn events in the requested period.
+    burned:  i64,
+    buckets: TokenCollectionActivityBuckets,
+}
+
+#[derive(SimpleObject)]
+struct TokenCollectionActivityBuckets {
+    /// The width (time interval) of each bucket.
+    bucket_width: TimeSpan,
+    /// Start of the bucket time period. Intended x-axis value.
+    #[graphql(name = "x_Time")]
+    x_time:       Vec<DateTime>,
+    /// Number of mint events within the bucket time period. Intended y-axis
+    /// value.
+    #[graphql(name = "y_Minted")]
+    y_minted:     Vec<i64>,
+    /// Number of burn events within the bucket time period. Intended y-axis
+    /// value.
+    #[graphql(name = "y_Burned")]
+    y_burned:     Vec<i64>,
+}
@@ -0,0 +1,33 @@
+-- Counts the mint and burn events of the tokens of a contract in buckets.
+-- Inputs:
+-- $1::timestamptz - end of the period
+-- $2::timestamptz - start of the period
+-- $3::interval    - width of the buckets, e.g. '1 days'
+-- $4::BIGINT      - contract index
+-- $5::BIGINT      - contract subindex
+WITH events AS (
+  SELECT
+    cis2_token_events.cis2_token_event,
+    blocks.slot_time
+  FROM cis2_token_events
+    JOIN tokens ON tokens.index = cis2_token_events.token_index
+    JOIN transactions ON transactions.index = cis2_token_events.transaction_index
+    JOIN blocks ON blocks.height = transactions.block_height
+  WHERE tokens.contract_index = $4
+    AND tokens.contract_sub_index = $5
+    AND blocks.slot_time >= $2
+)
+SELECT
+  bucket_time.bucket_start AS "bucket_time!",
+  COUNT(events.slot_time) FILTER (WHERE events.cis2_token_event ? 'Mint') AS "minted!",
+  COUNT(events.slot_time) FILTER (WHERE events.cis2_token_event ? 'Burn') AS "burned!"
+FROM date_bin_series(
+    $3::interval,
+    $2,
+    $1
+  ) AS bucket_time
+  LEFT JOIN events
+    ON events.slot_time >= bucket_time.bucket_start
+    AND events.slot_time < bucket_time.bucket_start + $3::interval
+GROUP BY bucket_time.bucket_start
+ORDER BY bucket_time.bucket_start;
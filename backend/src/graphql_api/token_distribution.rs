@@ -0,0 +1,364 @@
+//! Statistics on how the balance of a token is distributed among its holders,
+//! shared by protocol level tokens and CIS-2 tokens.
+//!
+//! Accounts configured using `--token-distribution-excluded-account`, such as
+//! the treasury of an issuer or a bridge, are marked among the top holders and
+//! left out of the concentration statistics, since their balance is not held by
+//! the market.
+
+use super::{ApiResult, ApiServiceConfig};
+use crate::address::AccountAddress;
+use async_graphql::SimpleObject;
+use sqlx::{types::Json, PgPool};
+
+/// The percentiles of the balances of the holders in the distribution.
+const BALANCE_PERCENTILES: [f64; 5] = [0.25, 0.5, 0.75, 0.9, 0.99];
+
+impl ApiServiceConfig {
+    /// Canonical addresses of the accounts excluded from the holder
+    /// distribution statistics.
+    fn token_distribution_excluded_addresses(&self) -> Vec<Vec<u8>> {
+        self.token_distribution_excluded_account
+            .iter()
+            .map(|address| address.get_canonical_address().0.to_vec())
+            .collect()
+    }
+}
+
+/// How the balance of a token is distributed among its holders.
+#[derive(SimpleObject)]
+pub struct TokenHolderDistribution {
+    /// Number of holders with a non-zero balance, not counting the excluded
+    /// holders.
+    holder_count:          i64,
+    /// Number of holders on the configured exclusion list, such as the
+    /// treasury of the issuer or bridges.
+    excluded_holder_count: i64,
+    /// Share of the total held balance held by the excluded holders.
+    excluded_share:        f64,
+    /// The holders with the largest balances, including the excluded holders.
+    top_holders:           Vec<TokenHolderShare>,
+    /// Share of the balance held by the top holders which are not excluded,
+    /// out of the balance held by all holders which are not excluded.
+    top_holders_share:     f64,
+    /// Gini coefficient of the balances of the holders which are not
+    /// excluded, from 0 when all hold the same balance towards 1 when a
+    /// single holder holds everything.
+    gini_coefficient:      f64,
+    /// The smallest number of holders which are not excluded, together
+    /// holding more than half of their balance.
+    nakamoto_coefficient:  i64,
+    /// Balances of the holders which are not excluded at the 25th, 50th,
+    /// 75th, 90th and 99th percentile, interpolated between the holders.
+    balance_percentiles:   Vec<TokenBalancePercentile>,
+    /// Number of holders which are not excluded per balance band, where each
+    /// band is a power of ten of the balance in whole units of the token.
+    balance_bands:         Vec<TokenBalanceBand>,
+}
+
+/// A holder with one of the largest balances of a token.
+#[derive(SimpleObject)]
+pub struct TokenHolderShare {
+    account_address: AccountAddress,
+    /// Balance in whole units of the token.
+    balance:         f64,
+    /// Share of the total held balance.
+    share:           f64,
+    /// Whether the holder is on the configured exclusion list.
+    excluded:        bool,
+}
+
+/// The balance at a percentile of the holders, ordered by balance.
+#[derive(SimpleObject)]
+pub struct TokenBalancePercentile {
+    /// The percentile as a fraction, such as 0.5 for the median.
+    percentile: f64,
+    balance:    f64,
+}
+
+/// The holders with a balance in the band, from `minBalance` (inclusive) to
+/// `maxBalance` (exclusive).
+#[derive(SimpleObject)]
+pub struct TokenBalanceBand {
+    min_balance:  f64,
+    max_balance:  f64,
+    holder_count: i64,
+    /// Share of the balance of the holders which are not excluded, held by
+    /// the holders in the band.
+    share:        f64,
+}
+
+/// A top holder as aggregated by the distribution query.
+#[derive(Debug, serde::Deserialize)]
+struct TopHolder {
+    address:  String,
+    balance:  f64,
+    excluded: bool,
+}
+
+/// A balance band as aggregated by the distribution query, where the band is
+/// the power of ten of the balance, `None` for balances below one.
+#[derive(Debug, serde::Deserialize)]
+struct BalanceBand {
+    band:         Option<i32>,
+    holder_count: i64,
+    balance:      f64,
+}
+
+impl TokenBalanceBand {
+    /// The band of holders holding `balance` out of the balance `held` by all
+    /// holders which are not excluded.
+    fn new(band: Option<i32>, holder_count: i64, balance: f64, held: f64) -> Self {
+        Self {
+            min_balance: band.map_or(0.0, |band| 10f64.powi(band)),
+            max_balance: 10f64.powi(band.map_or(0, |band| band + 1)),
+            holder_count,
+            share: share(balance, held),
+        }
+    }
+}
+
+/// The share of `part` out of `whole`, 0 when nothing is held.
+fn share(part: f64, whole: f64) -> f64 {
+    if whole > 0.0 {
+        part / whole
+    } else {
+        0.0
+    }
+}
+
+/// The Gini coefficient of the balances of `holder_count` holders together
+/// holding `held`. With the holders ranked by balance, largest first,
+/// `rank_weighted_held` is the sum of `(holder_count - rank + 1) * balance`
+/// and the coefficient is `2 * rank_weighted_held / (holder_count * held) -
+/// (holder_count + 1) / holder_count`.
+fn gini_coefficient(holder_count: i64, held: f64, rank_weighted_held: f64) -> f64 {
+    if holder_count == 0 || held <= 0.0 {
+        return 0.0;
+    }
+    let holder_count = holder_count as f64;
+    (2.0 * rank_weighted_held / (holder_count * held) - (holder_count + 1.0) / holder_count)
+        .max(0.0)
+}
+
+impl TokenHolderDistribution {
+    /// The distribution of a protocol level token among the accounts holding
+    /// it.
+    pub async fn query_plt(
+        pool: &PgPool,
+        config: &ApiServiceConfig,
+        token_index: i64,
+        decimals: i32,
+        top: u64,
+    ) -> ApiResult<Self> {
+        Self::query(pool, config, true, token_index, decimals, top).await
+    }
+
+    /// The distribution of a CIS-2 token among the accounts holding it.
+    /// Balances are in the smallest unit of the token, as the decimals are
+    /// only part of the token metadata.
+    pub async fn query_cis2(
+        pool: &PgPool,
+        config: &ApiServiceConfig,
+        token_index: i64,
+        top: u64,
+    ) -> ApiResult<Self> {
+        Self::query(pool, config, false, token_index, 0, top).await
+    }
+
+    /// Compute the distribution of the balances of a protocol level token in
+    /// `plt_accounts` or of a CIS-2 token in `account_tokens`. The query
+    /// aggregates the balances of the holders, from which the shares and the
+    /// Gini coefficient are computed.
+    async fn query(
+        pool: &PgPool,
+        config: &ApiServiceConfig,
+        is_plt: bool,
+        token_index: i64,
+        decimals: i32,
+        top: u64,
+    ) -> ApiResult<Self> {
+        let row = sqlx::query!(
+            r#"WITH holders AS (
+                SELECT
+                    accounts.address,
+                    holder_balances.balance / 10::NUMERIC ^ $3::INT AS balance,
+                    accounts.canonical_address = ANY($4::BYTEA[]) AS excluded
+                FROM (
+                    SELECT account_index, amount AS balance
+                    FROM plt_accounts
+                    WHERE $1 AND token_index = $2 AND amount > 0
+                    UNION ALL
+                    SELECT account_index, balance
+                    FROM account_tokens
+                    WHERE NOT $1 AND token_index = $2 AND balance > 0
+                ) AS holder_balances
+                    JOIN accounts ON accounts.index = holder_balances.account_index
+            ),
+            ranked AS (
+                SELECT
+                    balance,
+                    ROW_NUMBER() OVER (ORDER BY balance DESC, address) AS rank,
+                    SUM(balance) OVER (
+                        ORDER BY balance DESC, address
+                        ROWS UNBOUNDED PRECEDING
+                    ) AS cumulative_balance
+                FROM holders
+                WHERE NOT excluded
+            ),
+            totals AS (
+                SELECT
+                    COALESCE(SUM(balance), 0) AS total,
+                    COALESCE(SUM(balance) FILTER (WHERE NOT excluded), 0) AS held,
+                    COUNT(*) FILTER (WHERE NOT excluded) AS holder_count,
+                    COUNT(*) FILTER (WHERE excluded) AS excluded_holder_count
+                FROM holders
+            )
+            SELECT
+                totals.holder_count AS "holder_count!",
+                totals.excluded_holder_count AS "excluded_holder_count!",
+                totals.total::FLOAT8 AS "total!",
+                totals.held::FLOAT8 AS "held!",
+                COALESCE(
+                    (SELECT SUM(balance) FROM ranked WHERE rank <= $5),
+                    0
+                )::FLOAT8 AS "top_held!",
+                COALESCE(
+                    (SELECT SUM((totals.holder_count - rank + 1) * balance) FROM ranked),
+                    0
+                )::FLOAT8 AS "rank_weighted_held!",
+                COALESCE(
+                    (SELECT MIN(rank) FROM ranked WHERE cumulative_balance > totals.held / 2),
+                    0
+                ) AS "nakamoto_coefficient!",
+                (SELECT percentile_cont($6::FLOAT8[]) WITHIN GROUP (ORDER BY balance::FLOAT8)
+                    FROM ranked
+                ) AS balance_percentiles,
+                (SELECT COALESCE(
+                    jsonb_agg(
+                        jsonb_build_object(
+                            'address', address,
+                            'balance', balance::FLOAT8,
+                            'excluded', excluded
+                        )
+                        ORDER BY balance DESC, address
+                    ),
+                    '[]'
+                ) FROM (
+                    SELECT * FROM holders ORDER BY balance DESC, address LIMIT $5
+                ) AS top_holders) AS "top_holders!: Json<Vec<TopHolder>>",
+                (SELECT COALESCE(
+                    jsonb_agg(
+                        jsonb_build_object(
+                            'band', band,
+                            'holder_count', holder_count,
+                            'balance', balance::FLOAT8
+                        )
+                        ORDER BY band NULLS FIRST
+                    ),
+                    '[]'
+                ) FROM (
+                    SELECT band, COUNT(*) AS holder_count, SUM(balance) AS balance
+                    FROM (
+                        -- Balances below one whole unit are in the band from zero.
+                        SELECT
+                            CASE WHEN balance >= 1 THEN FLOOR(LOG(balance))::INT END AS band,
+                            balance
+                        FROM ranked
+                    ) AS holder_bands
+                    GROUP BY band
+                ) AS bands) AS "balance_bands!: Json<Vec<BalanceBand>>"
+            FROM totals"#,
+            is_plt,
+            token_index,
+            decimals,
+            &config.token_distribution_excluded_addresses(),
+            i64::try_from(top)?,
+            &BALANCE_PERCENTILES
+        )
+        .fetch_one(pool)
+        .await?;
+
+        let top_holders = row
+            .top_holders
+            .0
+            .into_iter()
+            .map(|holder| TokenHolderShare {
+                account_address: holder.address.into(),
+                balance:         holder.balance,
+                share:           share(holder.balance, row.total),
+                excluded:        holder.excluded,
+            })
+            .collect();
+        let balance_percentiles = BALANCE_PERCENTILES
+            .into_iter()
+            .zip(row.balance_percentiles.unwrap_or_default())
+            .map(|(percentile, balance)| TokenBalancePercentile {
+                percentile,
+                balance,
+            })
+            .collect();
+        let balance_bands = row
+            .balance_bands
+            .0
+            .into_iter()
+            .map(|band| TokenBalanceBand::new(band.band, band.holder_count, band.balance, row.held))
+            .collect();
+        Ok(Self {
+            holder_count: row.holder_count,
+            excluded_holder_count: row.excluded_holder_count,
+            excluded_share: share(row.total - row.held, row.total),
+            top_holders,
+            top_holders_share: share(row.top_held, row.held),
+            gini_coefficient: gini_coefficient(row.holder_count, row.held, row.rank_weighted_held),
+            nakamoto_coefficient: row.nakamoto_coefficient,
+            balance_percentiles,
+            balance_bands,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The sum of `(holder_count - rank + 1) * balance` over the balances,
+    /// as aggregated by the distribution query.
+    fn rank_weighted(balances: &[f64]) -> f64 {
+        let mut balances = balances.to_vec();
+        balances.sort_by(|a, b| b.total_cmp(a));
+        let holder_count = balances.len() as f64;
+        balances
+            .iter()
+            .enumerate()
+            .map(|(rank, balance)| (holder_count - rank as f64) * balance)
+            .sum()
+    }
+
+    #[test]
+    fn test_distribution_statistics() {
+        // The mean absolute difference of [1, 1, 2, 4] over all 16 ordered pairs is
+        // 20 / 16, and the mean is 2, making the Gini coefficient 20 / 16 / (2 * 2).
+        let balances = [1.0, 1.0, 2.0, 4.0];
+        assert_eq!(rank_weighted(&balances), 25.0);
+        assert_eq!(gini_coefficient(4, 8.0, rank_weighted(&balances)), 0.3125);
+        // Equal balances.
+        assert_eq!(gini_coefficient(4, 8.0, rank_weighted(&[2.0; 4])), 0.0);
+        assert_eq!(gini_coefficient(1, 5.0, rank_weighted(&[5.0])), 0.0);
+        assert_eq!(gini_coefficient(0, 0.0, 0.0), 0.0);
+
+        // The top two holders hold 4 + 2 out of 8.
+        assert_eq!(share(6.0, 8.0), 0.75);
+        assert_eq!(share(1.0, 0.0), 0.0);
+
+        let band = TokenBalanceBand::new(None, 2, 0.5, 8.0);
+        assert_eq!((band.min_balance, band.max_balance), (0.0, 1.0));
+        assert_eq!((band.holder_count, band.share), (2, 0.0625));
+        let band = TokenBalanceBand::new(Some(0), 3, 4.0, 8.0);
+        assert_eq!((band.min_balance, band.max_balance), (1.0, 10.0));
+        assert_eq!(band.share, 0.5);
+        let band = TokenBalanceBand::new(Some(2), 1, 250.0, 1000.0);
+        assert_eq!((band.min_balance, band.max_balance), (100.0, 1000.0));
+        assert_eq!(band.share, 0.25);
+    }
+}
@@ -1,5 +1,6 @@
 use super::{
-    block::Block, get_config, get_pool, ApiError, ApiResult, ConnectionQuery, InternalError,
+    block::Block, contract_call::ContractCallTree, get_config, get_pool,
+    sponsored_action::SponsoredAction, ApiError, ApiResult, ConnectionQuery, InternalError,
 };
 use crate::{
     address::AccountAddress,
@@ -198,6 +199,23 @@ impl Transaction {
 
     async fn ccd_cost(&self) -> ApiResult<Amount> { Ok(self.ccd_cost.try_into()?) }
 
+    /// The cost for the account signing the transaction in EUR, using the
+    /// MicroCcdPerEuro exchange rate in force at the block of the transaction.
+    async fn euro_cost<'a>(&self, ctx: &Context<'a>) -> ApiResult<f64> {
+        let euro_cost = sqlx::query_scalar!(
+            r#"SELECT
+                ($1::BIGINT * micro_ccd_per_euro_denominator
+                    / micro_ccd_per_euro_numerator)::FLOAT8 AS "euro_cost!"
+            FROM blocks
+            WHERE height = $2"#,
+            self.ccd_cost,
+            self.block_height
+        )
+        .fetch_one(get_pool(ctx)?)
+        .await?;
+        Ok(euro_cost)
+    }
+
     async fn energy_cost(&self) -> Energy { self.energy_cost }
 
     async fn block<'a>(&self, ctx: &Context<'a>) -> ApiResult<Block> {
@@ -263,6 +281,20 @@ impl Transaction {
             }))
         }
     }
+
+    /// The contract calls made by a contract update transaction, together with
+    /// the energy cost of the transaction. Without calls for other
+    /// transactions. The node does not report the energy used by each call,
+    /// only the energy cost of the whole transaction.
+    async fn call_tree(&self, ctx: &Context<'_>) -> ApiResult<ContractCallTree> {
+        ContractCallTree::query(get_pool(ctx)?, self.index, self.energy_cost).await
+    }
+
+    /// The CIS-3 sponsored actions executed by the transaction, where the
+    /// sender of the transaction pays for actions signed by other accounts.
+    async fn sponsored_actions(&self, ctx: &Context<'_>) -> ApiResult<Vec<SponsoredAction>> {
+        SponsoredAction::query_by_transaction(get_pool(ctx)?, self.index).await
+    }
 }
 
 #[derive(Union)]
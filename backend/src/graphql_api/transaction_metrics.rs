@@ -3,7 +3,10 @@ use std::sync::Arc;
 use async_graphql::{Context, Object, SimpleObject};
 use sqlx::postgres::types::PgInterval;
 
-use crate::graphql_api::{get_pool, ApiError, ApiResult, DateTime, MetricsPeriod, TimeSpan};
+use crate::{
+    graphql_api::{get_pool, ApiError, ApiResult, DateTime, MetricsPeriod, TimeSpan},
+    scalar_types::Amount,
+};
 
 #[derive(Default)]
 pub(crate) struct QueryTransactionMetrics;
@@ -14,6 +17,12 @@ struct TransactionMetrics {
     last_cumulative_transaction_count: i64,
     /// Total number of transactions in the requested period.
     transaction_count: i64,
+    /// Total transaction fees in microCCD in the requested period.
+    transaction_fees_micro_ccd: Amount,
+    /// Total transaction fees in EUR in the requested period, where the fees
+    /// of each block are converted using the MicroCcdPerEuro exchange rate in
+    /// force at the block.
+    transaction_fees_euro: f64,
     buckets: TransactionMetricsBuckets,
 }
 
@@ -35,6 +44,16 @@ struct TransactionMetricsBuckets {
     /// y-axis value.
     #[graphql(name = "y_TransactionCount")]
     y_transaction_count: Vec<i64>,
+
+    /// Transaction fees in microCCD within the bucket time period. Intended
+    /// y-axis value.
+    #[graphql(name = "y_TransactionFeesMicroCcd")]
+    y_transaction_fees_micro_ccd: Vec<Amount>,
+
+    /// Transaction fees in EUR within the bucket time period. Intended y-axis
+    /// value.
+    #[graphql(name = "y_TransactionFeesEuro")]
+    y_transaction_fees_euro: Vec<f64>,
 }
 
 #[Object]
@@ -68,24 +87,33 @@ impl QueryTransactionMetrics {
             } else {
                 (0, 0)
             };
-        let (x_time, (y_last_cumulative_transaction_count, y_transaction_count)) = rows
-            .iter()
-            .map(|row| {
-                let x_time = row.bucket_time;
-                let y_last_cumulative_transaction_count = row.end_cumulative_num_txs;
-                let y_transaction_count = row.end_cumulative_num_txs - row.start_cumulative_num_txs;
-                (x_time, (y_last_cumulative_transaction_count, y_transaction_count))
-            })
-            .collect();
+        let mut buckets = TransactionMetricsBuckets {
+            bucket_width: TimeSpan(bucket_width),
+            x_time: Vec::with_capacity(rows.len()),
+            y_last_cumulative_transaction_count: Vec::with_capacity(rows.len()),
+            y_transaction_count: Vec::with_capacity(rows.len()),
+            y_transaction_fees_micro_ccd: Vec::with_capacity(rows.len()),
+            y_transaction_fees_euro: Vec::with_capacity(rows.len()),
+        };
+        let mut transaction_fees_micro_ccd = 0;
+        let mut transaction_fees_euro = 0.0;
+        for row in rows {
+            buckets.x_time.push(row.bucket_time);
+            buckets.y_last_cumulative_transaction_count.push(row.end_cumulative_num_txs);
+            buckets
+                .y_transaction_count
+                .push(row.end_cumulative_num_txs - row.start_cumulative_num_txs);
+            buckets.y_transaction_fees_micro_ccd.push(row.ccd_fees.try_into()?);
+            buckets.y_transaction_fees_euro.push(row.euro_fees);
+            transaction_fees_micro_ccd += row.ccd_fees;
+            transaction_fees_euro += row.euro_fees;
+        }
         Ok(TransactionMetrics {
             last_cumulative_transaction_count,
             transaction_count,
-            buckets: TransactionMetricsBuckets {
-                bucket_width: TimeSpan(bucket_width),
-                x_time,
-                y_last_cumulative_transaction_count,
-                y_transaction_count,
-            },
+            transaction_fees_micro_ccd: transaction_fees_micro_ccd.try_into()?,
+            transaction_fees_euro,
+            buckets,
         })
     }
 }
@@ -1,14 +1,23 @@
 -- Counts transactions in buckets by counting the cumulative total number of
 -- transactions at or before (i.e. <=) the start of the bucket and the same number just
 -- before (i.e. <) the next bucket. The difference between the two numbers should
--- give the total number of transactions within the bucket.
+-- give the total number of transactions within the bucket. The transaction fees in CCD and
+-- in EUR within the bucket are computed the same way from the cumulative fees.
 SELECT
     -- The bucket time is the starting time of the bucket.
     bucket_time.bucket_start as "bucket_time!",
     -- Number of transactions at or before the bucket.
     COALESCE(before_bucket.cumulative_num_txs, 0) as "start_cumulative_num_txs!",
     -- Number of transactions at the end of the bucket.
-    COALESCE(after_bucket.cumulative_num_txs, 0) as "end_cumulative_num_txs!"
+    COALESCE(after_bucket.cumulative_num_txs, 0) as "end_cumulative_num_txs!",
+    -- Transaction fees in microCCD within the bucket.
+    COALESCE(after_bucket.cumulative_ccd_fees, 0)
+        - COALESCE(before_bucket.cumulative_ccd_fees, 0) as "ccd_fees!",
+    -- Transaction fees in EUR within the bucket.
+    (
+        COALESCE(after_bucket.cumulative_euro_fees, 0)
+            - COALESCE(before_bucket.cumulative_euro_fees, 0)
+    )::FLOAT8 as "euro_fees!"
 FROM
     -- We generate a time series of all the buckets where transactions will be counted.
     -- For the rest of the comments, let's go with the example of a full period of 7 days with 6 hour buckets.
@@ -22,7 +31,7 @@ FROM
     ) AS bucket_time
 LEFT JOIN LATERAL (
     -- Selects the cumulative number of transactions at or before the start of the bucket.
-    SELECT cumulative_num_txs
+    SELECT cumulative_num_txs, cumulative_ccd_fees, cumulative_euro_fees
     FROM blocks
     WHERE slot_time < bucket_time.bucket_start
     ORDER BY slot_time DESC
@@ -30,7 +39,7 @@ LEFT JOIN LATERAL (
 ) before_bucket ON true
 LEFT JOIN LATERAL (
     -- Selects the cumulative number of transactions at the end of the bucket.
-    SELECT cumulative_num_txs
+    SELECT cumulative_num_txs, cumulative_ccd_fees, cumulative_euro_fees
     FROM blocks
     WHERE slot_time < bucket_time.bucket_end
     ORDER BY slot_time DESC
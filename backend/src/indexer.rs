@@ -14,12 +14,32 @@ use tracing::info;
 mod block;
 mod block_preprocessor;
 mod block_processor;
+mod contract_event_decoder;
 mod db;
 mod ensure_affected_rows;
 mod genesis_data;
+mod module_build_info_reader;
+mod price_recorder;
 mod statistics;
+mod token_metadata_fetcher;
 
+pub(crate) use block::{
+    block_item::chain_update::{mark_effective_chain_updates, QueuedChainUpdate},
+    governance_keys::GovernanceKeys,
+    micro_ccd_per_euro,
+};
 pub use db::lock::acquire_indexer_lock;
+pub(crate) use db::{
+    account_cooldowns::PreparedAccountCooldowns,
+    account_credential::PreparedAccountCredential,
+    contract_call_tree::{CallTraceStep, PreparedContractCallTree},
+    contract_standards::{standard_identifiers, PreparedContractStandards},
+    credential_registry::{Cis4Event, PreparedCredentialRegistryEvents},
+    identity_registry::{PreparedAnonymityRevoker, PreparedIdentityProvider},
+    smart_contract_wallet::{Cis5Event, PreparedSmartContractWalletEvents},
+    sponsored_action::PreparedSponsoredAction,
+};
+pub(crate) use ensure_affected_rows::EnsureAffectedRows;
 
 #[derive(clap::Args)]
 pub struct IndexerServiceConfig {
@@ -62,6 +82,39 @@ pub struct IndexerServiceConfig {
     /// down the service.
     #[arg(long, env = "CCDSCAN_INDEXER_CONFIG_MAX_SUCCESSIVE_FAILURES", default_value = "10")]
     pub max_successive_failures:          u32,
+    /// Decode the parameters and events of contract updates using the schema
+    /// of the contract module, allowing the API to filter contract events by
+    /// the decoded values. Events indexed before enabling this are decoded
+    /// as well.
+    #[arg(long, env = "CCDSCAN_INDEXER_CONFIG_DECODE_CONTRACT_EVENTS")]
+    pub decode_contract_events:           bool,
+    /// Fetch the metadata JSON of CIS-2 tokens from their metadata URL,
+    /// allowing the API to filter the tokens of a collection by the attributes
+    /// in the metadata. Only HTTP(S) URLs are fetched.
+    #[arg(long, env = "CCDSCAN_INDEXER_CONFIG_FETCH_TOKEN_METADATA")]
+    pub fetch_token_metadata:             bool,
+    /// Sources of the prices of CCD and tokens recorded in the price history,
+    /// each provided as an HTTP(S) URL of a JSON document, `file:PATH` for a
+    /// JSON document read from a file or `chain` for the CCD to EUR exchange
+    /// rate on chain. The JSON documents map each asset to its price per
+    /// currency, such as `{"CCD": {"USD": 0.02}}`. When several sources
+    /// provide the same price, the source listed first is used.
+    #[arg(long, env = "CCDSCAN_INDEXER_CONFIG_PRICE_SOURCES", value_delimiter = ',')]
+    pub price_source:                     Vec<price_recorder::PriceSourceConfig>,
+    /// Identifiers of custom standards, in addition to CIS-0 through CIS-5,
+    /// for which the support of by contracts is tracked, allowing the API to
+    /// filter contracts by these.
+    #[arg(long, env = "CCDSCAN_INDEXER_CONFIG_EXTRA_STANDARD_IDENTIFIERS", value_delimiter = ',')]
+    pub extra_standard_identifier:        Vec<String>,
+    /// Number of seconds between recording the prices from the price sources,
+    /// at least one.
+    #[arg(
+        long,
+        env = "CCDSCAN_INDEXER_CONFIG_PRICE_RECORD_INTERVAL",
+        default_value = "300",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub price_record_interval:            u64,
 }
 
 /// Service traversing each block of the chain, indexing it into a database.
@@ -73,15 +126,23 @@ pub struct IndexerServiceConfig {
 /// count all rows via a table scan.
 pub struct IndexerService {
     /// List of Concordium nodes to cycle through when traversing.
-    endpoints:           Vec<v2::Endpoint>,
+    endpoints:                Vec<v2::Endpoint>,
     /// The block height to traversing from.
-    start_height:        u64,
+    start_height:             u64,
     /// State tracked by the block preprocessor during traversing.
-    block_pre_processor: block_preprocessor::BlockPreProcessor,
+    block_pre_processor:      block_preprocessor::BlockPreProcessor,
     /// State tracked by the block processor, which is submitting to the
     /// database.
-    block_processor:     block_processor::BlockProcessor,
-    config:              IndexerServiceConfig,
+    block_processor:          block_processor::BlockProcessor,
+    /// Decoder of contract events, if enabled.
+    contract_event_decoder:   Option<contract_event_decoder::ContractEventDecoder>,
+    /// Fetcher of CIS-2 token metadata, if enabled.
+    token_metadata_fetcher:   Option<token_metadata_fetcher::TokenMetadataFetcher>,
+    price_recorder:           Option<price_recorder::PriceRecorder>,
+    /// Reader of the build information of modules deployed before it was
+    /// tracked.
+    module_build_info_reader: module_build_info_reader::ModuleBuildInfoReader,
+    config:                   IndexerServiceConfig,
 }
 
 impl IndexerService {
@@ -125,8 +186,37 @@ impl IndexerService {
         let block_pre_processor = block_preprocessor::BlockPreProcessor::new(
             genesis_block_hash,
             config.max_successive_failures.into(),
+            standard_identifiers(&config.extra_standard_identifier),
             registry.sub_registry_with_prefix("preprocessor"),
         );
+        let contract_event_decoder = config.decode_contract_events.then(|| {
+            contract_event_decoder::ContractEventDecoder::new(
+                db_connect_options.clone(),
+                registry.sub_registry_with_prefix("contract_event_decoder"),
+            )
+        });
+        let token_metadata_fetcher = config
+            .fetch_token_metadata
+            .then(|| {
+                token_metadata_fetcher::TokenMetadataFetcher::new(
+                    db_connect_options.clone(),
+                    registry.sub_registry_with_prefix("token_metadata_fetcher"),
+                )
+            })
+            .transpose()?;
+        let price_recorder = (!config.price_source.is_empty()).then(|| {
+            price_recorder::PriceRecorder::new(
+                db_connect_options.clone(),
+                config.price_source.clone(),
+                Duration::from_secs(config.price_record_interval),
+                registry.sub_registry_with_prefix("price_recorder"),
+            )
+        });
+        let module_build_info_reader = module_build_info_reader::ModuleBuildInfoReader::new(
+            db_connect_options.clone(),
+            endpoints.clone(),
+            registry.sub_registry_with_prefix("module_build_info_reader"),
+        );
         let block_processor = block_processor::BlockProcessor::new(
             db_connect_options,
             db_connection,
@@ -141,6 +231,10 @@ impl IndexerService {
             start_height,
             block_pre_processor,
             block_processor,
+            contract_event_decoder,
+            token_metadata_fetcher,
+            price_recorder,
+            module_build_info_reader,
             config,
         })
     }
@@ -152,6 +246,9 @@ impl IndexerService {
             .context("Failed setting up TraverseConfig")?
             .set_max_parallel(self.config.max_parallel_block_preprocessors)
             .set_max_behind(std::time::Duration::from_secs(self.config.node_max_behind));
+        // The decoder, the metadata fetcher, the price recorder and the build
+        // information reader are stopped when the indexer stops.
+        let decoder_stop_signal = cancel_token.child_token();
         let processor_config = concordium_rust_sdk::indexer::ProcessorConfig::new()
             .set_stop_signal(cancel_token.cancelled_owned());
 
@@ -162,11 +259,33 @@ impl IndexerService {
             tokio::spawn(traverse_config.traverse(self.block_pre_processor, sender));
         let process_future =
             tokio::spawn(processor_config.process_event_stream(self.block_processor, receiver));
+        let decoder_future = self
+            .contract_event_decoder
+            .map(|decoder| tokio::spawn(decoder.run(decoder_stop_signal.clone())));
+        let fetcher_future = self
+            .token_metadata_fetcher
+            .map(|fetcher| tokio::spawn(fetcher.run(decoder_stop_signal.clone())));
+        let recorder_future = self
+            .price_recorder
+            .map(|recorder| tokio::spawn(recorder.run(decoder_stop_signal.clone())));
+        let reader_future =
+            tokio::spawn(self.module_build_info_reader.run(decoder_stop_signal.clone()));
         info!("Indexing from block height {}", self.start_height);
         // Wait for both processes to exit, in case one of them results in an error,
         // wait for the other which then eventually will stop gracefully as either end
         // of their channel will get dropped.
         let (traverse_result, process_result) = futures::join!(traverse_future, process_future);
+        decoder_stop_signal.cancel();
+        if let Some(decoder_future) = decoder_future {
+            decoder_future.await??;
+        }
+        if let Some(fetcher_future) = fetcher_future {
+            fetcher_future.await??;
+        }
+        if let Some(recorder_future) = recorder_future {
+            recorder_future.await??;
+        }
+        reader_future.await??;
         process_result?;
         Ok(traverse_result??)
     }
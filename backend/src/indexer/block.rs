@@ -4,9 +4,11 @@ use crate::indexer::{
     block_preprocessor::BlockData, block_processor::BlockProcessingContext, statistics::Statistics,
 };
 use anyhow::Context;
+use bigdecimal::{BigDecimal, RoundingMode};
 use block_item::PreparedBlockItem;
 use chrono::{DateTime, Utc};
-use concordium_rust_sdk::v2;
+use concordium_rust_sdk::{types::ExchangeRate, v2};
+use governance_keys::PreparedGovernanceKeys;
 use protocol_update_migration::ProtocolUpdateMigration;
 use special_transaction_outcomes::{
     validator_suspension::PreparedUnmarkPrimedForSuspension, PreparedSpecialTransactionOutcomes,
@@ -14,6 +16,7 @@ use special_transaction_outcomes::{
 use tracing::debug;
 
 pub mod block_item;
+pub mod governance_keys;
 pub mod protocol_update_migration;
 pub mod special_transaction_outcomes;
 
@@ -43,6 +46,10 @@ pub struct PreparedBlock {
     total_amount: i64,
     /// Total staked CCD including delegation at the time of this block.
     total_staked: i64,
+    /// The MicroCcdPerEuro exchange rate in force at the time of this block.
+    micro_ccd_per_euro: ExchangeRate,
+    /// Total transaction fees paid in the block (in microCCD).
+    transaction_fees: i64,
     /// Block hash of the last finalized block.
     block_last_finalized: String,
     /// Preprocessed block items, ready to be saved in the database.
@@ -59,10 +66,16 @@ pub struct PreparedBlock {
     protocol_update_migration: Option<ProtocolUpdateMigration>,
     /// Validator staking information to be updated in the database
     validator_staking_information: ValidatorStakingInformation,
+    /// Governance keys authorizing chain updates as of this block.
+    governance_keys: PreparedGovernanceKeys,
 }
 
 impl PreparedBlock {
-    pub async fn prepare(node_client: &mut v2::Client, data: &BlockData) -> anyhow::Result<Self> {
+    pub async fn prepare(
+        node_client: &mut v2::Client,
+        data: &BlockData,
+        standard_identifiers: &[String],
+    ) -> anyhow::Result<Self> {
         let height = i64::try_from(data.finalized_block_info.height.height)?;
         let hash = data.finalized_block_info.block_hash.to_string();
         let block_last_finalized = data.block_info.block_last_finalized.to_string();
@@ -76,13 +89,22 @@ impl PreparedBlock {
         let total_amount =
             i64::try_from(data.tokenomics_info.common_reward_data().total_amount.micro_ccd())?;
         let total_staked = i64::try_from(data.total_staked.micro_ccd())?;
+        let micro_ccd_per_euro = micro_ccd_per_euro(&data.chain_parameters);
         let mut prepared_block_items = Vec::new();
         for (item_summary, item) in data.events.iter().zip(data.items.iter()) {
             prepared_block_items.push(
-                PreparedBlockItem::prepare(node_client, data, item_summary, item, &mut statistics)
-                    .await?,
+                PreparedBlockItem::prepare(
+                    node_client,
+                    data,
+                    item_summary,
+                    item,
+                    standard_identifiers,
+                    &mut statistics,
+                )
+                .await?,
             )
         }
+        let transaction_fees = prepared_block_items.iter().map(|item| item.ccd_cost).sum();
 
         let special_transaction_outcomes = PreparedSpecialTransactionOutcomes::prepare(
             node_client,
@@ -99,6 +121,7 @@ impl PreparedBlock {
 
         let validator_staking_information: ValidatorStakingInformation =
             data.validator_staking_information.clone();
+        let governance_keys = PreparedGovernanceKeys::prepare(data);
 
         Ok(Self {
             hash,
@@ -107,6 +130,8 @@ impl PreparedBlock {
             baker_id,
             total_amount,
             total_staked,
+            micro_ccd_per_euro,
+            transaction_fees,
             block_last_finalized,
             prepared_block_items,
             special_transaction_outcomes,
@@ -114,6 +139,7 @@ impl PreparedBlock {
             statistics,
             protocol_update_migration,
             validator_staking_information,
+            governance_keys,
         })
     }
 
@@ -130,6 +156,10 @@ impl PreparedBlock {
         let mut total_staked = Vec::with_capacity(batch.len());
         let mut block_times = Vec::with_capacity(batch.len());
         let mut cumulative_num_txss = Vec::with_capacity(batch.len());
+        let mut micro_ccd_per_euro_numerators = Vec::with_capacity(batch.len());
+        let mut micro_ccd_per_euro_denominators = Vec::with_capacity(batch.len());
+        let mut cumulative_ccd_fees = Vec::with_capacity(batch.len());
+        let mut cumulative_euro_fees = Vec::with_capacity(batch.len());
 
         let mut finalizers = Vec::with_capacity(batch.len());
         let mut last_finalizeds = Vec::with_capacity(batch.len());
@@ -151,6 +181,15 @@ impl PreparedBlock {
             context.last_cumulative_num_txs += block.prepared_block_items.len() as i64;
             cumulative_num_txss.push(context.last_cumulative_num_txs);
             context.last_block_slot_time = block.slot_time;
+            micro_ccd_per_euro_numerators
+                .push(BigDecimal::from(block.micro_ccd_per_euro.numerator()));
+            micro_ccd_per_euro_denominators
+                .push(BigDecimal::from(block.micro_ccd_per_euro.denominator()));
+            context.last_cumulative_ccd_fees += block.transaction_fees;
+            cumulative_ccd_fees.push(context.last_cumulative_ccd_fees);
+            context.last_cumulative_euro_fees +=
+                micro_ccd_to_euro(block.transaction_fees, block.micro_ccd_per_euro);
+            cumulative_euro_fees.push(context.last_cumulative_euro_fees.clone());
 
             // Check if this block knows of a new finalized block.
             // If so, note it down so we can mark the blocks since last time as finalized by
@@ -173,7 +212,11 @@ impl PreparedBlock {
                 baker_id, 
                 total_amount, 
                 total_staked, 
-                cumulative_num_txs
+                cumulative_num_txs,
+                micro_ccd_per_euro_numerator,
+                micro_ccd_per_euro_denominator,
+                cumulative_ccd_fees,
+                cumulative_euro_fees
             )
             SELECT * FROM UNNEST(
                 $1::BIGINT[],
@@ -183,7 +226,11 @@ impl PreparedBlock {
                 $5::BIGINT[],
                 $6::BIGINT[],
                 $7::BIGINT[],
-                $8::BIGINT[]
+                $8::BIGINT[],
+                $9::NUMERIC[],
+                $10::NUMERIC[],
+                $11::BIGINT[],
+                $12::NUMERIC[]
             );",
             &heights,
             &hashes,
@@ -192,7 +239,11 @@ impl PreparedBlock {
             &baker_ids as &[Option<i64>],
             &total_amounts,
             &total_staked,
-            &cumulative_num_txss
+            &cumulative_num_txss,
+            &micro_ccd_per_euro_numerators,
+            &micro_ccd_per_euro_denominators,
+            &cumulative_ccd_fees,
+            &cumulative_euro_fees
         )
         .execute(tx.as_mut())
         .await?;
@@ -273,6 +324,8 @@ impl PreparedBlock {
         if let Some(migration) = self.protocol_update_migration.as_ref() {
             migration.save(tx).await?;
         }
+        let effective_update_types =
+            block_item::chain_update::mark_effective_chain_updates(tx, self.slot_time).await?;
         for item in self.prepared_block_items.iter() {
             item.save(tx, self.slot_time).await.with_context(|| {
                 format!(
@@ -283,6 +336,7 @@ impl PreparedBlock {
         }
         self.statistics.save(tx).await?;
         self.special_transaction_outcomes.save(tx).await?;
+        self.governance_keys.save(tx, self.height, &effective_update_types).await?;
 
         // gather vectors for the update query
         let ids = &self.validator_staking_information.ids;
@@ -314,3 +368,42 @@ impl PreparedBlock {
         Ok(())
     }
 }
+
+/// The MicroCcdPerEuro exchange rate of the chain parameters.
+pub(crate) fn micro_ccd_per_euro(chain_parameters: &v2::ChainParameters) -> ExchangeRate {
+    match chain_parameters {
+        v2::ChainParameters::V0(parameters) => parameters.micro_ccd_per_euro,
+        v2::ChainParameters::V1(parameters) => parameters.micro_ccd_per_euro,
+        v2::ChainParameters::V2(parameters) => parameters.micro_ccd_per_euro,
+        v2::ChainParameters::V3(parameters) => parameters.micro_ccd_per_euro,
+    }
+}
+
+/// Convert an amount of microCCD to EUR using the MicroCcdPerEuro exchange
+/// rate, rounded to 12 decimals as when computed by the database migration.
+fn micro_ccd_to_euro(micro_ccd: i64, micro_ccd_per_euro: ExchangeRate) -> BigDecimal {
+    let euro = BigDecimal::from(micro_ccd) * BigDecimal::from(micro_ccd_per_euro.denominator())
+        / BigDecimal::from(micro_ccd_per_euro.numerator());
+    euro.with_scale_round(12, RoundingMode::HalfUp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_micro_ccd_to_euro() {
+        // 3 CCD per EUR, so one CCD is a third of a EUR.
+        let rate = ExchangeRate::new(3_000_000, 1).unwrap();
+        assert_eq!(
+            micro_ccd_to_euro(1_000_000, rate),
+            BigDecimal::from_str("0.333333333333").unwrap()
+        );
+        assert_eq!(
+            micro_ccd_to_euro(2_000_000, rate),
+            BigDecimal::from_str("0.666666666667").unwrap()
+        );
+        assert_eq!(micro_ccd_to_euro(0, rate), BigDecimal::from(0));
+    }
+}
@@ -30,6 +30,7 @@ use concordium_rust_sdk::{
 
 mod account_creation;
 mod account_transaction;
+pub mod chain_update;
 mod plt_token_creation;
 
 /// Prepared block item (transaction), ready to be inserted in the database
@@ -39,7 +40,7 @@ pub struct PreparedBlockItem {
     pub block_item_hash: String,
     /// Cost for the account signing the block item (in microCCD), always 0 for
     /// update and credential deployments.
-    ccd_cost:            i64,
+    pub ccd_cost:        i64,
     /// Energy cost of the execution of the block item.
     energy_cost:         i64,
     /// Absolute height of the block.
@@ -78,6 +79,7 @@ impl PreparedBlockItem {
         data: &BlockData,
         item_summary: &BlockItemSummary,
         item: &BlockItem<EncodedPayload>,
+        standard_identifiers: &[String],
         statistics: &mut Statistics,
     ) -> anyhow::Result<Self> {
         let block_height = i64::try_from(data.finalized_block_info.height.height)?;
@@ -105,7 +107,7 @@ impl PreparedBlockItem {
                     (DbTransactionType::CredentialDeployment, None, Some(credential_type), None)
                 }
                 BlockItemSummaryDetails::Update(details) => {
-                    let update_type = UpdateTransactionType::from(details.update_type());
+                    let update_type = UpdateTransactionType::from(&details.payload);
                     (DbTransactionType::Update, None, None, Some(update_type))
                 }
                 BlockItemSummaryDetails::TokenCreationDetails(_token_creation_details) => (
@@ -134,7 +136,7 @@ impl PreparedBlockItem {
                     ..
                 }) = &item_summary.details
                 {
-                    PreparedTransactionRejectReason::prepare(reject_reason.clone())?
+                    PreparedTransactionRejectReason::prepare(reject_reason.clone(), item)?
                 } else {
                     anyhow::bail!("Invariant violation: Failed transaction without a reject reason")
                 };
@@ -148,9 +150,15 @@ impl PreparedBlockItem {
             .into_iter()
             .collect();
 
-        let prepared_event =
-            PreparedBlockItemEvent::prepare(node_client, data, item_summary, item, statistics)
-                .await?;
+        let prepared_event = PreparedBlockItemEvent::prepare(
+            node_client,
+            data,
+            item_summary,
+            item,
+            standard_identifiers,
+            statistics,
+        )
+        .await?;
 
         Ok(Self {
             block_item_hash,
@@ -270,7 +278,7 @@ enum PreparedBlockItemEvent {
     /// An account transaction event.
     AccountTransaction(Box<account_transaction::PreparedAccountTransaction>),
     /// Chain update transaction event.
-    ChainUpdate,
+    ChainUpdate(chain_update::PreparedChainUpdate),
     /// Token creation transaction event
     TokenCreation(Box<plt_token_creation::PreparedTokenCreationDetails>),
 }
@@ -281,12 +289,13 @@ impl PreparedBlockItemEvent {
         data: &BlockData,
         item_summary: &BlockItemSummary,
         item: &BlockItem<EncodedPayload>,
+        standard_identifiers: &[String],
         statistics: &mut Statistics,
     ) -> anyhow::Result<Self> {
         match &item_summary.details {
             BlockItemSummaryDetails::AccountCreation(details) => {
                 Ok(PreparedBlockItemEvent::AccountCreation(
-                    account_creation::PreparedAccountCreation::prepare(details)?,
+                    account_creation::PreparedAccountCreation::prepare(details, item)?,
                 ))
             }
             BlockItemSummaryDetails::AccountTransaction(details) => {
@@ -296,12 +305,15 @@ impl PreparedBlockItemEvent {
                         data,
                         details,
                         item,
+                        standard_identifiers,
                         statistics,
                     )
                     .await?,
                 )))
             }
-            BlockItemSummaryDetails::Update(_) => Ok(PreparedBlockItemEvent::ChainUpdate),
+            BlockItemSummaryDetails::Update(details) => Ok(PreparedBlockItemEvent::ChainUpdate(
+                chain_update::PreparedChainUpdate::prepare(data, details, item)?,
+            )),
             BlockItemSummaryDetails::TokenCreationDetails(token_creation_details) => {
                 Ok(PreparedBlockItemEvent::TokenCreation(Box::new(
                     plt_token_creation::PreparedTokenCreationDetails::prepare(
@@ -325,7 +337,9 @@ impl PreparedBlockItemEvent {
             PreparedBlockItemEvent::AccountTransaction(account_transaction_event) => {
                 account_transaction_event.save(tx, transaction_index, slot_time).await
             }
-            PreparedBlockItemEvent::ChainUpdate => Ok(()),
+            PreparedBlockItemEvent::ChainUpdate(event) => {
+                event.save(tx, transaction_index, slot_time).await
+            }
             PreparedBlockItemEvent::TokenCreation(event) => {
                 event.save(tx, transaction_index, slot_time).await
             }
@@ -2,7 +2,11 @@
 //! deployment) block item during the concurrent preprocessing and the logic for
 //! how to do the sequential processing into the database.
 
-use concordium_rust_sdk::base::contracts_common::CanonicalAccountAddress;
+use crate::indexer::db::account_credential::PreparedAccountCredential;
+use concordium_rust_sdk::base::{
+    contracts_common::CanonicalAccountAddress,
+    transactions::{BlockItem, EncodedPayload},
+};
 
 /// Prepared database insertion of a new account.
 #[derive(Debug)]
@@ -10,15 +14,25 @@ pub struct PreparedAccountCreation {
     /// The base58check representation of the canonical account address.
     account_address:   String,
     canonical_address: CanonicalAccountAddress,
+    /// The credential deployed when creating the account.
+    credential:        PreparedAccountCredential,
 }
 
 impl PreparedAccountCreation {
     pub fn prepare(
         details: &concordium_rust_sdk::types::AccountCreationDetails,
+        item: &BlockItem<EncodedPayload>,
     ) -> anyhow::Result<Self> {
+        let BlockItem::CredentialDeployment(message) = item else {
+            anyhow::bail!(
+                "Invariant violation: Block item with account creation outcome is not a \
+                 credential deployment"
+            )
+        };
         Ok(Self {
             account_address:   details.address.to_string(),
             canonical_address: details.address.get_canonical_address(),
+            credential:        PreparedAccountCredential::from_deployment(message)?,
         })
     }
 
@@ -49,6 +63,7 @@ impl PreparedAccountCreation {
         .execute(tx.as_mut())
         .await?;
 
+        self.credential.save(tx, account_index, Some(transaction_index)).await?;
         Ok(())
     }
 }
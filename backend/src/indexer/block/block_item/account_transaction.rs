@@ -15,6 +15,7 @@ use crate::{
         block_preprocessor::BlockData,
         db::update_account_balance::PreparedUpdateAccountBalance,
         statistics::Statistics,
+        PreparedAccountCooldowns,
     },
 };
 use anyhow::{Context, Ok};
@@ -28,6 +29,7 @@ use concordium_rust_sdk::{
 
 mod baker_events;
 mod contract_events;
+mod credential_events;
 mod delegation_events;
 mod module_events;
 mod plt_events;
@@ -38,9 +40,12 @@ mod transfer_events;
 pub struct PreparedAccountTransaction {
     /// Update the balance of the sender account with the cost (transaction
     /// fee).
-    fee:   PreparedUpdateAccountBalance,
+    fee:       PreparedUpdateAccountBalance,
     /// Updates based on the events of the account transaction.
-    event: PreparedEventEnvelope,
+    event:     PreparedEventEnvelope,
+    /// The stake of the sender account in cooldown, when the transaction
+    /// configures the stake of the sender.
+    cooldowns: Option<PreparedAccountCooldowns>,
 }
 
 impl PreparedAccountTransaction {
@@ -49,6 +54,7 @@ impl PreparedAccountTransaction {
         data: &BlockData,
         details: &AccountTransactionDetails,
         item: &BlockItem<EncodedPayload>,
+        standard_identifiers: &[String],
         statistics: &mut Statistics,
     ) -> anyhow::Result<Self> {
         let fee = PreparedUpdateAccountBalance::prepare(
@@ -63,12 +69,35 @@ impl PreparedAccountTransaction {
             details,
             item,
             &details.sender,
+            standard_identifiers,
             statistics,
         )
         .await?;
+        // From protocol version 7, stake removed by configuring a validator or
+        // delegator goes into cooldown, while added stake is first taken from
+        // the stake in cooldown.
+        let configures_stake = matches!(
+            details.effects,
+            AccountTransactionEffects::BakerConfigured { .. }
+                | AccountTransactionEffects::DelegationConfigured { .. }
+        );
+        let cooldowns =
+            if configures_stake && data.block_info.protocol_version >= ProtocolVersion::P7 {
+                Some(
+                    PreparedAccountCooldowns::prepare(
+                        node_client,
+                        v2::AccountIdentifier::Address(details.sender),
+                        v2::BlockIdentifier::AbsoluteHeight(data.block_info.block_height),
+                    )
+                    .await?,
+                )
+            } else {
+                None
+            };
         Ok(Self {
             fee,
             event,
+            cooldowns,
         })
     }
 
@@ -79,7 +108,11 @@ impl PreparedAccountTransaction {
         slot_time: DateTime<Utc>,
     ) -> anyhow::Result<()> {
         self.fee.save(tx, Some(transaction_index)).await?;
-        self.event.save(tx, transaction_index, slot_time).await
+        self.event.save(tx, transaction_index, slot_time).await?;
+        if let Some(cooldowns) = &self.cooldowns {
+            cooldowns.save(tx).await?;
+        }
+        Ok(())
     }
 }
 
@@ -102,10 +135,19 @@ impl PreparedEventEnvelope {
         details: &AccountTransactionDetails,
         item: &BlockItem<EncodedPayload>,
         sender: &AccountAddress,
+        standard_identifiers: &[String],
         statistics: &mut Statistics,
     ) -> anyhow::Result<Self> {
-        let event =
-            PreparedEvent::prepare(node_client, data, details, item, sender, statistics).await?;
+        let event = PreparedEvent::prepare(
+            node_client,
+            data,
+            details,
+            item,
+            sender,
+            standard_identifiers,
+            statistics,
+        )
+        .await?;
         let metadata = EventMetadata {
             protocol_version: data.block_info.protocol_version,
         };
@@ -150,6 +192,10 @@ enum PreparedEvent {
     ContractUpdate(contract_events::PreparedContractUpdates),
     /// A scheduled transfer got executed.
     ScheduledTransfer(transfer_events::PreparedScheduledReleases),
+    /// Credentials got added to or removed from an account.
+    CredentialsUpdated(credential_events::PreparedCredentialsUpdated),
+    /// The keys of a credential got updated.
+    CredentialKeysUpdated(credential_events::PreparedCredentialKeysUpdated),
     /// Rejected transaction.
     RejectedTransaction(rejected_events::PreparedRejectedEvent),
     /// No changes in the database was caused by this event.
@@ -165,6 +211,7 @@ impl PreparedEvent {
         details: &AccountTransactionDetails,
         item: &BlockItem<EncodedPayload>,
         sender: &AccountAddress,
+        standard_identifiers: &[String],
         statistics: &mut Statistics,
     ) -> anyhow::Result<Self> {
         let height = data.block_info.block_height;
@@ -192,14 +239,20 @@ impl PreparedEvent {
                     data,
                     event_data,
                     sender,
+                    standard_identifiers,
                 )
                 .await?,
             ),
             AccountTransactionEffects::ContractUpdateIssued {
                 effects,
             } => PreparedEvent::ContractUpdate(
-                contract_events::PreparedContractUpdates::prepare(node_client, data, effects)
-                    .await?,
+                contract_events::PreparedContractUpdates::prepare(
+                    node_client,
+                    data,
+                    effects,
+                    standard_identifiers,
+                )
+                .await?,
             ),
             AccountTransactionEffects::AccountTransfer {
                 amount,
@@ -330,13 +383,24 @@ impl PreparedEvent {
                     height,
                 )?,
             ),
-            AccountTransactionEffects::CredentialKeysUpdated {
-                ..
-            }
-            | AccountTransactionEffects::CredentialsUpdated {
+            AccountTransactionEffects::CredentialsUpdated {
+                removed_cred_ids,
+                new_threshold,
                 ..
-            }
-            | AccountTransactionEffects::DataRegistered {
+            } => PreparedEvent::CredentialsUpdated(
+                credential_events::PreparedCredentialsUpdated::prepare(
+                    sender,
+                    removed_cred_ids,
+                    *new_threshold,
+                    item,
+                )?,
+            ),
+            AccountTransactionEffects::CredentialKeysUpdated {
+                cred_id,
+            } => PreparedEvent::CredentialKeysUpdated(
+                credential_events::PreparedCredentialKeysUpdated::prepare(cred_id, item)?,
+            ),
+            AccountTransactionEffects::DataRegistered {
                 ..
             } => PreparedEvent::NoOperation,
             AccountTransactionEffects::DelegationConfigured {
@@ -405,6 +469,14 @@ impl PreparedEvent {
                 .save(tx, tx_idx)
                 .await
                 .context("Failed processing block item event with scheduled transfer"),
+            PreparedEvent::CredentialsUpdated(event) => event
+                .save(tx, tx_idx)
+                .await
+                .context("Failed processing block item event with credentials updated"),
+            PreparedEvent::CredentialKeysUpdated(event) => event
+                .save(tx)
+                .await
+                .context("Failed processing block item event with credential keys updated"),
             PreparedEvent::RejectedTransaction(event) => event
                 .save(tx, tx_idx)
                 .await
@@ -4,9 +4,17 @@
 
 use super::module_events::PreparedModuleLinkAction;
 use crate::{
-    graphql_api::AccountStatementEntryType,
+    graphql_api::{AccountStatementEntryType, Cis2BalanceChangeType},
     indexer::{
-        block_preprocessor::BlockData, db::update_account_balance::PreparedUpdateAccountBalance,
+        block_preprocessor::BlockData,
+        db::{
+            contract_call_tree::{CallTraceStep, PreparedContractCallTree},
+            contract_standards::PreparedContractStandards,
+            credential_registry::{Cis4Event, PreparedCredentialRegistryEvents},
+            smart_contract_wallet::{Cis5Event, PreparedSmartContractWalletEvents},
+            sponsored_action::PreparedSponsoredAction,
+            update_account_balance::PreparedUpdateAccountBalance,
+        },
         ensure_affected_rows::EnsureAffectedRows,
     },
     transaction_event::{
@@ -17,7 +25,7 @@ use crate::{
 use anyhow::Context;
 use bigdecimal::BigDecimal;
 use concordium_rust_sdk::{
-    cis0, cis2,
+    cis2,
     common::types::Amount,
     id::types::AccountAddress,
     types::{
@@ -38,6 +46,9 @@ pub struct PreparedContractInitialized {
     module_link_event:    PreparedModuleLinkAction,
     transfer_to_contract: PreparedUpdateAccountBalance,
     cis2_token_events:    Vec<CisEvent>,
+    standards:            PreparedContractStandards,
+    cis4_events:          Option<PreparedCredentialRegistryEvents>,
+    cis5_events:          Option<PreparedSmartContractWalletEvents>,
 }
 
 impl PreparedContractInitialized {
@@ -46,6 +57,7 @@ impl PreparedContractInitialized {
         data: &BlockData,
         event: &ContractInitializedEvent,
         sender_account: &AccountAddress,
+        standard_identifiers: &[String],
     ) -> anyhow::Result<Self> {
         let contract_address = event.address;
         let index = i64::try_from(event.address.index)?;
@@ -67,6 +79,15 @@ impl PreparedContractInitialized {
             AccountStatementEntryType::TransferOut,
         )?;
 
+        let standards = PreparedContractStandards::prepare(
+            node_client,
+            data.block_info.block_height,
+            contract_address,
+            event.init_name.as_contract_name(),
+            standard_identifiers,
+        )
+        .await?;
+
         // To track CIS2 tokens (e.g., token balances, total supply, token metadata
         // URLs), we gather the CIS2 events here. We check if logged contract
         // events can be parsed as CIS2 events. In addition, we check if the
@@ -90,29 +111,48 @@ impl PreparedContractInitialized {
         let potential_cis2_events =
             event.events.iter().filter_map(|log| log.try_into().ok()).collect::<Vec<_>>();
 
-        // If the vector `potential_cis2_events` is not empty, we verify that the smart
-        // contract supports the CIS2 standard before accepting the events as
-        // valid.
-        let cis2_token_events = if potential_cis2_events.is_empty() {
+        // We verify that the smart contract supports the CIS2 standard before
+        // accepting the events as valid.
+        let cis2_token_events = if standards.supports("CIS-2") {
+            potential_cis2_events.into_iter().map(|event: cis2::Event| event.into()).collect()
+        } else {
+            // If contract does not support `CIS2`, don't consider the events as CIS2
+            // events.
             vec![]
+        };
+
+        // CIS-4 events are handled the same way as the CIS2 events.
+        let potential_cis4_events = event
+            .events
+            .iter()
+            .filter_map(|log| Cis4Event::parse(log.as_ref()))
+            .collect::<Vec<_>>();
+        let cis4_events = if !potential_cis4_events.is_empty() && standards.supports("CIS-4") {
+            Some(
+                PreparedCredentialRegistryEvents::prepare(
+                    node_client,
+                    data.block_info.block_height,
+                    contract_address,
+                    &name,
+                    potential_cis4_events,
+                )
+                .await?,
+            )
         } else {
-            let supports_cis2 = cis0::supports(
-                node_client,
-                &v2::BlockIdentifier::AbsoluteHeight(data.block_info.block_height),
+            None
+        };
+        let potential_cis5_events = event
+            .events
+            .iter()
+            .filter_map(|log| Cis5Event::parse(log.as_ref()))
+            .collect::<Vec<_>>();
+        let cis5_events = if !potential_cis5_events.is_empty() && standards.supports("CIS-5") {
+            Some(PreparedSmartContractWalletEvents::prepare(
                 contract_address,
-                event.init_name.as_contract_name(),
-                cis0::StandardIdentifier::CIS2,
-            )
-            .await
-            .is_ok_and(|r| r.response.is_support());
-
-            if supports_cis2 {
-                potential_cis2_events.into_iter().map(|event: cis2::Event| event.into()).collect()
-            } else {
-                // If contract does not support `CIS2`, don't consider the events as CIS2
-                // events.
-                vec![]
-            }
+                potential_cis5_events,
+            )?)
+        } else {
+            None
         };
 
         Ok(Self {
@@ -124,6 +164,9 @@ impl PreparedContractInitialized {
             module_link_event,
             transfer_to_contract,
             cis2_token_events,
+            standards,
+            cis4_events,
+            cis5_events,
         })
     }
 
@@ -156,12 +199,25 @@ impl PreparedContractInitialized {
             .save(tx, transaction_index)
             .await
             .context("Failed linking new contract to module")?;
+        self.standards.save(tx).await?;
 
         for log in self.cis2_token_events.iter() {
             process_cis2_token_event(log, self.index, self.sub_index, transaction_index, tx)
                 .await
                 .context("Failed processing a CIS-2 event")?
         }
+        if let Some(cis4_events) = &self.cis4_events {
+            cis4_events
+                .save(tx, transaction_index)
+                .await
+                .context("Failed processing CIS-4 events")?;
+        }
+        if let Some(cis5_events) = &self.cis5_events {
+            cis5_events
+                .save(tx, transaction_index)
+                .await
+                .context("Failed processing CIS-5 events")?;
+        }
         self.transfer_to_contract.save(tx, Some(transaction_index)).await?;
         Ok(())
     }
@@ -171,7 +227,11 @@ impl PreparedContractInitialized {
 pub struct PreparedContractUpdates {
     /// Additional events to track from the trace elements in the update
     /// transaction.
-    trace_elements: Vec<PreparedTraceElement>,
+    trace_elements:    Vec<PreparedTraceElement>,
+    /// The tree of contract calls made by the transaction.
+    call_tree:         PreparedContractCallTree,
+    /// The CIS-3 sponsored actions executed by the transaction.
+    sponsored_actions: Vec<PreparedSponsoredAction>,
 }
 
 impl PreparedContractUpdates {
@@ -179,6 +239,7 @@ impl PreparedContractUpdates {
         node_client: &mut v2::Client,
         data: &BlockData,
         events: &[ContractTraceElement],
+        standard_identifiers: &[String],
     ) -> anyhow::Result<Self> {
         let trace_elements =
             join_all(events.iter().enumerate().map(|(trace_element_index, effect)| {
@@ -187,13 +248,49 @@ impl PreparedContractUpdates {
                     data,
                     effect,
                     trace_element_index,
+                    standard_identifiers,
                 )
             }))
             .await
             .into_iter()
             .collect::<Result<Vec<_>, anyhow::Error>>()?;
+        let steps =
+            events.iter().map(CallTraceStep::try_from).collect::<anyhow::Result<Vec<_>>>()?;
+
+        // Calls to `permit` are only recorded as sponsored actions when saved, if the
+        // contract supports CIS-3.
+        let mut sponsored_actions = Vec::new();
+        for (trace_element_index, (event, step)) in events.iter().zip(steps.iter()).enumerate() {
+            let (
+                ContractTraceElement::Updated {
+                    data: update,
+                },
+                CallTraceStep::Updated {
+                    caller,
+                    contract_index,
+                    contract_sub_index,
+                    entrypoint,
+                    ..
+                },
+            ) = (event, step)
+            else {
+                continue;
+            };
+            sponsored_actions.extend(PreparedSponsoredAction::detect(
+                trace_element_index.try_into()?,
+                *contract_index,
+                *contract_sub_index,
+                caller.clone(),
+                entrypoint,
+                update.message.as_ref(),
+            ));
+        }
+
+        let call_tree = PreparedContractCallTree::build(steps)?;
         Ok(Self {
             trace_elements,
+            call_tree,
+            sponsored_actions,
         })
     }
 
@@ -211,6 +308,16 @@ impl PreparedContractUpdates {
                 )
             })?;
         }
+        self.call_tree
+            .save(tx, transaction_index)
+            .await
+            .context("Failed saving the contract call tree")?;
+        for action in self.sponsored_actions.iter() {
+            action
+                .save(tx, transaction_index)
+                .await
+                .context("Failed saving the sponsored action")?;
+        }
         Ok(())
     }
 }
@@ -222,6 +329,8 @@ struct PreparedTraceElement {
     contract_sub_index:  i64,
     trace_element_index: i64,
     cis2_token_events:   Vec<CisEvent>,
+    cis4_events:         Option<PreparedCredentialRegistryEvents>,
+    cis5_events:         Option<PreparedSmartContractWalletEvents>,
     trace_event:         PreparedContractTraceEvent,
 }
 
@@ -231,6 +340,7 @@ impl PreparedTraceElement {
         data: &BlockData,
         event: &ContractTraceElement,
         trace_element_index: usize,
+        standard_identifiers: &[String],
     ) -> anyhow::Result<Self> {
         let contract_address = event.affected_address();
 
@@ -268,9 +378,23 @@ impl PreparedTraceElement {
                 address,
                 from,
                 to,
-            } => PreparedContractTraceEvent::Upgrade(PreparedTraceEventUpgrade::prepare(
-                *address, *from, *to,
-            )?),
+            } => {
+                // The new module might support other standards.
+                let standards = PreparedContractStandards::prepare_for_instance(
+                    &mut node_client,
+                    data.block_info.block_height,
+                    *address,
+                    standard_identifiers,
+                )
+                .await?;
+                PreparedContractTraceEvent::Upgrade(PreparedTraceEventUpgrade::prepare(
+                    *address,
+                    *from,
+                    *to,
+                    trace_element_index,
+                    standards,
+                )?)
+            }
         };
 
         // To track CIS2 tokens (e.g., token balances, total supply, token metadata
@@ -293,30 +417,40 @@ impl PreparedTraceElement {
         // handled here while CIS2 events logged in the
         // `ContractInitializedEvent` are handled at its corresponding
         // transaction type.
-        let potential_cis2_events = match event {
+        let logs = match event {
             ContractTraceElement::Updated {
                 data,
-            } => data.events.iter().filter_map(|log| log.try_into().ok()).collect::<Vec<_>>(),
-            ContractTraceElement::Transferred {
-                ..
-            } => vec![],
+            } => data.events.as_slice(),
             ContractTraceElement::Interrupted {
                 events,
                 ..
-            } => events.iter().filter_map(|log| log.try_into().ok()).collect::<Vec<_>>(),
-            ContractTraceElement::Resumed {
+            } => events.as_slice(),
+            ContractTraceElement::Transferred {
                 ..
-            } => vec![],
-            ContractTraceElement::Upgraded {
+            }
+            | ContractTraceElement::Resumed {
+                ..
+            }
+            | ContractTraceElement::Upgraded {
                 ..
-            } => vec![],
+            } => &[],
         };
+        let potential_cis2_events =
+            logs.iter().filter_map(|log| log.try_into().ok()).collect::<Vec<cis2::Event>>();
+        // CIS-4 and CIS-5 events are handled the same way as the CIS2 events.
+        let potential_cis4_events =
+            logs.iter().filter_map(|log| Cis4Event::parse(log.as_ref())).collect::<Vec<_>>();
+        let potential_cis5_events =
+            logs.iter().filter_map(|log| Cis5Event::parse(log.as_ref())).collect::<Vec<_>>();
 
-        // If the vector `potential_cis2_events` is not empty, we verify that the smart
-        // contract supports the CIS2 standard before accepting the events as
+        // If any of the logs are potential CIS2, CIS-4 or CIS-5 events, we verify that
+        // the smart contract supports the standard before accepting the events as
         // valid.
-        let cis2_token_events = if potential_cis2_events.is_empty() {
-            vec![]
+        let (cis2_token_events, cis4_events, cis5_events) = if potential_cis2_events.is_empty()
+            && potential_cis4_events.is_empty()
+            && potential_cis5_events.is_empty()
+        {
+            (vec![], None, None)
         } else {
             let contract_info = node_client
                 .get_instance_info(
@@ -325,24 +459,45 @@ impl PreparedTraceElement {
                 )
                 .await?;
             let contract_name = contract_info.response.name().as_contract_name();
-
-            let supports_cis2 = cis0::supports(
+            let standards = PreparedContractStandards::prepare(
                 &mut node_client,
-                &v2::BlockIdentifier::AbsoluteHeight(data.block_info.block_height),
+                data.block_info.block_height,
                 contract_address,
                 contract_name,
-                cis0::StandardIdentifier::CIS2,
+                standard_identifiers,
             )
-            .await
-            .is_ok_and(|r| r.response.is_support());
+            .await?;
 
-            if supports_cis2 {
-                potential_cis2_events.into_iter().map(|event: cis2::Event| event.into()).collect()
+            // If contract does not support `CIS2`, don't consider the events as CIS2
+            // events.
+            let cis2_token_events = if standards.supports("CIS-2") {
+                potential_cis2_events.into_iter().map(|event| event.into()).collect()
             } else {
-                // If contract does not support `CIS2`, don't consider the events as CIS2
-                // events.
                 vec![]
-            }
+            };
+            let cis4_events = if !potential_cis4_events.is_empty() && standards.supports("CIS-4") {
+                Some(
+                    PreparedCredentialRegistryEvents::prepare(
+                        &mut node_client,
+                        data.block_info.block_height,
+                        contract_address,
+                        contract_name.contract_name(),
+                        potential_cis4_events,
+                    )
+                    .await?,
+                )
+            } else {
+                None
+            };
+            let cis5_events = if !potential_cis5_events.is_empty() && standards.supports("CIS-5") {
+                Some(PreparedSmartContractWalletEvents::prepare(
+                    contract_address,
+                    potential_cis5_events,
+                )?)
+            } else {
+                None
+            };
+            (cis2_token_events, cis4_events, cis5_events)
         };
 
         Ok(Self {
@@ -351,6 +506,8 @@ impl PreparedTraceElement {
             contract_sub_index: sub_index,
             trace_element_index,
             cis2_token_events,
+            cis4_events,
+            cis5_events,
             trace_event,
         })
     }
@@ -398,6 +555,18 @@ impl PreparedTraceElement {
             .await
             .context("Failed processing CIS-2 token event")?
         }
+        if let Some(cis4_events) = &self.cis4_events {
+            cis4_events
+                .save(tx, transaction_index)
+                .await
+                .context("Failed processing CIS-4 events")?;
+        }
+        if let Some(cis5_events) = &self.cis5_events {
+            cis5_events
+                .save(tx, transaction_index)
+                .await
+                .context("Failed processing CIS-5 events")?;
+        }
         Ok(())
     }
 }
@@ -443,6 +612,8 @@ struct PreparedTraceEventUpgrade {
     module_removed:        PreparedModuleLinkAction,
     module_added:          PreparedModuleLinkAction,
     contract_last_upgrade: PreparedUpdateContractLastUpgrade,
+    upgrade:               PreparedContractUpgrade,
+    standards:             PreparedContractStandards,
 }
 
 impl PreparedTraceEventUpgrade {
@@ -450,19 +621,23 @@ impl PreparedTraceEventUpgrade {
         address: ContractAddress,
         from: sdk_types::hashes::ModuleReference,
         to: sdk_types::hashes::ModuleReference,
+        trace_element_index: i64,
+        standards: PreparedContractStandards,
     ) -> anyhow::Result<Self> {
         Ok(Self {
-            module_removed:        PreparedModuleLinkAction::prepare(
+            module_removed: PreparedModuleLinkAction::prepare(
                 from,
                 address,
                 ModuleReferenceContractLinkAction::Removed,
             )?,
-            module_added:          PreparedModuleLinkAction::prepare(
+            module_added: PreparedModuleLinkAction::prepare(
                 to,
                 address,
                 ModuleReferenceContractLinkAction::Added,
             )?,
             contract_last_upgrade: PreparedUpdateContractLastUpgrade::prepare(address)?,
+            upgrade: PreparedContractUpgrade::prepare(address, from, to, trace_element_index)?,
+            standards,
         })
     }
 
@@ -473,7 +648,9 @@ impl PreparedTraceEventUpgrade {
     ) -> anyhow::Result<()> {
         self.module_removed.save(tx, transaction_index).await?;
         self.module_added.save(tx, transaction_index).await?;
-        self.contract_last_upgrade.save(tx, transaction_index).await
+        self.contract_last_upgrade.save(tx, transaction_index).await?;
+        self.upgrade.save(tx, transaction_index).await?;
+        self.standards.save(tx).await
     }
 }
 
@@ -511,6 +688,59 @@ impl PreparedUpdateContractLastUpgrade {
     }
 }
 
+/// An entry in the upgrade history of a contract.
+#[derive(Debug)]
+struct PreparedContractUpgrade {
+    contract_index:        i64,
+    contract_sub_index:    i64,
+    trace_element_index:   i64,
+    from_module_reference: String,
+    to_module_reference:   String,
+}
+impl PreparedContractUpgrade {
+    fn prepare(
+        address: ContractAddress,
+        from: sdk_types::hashes::ModuleReference,
+        to: sdk_types::hashes::ModuleReference,
+        trace_element_index: i64,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            contract_index: i64::try_from(address.index)?,
+            contract_sub_index: i64::try_from(address.subindex)?,
+            trace_element_index,
+            from_module_reference: from.into(),
+            to_module_reference: to.into(),
+        })
+    }
+
+    async fn save(
+        &self,
+        tx: &mut sqlx::PgTransaction<'_>,
+        transaction_index: i64,
+    ) -> anyhow::Result<()> {
+        sqlx::query!(
+            "INSERT INTO contract_upgrades (
+                contract_index,
+                contract_sub_index,
+                transaction_index,
+                trace_element_index,
+                from_module_reference,
+                to_module_reference
+            ) VALUES ($1, $2, $3, $4, $5, $6)",
+            self.contract_index,
+            self.contract_sub_index,
+            transaction_index,
+            self.trace_element_index,
+            self.from_module_reference,
+            self.to_module_reference
+        )
+        .execute(tx.as_mut())
+        .await
+        .context("Failed inserting the contract upgrade")?;
+        Ok(())
+    }
+}
+
 /// Represent a transfer from contract to an account.
 #[derive(Debug)]
 struct PreparedTraceEventTransfer {
@@ -742,6 +972,15 @@ async fn process_cis2_token_event(
                 .execute(tx.as_mut())
                 .await
                 .context("Failed inserting or updating account balance from mint event")?;
+                insert_cis2_balance_change(
+                    tx,
+                    canonical_address.0.as_slice(),
+                    &token_address,
+                    Cis2BalanceChangeType::Mint,
+                    &tokens_minted,
+                    transaction_index,
+                )
+                .await?;
             }
 
             // Insert the token event into the table.
@@ -844,12 +1083,21 @@ async fn process_cis2_token_event(
                     DO UPDATE SET balance = account_tokens.balance + EXCLUDED.balance",
                     canonical_address.0.as_slice(),
                     token_address.to_string(),
-                    -tokens_burned
+                    -tokens_burned.clone()
                 )
                 .execute(tx.as_mut())
                 .await
                 .context("Failed inserting or updating account balance from burn event")?
                 .ensure_affected_rows_in_range(0..=1)?;
+                insert_cis2_balance_change(
+                    tx,
+                    canonical_address.0.as_slice(),
+                    &token_address,
+                    Cis2BalanceChangeType::Burn,
+                    &-tokens_burned,
+                    transaction_index,
+                )
+                .await?;
             }
 
             // Insert the token event into the table.
@@ -956,6 +1204,15 @@ async fn process_cis2_token_event(
                 .context(
                     "Failed inserting or updating account balance from transfer event (sender)",
                 )?;
+                insert_cis2_balance_change(
+                    tx,
+                    canonical_address.0.as_slice(),
+                    &token_address,
+                    Cis2BalanceChangeType::TransferOut,
+                    &-tokens_transferred.clone(),
+                    transaction_index,
+                )
+                .await?;
             }
 
             // If the `to` address doesn't already hold this token, insert a new row with a
@@ -985,6 +1242,15 @@ async fn process_cis2_token_event(
                 .await
                 .context("Failed inserting or updating account balance from transfer event (to)")?
                 .ensure_affected_rows_in_range(0..=1)?;
+                insert_cis2_balance_change(
+                    tx,
+                    canonical_address.0.as_slice(),
+                    &token_address,
+                    Cis2BalanceChangeType::TransferIn,
+                    &tokens_transferred,
+                    transaction_index,
+                )
+                .await?;
             }
 
             // Insert the token event into the table.
@@ -1039,7 +1305,7 @@ async fn process_cis2_token_event(
             sqlx::query!(
                 "
                     INSERT INTO tokens (index, token_index_per_contract, token_address, \
-                 contract_index, contract_sub_index, metadata_url, token_id, \
+                 contract_index, contract_sub_index, metadata_url, metadata_hash, token_id, \
                  init_transaction_index)
                     VALUES (
                         (SELECT COALESCE(MAX(index) + 1, 0) FROM tokens),
@@ -1050,14 +1316,20 @@ async fn process_cis2_token_event(
                         $3,
                         $4,
                         $5,
-                        $6
+                        $6,
+                        $7
                     )
                     ON CONFLICT (token_address)
-                    DO UPDATE SET metadata_url = EXCLUDED.metadata_url",
+                    DO UPDATE SET
+                        metadata_url = EXCLUDED.metadata_url,
+                        metadata_hash = EXCLUDED.metadata_hash,
+                        metadata_fetch_failures = 0,
+                        metadata_retry_after = NULL",
                 token_address,
                 contract_index,
                 contract_sub_index,
                 metadata_url.url(),
+                metadata_url.hash().map(|hash| hash.to_string()),
                 raw_token_id.to_string(),
                 transaction_index
             )
@@ -1093,3 +1365,53 @@ async fn process_cis2_token_event(
     }
     Ok(())
 }
+
+/// Record a change to the CIS-2 token balance of an account in
+/// `cis2_token_balance_changes`, after the balance in `account_tokens` got
+/// updated.
+async fn insert_cis2_balance_change(
+    tx: &mut sqlx::PgTransaction<'_>,
+    canonical_address: &[u8],
+    token_address: &str,
+    change_type: Cis2BalanceChangeType,
+    amount: &BigDecimal,
+    transaction_index: i64,
+) -> anyhow::Result<()> {
+    sqlx::query!(
+        "INSERT INTO cis2_token_balance_changes (
+            account_index,
+            token_index,
+            change_type,
+            amount,
+            account_balance,
+            transaction_index,
+            block_height,
+            slot_time
+        )
+        SELECT
+            account_tokens.account_index,
+            account_tokens.token_index,
+            $3,
+            $4,
+            account_tokens.balance,
+            $5,
+            blocks.height,
+            blocks.slot_time
+        FROM account_tokens
+            JOIN accounts ON accounts.index = account_tokens.account_index
+            JOIN tokens ON tokens.index = account_tokens.token_index
+            JOIN transactions ON transactions.index = $5
+            JOIN blocks ON blocks.height = transactions.block_height
+        WHERE accounts.canonical_address = $1
+            AND tokens.token_address = $2",
+        canonical_address,
+        token_address,
+        change_type as Cis2BalanceChangeType,
+        amount,
+        transaction_index
+    )
+    .execute(tx.as_mut())
+    .await
+    .context("Failed inserting CIS-2 token balance change")?;
+    Ok(())
+}
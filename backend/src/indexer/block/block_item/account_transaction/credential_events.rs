@@ -0,0 +1,138 @@
+//! This module contains information computed for account transactions updating
+//! the credentials of an account during the concurrent preprocessing and the
+//! logic for how to do the sequential processing into the database.
+
+use crate::indexer::db::account_credential::{PreparedAccountCredential, PreparedCredentialKeys};
+use anyhow::Context;
+use concordium_rust_sdk::{
+    base::{
+        contracts_common::CanonicalAccountAddress,
+        transactions::{BlockItem, EncodedPayload, Payload},
+    },
+    id::types::AccountAddress,
+    types::{AccountThreshold, CredentialRegistrationID},
+};
+
+/// Represents credentials being added to and removed from an account.
+#[derive(Debug)]
+pub struct PreparedCredentialsUpdated {
+    /// The account updating its credentials.
+    canonical_address: CanonicalAccountAddress,
+    /// Credentials added to the account.
+    added:             Vec<PreparedAccountCredential>,
+    /// Credential registration IDs of the credentials removed from the
+    /// account.
+    removed:           Vec<String>,
+    /// The new number of credentials needed to sign on behalf of the account.
+    new_threshold:     i16,
+}
+
+impl PreparedCredentialsUpdated {
+    pub fn prepare(
+        sender: &AccountAddress,
+        removed_cred_ids: &[CredentialRegistrationID],
+        new_threshold: AccountThreshold,
+        item: &BlockItem<EncodedPayload>,
+    ) -> anyhow::Result<Self> {
+        let BlockItem::AccountTransaction(account_transaction) = item else {
+            anyhow::bail!("Block item was expected to be an account transaction")
+        };
+        let payload = account_transaction
+            .payload
+            .decode()
+            .context("Failed decoding account transaction payload")?;
+        let Payload::UpdateCredentials {
+            new_cred_infos,
+            ..
+        } = payload
+        else {
+            anyhow::bail!(
+                "Unexpected payload for transaction of type UpdateCredentials: {:?}",
+                payload
+            )
+        };
+        let added = new_cred_infos
+            .iter()
+            .map(|(credential_index, info)| {
+                PreparedAccountCredential::from_deployment_info(credential_index.index, info)
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self {
+            canonical_address: sender.get_canonical_address(),
+            added,
+            removed: removed_cred_ids.iter().map(|id| id.to_string()).collect(),
+            new_threshold: u8::from(new_threshold).into(),
+        })
+    }
+
+    pub async fn save(
+        &self,
+        tx: &mut sqlx::PgTransaction<'_>,
+        transaction_index: i64,
+    ) -> anyhow::Result<()> {
+        let account_index = sqlx::query_scalar!(
+            "UPDATE accounts
+            SET signature_threshold = $2
+            WHERE canonical_address = $1
+            RETURNING index",
+            self.canonical_address.0.as_slice(),
+            self.new_threshold
+        )
+        .fetch_one(tx.as_mut())
+        .await?;
+        sqlx::query!(
+            "DELETE FROM account_credentials
+            WHERE account_index = $1 AND credential_registration_id = ANY($2)",
+            account_index,
+            &self.removed
+        )
+        .execute(tx.as_mut())
+        .await?;
+        for credential in self.added.iter() {
+            credential.save(tx, account_index, Some(transaction_index)).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Represents the keys of a credential being replaced.
+#[derive(Debug)]
+pub struct PreparedCredentialKeysUpdated {
+    /// The credential registration ID of the credential, hex encoded.
+    credential_registration_id: String,
+    /// The new keys of the credential.
+    keys: PreparedCredentialKeys,
+}
+
+impl PreparedCredentialKeysUpdated {
+    pub fn prepare(
+        cred_id: &CredentialRegistrationID,
+        item: &BlockItem<EncodedPayload>,
+    ) -> anyhow::Result<Self> {
+        let BlockItem::AccountTransaction(account_transaction) = item else {
+            anyhow::bail!("Block item was expected to be an account transaction")
+        };
+        let payload = account_transaction
+            .payload
+            .decode()
+            .context("Failed decoding account transaction payload")?;
+        let Payload::UpdateCredentialKeys {
+            keys,
+            ..
+        } = payload
+        else {
+            anyhow::bail!(
+                "Unexpected payload for transaction of type UpdateCredentialKeys: {:?}",
+                payload
+            )
+        };
+        Ok(Self {
+            credential_registration_id: cred_id.to_string(),
+            keys: PreparedCredentialKeys::prepare(&keys),
+        })
+    }
+
+    pub async fn save(&self, tx: &mut sqlx::PgTransaction<'_>) -> anyhow::Result<()> {
+        self.keys.save(tx, &self.credential_registration_id).await
+    }
+}
@@ -2,7 +2,10 @@
 //! events in an account transaction during the concurrent preprocessing and the
 //! logic for how to do the sequential processing into the database.
 
-use crate::transaction_event::smart_contracts::ModuleReferenceContractLinkAction;
+use crate::{
+    module_verification::ModuleBuildInfo,
+    transaction_event::smart_contracts::ModuleReferenceContractLinkAction,
+};
 use anyhow::Context;
 use concordium_rust_sdk::{
     base::smart_contracts::WasmVersion,
@@ -14,6 +17,8 @@ use concordium_rust_sdk::{
 pub struct PreparedModuleDeployed {
     module_reference: String,
     schema:           Option<Vec<u8>>,
+    /// Build information embedded by a verifiable build.
+    build_info:       Option<ModuleBuildInfo>,
 }
 
 impl PreparedModuleDeployed {
@@ -34,10 +39,12 @@ impl PreparedModuleDeployed {
         .ok();
 
         let schema = schema.as_ref().map(concordium_rust_sdk::base::contracts_common::to_bytes);
+        let build_info = ModuleBuildInfo::from_module_source(wasm_module.source.as_ref());
 
         Ok(Self {
             module_reference: module_reference.into(),
             schema,
+            build_info,
         })
     }
 
@@ -46,15 +53,24 @@ impl PreparedModuleDeployed {
         tx: &mut sqlx::PgTransaction<'_>,
         transaction_index: i64,
     ) -> anyhow::Result<()> {
+        let build_info = self.build_info.as_ref();
         sqlx::query!(
             "INSERT INTO smart_contract_modules (
                 module_reference,
                 transaction_index,
-                schema
-            ) VALUES ($1, $2, $3)",
+                schema,
+                build_archive_hash,
+                build_image,
+                build_command,
+                build_source_link
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7)",
             self.module_reference,
             transaction_index,
-            self.schema
+            self.schema,
+            build_info.map(|info| info.archive_hash.as_str()),
+            build_info.map(|info| info.image.as_str()),
+            build_info.map(|info| info.build_command.as_slice()),
+            build_info.and_then(|info| info.source_link.as_deref())
         )
         .execute(tx.as_mut())
         .await
@@ -0,0 +1,219 @@
+//! Information computed for a single chain update block item during the
+//! concurrent preprocessing and the logic for how to do the sequential
+//! processing into the database.
+
+use crate::{
+    indexer::{
+        block_preprocessor::BlockData,
+        db::identity_registry::{PreparedAnonymityRevoker, PreparedIdentityProvider},
+    },
+    transaction_event::chain_update::{ChainUpdatePayload, ChainUpdateState},
+    transaction_type::UpdateTransactionType,
+};
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use concordium_rust_sdk::{
+    base::transactions::{BlockItem, EncodedPayload},
+    types::{UpdateDetails, UpdatePayload},
+};
+
+/// Prepared database insertion of a chain update into the update queue.
+#[derive(Debug)]
+pub struct QueuedChainUpdate {
+    /// The type of update, determining the queue of the update.
+    update_type:     UpdateTransactionType,
+    /// The update sequence number of the update instruction.
+    sequence_number: i64,
+    /// The time at which the update takes effect. None when the update is
+    /// effective immediately.
+    effective_time:  Option<DateTime<Utc>>,
+    /// The update payload as it is represented in the GraphQL API.
+    payload:         serde_json::Value,
+}
+
+impl QueuedChainUpdate {
+    pub fn prepare(
+        details: &UpdateDetails,
+        item: &BlockItem<EncodedPayload>,
+    ) -> anyhow::Result<Self> {
+        let BlockItem::UpdateInstruction(instruction) = item else {
+            anyhow::bail!(
+                "Invariant violation: Block item with chain update outcome is not an update \
+                 instruction"
+            )
+        };
+        let sequence_number = i64::try_from(instruction.header.seq_number.number)?;
+        let effective_time = if details.effective_time.seconds == 0 {
+            None
+        } else {
+            Some(
+                DateTime::from_timestamp(details.effective_time.seconds.try_into()?, 0)
+                    .context("Failed to parse effective time")?,
+            )
+        };
+        let payload = serde_json::to_value(ChainUpdatePayload::from(details.payload.clone()))?;
+        Ok(Self {
+            update_type: UpdateTransactionType::from(&details.payload),
+            sequence_number,
+            effective_time,
+            payload,
+        })
+    }
+
+    /// Enqueue the update, superseding the queued updates it replaces.
+    /// Returns the transaction indices of the superseded updates.
+    pub async fn save(
+        &self,
+        tx: &mut sqlx::PgTransaction<'_>,
+        transaction_index: i64,
+        slot_time: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<i64>> {
+        // Updates effective immediately are considered enqueued with the slot time of
+        // the block as the effective time.
+        let effective_time = self.effective_time.unwrap_or(slot_time);
+        let state = if effective_time > slot_time {
+            ChainUpdateState::Queued
+        } else {
+            ChainUpdateState::Effective
+        };
+        // Enqueuing an update removes every update of the same type from the queue,
+        // which have an effective time at or after the effective time of the new
+        // update.
+        let superseded = sqlx::query_scalar!(
+            "UPDATE chain_update_queue
+            SET
+                state = 'Superseded',
+                superseded_by_transaction_index = $1
+            WHERE state = 'Queued'
+                AND update_type = $2
+                AND effective_time >= $3
+            RETURNING transaction_index",
+            transaction_index,
+            self.update_type as UpdateTransactionType,
+            effective_time
+        )
+        .fetch_all(tx.as_mut())
+        .await?;
+        sqlx::query!(
+            "INSERT INTO chain_update_queue (
+                transaction_index,
+                update_type,
+                sequence_number,
+                effective_time,
+                enqueued_time,
+                state,
+                payload
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            transaction_index,
+            self.update_type as UpdateTransactionType,
+            self.sequence_number,
+            effective_time,
+            slot_time,
+            state as ChainUpdateState,
+            self.payload
+        )
+        .execute(tx.as_mut())
+        .await?;
+        Ok(superseded)
+    }
+}
+
+/// Prepared database insertion of a chain update, enqueuing the update and
+/// registering the identity providers and anonymity revokers it adds.
+#[derive(Debug)]
+pub struct PreparedChainUpdate {
+    /// The update to enqueue.
+    update:          QueuedChainUpdate,
+    /// Height of the block containing the update.
+    block_height:    i64,
+    /// Update of the identity provider and anonymity revoker registry.
+    registry_update: Option<PreparedRegistryUpdate>,
+}
+
+/// Identity provider or anonymity revoker added by a chain update.
+#[derive(Debug)]
+enum PreparedRegistryUpdate {
+    IdentityProvider(PreparedIdentityProvider),
+    AnonymityRevoker(PreparedAnonymityRevoker),
+}
+
+impl PreparedChainUpdate {
+    pub fn prepare(
+        data: &BlockData,
+        details: &UpdateDetails,
+        item: &BlockItem<EncodedPayload>,
+    ) -> anyhow::Result<Self> {
+        let registry_update = match &details.payload {
+            UpdatePayload::AddIdentityProvider(info) => Some(
+                PreparedRegistryUpdate::IdentityProvider(PreparedIdentityProvider::prepare(info)),
+            ),
+            UpdatePayload::AddAnonymityRevoker(info) => Some(
+                PreparedRegistryUpdate::AnonymityRevoker(PreparedAnonymityRevoker::prepare(info)),
+            ),
+            _ => None,
+        };
+        Ok(Self {
+            update: QueuedChainUpdate::prepare(details, item)?,
+            block_height: data.finalized_block_info.height.height.try_into()?,
+            registry_update,
+        })
+    }
+
+    pub async fn save(
+        &self,
+        tx: &mut sqlx::PgTransaction<'_>,
+        transaction_index: i64,
+        slot_time: DateTime<Utc>,
+    ) -> anyhow::Result<()> {
+        let superseded = self.update.save(tx, transaction_index, slot_time).await?;
+
+        // Identity providers and anonymity revokers are registered when the update is
+        // enqueued and removed again if the update gets superseded.
+        match &self.registry_update {
+            Some(PreparedRegistryUpdate::IdentityProvider(identity_provider)) => {
+                if !superseded.is_empty() {
+                    sqlx::query!(
+                        "DELETE FROM identity_providers WHERE transaction_index = ANY($1)",
+                        &superseded
+                    )
+                    .execute(tx.as_mut())
+                    .await?;
+                }
+                identity_provider.save(tx, self.block_height, Some(transaction_index)).await?;
+            }
+            Some(PreparedRegistryUpdate::AnonymityRevoker(anonymity_revoker)) => {
+                if !superseded.is_empty() {
+                    sqlx::query!(
+                        "DELETE FROM anonymity_revokers WHERE transaction_index = ANY($1)",
+                        &superseded
+                    )
+                    .execute(tx.as_mut())
+                    .await?;
+                }
+                anonymity_revoker.save(tx, self.block_height, Some(transaction_index)).await?;
+            }
+            None => {}
+        }
+        Ok(())
+    }
+}
+
+/// Mark every queued chain update with an effective time at or before the
+/// provided slot time as effective.
+/// Returns the types of the chain updates which took effect.
+pub async fn mark_effective_chain_updates(
+    tx: &mut sqlx::PgTransaction<'_>,
+    slot_time: DateTime<Utc>,
+) -> anyhow::Result<Vec<UpdateTransactionType>> {
+    let update_types = sqlx::query_scalar!(
+        r#"UPDATE chain_update_queue
+        SET state = 'Effective'
+        WHERE state = 'Queued' AND effective_time <= $1
+        RETURNING update_type as "update_type: UpdateTransactionType""#,
+        slot_time
+    )
+    .fetch_all(tx.as_mut())
+    .await
+    .context("Failed marking queued chain updates as effective")?;
+    Ok(update_types)
+}
@@ -0,0 +1,330 @@
+//! Tracking of the governance keys authorizing chain updates.
+//!
+//! The key sets are read from the chain parameters of a block and a new
+//! version of a key set is stored whenever it differs from the current one.
+//! Since comparing the key sets requires reading the current ones from the
+//! database, this is only done for blocks where the keys might have changed.
+
+use crate::{
+    indexer::block_preprocessor::BlockData,
+    transaction_event::chain_update::{GovernanceKeyLevel, Level2Authorization},
+    transaction_type::UpdateTransactionType,
+};
+use concordium_rust_sdk::{
+    id::types::VerifyKey,
+    types::{
+        AccessStructure, AuthorizationsV0, AuthorizationsV1, BlockHeight, BlockItemSummaryDetails,
+        HigherLevelAccessStructure, UpdatePublicKey,
+    },
+    v2,
+};
+
+/// Governance keys of a block, ready to be saved in the database.
+#[derive(Debug)]
+pub struct PreparedGovernanceKeys {
+    /// The governance keys found in the chain parameters of the block.
+    keys:               GovernanceKeys,
+    /// Whether the keys might have changed in this block, independent of
+    /// queued updates taking effect. This is the case for the first block
+    /// after a protocol update and for blocks containing updates of the keys
+    /// effective immediately.
+    might_have_changed: bool,
+}
+
+impl PreparedGovernanceKeys {
+    pub fn prepare(data: &BlockData) -> Self {
+        let first_block_in_era = data.block_info.era_block_height == BlockHeight::from(0);
+        let immediate_key_update = data.events.iter().any(|summary| {
+            matches!(
+                &summary.details,
+                BlockItemSummaryDetails::Update(details)
+                    if details.effective_time.seconds == 0
+                        && is_key_update(UpdateTransactionType::from(&details.payload))
+            )
+        });
+        Self {
+            keys:               GovernanceKeys::from_chain_parameters(&data.chain_parameters),
+            might_have_changed: first_block_in_era || immediate_key_update,
+        }
+    }
+
+    /// Store the key sets which changed in this block.
+    /// Takes the types of the queued chain updates which took effect in the
+    /// block.
+    pub async fn save(
+        &self,
+        tx: &mut sqlx::PgTransaction<'_>,
+        block_height: i64,
+        effective_update_types: &[UpdateTransactionType],
+    ) -> anyhow::Result<()> {
+        if self.might_have_changed || effective_update_types.iter().copied().any(is_key_update) {
+            self.keys.save(tx, block_height).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether the type of chain update changes any of the governance keys.
+fn is_key_update(update_type: UpdateTransactionType) -> bool {
+    matches!(
+        update_type,
+        UpdateTransactionType::UpdateRootKeys
+            | UpdateTransactionType::UpdateLevel1Keys
+            | UpdateTransactionType::UpdateLevel2Keys
+    )
+}
+
+/// The root, level 1 and level 2 governance key sets.
+#[derive(Debug)]
+pub struct GovernanceKeys {
+    root:   KeySet,
+    level1: KeySet,
+    level2: KeySet,
+}
+
+impl GovernanceKeys {
+    pub fn from_chain_parameters(chain_parameters: &v2::ChainParameters) -> Self {
+        let (root, level1, level2) = match chain_parameters {
+            v2::ChainParameters::V0(parameters) => (
+                KeySet::from_higher_level(GovernanceKeyLevel::Root, &parameters.keys.root_keys),
+                KeySet::from_higher_level(
+                    GovernanceKeyLevel::Level1,
+                    &parameters.keys.level_1_keys,
+                ),
+                KeySet::from_authorizations_v0(&parameters.keys.level_2_keys),
+            ),
+            v2::ChainParameters::V1(parameters) => (
+                KeySet::from_higher_level(GovernanceKeyLevel::Root, &parameters.keys.root_keys),
+                KeySet::from_higher_level(
+                    GovernanceKeyLevel::Level1,
+                    &parameters.keys.level_1_keys,
+                ),
+                KeySet::from_authorizations_v1(&parameters.keys.level_2_keys),
+            ),
+            v2::ChainParameters::V2(parameters) => (
+                KeySet::from_higher_level(GovernanceKeyLevel::Root, &parameters.keys.root_keys),
+                KeySet::from_higher_level(
+                    GovernanceKeyLevel::Level1,
+                    &parameters.keys.level_1_keys,
+                ),
+                KeySet::from_authorizations_v1(&parameters.keys.level_2_keys),
+            ),
+            v2::ChainParameters::V3(parameters) => (
+                KeySet::from_higher_level(GovernanceKeyLevel::Root, &parameters.keys.root_keys),
+                KeySet::from_higher_level(
+                    GovernanceKeyLevel::Level1,
+                    &parameters.keys.level_1_keys,
+                ),
+                KeySet::from_authorizations_v1(&parameters.keys.level_2_keys),
+            ),
+        };
+        Self {
+            root,
+            level1,
+            level2,
+        }
+    }
+
+    /// Store a new version of each key set which differs from the current
+    /// version in the database. The new versions are marked as effective from
+    /// the provided block height.
+    pub async fn save(
+        &self,
+        tx: &mut sqlx::PgTransaction<'_>,
+        block_height: i64,
+    ) -> anyhow::Result<()> {
+        for key_set in [&self.root, &self.level1, &self.level2] {
+            key_set.save(tx, block_height).await?;
+        }
+        Ok(())
+    }
+}
+
+/// A single version of a governance key set.
+#[derive(Debug, PartialEq, Eq)]
+struct KeySet {
+    level:             GovernanceKeyLevel,
+    /// Hex encoded verify keys, where the position in the list is the key
+    /// index.
+    keys:              Vec<String>,
+    /// Threshold of keys needed for root and level 1 key sets.
+    threshold:         Option<i32>,
+    /// Access structures for each kind of level 2 update. Only non-empty for
+    /// level 2 key sets.
+    access_structures: Vec<Level2AccessStructure>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Level2AccessStructure {
+    authorization: Level2Authorization,
+    key_indices:   Vec<i32>,
+    threshold:     i32,
+}
+
+impl KeySet {
+    fn from_higher_level<Kind>(
+        level: GovernanceKeyLevel,
+        structure: &HigherLevelAccessStructure<Kind>,
+    ) -> Self {
+        Self {
+            level,
+            keys: structure.keys.iter().map(verify_key_hex).collect(),
+            threshold: Some(u16::from(structure.threshold).into()),
+            access_structures: Vec::new(),
+        }
+    }
+
+    fn from_authorizations_v0(authorizations: &AuthorizationsV0) -> Self {
+        let access_structures = [
+            (Level2Authorization::Emergency, &authorizations.emergency),
+            (Level2Authorization::Protocol, &authorizations.protocol),
+            (Level2Authorization::ElectionDifficulty, &authorizations.election_difficulty),
+            (Level2Authorization::EuroPerEnergy, &authorizations.euro_per_energy),
+            (Level2Authorization::MicroCcdPerEuro, &authorizations.micro_gtu_per_euro),
+            (Level2Authorization::FoundationAccount, &authorizations.foundation_account),
+            (Level2Authorization::MintDistribution, &authorizations.mint_distribution),
+            (
+                Level2Authorization::TransactionFeeDistribution,
+                &authorizations.transaction_fee_distribution,
+            ),
+            (Level2Authorization::GasRewards, &authorizations.param_gas_rewards),
+            (Level2Authorization::PoolParameters, &authorizations.pool_parameters),
+            (Level2Authorization::AddAnonymityRevoker, &authorizations.add_anonymity_revoker),
+            (Level2Authorization::AddIdentityProvider, &authorizations.add_identity_provider),
+        ]
+        .into_iter()
+        .map(|(authorization, structure)| Level2AccessStructure::new(authorization, structure))
+        .collect();
+        Self {
+            level: GovernanceKeyLevel::Level2,
+            keys: authorizations.keys.iter().map(verify_key_hex).collect(),
+            threshold: None,
+            access_structures,
+        }
+    }
+
+    fn from_authorizations_v1(authorizations: &AuthorizationsV1) -> Self {
+        let mut key_set = Self::from_authorizations_v0(&authorizations.v0);
+        key_set.access_structures.push(Level2AccessStructure::new(
+            Level2Authorization::CooldownParameters,
+            &authorizations.cooldown_parameters,
+        ));
+        key_set.access_structures.push(Level2AccessStructure::new(
+            Level2Authorization::TimeParameters,
+            &authorizations.time_parameters,
+        ));
+        if let Some(create_plt) = authorizations.create_plt.as_ref() {
+            key_set
+                .access_structures
+                .push(Level2AccessStructure::new(Level2Authorization::CreatePlt, create_plt));
+        }
+        key_set
+    }
+
+    /// Read the current version of the key set at the level from the database.
+    async fn load_current(
+        tx: &mut sqlx::PgTransaction<'_>,
+        level: GovernanceKeyLevel,
+    ) -> anyhow::Result<Option<(i64, Self)>> {
+        let Some(row) = sqlx::query!(
+            "SELECT id, keys, threshold
+            FROM governance_key_sets
+            WHERE level = $1 AND replaced_block_height IS NULL",
+            level as GovernanceKeyLevel
+        )
+        .fetch_optional(tx.as_mut())
+        .await?
+        else {
+            return Ok(None);
+        };
+        let access_structures = sqlx::query_as!(
+            Level2AccessStructure,
+            r#"SELECT
+                authorization_kind as "authorization: Level2Authorization",
+                key_indices,
+                threshold
+            FROM governance_level2_access_structures
+            WHERE key_set_id = $1
+            ORDER BY authorization_kind"#,
+            row.id
+        )
+        .fetch_all(tx.as_mut())
+        .await?;
+        Ok(Some((row.id, Self {
+            level,
+            keys: row.keys,
+            threshold: row.threshold,
+            access_structures,
+        })))
+    }
+
+    async fn save(
+        &self,
+        tx: &mut sqlx::PgTransaction<'_>,
+        block_height: i64,
+    ) -> anyhow::Result<()> {
+        let current = Self::load_current(tx, self.level).await?;
+        if let Some((current_id, current)) = current {
+            if current == *self {
+                return Ok(());
+            }
+            sqlx::query!(
+                "UPDATE governance_key_sets SET replaced_block_height = $2 WHERE id = $1",
+                current_id,
+                block_height
+            )
+            .execute(tx.as_mut())
+            .await?;
+        }
+        let id = sqlx::query_scalar!(
+            "INSERT INTO governance_key_sets (level, effective_block_height, keys, threshold)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id",
+            self.level as GovernanceKeyLevel,
+            block_height,
+            &self.keys,
+            self.threshold
+        )
+        .fetch_one(tx.as_mut())
+        .await?;
+        // Key sets are only replaced by rare chain updates, so the access structures
+        // are inserted one by one.
+        for structure in self.access_structures.iter() {
+            sqlx::query!(
+                "INSERT INTO governance_level2_access_structures (
+                    key_set_id,
+                    authorization_kind,
+                    key_indices,
+                    threshold
+                ) VALUES ($1, $2, $3, $4)",
+                id,
+                structure.authorization as Level2Authorization,
+                &structure.key_indices,
+                structure.threshold
+            )
+            .execute(tx.as_mut())
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+impl Level2AccessStructure {
+    fn new(authorization: Level2Authorization, structure: &AccessStructure) -> Self {
+        Self {
+            authorization,
+            key_indices: structure
+                .authorized_keys
+                .iter()
+                .map(|key_index| i32::from(key_index.index))
+                .collect(),
+            threshold: u16::from(structure.threshold).into(),
+        }
+    }
+}
+
+fn verify_key_hex(key: &UpdatePublicKey) -> String {
+    match &key.public {
+        VerifyKey::Ed25519VerifyKey(key) => hex::encode(key.as_bytes()),
+    }
+}
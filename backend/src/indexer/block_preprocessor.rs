@@ -46,11 +46,14 @@ pub struct BlockPreProcessor {
     /// Max number of acceptable successive failures before shutting down the
     /// service.
     max_successive_failures:      u64,
+    /// Identifiers of the standards queried for the support of by contracts.
+    standard_identifiers:         Vec<String>,
 }
 impl BlockPreProcessor {
     pub fn new(
         genesis_hash: sdk_types::hashes::BlockHash,
         max_successive_failures: u64,
+        standard_identifiers: Vec<String>,
         registry: &mut Registry,
     ) -> Self {
         let established_node_connections = Family::default();
@@ -88,6 +91,7 @@ impl BlockPreProcessor {
             blocks_being_preprocessed,
             node_response_time,
             max_successive_failures,
+            standard_identifiers,
         }
     }
 }
@@ -252,9 +256,10 @@ impl concordium_rust_sdk::indexer::Indexer for BlockPreProcessor {
                 validator_staking_information,
             };
 
-            let prepared_block = PreparedBlock::prepare(&mut client, &data)
-                .await
-                .map_err(v2::RPCError::ParseError)?;
+            let prepared_block =
+                PreparedBlock::prepare(&mut client, &data, &self.standard_identifiers)
+                    .await
+                    .map_err(v2::RPCError::ParseError)?;
             let node_response_time = start_fetching.elapsed();
             self.node_response_time.get_or_create(label).observe(node_response_time.as_secs_f64());
             Ok(prepared_block)
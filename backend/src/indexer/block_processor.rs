@@ -7,6 +7,7 @@
 use super::block::PreparedBlock;
 use crate::indexer::acquire_indexer_lock;
 use anyhow::Context;
+use bigdecimal::BigDecimal;
 use chrono::{DateTime, Utc};
 use concordium_rust_sdk::indexer::{async_trait, ProcessEvent};
 use prometheus_client::{
@@ -79,7 +80,9 @@ impl BlockProcessor {
             "
             SELECT
                 slot_time,
-                cumulative_num_txs
+                cumulative_num_txs,
+                cumulative_ccd_fees,
+                cumulative_euro_fees
             FROM blocks
             ORDER BY height DESC
             LIMIT 1
@@ -92,6 +95,8 @@ impl BlockProcessor {
             last_finalized_hash:               last_finalized_block.hash,
             last_block_slot_time:              last_block.slot_time,
             last_cumulative_num_txs:           last_block.cumulative_num_txs,
+            last_cumulative_ccd_fees:          last_block.cumulative_ccd_fees,
+            last_cumulative_euro_fees:         last_block.cumulative_euro_fees,
             last_cumulative_finalization_time: last_finalized_block
                 .cumulative_finalization_time
                 .unwrap_or(0),
@@ -264,6 +269,10 @@ pub struct BlockProcessingContext {
     /// This, along with the number of transactions in the current block,
     /// is used to calculate the next cumulative_num_txs.
     pub last_cumulative_num_txs:           i64,
+    /// The values of cumulative_ccd_fees and cumulative_euro_fees from the
+    /// last block, used to calculate the cumulative fees of the next block.
+    pub last_cumulative_ccd_fees:          i64,
+    pub last_cumulative_euro_fees:         BigDecimal,
     /// The cumulative_finalization_time in milliseconds of the last finalized
     /// block. This is used to efficiently update the
     /// cumulative_finalization_time of newly finalized blocks.
@@ -0,0 +1,389 @@
+//! Decoding of the parameters and events of contract events using the schema of
+//! the contract module, storing the decoded values in the `contract_events`
+//! table, such that the events can be filtered by the decoded values.
+//!
+//! The decoder runs next to the processing of blocks, following the contract
+//! events in the order they got indexed. The progress is stored in the
+//! database, such that enabling the decoder also decodes the events indexed
+//! before. When a schema is uploaded for a module, the events of the contracts
+//! using the module are decoded again, tracked by a separate cursor for each
+//! module in the `contract_event_redecoding` table.
+
+use crate::transaction_event::Event;
+use anyhow::Context;
+use concordium_rust_sdk::base::{
+    contracts_common::{
+        schema::{Type, VersionedModuleSchema},
+        Cursor,
+    },
+    smart_contracts::ReceiveName,
+};
+use prometheus_client::{
+    metrics::{counter::Counter, gauge::Gauge},
+    registry::Registry,
+};
+use sqlx::{postgres::PgConnectOptions, Connection, PgConnection};
+use std::{collections::HashMap, time::Duration};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
+
+/// The number of contract events decoded in each database transaction.
+const BATCH_SIZE: i64 = 1000;
+/// Time to wait before checking for new contract events, when every event is
+/// decoded.
+const IDLE_DELAY: Duration = Duration::from_secs(5);
+/// Time to wait before retrying after failing to decode a batch.
+const RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// Service decoding the contract events.
+pub struct ContractEventDecoder {
+    db_connect_options: PgConnectOptions,
+    /// Metric counting the contract events decoded using a schema.
+    decoded_events:     Counter,
+    /// Metric tracking the index of the last contract event processed.
+    last_index:         Gauge,
+}
+
+impl ContractEventDecoder {
+    pub fn new(db_connect_options: PgConnectOptions, registry: &mut Registry) -> Self {
+        let decoded_events = Counter::default();
+        registry.register(
+            "decoded_events",
+            "Total number of contract events decoded using a module schema",
+            decoded_events.clone(),
+        );
+        let last_index = Gauge::default();
+        registry.register(
+            "last_index",
+            "Index of the last contract event processed by the decoder",
+            last_index.clone(),
+        );
+        Self {
+            db_connect_options,
+            decoded_events,
+            last_index,
+        }
+    }
+
+    /// Run the decoder until signaled by the `stop_signal`. Failures are
+    /// logged and retried, as the decoding is not required for indexing
+    /// blocks.
+    pub async fn run(self, stop_signal: CancellationToken) -> anyhow::Result<()> {
+        info!("Decoding contract events");
+        let mut db_connection = None;
+        loop {
+            let delay = match self.decode_next(&mut db_connection).await {
+                Ok(true) => Duration::ZERO,
+                Ok(false) => IDLE_DELAY,
+                Err(err) => {
+                    error!("Failed decoding contract events: {:?}", err);
+                    db_connection = None;
+                    RETRY_DELAY
+                }
+            };
+            tokio::select! {
+                _ = stop_signal.cancelled() => return Ok(()),
+                _ = tokio::time::sleep(delay) => {}
+            }
+        }
+    }
+
+    /// Decode the next batch of contract events, connecting to the database if
+    /// not connected already.
+    async fn decode_next(&self, db_connection: &mut Option<PgConnection>) -> anyhow::Result<bool> {
+        let db_connection = match db_connection {
+            Some(db_connection) => db_connection,
+            None => {
+                db_connection.insert(PgConnection::connect_with(&self.db_connect_options).await?)
+            }
+        };
+        self.decode_batch(db_connection).await
+    }
+
+    /// Decode the next batch of contract events, and the next batch of events
+    /// to decode again for a module with a schema stored after its events got
+    /// processed. Returns whether more events are ready to be decoded.
+    async fn decode_batch(&self, db_connection: &mut PgConnection) -> anyhow::Result<bool> {
+        let mut tx = db_connection.begin().await?;
+        let last_index =
+            sqlx::query_scalar!("SELECT last_index FROM contract_event_decoding FOR UPDATE")
+                .fetch_one(tx.as_mut())
+                .await?;
+        let batch = self.decode_events(&mut tx, last_index, i64::MAX, None).await?;
+        let last_index = batch.last_index.unwrap_or(last_index);
+        sqlx::query!("UPDATE contract_event_decoding SET last_index = $1", last_index)
+            .execute(tx.as_mut())
+            .await?;
+
+        // Events after the last index processed are decoded using the stored schema
+        // by the batches above.
+        let redecoding = sqlx::query!(
+            "SELECT module_reference, last_index
+            FROM contract_event_redecoding
+            LIMIT 1
+            FOR UPDATE"
+        )
+        .fetch_optional(tx.as_mut())
+        .await?;
+        let mut redecoded = None;
+        if let Some(redecoding) = redecoding {
+            let batch = self
+                .decode_events(
+                    &mut tx,
+                    redecoding.last_index,
+                    last_index,
+                    Some(&redecoding.module_reference),
+                )
+                .await?;
+            match batch.last_index {
+                Some(batch_last_index) if batch.processed == BATCH_SIZE => {
+                    sqlx::query!(
+                        "UPDATE contract_event_redecoding SET last_index = $2
+                        WHERE module_reference = $1",
+                        redecoding.module_reference,
+                        batch_last_index
+                    )
+                    .execute(tx.as_mut())
+                    .await?;
+                }
+                _ => {
+                    sqlx::query!(
+                        "DELETE FROM contract_event_redecoding WHERE module_reference = $1",
+                        redecoding.module_reference
+                    )
+                    .execute(tx.as_mut())
+                    .await?;
+                }
+            }
+            redecoded = Some(batch);
+        }
+        tx.commit().await?;
+
+        self.last_index.set(last_index);
+        self.decoded_events.inc_by(batch.decoded);
+        let more_redecoding = match redecoded {
+            Some(redecoded) => {
+                self.decoded_events.inc_by(redecoded.decoded);
+                redecoded.processed == BATCH_SIZE
+            }
+            None => false,
+        };
+        Ok(batch.processed == BATCH_SIZE || more_redecoding)
+    }
+
+    /// Decode the next batch of contract events with an index after
+    /// `after_index` and up to `until_index`, restricted to the contracts
+    /// using the module at some point when provided.
+    async fn decode_events(
+        &self,
+        tx: &mut sqlx::PgTransaction<'_>,
+        after_index: i64,
+        until_index: i64,
+        module_reference: Option<&str>,
+    ) -> anyhow::Result<DecodedBatch> {
+        // The module of the contract at the time of the event is the module after the
+        // latest upgrade before the event, or the module before the first upgrade.
+        let rows = sqlx::query!(
+            r#"SELECT
+                contract_events.index,
+                contract_events.trace_element_index,
+                transactions.events as "events!",
+                contracts.name as contract_name,
+                COALESCE(
+                    (SELECT to_module_reference
+                        FROM contract_upgrades
+                        WHERE contract_upgrades.contract_index = contracts.index
+                            AND contract_upgrades.contract_sub_index = contracts.sub_index
+                            AND (
+                                contract_upgrades.transaction_index,
+                                contract_upgrades.trace_element_index
+                            ) < (
+                                contract_events.transaction_index,
+                                contract_events.trace_element_index
+                            )
+                        ORDER BY contract_upgrades.id DESC
+                        LIMIT 1),
+                    (SELECT from_module_reference
+                        FROM contract_upgrades
+                        WHERE contract_upgrades.contract_index = contracts.index
+                            AND contract_upgrades.contract_sub_index = contracts.sub_index
+                        ORDER BY contract_upgrades.id ASC
+                        LIMIT 1),
+                    contracts.module_reference
+                ) as "module_reference!"
+            FROM contract_events
+                JOIN transactions ON transactions.index = contract_events.transaction_index
+                JOIN contracts ON contracts.index = contract_events.contract_index
+                    AND contracts.sub_index = contract_events.contract_sub_index
+            WHERE contract_events.index > $1
+                AND contract_events.index <= $2
+                AND (
+                    $3::TEXT IS NULL
+                    OR contracts.module_reference = $3
+                    OR EXISTS(
+                        SELECT 1
+                        FROM contract_upgrades
+                        WHERE contract_upgrades.contract_index = contracts.index
+                            AND contract_upgrades.contract_sub_index = contracts.sub_index
+                            AND $3 IN (from_module_reference, to_module_reference)
+                    )
+                )
+            ORDER BY contract_events.index
+            LIMIT $4"#,
+            after_index,
+            until_index,
+            module_reference,
+            BATCH_SIZE
+        )
+        .fetch_all(tx.as_mut())
+        .await?;
+        let batch_last_index = rows.last().map(|row| row.index);
+        let processed = i64::try_from(rows.len())?;
+
+        let module_references: Vec<String> =
+            rows.iter().map(|row| row.module_reference.clone()).collect();
+        let schemas = sqlx::query!(
+            "SELECT module_reference, schema as \"schema!\"
+            FROM smart_contract_modules
+            WHERE module_reference = ANY($1) AND schema IS NOT NULL",
+            &module_references
+        )
+        .fetch_all(tx.as_mut())
+        .await?
+        .into_iter()
+        // Schemas failing to parse are treated as missing.
+        .filter_map(|row| {
+            VersionedModuleSchema::new(&row.schema, &None)
+                .ok()
+                .map(|schema| (row.module_reference, schema))
+        })
+        .collect::<HashMap<_, _>>();
+
+        let mut indices = Vec::new();
+        let mut decoded_values = Vec::new();
+        for row in rows.into_iter() {
+            if module_reference
+                .is_some_and(|module_reference| module_reference != row.module_reference)
+            {
+                continue;
+            }
+            let Some(schema) = schemas.get(&row.module_reference) else {
+                continue;
+            };
+            let mut events: Vec<Event> = serde_json::from_value(row.events)
+                .context("Failed to deserialize events from database")?;
+            let trace_element_index = usize::try_from(row.trace_element_index)?;
+            if trace_element_index >= events.len() {
+                continue;
+            }
+            let decoded = match events.swap_remove(trace_element_index) {
+                Event::ContractUpdated(update) => {
+                    let entrypoint = ReceiveName::new(&update.receive_name)
+                        .map(|name| name.entrypoint_name().to_string())
+                        .ok();
+                    decode_values(
+                        schema,
+                        &row.contract_name,
+                        entrypoint.as_deref(),
+                        Some(update.input_parameter.as_slice()),
+                        &update.contract_logs_raw,
+                    )
+                }
+                Event::ContractInterrupted(interrupted) => decode_values(
+                    schema,
+                    &row.contract_name,
+                    None,
+                    None,
+                    &interrupted.contract_logs_raw,
+                ),
+                _ => continue,
+            };
+            indices.push(row.index);
+            decoded_values.push(decoded);
+        }
+
+        sqlx::query!(
+            "UPDATE contract_events
+            SET decoded = decoded_values.decoded
+            FROM UNNEST($1::BIGINT[], $2::JSONB[]) AS decoded_values(index, decoded)
+            WHERE contract_events.index = decoded_values.index",
+            &indices,
+            &decoded_values
+        )
+        .execute(tx.as_mut())
+        .await?;
+
+        Ok(DecodedBatch {
+            processed,
+            decoded: indices.len().try_into()?,
+            last_index: batch_last_index,
+        })
+    }
+}
+
+/// Result of decoding a batch of contract events.
+struct DecodedBatch {
+    /// The number of events processed.
+    processed:  i64,
+    /// The number of events decoded using a schema.
+    decoded:    u64,
+    /// Index of the last event processed, `None` if no events were processed.
+    last_index: Option<i64>,
+}
+
+/// Decode a value using the type from the schema, `None` if the type is
+/// missing or the value fails to decode.
+fn decode_value(value_type: Option<&Type>, value: &[u8]) -> Option<serde_json::Value> {
+    value_type?.to_json(&mut Cursor::new(value)).ok()
+}
+
+/// Decode the parameter of an entrypoint and the logged events of a contract
+/// into the object stored in the `decoded` column of `contract_events`.
+fn decode_values(
+    schema: &VersionedModuleSchema,
+    contract_name: &str,
+    entrypoint: Option<&str>,
+    parameter: Option<&[u8]>,
+    logs: &[Vec<u8>],
+) -> serde_json::Value {
+    let parameter = entrypoint.zip(parameter).and_then(|(entrypoint, parameter)| {
+        let parameter_type = schema.get_receive_param_schema(contract_name, entrypoint).ok();
+        decode_value(parameter_type.as_ref(), parameter)
+    });
+    let event_type = schema.get_event_schema(contract_name).ok();
+    let events: Vec<_> = logs.iter().map(|log| decode_value(event_type.as_ref(), log)).collect();
+    serde_json::json!({
+        "parameter": parameter,
+        "events": events,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use concordium_rust_sdk::base::contracts_common::schema::{ContractV3, FunctionV2, ModuleV3};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_decode_values() {
+        let contract = ContractV3 {
+            init:    None,
+            receive: BTreeMap::from([("transfer".to_string(), FunctionV2 {
+                parameter:    Some(Type::U16),
+                return_value: None,
+                error:        None,
+            })]),
+            event:   Some(Type::U8),
+        };
+        let schema = VersionedModuleSchema::V3(ModuleV3 {
+            contracts: BTreeMap::from([("token".to_string(), contract)]),
+        });
+
+        let decoded =
+            decode_values(&schema, "token", Some("transfer"), Some(&[1, 0]), &[vec![7], vec![]]);
+        assert_eq!(decoded, serde_json::json!({"parameter": 1, "events": [7, null]}));
+
+        let decoded = decode_values(&schema, "token", Some("unknown"), Some(&[1, 0]), &[]);
+        assert_eq!(decoded, serde_json::json!({"parameter": null, "events": []}));
+    }
+}
@@ -1,4 +1,13 @@
 //! Types and functions for building and executing database operations.
 
+pub mod account_cooldowns;
+pub mod account_credential;
+pub mod contract_call_tree;
+pub mod contract_log;
+pub mod contract_standards;
+pub mod credential_registry;
+pub mod identity_registry;
 pub mod lock;
+pub mod smart_contract_wallet;
+pub mod sponsored_action;
 pub mod update_account_balance;
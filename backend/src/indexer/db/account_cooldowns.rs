@@ -0,0 +1,74 @@
+//! Module with database operations for tracking the stake of accounts in
+//! cooldown.
+//!
+//! From protocol version 7, stake removed from a validator or delegator stays
+//! in cooldown before it is released at the first payday after the end of the
+//! cooldown. The cooldowns of an account are read from the node whenever the
+//! account configures its stake, replacing the cooldowns stored before. Stake
+//! in cooldown is projected to end at the stored end time, which is only final
+//! once the cooldown starts at the next payday.
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use concordium_rust_sdk::{
+    types::{AccountIndex, Cooldown},
+    v2,
+};
+
+/// The stake of an account in cooldown.
+#[derive(Debug)]
+pub struct PreparedAccountCooldowns {
+    account_index: i64,
+    end_times:     Vec<DateTime<Utc>>,
+    amounts:       Vec<i64>,
+}
+
+impl PreparedAccountCooldowns {
+    /// Read the cooldowns of the account from the node, as of the block.
+    pub async fn prepare(
+        node_client: &mut v2::Client,
+        account: v2::AccountIdentifier,
+        block: v2::BlockIdentifier,
+    ) -> anyhow::Result<Self> {
+        let info = node_client
+            .get_account_info(&account, block)
+            .await
+            .context("Failed reading the stake cooldowns of the account")?
+            .response;
+        Self::from_cooldowns(info.account_index, &info.cooldowns)
+    }
+
+    pub fn from_cooldowns(
+        account_index: AccountIndex,
+        cooldowns: &[Cooldown],
+    ) -> anyhow::Result<Self> {
+        let mut end_times = Vec::with_capacity(cooldowns.len());
+        let mut amounts = Vec::with_capacity(cooldowns.len());
+        for cooldown in cooldowns {
+            end_times.push(DateTime::<Utc>::try_from(cooldown.end_time)?);
+            amounts.push(i64::try_from(cooldown.amount.micro_ccd())?);
+        }
+        Ok(Self {
+            account_index: i64::try_from(account_index.index)?,
+            end_times,
+            amounts,
+        })
+    }
+
+    pub async fn save(&self, tx: &mut sqlx::PgTransaction<'_>) -> anyhow::Result<()> {
+        sqlx::query!("DELETE FROM account_cooldowns WHERE account_index = $1", self.account_index)
+            .execute(tx.as_mut())
+            .await?;
+        sqlx::query!(
+            "INSERT INTO account_cooldowns (account_index, end_time, amount)
+            SELECT $1, UNNEST($2::TIMESTAMPTZ[]), UNNEST($3::BIGINT[])",
+            self.account_index,
+            &self.end_times,
+            &self.amounts
+        )
+        .execute(tx.as_mut())
+        .await
+        .context("Failed inserting the stake cooldowns of the account")?;
+        Ok(())
+    }
+}
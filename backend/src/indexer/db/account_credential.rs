@@ -0,0 +1,251 @@
+//! Module with database operations for tracking the credentials of accounts.
+
+use crate::indexer::ensure_affected_rows::EnsureAffectedRows;
+use anyhow::Context;
+use chrono::NaiveDate;
+use concordium_rust_sdk::{
+    common::to_bytes,
+    id::{
+        constants::{ArCurve, AttributeKind, IpPairing},
+        types::{
+            AccountCredential, AccountCredentialMessage, AccountCredentialWithoutProofs,
+            CredentialDeploymentInfo, CredentialDeploymentValues, CredentialPublicKeys,
+            InitialCredentialDeploymentValues, VerifyKey, YearMonth,
+        },
+    },
+};
+
+/// Represents a credential added to an account.
+#[derive(Debug)]
+pub struct PreparedAccountCredential {
+    /// The credential registration ID of the credential, hex encoded.
+    credential_registration_id: String,
+    /// Index of the credential on the account.
+    credential_index: i16,
+    /// The identity provider which issued the identity behind the credential.
+    identity_provider_id: i64,
+    /// Whether this is an initial credential, which is created by the
+    /// identity provider and has no anonymity revokers.
+    is_initial: bool,
+    /// The anonymity revokers able to reveal the identity behind the
+    /// credential. Empty for initial credentials.
+    anonymity_revoker_ids: Vec<i64>,
+    /// Number of anonymity revokers needed to reveal the identity behind the
+    /// credential. `None` for initial credentials.
+    anonymity_revoker_threshold: Option<i16>,
+    /// First day of the month where the credential got created.
+    valid_from: NaiveDate,
+    /// First day of the last month where the credential is valid.
+    valid_to: NaiveDate,
+    /// The keys of the credential.
+    keys: PreparedCredentialKeys,
+}
+
+impl PreparedAccountCredential {
+    /// Prepare the credential from a credential deployment creating a new
+    /// account. The credential is always the first credential of the account.
+    pub fn from_deployment(
+        message: &AccountCredentialMessage<IpPairing, ArCurve, AttributeKind>,
+    ) -> anyhow::Result<Self> {
+        match &message.credential {
+            AccountCredential::Initial {
+                icdi,
+            } => Self::from_initial_values(0, &icdi.values),
+            AccountCredential::Normal {
+                cdi,
+            } => Self::from_values(0, &cdi.values),
+        }
+    }
+
+    /// Prepare a credential from the credential deployment information, as
+    /// found in transactions updating the credentials of an account.
+    pub fn from_deployment_info(
+        credential_index: u8,
+        info: &CredentialDeploymentInfo<IpPairing, ArCurve, AttributeKind>,
+    ) -> anyhow::Result<Self> {
+        Self::from_values(credential_index, &info.values)
+    }
+
+    /// Prepare a credential from the credential information of an account, as
+    /// returned by the node.
+    pub fn from_account_info(
+        credential_index: u8,
+        credential: &AccountCredentialWithoutProofs<ArCurve, AttributeKind>,
+    ) -> anyhow::Result<Self> {
+        match credential {
+            AccountCredentialWithoutProofs::Initial {
+                icdv,
+            } => Self::from_initial_values(credential_index, icdv),
+            AccountCredentialWithoutProofs::Normal {
+                cdv,
+                ..
+            } => Self::from_values(credential_index, cdv),
+        }
+    }
+
+    /// The hex encoded credential registration ID of the credential.
+    pub fn credential_registration_id(&self) -> &str { &self.credential_registration_id }
+
+    /// The identity provider which issued the identity behind the credential.
+    pub fn identity_provider_id(&self) -> i64 { self.identity_provider_id }
+
+    fn from_initial_values(
+        credential_index: u8,
+        values: &InitialCredentialDeploymentValues<ArCurve, AttributeKind>,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            credential_registration_id: hex::encode(to_bytes(&values.reg_id)),
+            credential_index: credential_index.into(),
+            identity_provider_id: values.ip_identity.0.into(),
+            is_initial: true,
+            anonymity_revoker_ids: Vec::new(),
+            anonymity_revoker_threshold: None,
+            valid_from: first_day_of_month(values.policy.created_at)?,
+            valid_to: first_day_of_month(values.policy.valid_to)?,
+            keys: PreparedCredentialKeys::prepare(&values.cred_account),
+        })
+    }
+
+    fn from_values(
+        credential_index: u8,
+        values: &CredentialDeploymentValues<ArCurve, AttributeKind>,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            credential_registration_id: hex::encode(to_bytes(&values.cred_id)),
+            credential_index: credential_index.into(),
+            identity_provider_id: values.ip_identity.0.into(),
+            is_initial: false,
+            anonymity_revoker_ids: values
+                .ar_data
+                .keys()
+                .map(|ar_identity| u32::from(*ar_identity).into())
+                .collect(),
+            anonymity_revoker_threshold: Some(u8::from(values.threshold).into()),
+            valid_from: first_day_of_month(values.policy.created_at)?,
+            valid_to: first_day_of_month(values.policy.valid_to)?,
+            keys: PreparedCredentialKeys::prepare(&values.cred_key_info),
+        })
+    }
+
+    /// Insert the credential for the account.
+    /// The transaction index is the transaction deploying the credential, which
+    /// is `None` for credentials of genesis accounts.
+    pub async fn save(
+        &self,
+        tx: &mut sqlx::PgTransaction<'_>,
+        account_index: i64,
+        transaction_index: Option<i64>,
+    ) -> anyhow::Result<()> {
+        sqlx::query!(
+            "INSERT INTO account_credentials (
+                credential_registration_id,
+                account_index,
+                credential_index,
+                identity_provider_id,
+                transaction_index,
+                is_initial,
+                anonymity_revoker_ids,
+                anonymity_revoker_threshold,
+                valid_from,
+                valid_to,
+                key_threshold,
+                keys
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)",
+            self.credential_registration_id,
+            account_index,
+            self.credential_index,
+            self.identity_provider_id,
+            transaction_index,
+            self.is_initial,
+            &self.anonymity_revoker_ids,
+            self.anonymity_revoker_threshold,
+            self.valid_from,
+            self.valid_to,
+            self.keys.threshold,
+            &self.keys.keys
+        )
+        .execute(tx.as_mut())
+        .await?;
+        Ok(())
+    }
+
+    /// Update the details of an existing credential, which were not tracked
+    /// when the credential got indexed.
+    pub async fn save_details(&self, tx: &mut sqlx::PgTransaction<'_>) -> anyhow::Result<()> {
+        sqlx::query!(
+            "UPDATE account_credentials
+            SET
+                is_initial = $2,
+                anonymity_revoker_ids = $3,
+                anonymity_revoker_threshold = $4,
+                valid_from = $5,
+                valid_to = $6,
+                key_threshold = $7,
+                keys = $8
+            WHERE credential_registration_id = $1",
+            self.credential_registration_id,
+            self.is_initial,
+            &self.anonymity_revoker_ids,
+            self.anonymity_revoker_threshold,
+            self.valid_from,
+            self.valid_to,
+            self.keys.threshold,
+            &self.keys.keys
+        )
+        .execute(tx.as_mut())
+        .await?
+        .ensure_affected_one_row()?;
+        Ok(())
+    }
+}
+
+/// The public keys of a credential and the number of keys needed to sign on
+/// behalf of the credential.
+#[derive(Debug)]
+pub struct PreparedCredentialKeys {
+    /// Hex encoded Ed25519 verify keys, ordered by key index.
+    keys:      Vec<String>,
+    /// Number of keys needed to sign on behalf of the credential.
+    threshold: i16,
+}
+
+impl PreparedCredentialKeys {
+    pub fn prepare(keys: &CredentialPublicKeys) -> Self {
+        Self {
+            keys:      keys
+                .keys
+                .values()
+                .map(|key| match key {
+                    VerifyKey::Ed25519VerifyKey(key) => hex::encode(key.as_bytes()),
+                })
+                .collect(),
+            threshold: u8::from(keys.threshold).into(),
+        }
+    }
+
+    /// Update the keys of the credential with the provided registration ID.
+    pub async fn save(
+        &self,
+        tx: &mut sqlx::PgTransaction<'_>,
+        credential_registration_id: &str,
+    ) -> anyhow::Result<()> {
+        sqlx::query!(
+            "UPDATE account_credentials
+            SET keys = $2, key_threshold = $3
+            WHERE credential_registration_id = $1",
+            credential_registration_id,
+            &self.keys,
+            self.threshold
+        )
+        .execute(tx.as_mut())
+        .await?
+        .ensure_affected_one_row()?;
+        Ok(())
+    }
+}
+
+/// The first day of the provided month.
+fn first_day_of_month(year_month: YearMonth) -> anyhow::Result<NaiveDate> {
+    NaiveDate::from_ymd_opt(year_month.year.into(), year_month.month.into(), 1)
+        .with_context(|| format!("Invalid credential validity month: {}", year_month))
+}
@@ -0,0 +1,363 @@
+//! Module with database operations for the tree of contract calls made by a
+//! contract update transaction.
+//!
+//! The node reports the execution of a contract update as a flat list of
+//! trace elements, where a contract making a nested call is `Interrupted`
+//! and later `Resumed`, and a contract call is reported as `Updated` once it
+//! completes. Nested calls therefore complete, and are reported, before the
+//! call making them. The tree is reconstructed by tracking the depth of the
+//! trace and letting each completed call adopt the completed calls one level
+//! deeper which are not yet adopted.
+
+use crate::{
+    address::Address,
+    transaction_event::{protocol_level_tokens::canonical_address, Event},
+};
+use concordium_rust_sdk::types::{self as sdk_types, ContractTraceElement};
+
+/// A trace element, as relevant for reconstructing the call tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallTraceStep {
+    /// A contract got interrupted, making a nested call.
+    Interrupted,
+    /// An interrupted contract got resumed, after a nested call.
+    Resumed,
+    /// A contract call completed.
+    Updated {
+        caller:             CallCaller,
+        contract_index:     i64,
+        contract_sub_index: i64,
+        entrypoint:         String,
+        amount:             i64,
+    },
+    /// A trace element not affecting the call tree, such as transfers to
+    /// accounts and upgrades.
+    Other,
+}
+
+/// The instigator of a contract call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallCaller {
+    /// Base58check representation of the account address.
+    Account(String),
+    Contract {
+        index:     i64,
+        sub_index: i64,
+    },
+}
+
+impl TryFrom<&ContractTraceElement> for CallTraceStep {
+    type Error = anyhow::Error;
+
+    fn try_from(element: &ContractTraceElement) -> anyhow::Result<Self> {
+        let step = match element {
+            ContractTraceElement::Interrupted {
+                ..
+            } => CallTraceStep::Interrupted,
+            ContractTraceElement::Resumed {
+                ..
+            } => CallTraceStep::Resumed,
+            ContractTraceElement::Updated {
+                data,
+            } => CallTraceStep::Updated {
+                caller:             match data.instigator {
+                    sdk_types::Address::Account(address) => {
+                        CallCaller::Account(address.to_string())
+                    }
+                    sdk_types::Address::Contract(address) => CallCaller::Contract {
+                        index:     address.index.try_into()?,
+                        sub_index: address.subindex.try_into()?,
+                    },
+                },
+                contract_index:     data.address.index.try_into()?,
+                contract_sub_index: data.address.subindex.try_into()?,
+                entrypoint:         data.receive_name.to_string(),
+                amount:             data.amount.micro_ccd().try_into()?,
+            },
+            ContractTraceElement::Transferred {
+                ..
+            }
+            | ContractTraceElement::Upgraded {
+                ..
+            } => CallTraceStep::Other,
+        };
+        Ok(step)
+    }
+}
+
+impl TryFrom<&Event> for CallTraceStep {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &Event) -> anyhow::Result<Self> {
+        let step = match event {
+            Event::ContractInterrupted(_) => CallTraceStep::Interrupted,
+            Event::ContractResumed(_) => CallTraceStep::Resumed,
+            Event::ContractUpdated(updated) => CallTraceStep::Updated {
+                caller:             match &updated.instigator {
+                    Address::AccountAddress(address) => CallCaller::Account(address.to_string()),
+                    Address::ContractAddress(address) => CallCaller::Contract {
+                        index:     address.index.0.try_into()?,
+                        sub_index: address.sub_index.0.try_into()?,
+                    },
+                },
+                contract_index:     updated.contract_address.index.0.try_into()?,
+                contract_sub_index: updated.contract_address.sub_index.0.try_into()?,
+                entrypoint:         updated.receive_name.clone(),
+                amount:             updated.amount.0.try_into()?,
+            },
+            _ => CallTraceStep::Other,
+        };
+        Ok(step)
+    }
+}
+
+/// A single contract call in the call tree of a transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreparedContractCall {
+    /// Index of the trace element reporting the completed call.
+    trace_element_index: i64,
+    /// Index of the trace element reporting the call making this call. `None`
+    /// for the calls made directly by the transaction.
+    parent_trace_element_index: Option<i64>,
+    /// Number of calls between the transaction and this call, 0 for calls made
+    /// directly by the transaction.
+    depth: i16,
+    caller: CallCaller,
+    contract_index: i64,
+    contract_sub_index: i64,
+    /// The receive name of the entrypoint called.
+    entrypoint: String,
+    /// Amount of CCD sent with the call (in microCCD).
+    amount: i64,
+}
+
+/// The call tree of a contract update transaction.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct PreparedContractCallTree {
+    /// The calls ordered by their trace element index, meaning nested calls
+    /// come before the calls making them.
+    calls: Vec<PreparedContractCall>,
+}
+
+impl PreparedContractCallTree {
+    /// Reconstruct the call tree from the trace elements of a transaction.
+    pub fn build(steps: impl IntoIterator<Item = CallTraceStep>) -> anyhow::Result<Self> {
+        let mut calls: Vec<PreparedContractCall> = Vec::new();
+        // For each depth, the positions in `calls` of the completed calls at this
+        // depth, which are not yet adopted by a parent call.
+        let mut unadopted: Vec<Vec<usize>> = vec![Vec::new()];
+        let mut depth: usize = 0;
+        for (trace_element_index, step) in steps.into_iter().enumerate() {
+            match step {
+                CallTraceStep::Interrupted => {
+                    depth += 1;
+                    if unadopted.len() <= depth {
+                        unadopted.push(Vec::new());
+                    }
+                }
+                CallTraceStep::Resumed => {
+                    depth = depth.checked_sub(1).ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Contract resumed without being interrupted at trace element {}",
+                            trace_element_index
+                        )
+                    })?;
+                }
+                CallTraceStep::Updated {
+                    caller,
+                    contract_index,
+                    contract_sub_index,
+                    entrypoint,
+                    amount,
+                } => {
+                    let trace_element_index = i64::try_from(trace_element_index)?;
+                    if let Some(children) = unadopted.get_mut(depth + 1) {
+                        for child in children.drain(..) {
+                            calls[child].parent_trace_element_index = Some(trace_element_index);
+                        }
+                    }
+                    unadopted[depth].push(calls.len());
+                    calls.push(PreparedContractCall {
+                        trace_element_index,
+                        parent_trace_element_index: None,
+                        depth: depth.try_into()?,
+                        caller,
+                        contract_index,
+                        contract_sub_index,
+                        entrypoint,
+                        amount,
+                    });
+                }
+                CallTraceStep::Other => {}
+            }
+        }
+        if depth != 0 {
+            anyhow::bail!("Contract trace ended with {} interrupted contracts", depth);
+        }
+        Ok(Self {
+            calls,
+        })
+    }
+
+    /// Insert the calls of the transaction and update the aggregated edges
+    /// between contracts calling each other.
+    pub async fn save(
+        &self,
+        tx: &mut sqlx::PgTransaction<'_>,
+        transaction_index: i64,
+    ) -> anyhow::Result<()> {
+        for call in self.calls.iter() {
+            let (caller_account, caller_contract_index, caller_contract_sub_index) =
+                match &call.caller {
+                    CallCaller::Account(address) => (Some(canonical_address(address)?), None, None),
+                    CallCaller::Contract {
+                        index,
+                        sub_index,
+                    } => (None, Some(*index), Some(*sub_index)),
+                };
+            sqlx::query!(
+                "INSERT INTO contract_calls (
+                    transaction_index,
+                    trace_element_index,
+                    parent_trace_element_index,
+                    depth,
+                    caller_account_index,
+                    caller_contract_index,
+                    caller_contract_sub_index,
+                    contract_index,
+                    contract_sub_index,
+                    entrypoint,
+                    amount
+                ) VALUES (
+                    $1,
+                    $2,
+                    $3,
+                    $4,
+                    (SELECT index FROM accounts WHERE canonical_address = $5),
+                    $6,
+                    $7,
+                    $8,
+                    $9,
+                    $10,
+                    $11
+                )",
+                transaction_index,
+                call.trace_element_index,
+                call.parent_trace_element_index,
+                call.depth,
+                caller_account.as_deref(),
+                caller_contract_index,
+                caller_contract_sub_index,
+                call.contract_index,
+                call.contract_sub_index,
+                call.entrypoint,
+                call.amount
+            )
+            .execute(tx.as_mut())
+            .await?;
+
+            if let (Some(caller_index), Some(caller_sub_index)) =
+                (caller_contract_index, caller_contract_sub_index)
+            {
+                sqlx::query!(
+                    "INSERT INTO contract_call_edges (
+                        caller_contract_index,
+                        caller_contract_sub_index,
+                        contract_index,
+                        contract_sub_index,
+                        call_count,
+                        first_transaction_index,
+                        last_transaction_index
+                    ) VALUES ($1, $2, $3, $4, 1, $5, $5)
+                    ON CONFLICT (
+                        caller_contract_index,
+                        caller_contract_sub_index,
+                        contract_index,
+                        contract_sub_index
+                    ) DO UPDATE SET
+                        call_count = contract_call_edges.call_count + 1,
+                        last_transaction_index = EXCLUDED.last_transaction_index",
+                    caller_index,
+                    caller_sub_index,
+                    call.contract_index,
+                    call.contract_sub_index,
+                    transaction_index
+                )
+                .execute(tx.as_mut())
+                .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn updated(caller: CallCaller, contract_index: i64) -> CallTraceStep {
+        CallTraceStep::Updated {
+            caller,
+            contract_index,
+            contract_sub_index: 0,
+            entrypoint: format!("contract{}.receive", contract_index),
+            amount: 0,
+        }
+    }
+
+    fn contract(index: i64) -> CallCaller {
+        CallCaller::Contract {
+            index,
+            sub_index: 0,
+        }
+    }
+
+    fn parents_and_depths(tree: &PreparedContractCallTree) -> Vec<(i64, Option<i64>, i16)> {
+        tree.calls
+            .iter()
+            .map(|call| (call.contract_index, call.parent_trace_element_index, call.depth))
+            .collect()
+    }
+
+    #[test]
+    fn test_single_call() {
+        let account = CallCaller::Account("account".to_string());
+        let tree = PreparedContractCallTree::build([updated(account, 1)]).unwrap();
+        assert_eq!(parents_and_depths(&tree), vec![(1, None, 0)]);
+    }
+
+    #[test]
+    fn test_nested_calls() {
+        let account = CallCaller::Account("account".to_string());
+        // Contract 1 calls contract 2, which calls contract 3, then contract 1 calls
+        // contract 4 and transfers CCD to an account.
+        let steps = [
+            CallTraceStep::Interrupted,
+            CallTraceStep::Interrupted,
+            updated(contract(2), 3),
+            CallTraceStep::Resumed,
+            updated(contract(1), 2),
+            CallTraceStep::Resumed,
+            CallTraceStep::Interrupted,
+            updated(contract(1), 4),
+            CallTraceStep::Resumed,
+            CallTraceStep::Interrupted,
+            CallTraceStep::Other,
+            CallTraceStep::Resumed,
+            updated(account, 1),
+        ];
+        let tree = PreparedContractCallTree::build(steps).unwrap();
+        assert_eq!(parents_and_depths(&tree), vec![
+            (3, Some(4), 2),
+            (2, Some(12), 1),
+            (4, Some(12), 1),
+            (1, None, 0)
+        ]);
+    }
+
+    #[test]
+    fn test_unbalanced_trace() {
+        assert!(PreparedContractCallTree::build([CallTraceStep::Resumed]).is_err());
+        assert!(PreparedContractCallTree::build([CallTraceStep::Interrupted]).is_err());
+    }
+}
@@ -0,0 +1,113 @@
+//! Reader for the contract logs and parameters of the CIS standards, which are
+//! serialized using the little-endian serialization of smart contracts.
+
+use bigdecimal::{num_bigint::BigUint, BigDecimal};
+use concordium_rust_sdk::{
+    id::types::AccountAddress,
+    types::{Address, ContractAddress},
+};
+
+/// Maximum number of bytes of a CIS-2 token amount, as defined by the
+/// standard.
+const MAX_TOKEN_AMOUNT_BYTES: usize = 37;
+
+pub struct LogReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> LogReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+        }
+    }
+
+    pub fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.bytes.len() < len {
+            return None;
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Some(taken)
+    }
+
+    pub fn u8(&mut self) -> Option<u8> { self.take(1).map(|bytes| bytes[0]) }
+
+    pub fn u16(&mut self) -> Option<u16> {
+        self.take(2).map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    pub fn u64(&mut self) -> Option<u64> {
+        self.take(8).and_then(|bytes| bytes.try_into().ok()).map(u64::from_le_bytes)
+    }
+
+    pub fn bool(&mut self) -> Option<bool> {
+        match self.u8()? {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+
+    /// Hex encoded Ed25519 public key.
+    pub fn public_key(&mut self) -> Option<String> { self.take(32).map(hex::encode) }
+
+    /// String prefixed by its length as a single byte.
+    pub fn short_string(&mut self) -> Option<String> {
+        let len = self.u8()?;
+        self.string(len.into())
+    }
+
+    pub fn string(&mut self, len: usize) -> Option<String> {
+        let bytes = self.take(len)?;
+        // PostgreSQL text does not support NUL characters.
+        String::from_utf8(bytes.to_vec()).ok().map(|text| text.replace('\0', "\u{FFFD}"))
+    }
+
+    pub fn contract_address(&mut self) -> Option<ContractAddress> {
+        Some(ContractAddress::new(self.u64()?, self.u64()?))
+    }
+
+    pub fn address(&mut self) -> Option<Address> {
+        match self.u8()? {
+            0 => Some(Address::Account(AccountAddress(self.take(32)?.try_into().ok()?))),
+            1 => Some(Address::Contract(self.contract_address()?)),
+            _ => None,
+        }
+    }
+
+    /// Hex encoded CIS-2 token ID, prefixed by its length as a single byte.
+    pub fn token_id(&mut self) -> Option<String> {
+        let len = self.u8()?;
+        self.take(len.into()).map(hex::encode)
+    }
+
+    /// CIS-2 token amount, encoded using unsigned LEB128.
+    pub fn token_amount(&mut self) -> Option<BigDecimal> {
+        let mut amount = BigUint::default();
+        for position in 0..MAX_TOKEN_AMOUNT_BYTES {
+            let byte = self.u8()?;
+            amount |= BigUint::from(byte & 0x7f) << (7 * position);
+            if byte & 0x80 == 0 {
+                return Some(BigDecimal::from_biguint(amount, 0));
+            }
+        }
+        None
+    }
+
+    pub fn is_empty(&self) -> bool { self.bytes.is_empty() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_amount() {
+        let mut reader = LogReader::new(&[0xe5, 0x8e, 0x26]);
+        assert_eq!(reader.token_amount(), Some(BigDecimal::from(624485)));
+        assert!(reader.is_empty());
+        // Amounts exceeding the maximum number of bytes are rejected.
+        assert_eq!(LogReader::new(&[0xff; 38]).token_amount(), None);
+    }
+}
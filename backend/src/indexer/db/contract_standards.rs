@@ -0,0 +1,122 @@
+//! Module with database operations for tracking the standards supported by
+//! smart contract instances, as reported by their CIS-0 `supports` entrypoint.
+
+use crate::indexer::ensure_affected_rows::EnsureAffectedRows;
+use anyhow::Context;
+use concordium_rust_sdk::{
+    cis0,
+    types::{smart_contracts::ContractName, AbsoluteBlockHeight, ContractAddress},
+    v2,
+};
+
+/// The standard identifiers queried for every contract. Custom standards are
+/// tracked by configuring the indexer with additional identifiers.
+pub const STANDARD_IDENTIFIERS: [&str; 6] = ["CIS-0", "CIS-1", "CIS-2", "CIS-3", "CIS-4", "CIS-5"];
+
+/// The standard identifiers queried for every contract, which are the
+/// identifiers in `STANDARD_IDENTIFIERS` followed by the provided additional
+/// identifiers.
+pub fn standard_identifiers(extra_identifiers: &[String]) -> Vec<String> {
+    STANDARD_IDENTIFIERS
+        .iter()
+        .map(|id| id.to_string())
+        .chain(
+            extra_identifiers
+                .iter()
+                .filter(|id| !STANDARD_IDENTIFIERS.contains(&id.as_str()))
+                .cloned(),
+        )
+        .collect()
+}
+
+/// The standards supported by a contract.
+#[derive(Debug)]
+pub struct PreparedContractStandards {
+    contract_index:      i64,
+    contract_sub_index:  i64,
+    /// Identifiers of the supported standards, such as `CIS-2`.
+    supported_standards: Vec<String>,
+}
+
+impl PreparedContractStandards {
+    /// Query which of the provided standards are supported by the contract in
+    /// the state after the provided block.
+    pub async fn prepare(
+        node_client: &mut v2::Client,
+        block_height: AbsoluteBlockHeight,
+        address: ContractAddress,
+        contract_name: ContractName<'_>,
+        standard_identifiers: &[String],
+    ) -> anyhow::Result<Self> {
+        let identifiers: Vec<_> = standard_identifiers
+            .iter()
+            .map(|id| cis0::StandardIdentifier::Other(id.clone()))
+            .collect();
+        // Contracts not implementing CIS-0 reject the query, meaning no standards are
+        // supported.
+        let supported_standards = match cis0::supports_multi(
+            node_client,
+            &v2::BlockIdentifier::AbsoluteHeight(block_height),
+            address,
+            contract_name,
+            &identifiers,
+        )
+        .await
+        {
+            Ok(response) => standard_identifiers
+                .iter()
+                .zip(response.response.results.iter())
+                .filter(|(_, result)| result.is_support())
+                .map(|(id, _)| id.clone())
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        Ok(Self {
+            contract_index: address.index.try_into()?,
+            contract_sub_index: address.subindex.try_into()?,
+            supported_standards,
+        })
+    }
+
+    /// Query the standards supported by a contract, looking up the name of the
+    /// contract first. Used when the name is not part of the event, such as
+    /// for contract upgrades.
+    pub async fn prepare_for_instance(
+        node_client: &mut v2::Client,
+        block_height: AbsoluteBlockHeight,
+        address: ContractAddress,
+        standard_identifiers: &[String],
+    ) -> anyhow::Result<Self> {
+        let instance_info = node_client
+            .get_instance_info(address, &v2::BlockIdentifier::AbsoluteHeight(block_height))
+            .await?
+            .response;
+        Self::prepare(
+            node_client,
+            block_height,
+            address,
+            instance_info.name().as_contract_name(),
+            standard_identifiers,
+        )
+        .await
+    }
+
+    /// Whether the standard with the provided identifier is supported.
+    pub fn supports(&self, standard: &str) -> bool {
+        self.supported_standards.iter().any(|id| id == standard)
+    }
+
+    pub async fn save(&self, tx: &mut sqlx::PgTransaction<'_>) -> anyhow::Result<()> {
+        sqlx::query!(
+            "UPDATE contracts SET supported_standards = $3 WHERE index = $1 AND sub_index = $2",
+            self.contract_index,
+            self.contract_sub_index,
+            &self.supported_standards
+        )
+        .execute(tx.as_mut())
+        .await?
+        .ensure_affected_one_row()
+        .context("Failed updating the supported standards of contract")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,730 @@
+//! Module with database operations for tracking CIS-4 credential registries,
+//! such as the registries behind Concordium Web3 ID credentials.
+//!
+//! The events are parsed from the contract logs according to the CIS-4
+//! standard. The standard defines no event for restoring a revoked
+//! credential, so the restore event logged by the credential registry
+//! provided by Concordium is parsed as well. Other contracts are free to log
+//! custom events using the same tag, so a restore is only recorded when the
+//! `credentialStatus` entrypoint of the registry reports the credential as no
+//! longer revoked.
+
+use super::contract_log::LogReader;
+use crate::graphql_api::Cis4CredentialEventType;
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use concordium_rust_sdk::{
+    types::{
+        smart_contracts::{
+            ContractContext, InvokeContractResult, OwnedParameter, OwnedReceiveName,
+        },
+        AbsoluteBlockHeight, ContractAddress,
+    },
+    v2,
+};
+
+/// Event logged by a CIS-4 credential registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cis4Event {
+    /// A credential got registered.
+    Register {
+        /// Hex encoded public key identifying the credential holder.
+        holder_id:       String,
+        schema_ref:      Cis4MetadataUrl,
+        credential_type: String,
+    },
+    /// A credential got revoked.
+    Revoke {
+        holder_id: String,
+        revoker:   Cis4Revoker,
+        reason:    Option<String>,
+    },
+    /// A revoked credential got restored. Not part of the CIS-4 standard.
+    Restore {
+        holder_id: String,
+        reason:    Option<String>,
+    },
+    /// The metadata of the issuer changed.
+    IssuerMetadata(Cis4MetadataUrl),
+    /// The metadata of a credential changed.
+    CredentialMetadata {
+        holder_id:    String,
+        metadata_url: Cis4MetadataUrl,
+    },
+    /// The schema of a credential type changed.
+    Schema {
+        credential_type: String,
+        schema_ref:      Cis4MetadataUrl,
+    },
+    /// A revocation key got registered or removed.
+    RevocationKey {
+        /// Hex encoded Ed25519 public key.
+        key:    String,
+        action: Cis4RevocationKeyAction,
+    },
+}
+
+/// URL of a metadata file, with an optional hex encoded SHA256 hash of the
+/// contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cis4MetadataUrl {
+    pub url:  String,
+    pub hash: Option<String>,
+}
+
+/// The party revoking a credential.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cis4Revoker {
+    Issuer,
+    Holder,
+    /// A revocation key, hex encoded.
+    Other(String),
+}
+
+impl std::fmt::Display for Cis4Revoker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Cis4Revoker::Issuer => write!(f, "Issuer"),
+            Cis4Revoker::Holder => write!(f, "Holder"),
+            Cis4Revoker::Other(key) => write!(f, "{}", key),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cis4RevocationKeyAction {
+    Register,
+    Remove,
+}
+
+/// URL prefixed by its length as two bytes, followed by an optional hash.
+fn read_metadata_url(reader: &mut LogReader) -> Option<Cis4MetadataUrl> {
+    let len = reader.u16()?;
+    let url = reader.string(len.into())?;
+    let hash = match reader.u8()? {
+        0 => None,
+        1 => Some(hex::encode(reader.take(32)?)),
+        _ => return None,
+    };
+    Some(Cis4MetadataUrl {
+        url,
+        hash,
+    })
+}
+
+impl Cis4Event {
+    /// Parse a contract log as a CIS-4 event. `None` if the log is not a
+    /// well-formed CIS-4 event.
+    pub fn parse(log: &[u8]) -> Option<Self> {
+        let mut reader = LogReader::new(log);
+        let event = match reader.u8()? {
+            249 => Cis4Event::Register {
+                holder_id:       reader.public_key()?,
+                schema_ref:      read_metadata_url(&mut reader)?,
+                credential_type: reader.short_string()?,
+            },
+            248 => Cis4Event::Revoke {
+                holder_id: reader.public_key()?,
+                revoker:   match reader.u8()? {
+                    0 => Cis4Revoker::Issuer,
+                    1 => Cis4Revoker::Holder,
+                    2 => Cis4Revoker::Other(reader.public_key()?),
+                    _ => return None,
+                },
+                reason:    match reader.u8()? {
+                    0 => None,
+                    1 => Some(reader.short_string()?),
+                    _ => return None,
+                },
+            },
+            247 => Cis4Event::IssuerMetadata(read_metadata_url(&mut reader)?),
+            246 => Cis4Event::CredentialMetadata {
+                holder_id:    reader.public_key()?,
+                metadata_url: read_metadata_url(&mut reader)?,
+            },
+            245 => Cis4Event::Schema {
+                credential_type: reader.short_string()?,
+                schema_ref:      read_metadata_url(&mut reader)?,
+            },
+            244 => Cis4Event::RevocationKey {
+                key:    reader.public_key()?,
+                action: match reader.u8()? {
+                    0 => Cis4RevocationKeyAction::Register,
+                    1 => Cis4RevocationKeyAction::Remove,
+                    _ => return None,
+                },
+            },
+            0 => Cis4Event::Restore {
+                holder_id: reader.public_key()?,
+                reason:    match reader.u8()? {
+                    0 => None,
+                    1 => Some(reader.short_string()?),
+                    _ => return None,
+                },
+            },
+            _ => return None,
+        };
+        reader.is_empty().then_some(event)
+    }
+}
+
+/// The part of a credential entry in a registry, which is not part of the
+/// event registering the credential.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cis4CredentialEntry {
+    holder_revocable: bool,
+    valid_from:       DateTime<Utc>,
+    valid_until:      Option<DateTime<Utc>>,
+    metadata_url:     Cis4MetadataUrl,
+}
+
+impl Cis4CredentialEntry {
+    /// Parse the return value of the `credentialEntry` entrypoint.
+    fn parse(return_value: &[u8]) -> Option<Self> {
+        let mut reader = LogReader::new(return_value);
+        let _holder_id = reader.public_key()?;
+        let holder_revocable = reader.bool()?;
+        let valid_from = timestamp(reader.u64()?)?;
+        let valid_until = match reader.u8()? {
+            0 => None,
+            1 => Some(timestamp(reader.u64()?)?),
+            _ => return None,
+        };
+        let metadata_url = read_metadata_url(&mut reader)?;
+        Some(Self {
+            holder_revocable,
+            valid_from,
+            valid_until,
+            metadata_url,
+        })
+    }
+
+    /// Query the entry of a credential from the registry, in the state after
+    /// the provided block. `None` if the registry does not know the credential
+    /// or the response does not follow the standard.
+    async fn query(
+        node_client: &mut v2::Client,
+        block_height: AbsoluteBlockHeight,
+        address: ContractAddress,
+        contract_name: &str,
+        holder_id: &str,
+    ) -> anyhow::Result<Option<Self>> {
+        let context = ContractContext::new(
+            address,
+            OwnedReceiveName::new_unchecked(format!("{}.credentialEntry", contract_name)),
+        );
+        let context = ContractContext {
+            parameter: OwnedParameter::new_unchecked(hex::decode(holder_id)?),
+            ..context
+        };
+        let result = node_client
+            .invoke_instance(&v2::BlockIdentifier::AbsoluteHeight(block_height), &context)
+            .await?
+            .response;
+        let entry = match result {
+            InvokeContractResult::Success {
+                return_value: Some(return_value),
+                ..
+            } => Self::parse(&return_value.value),
+            _ => None,
+        };
+        Ok(entry)
+    }
+}
+
+/// The status of a credential as reported by the `credentialStatus`
+/// entrypoint of a registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cis4CredentialStatus {
+    Active,
+    Revoked,
+    Expired,
+    NotActivated,
+}
+
+impl Cis4CredentialStatus {
+    /// Parse the return value of the `credentialStatus` entrypoint.
+    fn parse(return_value: &[u8]) -> Option<Self> {
+        let status = match return_value {
+            [0] => Self::Active,
+            [1] => Self::Revoked,
+            [2] => Self::Expired,
+            [3] => Self::NotActivated,
+            _ => return None,
+        };
+        Some(status)
+    }
+
+    /// Query the status of a credential from the registry, in the state after
+    /// the provided block. `None` if the registry does not know the credential
+    /// or the response does not follow the standard.
+    async fn query(
+        node_client: &mut v2::Client,
+        block_height: AbsoluteBlockHeight,
+        address: ContractAddress,
+        contract_name: &str,
+        holder_id: &str,
+    ) -> anyhow::Result<Option<Self>> {
+        let context = ContractContext::new(
+            address,
+            OwnedReceiveName::new_unchecked(format!("{}.credentialStatus", contract_name)),
+        );
+        let context = ContractContext {
+            parameter: OwnedParameter::new_unchecked(hex::decode(holder_id)?),
+            ..context
+        };
+        let result = node_client
+            .invoke_instance(&v2::BlockIdentifier::AbsoluteHeight(block_height), &context)
+            .await?
+            .response;
+        let status = match result {
+            InvokeContractResult::Success {
+                return_value: Some(return_value),
+                ..
+            } => Self::parse(&return_value.value),
+            _ => None,
+        };
+        Ok(status)
+    }
+}
+
+/// Timestamp of milliseconds since the Unix epoch.
+fn timestamp(millis: u64) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp_millis(millis.try_into().ok()?)
+}
+
+/// The CIS-4 events logged by a credential registry in a transaction.
+#[derive(Debug)]
+pub struct PreparedCredentialRegistryEvents {
+    contract_index:     i64,
+    contract_sub_index: i64,
+    /// The events, together with the credential entry for register events.
+    /// The entry is `None` for other events, or if it could not be queried
+    /// from the registry.
+    events:             Vec<(Cis4Event, Option<Cis4CredentialEntry>)>,
+}
+
+impl PreparedCredentialRegistryEvents {
+    /// Prepare the events, querying the registry for the entries of the
+    /// registered credentials. Restore events are left out unless the registry
+    /// reports the credential as no longer revoked.
+    pub async fn prepare(
+        node_client: &mut v2::Client,
+        block_height: AbsoluteBlockHeight,
+        address: ContractAddress,
+        contract_name: &str,
+        events: Vec<Cis4Event>,
+    ) -> anyhow::Result<Self> {
+        let mut prepared_events = Vec::with_capacity(events.len());
+        for event in events {
+            let entry = match &event {
+                Cis4Event::Register {
+                    holder_id,
+                    ..
+                } => {
+                    Cis4CredentialEntry::query(
+                        node_client,
+                        block_height,
+                        address,
+                        contract_name,
+                        holder_id,
+                    )
+                    .await?
+                }
+                Cis4Event::Restore {
+                    holder_id,
+                    ..
+                } => {
+                    let status = Cis4CredentialStatus::query(
+                        node_client,
+                        block_height,
+                        address,
+                        contract_name,
+                        holder_id,
+                    )
+                    .await?;
+                    if matches!(status, None | Some(Cis4CredentialStatus::Revoked)) {
+                        continue;
+                    }
+                    None
+                }
+                _ => None,
+            };
+            prepared_events.push((event, entry));
+        }
+        Ok(Self {
+            contract_index:     address.index.try_into()?,
+            contract_sub_index: address.subindex.try_into()?,
+            events:             prepared_events,
+        })
+    }
+
+    pub async fn save(
+        &self,
+        tx: &mut sqlx::PgTransaction<'_>,
+        transaction_index: i64,
+    ) -> anyhow::Result<()> {
+        sqlx::query!(
+            "INSERT INTO cis4_registries (contract_index, contract_sub_index, transaction_index)
+            VALUES ($1, $2, $3)
+            ON CONFLICT DO NOTHING",
+            self.contract_index,
+            self.contract_sub_index,
+            transaction_index
+        )
+        .execute(tx.as_mut())
+        .await?;
+
+        for (event, entry) in self.events.iter() {
+            match event {
+                Cis4Event::Register {
+                    holder_id,
+                    schema_ref,
+                    credential_type,
+                } => {
+                    // Registries following the standard reject registering a credential
+                    // again, so only the latest registration is kept.
+                    sqlx::query!(
+                        "INSERT INTO cis4_credentials (
+                            contract_index,
+                            contract_sub_index,
+                            holder_id,
+                            credential_type,
+                            schema_url,
+                            holder_revocable,
+                            valid_from,
+                            valid_until,
+                            metadata_url,
+                            transaction_index
+                        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                        ON CONFLICT (contract_index, contract_sub_index, holder_id) DO UPDATE SET
+                            credential_type = EXCLUDED.credential_type,
+                            schema_url = EXCLUDED.schema_url,
+                            holder_revocable = EXCLUDED.holder_revocable,
+                            valid_from = EXCLUDED.valid_from,
+                            valid_until = EXCLUDED.valid_until,
+                            metadata_url = EXCLUDED.metadata_url,
+                            revoked = FALSE,
+                            transaction_index = EXCLUDED.transaction_index",
+                        self.contract_index,
+                        self.contract_sub_index,
+                        holder_id,
+                        credential_type,
+                        schema_ref.url,
+                        entry.as_ref().map(|entry| entry.holder_revocable),
+                        entry.as_ref().map(|entry| entry.valid_from),
+                        entry.as_ref().and_then(|entry| entry.valid_until),
+                        entry.as_ref().map(|entry| entry.metadata_url.url.as_str()),
+                        transaction_index
+                    )
+                    .execute(tx.as_mut())
+                    .await?;
+                    self.save_credential_event(
+                        tx,
+                        transaction_index,
+                        holder_id,
+                        Cis4CredentialEventType::Registered,
+                        None,
+                        None,
+                    )
+                    .await?;
+                }
+                Cis4Event::Revoke {
+                    holder_id,
+                    revoker,
+                    reason,
+                } => {
+                    sqlx::query!(
+                        "UPDATE cis4_credentials SET revoked = TRUE
+                        WHERE contract_index = $1 AND contract_sub_index = $2 AND holder_id = $3",
+                        self.contract_index,
+                        self.contract_sub_index,
+                        holder_id
+                    )
+                    .execute(tx.as_mut())
+                    .await?;
+                    self.save_credential_event(
+                        tx,
+                        transaction_index,
+                        holder_id,
+                        Cis4CredentialEventType::Revoked,
+                        Some(revoker.to_string()),
+                        reason.as_deref(),
+                    )
+                    .await?;
+                }
+                Cis4Event::Restore {
+                    holder_id,
+                    reason,
+                } => {
+                    sqlx::query!(
+                        "UPDATE cis4_credentials SET revoked = FALSE
+                        WHERE contract_index = $1 AND contract_sub_index = $2 AND holder_id = $3",
+                        self.contract_index,
+                        self.contract_sub_index,
+                        holder_id
+                    )
+                    .execute(tx.as_mut())
+                    .await?;
+                    self.save_credential_event(
+                        tx,
+                        transaction_index,
+                        holder_id,
+                        Cis4CredentialEventType::Restored,
+                        None,
+                        reason.as_deref(),
+                    )
+                    .await?;
+                }
+                Cis4Event::CredentialMetadata {
+                    holder_id,
+                    metadata_url,
+                } => {
+                    sqlx::query!(
+                        "UPDATE cis4_credentials SET metadata_url = $4
+                        WHERE contract_index = $1 AND contract_sub_index = $2 AND holder_id = $3",
+                        self.contract_index,
+                        self.contract_sub_index,
+                        holder_id,
+                        metadata_url.url
+                    )
+                    .execute(tx.as_mut())
+                    .await?;
+                    self.save_credential_event(
+                        tx,
+                        transaction_index,
+                        holder_id,
+                        Cis4CredentialEventType::MetadataUpdated,
+                        None,
+                        None,
+                    )
+                    .await?;
+                }
+                Cis4Event::IssuerMetadata(metadata_url) => {
+                    sqlx::query!(
+                        "UPDATE cis4_registries
+                        SET issuer_metadata_url = $3, issuer_metadata_hash = $4
+                        WHERE contract_index = $1 AND contract_sub_index = $2",
+                        self.contract_index,
+                        self.contract_sub_index,
+                        metadata_url.url,
+                        metadata_url.hash
+                    )
+                    .execute(tx.as_mut())
+                    .await?;
+                }
+                Cis4Event::Schema {
+                    credential_type,
+                    schema_ref,
+                } => {
+                    sqlx::query!(
+                        "UPDATE cis4_registries SET credential_type = $3, schema_url = $4
+                        WHERE contract_index = $1 AND contract_sub_index = $2",
+                        self.contract_index,
+                        self.contract_sub_index,
+                        credential_type,
+                        schema_ref.url
+                    )
+                    .execute(tx.as_mut())
+                    .await?;
+                }
+                Cis4Event::RevocationKey {
+                    key,
+                    action,
+                } => {
+                    sqlx::query!(
+                        "INSERT INTO cis4_revocation_keys (
+                            contract_index,
+                            contract_sub_index,
+                            key,
+                            active,
+                            transaction_index
+                        ) VALUES ($1, $2, $3, $4, $5)
+                        ON CONFLICT (contract_index, contract_sub_index, key) DO UPDATE SET
+                            active = EXCLUDED.active,
+                            transaction_index = EXCLUDED.transaction_index",
+                        self.contract_index,
+                        self.contract_sub_index,
+                        key,
+                        *action == Cis4RevocationKeyAction::Register,
+                        transaction_index
+                    )
+                    .execute(tx.as_mut())
+                    .await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Add an event to the history of a credential. Events for credentials
+    /// unknown to the registry are ignored.
+    async fn save_credential_event(
+        &self,
+        tx: &mut sqlx::PgTransaction<'_>,
+        transaction_index: i64,
+        holder_id: &str,
+        event_type: Cis4CredentialEventType,
+        revoker: Option<String>,
+        reason: Option<&str>,
+    ) -> anyhow::Result<()> {
+        sqlx::query!(
+            "INSERT INTO cis4_credential_events (
+                credential_id,
+                transaction_index,
+                event_type,
+                revoker,
+                reason
+            )
+            SELECT id, $4, $5, $6, $7
+            FROM cis4_credentials
+            WHERE contract_index = $1 AND contract_sub_index = $2 AND holder_id = $3",
+            self.contract_index,
+            self.contract_sub_index,
+            holder_id,
+            transaction_index,
+            event_type as Cis4CredentialEventType,
+            revoker,
+            reason
+        )
+        .execute(tx.as_mut())
+        .await
+        .context("Failed inserting CIS-4 credential event")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata_url(url: &str, hash: Option<[u8; 32]>) -> Vec<u8> {
+        let mut bytes = (url.len() as u16).to_le_bytes().to_vec();
+        bytes.extend_from_slice(url.as_bytes());
+        match hash {
+            None => bytes.push(0),
+            Some(hash) => {
+                bytes.push(1);
+                bytes.extend_from_slice(&hash);
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_parse_register() {
+        let mut log = vec![249];
+        log.extend_from_slice(&[1; 32]);
+        log.extend(metadata_url("https://schema", None));
+        log.push(4);
+        log.extend_from_slice(b"Test");
+        assert_eq!(
+            Cis4Event::parse(&log),
+            Some(Cis4Event::Register {
+                holder_id:       hex::encode([1; 32]),
+                schema_ref:      Cis4MetadataUrl {
+                    url:  "https://schema".to_string(),
+                    hash: None,
+                },
+                credential_type: "Test".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_revoke() {
+        let mut log = vec![248];
+        log.extend_from_slice(&[1; 32]);
+        log.push(2);
+        log.extend_from_slice(&[2; 32]);
+        log.push(1);
+        log.push(4);
+        log.extend_from_slice(b"Lost");
+        assert_eq!(
+            Cis4Event::parse(&log),
+            Some(Cis4Event::Revoke {
+                holder_id: hex::encode([1; 32]),
+                revoker:   Cis4Revoker::Other(hex::encode([2; 32])),
+                reason:    Some("Lost".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_restore() {
+        let mut log = vec![0];
+        log.extend_from_slice(&[1; 32]);
+        log.push(1);
+        log.push(5);
+        log.extend_from_slice(b"Found");
+        assert_eq!(
+            Cis4Event::parse(&log),
+            Some(Cis4Event::Restore {
+                holder_id: hex::encode([1; 32]),
+                reason:    Some("Found".to_string()),
+            })
+        );
+        // Without a reason.
+        let mut log = vec![0];
+        log.extend_from_slice(&[1; 32]);
+        log.push(0);
+        assert_eq!(
+            Cis4Event::parse(&log),
+            Some(Cis4Event::Restore {
+                holder_id: hex::encode([1; 32]),
+                reason:    None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_credential_status() {
+        assert_eq!(Cis4CredentialStatus::parse(&[0]), Some(Cis4CredentialStatus::Active));
+        assert_eq!(Cis4CredentialStatus::parse(&[1]), Some(Cis4CredentialStatus::Revoked));
+        assert_eq!(Cis4CredentialStatus::parse(&[3]), Some(Cis4CredentialStatus::NotActivated));
+        assert_eq!(Cis4CredentialStatus::parse(&[4]), None);
+        assert_eq!(Cis4CredentialStatus::parse(&[0, 0]), None);
+    }
+
+    #[test]
+    fn test_parse_issuer_metadata_with_hash() {
+        let mut log = vec![247];
+        log.extend(metadata_url("https://issuer", Some([3; 32])));
+        assert_eq!(
+            Cis4Event::parse(&log),
+            Some(Cis4Event::IssuerMetadata(Cis4MetadataUrl {
+                url:  "https://issuer".to_string(),
+                hash: Some(hex::encode([3; 32])),
+            }))
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_other_logs() {
+        // A CIS-2 transfer event.
+        assert_eq!(Cis4Event::parse(&[255, 0, 1]), None);
+        // Truncated revocation key event.
+        assert_eq!(Cis4Event::parse(&[244, 1, 2, 3]), None);
+        // Trailing bytes.
+        let mut log = vec![244];
+        log.extend_from_slice(&[1; 32]);
+        log.extend_from_slice(&[0, 0]);
+        assert_eq!(Cis4Event::parse(&log), None);
+    }
+
+    #[test]
+    fn test_parse_credential_entry() {
+        let mut entry = [1; 32].to_vec();
+        entry.push(1);
+        entry.extend_from_slice(&1_700_000_000_000u64.to_le_bytes());
+        entry.push(0);
+        entry.extend(metadata_url("https://credential", None));
+        entry.extend(metadata_url("https://schema", None));
+        entry.extend_from_slice(&5u64.to_le_bytes());
+        let entry = Cis4CredentialEntry::parse(&entry).unwrap();
+        assert!(entry.holder_revocable);
+        assert_eq!(entry.valid_from.timestamp_millis(), 1_700_000_000_000);
+        assert_eq!(entry.valid_until, None);
+        assert_eq!(entry.metadata_url.url, "https://credential");
+    }
+}
@@ -0,0 +1,134 @@
+//! Module with database operations for the registry of identity providers and
+//! anonymity revokers.
+
+use concordium_rust_sdk::{
+    common::to_bytes,
+    id::{
+        constants::{ArCurve, IpPairing},
+        types::{ArInfo, IpInfo},
+    },
+};
+
+/// Represents an identity provider being added to the chain.
+#[derive(Debug)]
+pub struct PreparedIdentityProvider {
+    id:             i64,
+    name:           String,
+    url:            String,
+    description:    String,
+    /// Hex encoded public key used to verify the signatures of the identity
+    /// provider on identity objects.
+    verify_key:     String,
+    /// Hex encoded Ed25519 public key used to verify initial account
+    /// creations.
+    cdi_verify_key: String,
+}
+
+impl PreparedIdentityProvider {
+    pub fn prepare(info: &IpInfo<IpPairing>) -> Self {
+        Self {
+            id:             info.ip_identity.0.into(),
+            name:           info.ip_description.name.clone(),
+            url:            info.ip_description.url.clone(),
+            description:    info.ip_description.description.clone(),
+            verify_key:     hex::encode(to_bytes(&info.ip_verify_key)),
+            cdi_verify_key: hex::encode(to_bytes(&info.ip_cdi_verify_key)),
+        }
+    }
+
+    /// Insert the identity provider. The transaction index is the chain update
+    /// adding the identity provider, which is `None` for identity providers
+    /// present in genesis.
+    pub async fn save(
+        &self,
+        tx: &mut sqlx::PgTransaction<'_>,
+        block_height: i64,
+        transaction_index: Option<i64>,
+    ) -> anyhow::Result<()> {
+        // Adding an identity provider using an existing identity has no effect on
+        // chain.
+        sqlx::query!(
+            "INSERT INTO identity_providers (
+                id,
+                name,
+                url,
+                description,
+                verify_key,
+                cdi_verify_key,
+                creation_block_height,
+                transaction_index
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (id) DO NOTHING",
+            self.id,
+            self.name,
+            self.url,
+            self.description,
+            self.verify_key,
+            self.cdi_verify_key,
+            block_height,
+            transaction_index
+        )
+        .execute(tx.as_mut())
+        .await?;
+        Ok(())
+    }
+}
+
+/// Represents an anonymity revoker being added to the chain.
+#[derive(Debug)]
+pub struct PreparedAnonymityRevoker {
+    id:          i64,
+    name:        String,
+    url:         String,
+    description: String,
+    /// Hex encoded public key used for encrypting data for the anonymity
+    /// revoker.
+    public_key:  String,
+}
+
+impl PreparedAnonymityRevoker {
+    pub fn prepare(info: &ArInfo<ArCurve>) -> Self {
+        Self {
+            id:          u32::from(info.ar_identity).into(),
+            name:        info.ar_description.name.clone(),
+            url:         info.ar_description.url.clone(),
+            description: info.ar_description.description.clone(),
+            public_key:  hex::encode(to_bytes(&info.ar_public_key)),
+        }
+    }
+
+    /// Insert the anonymity revoker. The transaction index is the chain update
+    /// adding the anonymity revoker, which is `None` for anonymity revokers
+    /// present in genesis.
+    pub async fn save(
+        &self,
+        tx: &mut sqlx::PgTransaction<'_>,
+        block_height: i64,
+        transaction_index: Option<i64>,
+    ) -> anyhow::Result<()> {
+        // Adding an anonymity revoker using an existing identity has no effect on
+        // chain.
+        sqlx::query!(
+            "INSERT INTO anonymity_revokers (
+                id,
+                name,
+                url,
+                description,
+                public_key,
+                creation_block_height,
+                transaction_index
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (id) DO NOTHING",
+            self.id,
+            self.name,
+            self.url,
+            self.description,
+            self.public_key,
+            block_height,
+            transaction_index
+        )
+        .execute(tx.as_mut())
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,420 @@
+//! Module with database operations for tracking CIS-5 smart contract wallets,
+//! which hold CCD and CIS-2 tokens on behalf of public keys rather than
+//! accounts.
+//!
+//! The events are parsed from the contract logs according to the CIS-5
+//! standard and the balance of each public key is derived from the deposits,
+//! withdrawals and transfers logged by the wallet.
+
+use super::contract_log::LogReader;
+use crate::graphql_api::Cis5BalanceChangeType;
+use anyhow::Context;
+use bigdecimal::{BigDecimal, ToPrimitive};
+use concordium_rust_sdk::types::{Address, ContractAddress};
+
+/// Event logged by a CIS-5 smart contract wallet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cis5Event {
+    /// The nonce of a public key got updated by a sponsored action.
+    Nonce {
+        /// Hex encoded Ed25519 public key.
+        public_key: String,
+        nonce:      u64,
+    },
+    /// Funds got deposited from an account or contract to a public key.
+    Deposit {
+        asset:  Cis5Asset,
+        amount: BigDecimal,
+        from:   Address,
+        to:     String,
+    },
+    /// Funds got withdrawn from a public key to an account or contract.
+    Withdraw {
+        asset:  Cis5Asset,
+        amount: BigDecimal,
+        from:   String,
+        to:     Address,
+    },
+    /// Funds got transferred between two public keys in the wallet.
+    Transfer {
+        asset:  Cis5Asset,
+        amount: BigDecimal,
+        from:   String,
+        to:     String,
+    },
+}
+
+/// The funds held by a smart contract wallet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cis5Asset {
+    Ccd,
+    Cis2Token {
+        contract: ContractAddress,
+        /// Hex encoded token ID.
+        token_id: String,
+    },
+}
+
+impl Cis5Event {
+    /// Parse a contract log as a CIS-5 event. `None` if the log is not a
+    /// well-formed CIS-5 event.
+    pub fn parse(log: &[u8]) -> Option<Self> {
+        let mut reader = LogReader::new(log);
+        let event = match reader.u8()? {
+            250 => Cis5Event::Nonce {
+                public_key: reader.public_key()?,
+                nonce:      reader.u64()?,
+            },
+            249 => Cis5Event::Deposit {
+                amount: reader.u64()?.into(),
+                asset:  Cis5Asset::Ccd,
+                from:   reader.address()?,
+                to:     reader.public_key()?,
+            },
+            248 => Cis5Event::Deposit {
+                amount: reader.token_amount()?,
+                asset:  read_token(&mut reader)?,
+                from:   reader.address()?,
+                to:     reader.public_key()?,
+            },
+            247 => Cis5Event::Withdraw {
+                amount: reader.u64()?.into(),
+                asset:  Cis5Asset::Ccd,
+                from:   reader.public_key()?,
+                to:     reader.address()?,
+            },
+            246 => Cis5Event::Withdraw {
+                amount: reader.token_amount()?,
+                asset:  read_token(&mut reader)?,
+                from:   reader.public_key()?,
+                to:     reader.address()?,
+            },
+            245 => Cis5Event::Transfer {
+                amount: reader.u64()?.into(),
+                asset:  Cis5Asset::Ccd,
+                from:   reader.public_key()?,
+                to:     reader.public_key()?,
+            },
+            244 => Cis5Event::Transfer {
+                amount: reader.token_amount()?,
+                asset:  read_token(&mut reader)?,
+                from:   reader.public_key()?,
+                to:     reader.public_key()?,
+            },
+            _ => return None,
+        };
+        reader.is_empty().then_some(event)
+    }
+}
+
+/// Token ID followed by the address of the CIS-2 contract of the token.
+fn read_token(reader: &mut LogReader) -> Option<Cis5Asset> {
+    let token_id = reader.token_id()?;
+    Some(Cis5Asset::Cis2Token {
+        contract: reader.contract_address()?,
+        token_id,
+    })
+}
+
+/// The other party of a change to the balance of a public key.
+enum Cis5Counterparty<'a> {
+    Address(&'a Address),
+    PublicKey(&'a str),
+}
+
+/// The CIS-5 events logged by a smart contract wallet in a transaction.
+#[derive(Debug)]
+pub struct PreparedSmartContractWalletEvents {
+    contract_index:     i64,
+    contract_sub_index: i64,
+    events:             Vec<Cis5Event>,
+}
+
+impl PreparedSmartContractWalletEvents {
+    pub fn prepare(address: ContractAddress, events: Vec<Cis5Event>) -> anyhow::Result<Self> {
+        Ok(Self {
+            contract_index: address.index.try_into()?,
+            contract_sub_index: address.subindex.try_into()?,
+            events,
+        })
+    }
+
+    pub async fn save(
+        &self,
+        tx: &mut sqlx::PgTransaction<'_>,
+        transaction_index: i64,
+    ) -> anyhow::Result<()> {
+        for event in self.events.iter() {
+            match event {
+                Cis5Event::Nonce {
+                    public_key,
+                    nonce,
+                } => {
+                    let holder_id = self.save_holder(tx, transaction_index, public_key).await?;
+                    sqlx::query!(
+                        "UPDATE cis5_public_key_holders SET nonce = $2 WHERE id = $1",
+                        holder_id,
+                        i64::try_from(*nonce)?
+                    )
+                    .execute(tx.as_mut())
+                    .await?;
+                }
+                Cis5Event::Deposit {
+                    asset,
+                    amount,
+                    from,
+                    to,
+                } => {
+                    self.save_balance_change(
+                        tx,
+                        transaction_index,
+                        to,
+                        Cis5BalanceChangeType::Deposit,
+                        asset,
+                        amount,
+                        Cis5Counterparty::Address(from),
+                    )
+                    .await?;
+                }
+                Cis5Event::Withdraw {
+                    asset,
+                    amount,
+                    from,
+                    to,
+                } => {
+                    self.save_balance_change(
+                        tx,
+                        transaction_index,
+                        from,
+                        Cis5BalanceChangeType::Withdraw,
+                        asset,
+                        amount,
+                        Cis5Counterparty::Address(to),
+                    )
+                    .await?;
+                }
+                Cis5Event::Transfer {
+                    asset,
+                    amount,
+                    from,
+                    to,
+                } => {
+                    self.save_balance_change(
+                        tx,
+                        transaction_index,
+                        from,
+                        Cis5BalanceChangeType::TransferOut,
+                        asset,
+                        amount,
+                        Cis5Counterparty::PublicKey(to),
+                    )
+                    .await?;
+                    self.save_balance_change(
+                        tx,
+                        transaction_index,
+                        to,
+                        Cis5BalanceChangeType::TransferIn,
+                        asset,
+                        amount,
+                        Cis5Counterparty::PublicKey(from),
+                    )
+                    .await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Insert the public key into the wallet, if not already present, and
+    /// return the ID of the holder.
+    async fn save_holder(
+        &self,
+        tx: &mut sqlx::PgTransaction<'_>,
+        transaction_index: i64,
+        public_key: &str,
+    ) -> anyhow::Result<i64> {
+        let holder_id = sqlx::query_scalar!(
+            "INSERT INTO cis5_public_key_holders (
+                contract_index,
+                contract_sub_index,
+                public_key,
+                transaction_index
+            ) VALUES ($1, $2, $3, $4)
+            ON CONFLICT (contract_index, contract_sub_index, public_key)
+                DO UPDATE SET public_key = EXCLUDED.public_key
+            RETURNING id",
+            self.contract_index,
+            self.contract_sub_index,
+            public_key,
+            transaction_index
+        )
+        .fetch_one(tx.as_mut())
+        .await?;
+        Ok(holder_id)
+    }
+
+    /// Update the balance of the public key and add the change to its history.
+    #[allow(clippy::too_many_arguments)]
+    async fn save_balance_change(
+        &self,
+        tx: &mut sqlx::PgTransaction<'_>,
+        transaction_index: i64,
+        public_key: &str,
+        change_type: Cis5BalanceChangeType,
+        asset: &Cis5Asset,
+        amount: &BigDecimal,
+        counterparty: Cis5Counterparty<'_>,
+    ) -> anyhow::Result<()> {
+        let holder_id = self.save_holder(tx, transaction_index, public_key).await?;
+        let delta = match change_type {
+            Cis5BalanceChangeType::Deposit | Cis5BalanceChangeType::TransferIn => amount.clone(),
+            Cis5BalanceChangeType::Withdraw | Cis5BalanceChangeType::TransferOut => -amount,
+        };
+        let (token_contract_index, token_contract_sub_index, token_id) = match asset {
+            Cis5Asset::Ccd => {
+                sqlx::query!(
+                    "UPDATE cis5_public_key_holders SET ccd_balance = ccd_balance + $2 WHERE id = \
+                     $1",
+                    holder_id,
+                    delta.to_i64().context("CCD amount out of range")?
+                )
+                .execute(tx.as_mut())
+                .await?;
+                (None, None, None)
+            }
+            Cis5Asset::Cis2Token {
+                contract,
+                token_id,
+            } => {
+                let contract_index = i64::try_from(contract.index)?;
+                let contract_sub_index = i64::try_from(contract.subindex)?;
+                // Note: Some `buggy` wallets might withdraw more tokens than deposited. The
+                // balance can have a negative value in that case.
+                sqlx::query!(
+                    "INSERT INTO cis5_token_balances (
+                        holder_id,
+                        token_contract_index,
+                        token_contract_sub_index,
+                        token_id,
+                        balance
+                    ) VALUES ($1, $2, $3, $4, $5)
+                    ON CONFLICT (holder_id, token_contract_index, token_contract_sub_index, \
+                     token_id)
+                        DO UPDATE SET balance = cis5_token_balances.balance + EXCLUDED.balance",
+                    holder_id,
+                    contract_index,
+                    contract_sub_index,
+                    token_id,
+                    delta
+                )
+                .execute(tx.as_mut())
+                .await?;
+                (Some(contract_index), Some(contract_sub_index), Some(token_id.as_str()))
+            }
+        };
+
+        let (counterparty_account, counterparty_contract, counterparty_public_key) =
+            match counterparty {
+                Cis5Counterparty::Address(Address::Account(address)) => {
+                    (Some(address.get_canonical_address()), None, None)
+                }
+                Cis5Counterparty::Address(Address::Contract(address)) => {
+                    (None, Some(*address), None)
+                }
+                Cis5Counterparty::PublicKey(public_key) => (None, None, Some(public_key)),
+            };
+        let counterparty_contract_index =
+            counterparty_contract.map(|address| i64::try_from(address.index)).transpose()?;
+        let counterparty_contract_sub_index =
+            counterparty_contract.map(|address| i64::try_from(address.subindex)).transpose()?;
+        sqlx::query!(
+            "INSERT INTO cis5_balance_changes (
+                holder_id,
+                transaction_index,
+                change_type,
+                token_contract_index,
+                token_contract_sub_index,
+                token_id,
+                amount,
+                counterparty_account_index,
+                counterparty_contract_index,
+                counterparty_contract_sub_index,
+                counterparty_public_key
+            ) VALUES (
+                $1,
+                $2,
+                $3,
+                $4,
+                $5,
+                $6,
+                $7,
+                (SELECT index FROM accounts WHERE canonical_address = $8),
+                $9,
+                $10,
+                $11
+            )",
+            holder_id,
+            transaction_index,
+            change_type as Cis5BalanceChangeType,
+            token_contract_index,
+            token_contract_sub_index,
+            token_id,
+            amount,
+            counterparty_account.as_ref().map(|address| address.0.as_slice()),
+            counterparty_contract_index,
+            counterparty_contract_sub_index,
+            counterparty_public_key
+        )
+        .execute(tx.as_mut())
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use concordium_rust_sdk::id::types::AccountAddress;
+
+    #[test]
+    fn test_parse_deposit_ccd() {
+        let mut log = vec![249];
+        log.extend(1_000_000u64.to_le_bytes());
+        log.push(0);
+        log.extend([1; 32]);
+        log.extend([2; 32]);
+        assert_eq!(
+            Cis5Event::parse(&log),
+            Some(Cis5Event::Deposit {
+                asset:  Cis5Asset::Ccd,
+                amount: BigDecimal::from(1_000_000),
+                from:   Address::Account(AccountAddress([1; 32])),
+                to:     hex::encode([2; 32]),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_transfer_tokens() {
+        let mut log = vec![244, 10, 2, 0xab, 0xcd];
+        log.extend(5u64.to_le_bytes());
+        log.extend(0u64.to_le_bytes());
+        log.extend([1; 32]);
+        log.extend([2; 32]);
+        assert_eq!(
+            Cis5Event::parse(&log),
+            Some(Cis5Event::Transfer {
+                asset:  Cis5Asset::Cis2Token {
+                    contract: ContractAddress::new(5, 0),
+                    token_id: "abcd".to_string(),
+                },
+                amount: BigDecimal::from(10),
+                from:   hex::encode([1; 32]),
+                to:     hex::encode([2; 32]),
+            })
+        );
+        // Trailing bytes are not part of a CIS-5 event.
+        log.push(0);
+        assert_eq!(Cis5Event::parse(&log), None);
+    }
+}
@@ -0,0 +1,371 @@
+//! Module with database operations for tracking CIS-3 sponsored actions,
+//! where a sponsor submits the transaction and pays for an action signed by
+//! another account.
+//!
+//! Sponsored actions are detected as calls to the `permit` entrypoint of a
+//! contract supporting CIS-3. The parameter of `permit` is decoded using the
+//! schema of the contract module, reading the fields named by the standard,
+//! or using the serialization defined by the standard when the module has no
+//! schema for the parameter.
+
+use super::{contract_call_tree::CallCaller, contract_log::LogReader};
+use crate::transaction_event::protocol_level_tokens::canonical_address;
+use chrono::{DateTime, Utc};
+use concordium_rust_sdk::{
+    base::contracts_common::{
+        schema::{Type, VersionedModuleSchema},
+        Cursor,
+    },
+    id::types::AccountAddress,
+};
+
+/// The name of the entrypoint executing sponsored actions.
+const PERMIT_ENTRYPOINT: &str = "permit";
+
+/// The parameter of the CIS-3 `permit` entrypoint, without the signatures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PermitParameter {
+    signer:             AccountAddress,
+    contract_index:     u64,
+    contract_sub_index: u64,
+    nonce:              u64,
+    /// Expiry of the signature.
+    timestamp:          DateTime<Utc>,
+    entrypoint:         String,
+}
+
+impl PermitParameter {
+    /// Decode the parameter using the type of the `permit` parameter in the
+    /// schema, or the serialization defined by the standard when the schema
+    /// has no type for the parameter.
+    fn parse(
+        schema: Option<&VersionedModuleSchema>,
+        contract_name: &str,
+        parameter: &[u8],
+    ) -> Option<Self> {
+        let parameter_type = schema.and_then(|schema| {
+            schema.get_receive_param_schema(contract_name, PERMIT_ENTRYPOINT).ok()
+        });
+        match parameter_type {
+            Some(parameter_type) => Self::parse_with_type(&parameter_type, parameter),
+            None => Self::parse_standard(parameter),
+        }
+    }
+
+    /// Decode the parameter into JSON using its type, and read the fields as
+    /// named by the standard.
+    fn parse_with_type(parameter_type: &Type, parameter: &[u8]) -> Option<Self> {
+        let value = parameter_type.to_json(&mut Cursor::new(parameter)).ok()?;
+        let message = value.get("message")?;
+        let contract_address = message.get("contract_address")?;
+        let timestamp = DateTime::parse_from_rfc3339(message.get("timestamp")?.as_str()?).ok()?;
+        Some(Self {
+            signer:             value.get("signer")?.as_str()?.parse().ok()?,
+            contract_index:     contract_address.get("index")?.as_u64()?,
+            contract_sub_index: contract_address.get("subindex")?.as_u64()?,
+            nonce:              message.get("nonce")?.as_u64()?,
+            timestamp:          timestamp.with_timezone(&Utc),
+            entrypoint:         message.get("entry_point")?.as_str()?.to_string(),
+        })
+    }
+
+    /// Decode the parameter following the serialization defined by the
+    /// standard.
+    fn parse_standard(parameter: &[u8]) -> Option<Self> {
+        let mut reader = LogReader::new(parameter);
+        // The signatures are a map from credential indices to maps from key indices to
+        // signatures, where the number of entries is a single byte.
+        let credentials = reader.u8()?;
+        for _ in 0..credentials {
+            let _credential_index = reader.u8()?;
+            let keys = reader.u8()?;
+            for _ in 0..keys {
+                let _key_index = reader.u8()?;
+                // Only Ed25519 signatures are defined.
+                if reader.u8()? != 0 {
+                    return None;
+                }
+                reader.take(64)?;
+            }
+        }
+        let signer = AccountAddress(reader.take(32)?.try_into().ok()?);
+        let contract_index = reader.u64()?;
+        let contract_sub_index = reader.u64()?;
+        let nonce = reader.u64()?;
+        let timestamp = DateTime::from_timestamp_millis(reader.u64()?.try_into().ok()?)?;
+        let entrypoint_len = reader.u16()?;
+        let entrypoint = reader.string(entrypoint_len.into())?;
+        let payload_len = reader.u16()?;
+        reader.take(payload_len.into())?;
+        reader.is_empty().then_some(Self {
+            signer,
+            contract_index,
+            contract_sub_index,
+            nonce,
+            timestamp,
+            entrypoint,
+        })
+    }
+}
+
+/// A call to the `permit` entrypoint of a contract, which is recorded as a
+/// sponsored action when saved, if the contract supports CIS-3 and the
+/// parameter is a message signed for the contract.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreparedSponsoredAction {
+    /// Index of the trace element reporting the call to `permit`.
+    trace_element_index: i64,
+    contract_index:      i64,
+    contract_sub_index:  i64,
+    /// The account or contract calling `permit`, paying for the action.
+    sponsor:             CallCaller,
+    /// The parameter of the call.
+    parameter:           Vec<u8>,
+}
+
+impl PreparedSponsoredAction {
+    /// Detect a potential sponsored action from a contract call. `None` if the
+    /// call is not to `permit`.
+    pub fn detect(
+        trace_element_index: i64,
+        contract_index: i64,
+        contract_sub_index: i64,
+        caller: CallCaller,
+        receive_name: &str,
+        parameter: &[u8],
+    ) -> Option<Self> {
+        let is_permit = receive_name
+            .split_once('.')
+            .is_some_and(|(_, entrypoint)| entrypoint == PERMIT_ENTRYPOINT);
+        is_permit.then(|| Self {
+            trace_element_index,
+            contract_index,
+            contract_sub_index,
+            sponsor: caller,
+            parameter: parameter.to_vec(),
+        })
+    }
+
+    /// Decode the parameter using the schema of the module of the contract.
+    /// `None` if the parameter does not follow the standard or is signed for
+    /// another contract, which the contract rejects.
+    fn permit(
+        &self,
+        schema: Option<&VersionedModuleSchema>,
+        contract_name: &str,
+    ) -> anyhow::Result<Option<PermitParameter>> {
+        let Some(permit) = PermitParameter::parse(schema, contract_name, &self.parameter) else {
+            return Ok(None);
+        };
+        let for_contract = i64::try_from(permit.contract_index)? == self.contract_index
+            && i64::try_from(permit.contract_sub_index)? == self.contract_sub_index;
+        Ok(for_contract.then_some(permit))
+    }
+
+    pub async fn save(
+        &self,
+        tx: &mut sqlx::PgTransaction<'_>,
+        transaction_index: i64,
+    ) -> anyhow::Result<()> {
+        let contract = sqlx::query!(
+            r#"SELECT
+                contracts.name,
+                contracts.supported_standards @> ARRAY['CIS-3'] AS "supports_cis3!",
+                smart_contract_modules.schema
+            FROM contracts
+                JOIN smart_contract_modules
+                    ON smart_contract_modules.module_reference = contracts.module_reference
+            WHERE contracts.index = $1 AND contracts.sub_index = $2"#,
+            self.contract_index,
+            self.contract_sub_index
+        )
+        .fetch_one(tx.as_mut())
+        .await?;
+        if !contract.supports_cis3 {
+            return Ok(());
+        }
+        let schema =
+            contract.schema.and_then(|schema| VersionedModuleSchema::new(&schema, &None).ok());
+        let Some(permit) = self.permit(schema.as_ref(), &contract.name)? else {
+            return Ok(());
+        };
+
+        let (sponsor_account, sponsor_contract_index, sponsor_contract_sub_index) =
+            match &self.sponsor {
+                CallCaller::Account(address) => (Some(canonical_address(address)?), None, None),
+                CallCaller::Contract {
+                    index,
+                    sub_index,
+                } => (None, Some(*index), Some(*sub_index)),
+            };
+        let signer = permit.signer.get_canonical_address();
+        sqlx::query!(
+            "INSERT INTO sponsored_actions (
+                transaction_index,
+                trace_element_index,
+                contract_index,
+                contract_sub_index,
+                signer_account_index,
+                sponsor_account_index,
+                sponsor_contract_index,
+                sponsor_contract_sub_index,
+                entrypoint,
+                nonce,
+                signature_expiry
+            ) VALUES (
+                $1,
+                $2,
+                $3,
+                $4,
+                (SELECT index FROM accounts WHERE canonical_address = $5),
+                (SELECT index FROM accounts WHERE canonical_address = $6),
+                $7,
+                $8,
+                $9,
+                $10,
+                $11
+            )",
+            transaction_index,
+            self.trace_element_index,
+            self.contract_index,
+            self.contract_sub_index,
+            signer.0.as_slice(),
+            sponsor_account.as_deref(),
+            sponsor_contract_index,
+            sponsor_contract_sub_index,
+            permit.entrypoint,
+            i64::try_from(permit.nonce)?,
+            permit.timestamp
+        )
+        .execute(tx.as_mut())
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use concordium_rust_sdk::base::contracts_common::schema::{Fields, SizeLength};
+
+    fn permit_message(contract_index: u64) -> Vec<u8> {
+        let mut message = Vec::new();
+        message.extend(contract_index.to_le_bytes());
+        message.extend(0u64.to_le_bytes());
+        message.extend(3u64.to_le_bytes());
+        message.extend(1_700_000_000_000u64.to_le_bytes());
+        message.extend(8u16.to_le_bytes());
+        message.extend(b"transfer");
+        message.extend(2u16.to_le_bytes());
+        message.extend([0, 0]);
+        message
+    }
+
+    fn permit_parameter(contract_index: u64) -> Vec<u8> {
+        // One credential with one Ed25519 signature.
+        let mut parameter = vec![1, 0, 1, 0, 0];
+        parameter.extend([7; 64]);
+        parameter.extend([1; 32]);
+        parameter.extend(permit_message(contract_index));
+        parameter
+    }
+
+    fn expected_permit(contract_index: u64) -> PermitParameter {
+        PermitParameter {
+            signer: AccountAddress([1; 32]),
+            contract_index,
+            contract_sub_index: 0,
+            nonce: 3,
+            timestamp: DateTime::from_timestamp_millis(1_700_000_000_000).unwrap(),
+            entrypoint: "transfer".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_standard_permit() {
+        assert_eq!(
+            PermitParameter::parse(None, "token", &permit_parameter(5)),
+            Some(expected_permit(5))
+        );
+    }
+
+    #[test]
+    fn test_parse_permit_with_schema() {
+        // A parameter with the fields of the standard, but in another order.
+        let message_type = Type::Struct(Fields::Named(vec![
+            ("contract_address".to_string(), Type::ContractAddress),
+            ("nonce".to_string(), Type::U64),
+            ("timestamp".to_string(), Type::Timestamp),
+            ("entry_point".to_string(), Type::String(SizeLength::U16)),
+            ("payload".to_string(), Type::List(SizeLength::U16, Box::new(Type::U8))),
+        ]));
+        let signature_type = Type::Enum(vec![(
+            "Ed25519".to_string(),
+            Fields::Unnamed(vec![Type::Array(64, Box::new(Type::U8))]),
+        )]);
+        let parameter_type = Type::Struct(Fields::Named(vec![
+            ("message".to_string(), message_type),
+            ("signer".to_string(), Type::AccountAddress),
+            (
+                "signature".to_string(),
+                Type::Map(
+                    SizeLength::U8,
+                    Box::new(Type::U8),
+                    Box::new(Type::Map(
+                        SizeLength::U8,
+                        Box::new(Type::U8),
+                        Box::new(signature_type),
+                    )),
+                ),
+            ),
+        ]));
+        let mut parameter = permit_message(5);
+        parameter.extend([1; 32]);
+        parameter.extend([1, 0, 1, 0, 0]);
+        parameter.extend([7; 64]);
+        assert_eq!(
+            PermitParameter::parse_with_type(&parameter_type, &parameter),
+            Some(expected_permit(5))
+        );
+        // The standard serialization does not apply to this parameter.
+        assert_eq!(PermitParameter::parse_standard(&parameter), None);
+    }
+
+    #[test]
+    fn test_detect_permit() {
+        let sponsor = CallCaller::Account("sponsor".to_string());
+        let action = PreparedSponsoredAction::detect(
+            0,
+            5,
+            0,
+            sponsor.clone(),
+            "token.permit",
+            &permit_parameter(5),
+        )
+        .unwrap();
+        assert_eq!(action.sponsor, sponsor);
+        assert_eq!(action.permit(None, "token").unwrap(), Some(expected_permit(5)));
+    }
+
+    #[test]
+    fn test_ignore_other_calls() {
+        let sponsor = CallCaller::Account("sponsor".to_string());
+        // Other entrypoints.
+        assert_eq!(
+            PreparedSponsoredAction::detect(
+                0,
+                5,
+                0,
+                sponsor.clone(),
+                "token.transfer",
+                &permit_parameter(5)
+            ),
+            None
+        );
+        // Messages signed for another contract.
+        let action =
+            PreparedSponsoredAction::detect(0, 5, 0, sponsor, "token.permit", &permit_parameter(6))
+                .unwrap();
+        assert_eq!(action.permit(None, "token").unwrap(), None);
+    }
+}
@@ -1,9 +1,17 @@
 //! Function and types for populating the database with initial information
 //! found in the genesis block.
 
-use super::block_preprocessor::compute_validator_staking_information;
+use super::{
+    block::{governance_keys::GovernanceKeys, micro_ccd_per_euro},
+    block_preprocessor::compute_validator_staking_information,
+    db::{
+        account_credential::PreparedAccountCredential,
+        identity_registry::{PreparedAnonymityRevoker, PreparedIdentityProvider},
+    },
+};
 use crate::transaction_event::baker::BakerPoolOpenStatus;
 use anyhow::Context;
+use bigdecimal::BigDecimal;
 use concordium_rust_sdk::{
     types::{AccountStakingInfo, PartsPerHundredThousands},
     v2,
@@ -34,6 +42,9 @@ pub async fn save_genesis_data(
 
     let total_amount =
         i64::try_from(genesis_tokenomics.common_reward_data().total_amount.micro_ccd())?;
+    let genesis_chain_parameters =
+        client.get_block_chain_parameters(genesis_height).await?.response;
+    let genesis_micro_ccd_per_euro = micro_ccd_per_euro(&genesis_chain_parameters);
     sqlx::query!(
         "INSERT INTO blocks (
             height,
@@ -43,12 +54,18 @@ pub async fn save_genesis_data(
             finalization_time,
             total_amount,
             total_staked,
-            cumulative_num_txs
-        ) VALUES (0, $1, $2, 0, 0, $3, $4, 0);",
+            cumulative_num_txs,
+            micro_ccd_per_euro_numerator,
+            micro_ccd_per_euro_denominator,
+            cumulative_ccd_fees,
+            cumulative_euro_fees
+        ) VALUES (0, $1, $2, 0, 0, $3, $4, 0, $5, $6, 0, 0);",
         block_hash,
         slot_time,
         total_amount,
         total_staked,
+        BigDecimal::from(genesis_micro_ccd_per_euro.numerator()),
+        BigDecimal::from(genesis_micro_ccd_per_euro.denominator()),
     )
     .execute(&mut *tx)
     .await?;
@@ -62,6 +79,15 @@ pub async fn save_genesis_data(
     .execute(&mut *tx)
     .await?;
 
+    let mut identity_providers = client.get_identity_providers(genesis_height).await?.response;
+    while let Some(info) = identity_providers.try_next().await? {
+        PreparedIdentityProvider::prepare(&info).save(&mut tx, 0, None).await?;
+    }
+    let mut anonymity_revokers = client.get_anonymity_revokers(genesis_height).await?.response;
+    while let Some(info) = anonymity_revokers.try_next().await? {
+        PreparedAnonymityRevoker::prepare(&info).save(&mut tx, 0, None).await?;
+    }
+
     let mut genesis_accounts = client.get_account_list(genesis_height).await?.response;
     while let Some(account) = genesis_accounts.try_next().await? {
         let info = client.get_account_info(&account.into(), genesis_height).await?.response;
@@ -70,18 +96,35 @@ pub async fn save_genesis_data(
         let canonical_address = account.get_canonical_address();
         let amount = i64::try_from(info.account_amount.micro_ccd)?;
 
+        let signature_threshold = i16::from(u8::from(info.account_threshold));
+
         // Note that we override the usual default num_txs = 1 here
         // because the genesis accounts do not have a creation transaction.
         sqlx::query!(
-            "INSERT INTO accounts (index, address, amount, canonical_address, num_txs)
-            VALUES ($1, $2, $3, $4, 0)",
+            "INSERT INTO accounts (
+                index,
+                address,
+                amount,
+                canonical_address,
+                num_txs,
+                signature_threshold
+            ) VALUES ($1, $2, $3, $4, 0, $5)",
             index,
             account_address,
             amount,
-            canonical_address.0.as_slice()
+            canonical_address.0.as_slice(),
+            signature_threshold
         )
         .execute(&mut *tx)
         .await?;
+        for (credential_index, credential) in info.account_credentials.iter() {
+            PreparedAccountCredential::from_account_info(
+                credential_index.index,
+                &credential.value,
+            )?
+            .save(&mut tx, index, None)
+            .await?;
+        }
 
         if let Some(AccountStakingInfo::Baker {
             staked_amount,
@@ -127,6 +170,8 @@ pub async fn save_genesis_data(
         }
     }
 
+    GovernanceKeys::from_chain_parameters(&genesis_chain_parameters).save(&mut tx, 0).await?;
+
     tx.commit().await.context("Failed to commit SQL transaction")?;
     Ok(())
 }
@@ -0,0 +1,128 @@
+//! Reading of the build information embedded by the verifiable builds of
+//! `cargo concordium` in the modules deployed before the build information
+//! was tracked.
+//!
+//! Modules deployed since are read when indexed, so the reader stops once
+//! every module marked as pending in the `smart_contract_modules` table is
+//! read.
+
+use crate::module_verification::ModuleBuildInfo;
+use concordium_rust_sdk::{types::hashes::ModuleReference, v2};
+use prometheus_client::{metrics::counter::Counter, registry::Registry};
+use sqlx::{postgres::PgConnectOptions, Connection, PgConnection};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
+
+/// The number of modules read in each database transaction.
+const BATCH_SIZE: i64 = 20;
+/// Time to wait before retrying after failing to read a batch.
+const RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// Service reading the build information of modules from the node.
+pub struct ModuleBuildInfoReader {
+    db_connect_options: PgConnectOptions,
+    /// The nodes to read module sources from, cycled through on failures.
+    endpoints:          Vec<v2::Endpoint>,
+    /// Metric counting the modules read.
+    read:               Counter,
+}
+
+impl ModuleBuildInfoReader {
+    pub fn new(
+        db_connect_options: PgConnectOptions,
+        endpoints: Vec<v2::Endpoint>,
+        registry: &mut Registry,
+    ) -> Self {
+        let read = Counter::default();
+        registry.register(
+            "read",
+            "Total number of modules with the build information read",
+            read.clone(),
+        );
+        Self {
+            db_connect_options,
+            endpoints,
+            read,
+        }
+    }
+
+    /// Run the reader until every pending module is read or until signaled by
+    /// the `stop_signal`. Failures are logged and retried, as the build
+    /// information is not required for indexing blocks.
+    pub async fn run(self, stop_signal: CancellationToken) -> anyhow::Result<()> {
+        let mut endpoint_index = 0;
+        loop {
+            if stop_signal.is_cancelled() {
+                return Ok(());
+            }
+            let endpoint = &self.endpoints[endpoint_index % self.endpoints.len()];
+            match self.read_batch(endpoint).await {
+                Ok(0) => {
+                    info!("Build information of every module is read");
+                    return Ok(());
+                }
+                Ok(_) => continue,
+                Err(err) => {
+                    error!("Failed reading module build information: {:?}", err);
+                    endpoint_index += 1;
+                }
+            }
+            tokio::select! {
+                _ = stop_signal.cancelled() => return Ok(()),
+                _ = tokio::time::sleep(RETRY_DELAY) => {}
+            }
+        }
+    }
+
+    /// Read the build information of the next batch of pending modules,
+    /// returning the number of modules read.
+    async fn read_batch(&self, endpoint: &v2::Endpoint) -> anyhow::Result<usize> {
+        let mut db_connection = PgConnection::connect_with(&self.db_connect_options).await?;
+        let mut tx = db_connection.begin().await?;
+        let modules = sqlx::query_scalar!(
+            "SELECT module_reference
+            FROM smart_contract_modules
+            WHERE build_info_pending
+            LIMIT $1",
+            BATCH_SIZE
+        )
+        .fetch_all(tx.as_mut())
+        .await?;
+        if modules.is_empty() {
+            return Ok(0);
+        }
+        let mut client = v2::Client::new(endpoint.clone()).await?;
+        for module_reference in modules.iter() {
+            let wasm_module = client
+                .get_module_source(
+                    &module_reference.parse::<ModuleReference>()?,
+                    v2::BlockIdentifier::LastFinal,
+                )
+                .await?
+                .response;
+            let build_info = ModuleBuildInfo::from_module_source(wasm_module.source.as_ref());
+            let build_info = build_info.as_ref();
+            sqlx::query!(
+                "UPDATE smart_contract_modules
+                SET
+                    build_archive_hash = $2,
+                    build_image = $3,
+                    build_command = $4,
+                    build_source_link = $5,
+                    build_info_pending = FALSE
+                WHERE module_reference = $1",
+                module_reference,
+                build_info.map(|info| info.archive_hash.as_str()),
+                build_info.map(|info| info.image.as_str()),
+                build_info.map(|info| info.build_command.as_slice()),
+                build_info.and_then(|info| info.source_link.as_deref())
+            )
+            .execute(tx.as_mut())
+            .await?;
+        }
+        tx.commit().await?;
+        self.read.inc_by(modules.len().try_into()?);
+        Ok(modules.len())
+    }
+}
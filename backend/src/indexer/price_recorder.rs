@@ -0,0 +1,407 @@
+//! Recording of the prices of CCD and tokens into the `price_history` table,
+//! from the price sources configured using `--price-source`.
+//!
+//! Prices are recorded at a fixed interval, running next to the processing of
+//! blocks. Every source provides a list of quotes, and when several sources
+//! quote the same asset in the same currency, the quote of the source listed
+//! first is recorded.
+
+use anyhow::Context;
+use chrono::Utc;
+use futures::future::{BoxFuture, FutureExt};
+use prometheus_client::{metrics::counter::Counter, registry::Registry};
+use sqlx::{postgres::PgConnectOptions, Connection, PgConnection, PgPool};
+use std::{collections::HashMap, path::PathBuf, str::FromStr, time::Duration};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+/// The most time spent fetching the prices from an HTTP source.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Time to wait before retrying after failing to store the prices.
+const RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// The price of one whole unit of an asset in a fiat currency.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceQuote {
+    /// The asset being priced: `CCD`, the token id of a protocol level token,
+    /// the token address of a CIS-2 token or the ISO 4217 code of a fiat
+    /// currency.
+    pub asset:    String,
+    /// ISO 4217 code of the currency of the price.
+    pub currency: String,
+    pub price:    f64,
+}
+
+/// A source of prices.
+pub trait PriceSource: Send + Sync {
+    /// Name of the source, recorded with the prices it provides.
+    fn name(&self) -> String;
+
+    /// Fetch the current prices provided by the source.
+    fn fetch_prices(&self) -> BoxFuture<'_, anyhow::Result<Vec<PriceQuote>>>;
+}
+
+/// A price source as configured using `--price-source`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PriceSourceConfig {
+    /// JSON document fetched from an HTTP(S) URL.
+    Http(reqwest::Url),
+    /// JSON document read from a file, provided as `file:PATH`.
+    File(PathBuf),
+    /// The CCD to EUR exchange rate on chain, provided as `chain`.
+    Chain,
+}
+
+impl FromStr for PriceSourceConfig {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if value == "chain" {
+            Ok(Self::Chain)
+        } else if let Some(path) = value.strip_prefix("file:") {
+            Ok(Self::File(PathBuf::from(path)))
+        } else if value.starts_with("http://") || value.starts_with("https://") {
+            Ok(Self::Http(reqwest::Url::parse(value)?))
+        } else {
+            anyhow::bail!(
+                "Expected price source as an HTTP(S) URL, `file:PATH` or `chain`, got '{}'",
+                value
+            )
+        }
+    }
+}
+
+impl PriceSourceConfig {
+    /// Construct the price source, where the on-chain source reads the
+    /// exchange rate from the database.
+    fn into_source(self, db_connect_options: &PgConnectOptions) -> Box<dyn PriceSource> {
+        match self {
+            Self::Http(url) => Box::new(HttpJsonPriceSource::new(url)),
+            Self::File(path) => Box::new(FilePriceSource {
+                path,
+            }),
+            Self::Chain => Box::new(ChainPriceSource {
+                pool: PgPool::connect_lazy_with(db_connect_options.clone()),
+            }),
+        }
+    }
+}
+
+/// Parse the JSON document of the HTTP and file sources, which maps each
+/// asset to its price per currency, such as `{"CCD": {"USD": 0.02}}`.
+fn parse_price_document(document: &[u8]) -> anyhow::Result<Vec<PriceQuote>> {
+    let document: HashMap<String, HashMap<String, f64>> =
+        serde_json::from_slice(document).context("Invalid price document")?;
+    let mut quotes: Vec<PriceQuote> = document
+        .into_iter()
+        .flat_map(|(asset, prices)| {
+            prices.into_iter().map(move |(currency, price)| PriceQuote {
+                asset: asset.clone(),
+                currency: currency.to_ascii_uppercase(),
+                price,
+            })
+        })
+        .filter(|quote| quote.price.is_finite() && quote.price >= 0.0)
+        .collect();
+    quotes.sort_by(|a, b| (&a.asset, &a.currency).cmp(&(&b.asset, &b.currency)));
+    Ok(quotes)
+}
+
+/// Price source fetching a JSON document of prices from an HTTP(S) URL.
+pub struct HttpJsonPriceSource {
+    url:    reqwest::Url,
+    client: reqwest::Client,
+}
+
+impl HttpJsonPriceSource {
+    pub fn new(url: reqwest::Url) -> Self {
+        Self {
+            url,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+impl PriceSource for HttpJsonPriceSource {
+    fn name(&self) -> String { self.url.to_string() }
+
+    fn fetch_prices(&self) -> BoxFuture<'_, anyhow::Result<Vec<PriceQuote>>> {
+        async move {
+            let response = self
+                .client
+                .get(self.url.clone())
+                .timeout(REQUEST_TIMEOUT)
+                .send()
+                .await?
+                .error_for_status()?;
+            parse_price_document(&response.bytes().await?)
+        }
+        .boxed()
+    }
+}
+
+/// Price source reading a JSON document of prices from a file, read again every
+/// time prices are recorded.
+pub struct FilePriceSource {
+    path: PathBuf,
+}
+
+impl PriceSource for FilePriceSource {
+    fn name(&self) -> String { format!("file:{}", self.path.display()) }
+
+    fn fetch_prices(&self) -> BoxFuture<'_, anyhow::Result<Vec<PriceQuote>>> {
+        async move {
+            let document = tokio::fs::read(&self.path)
+                .await
+                .with_context(|| format!("Failed reading {}", self.path.display()))?;
+            parse_price_document(&document)
+        }
+        .boxed()
+    }
+}
+
+/// Price source providing the price of CCD in EUR from the `MicroCcdPerEuro`
+/// exchange rate in force at the latest indexed block.
+pub struct ChainPriceSource {
+    pool: PgPool,
+}
+
+impl PriceSource for ChainPriceSource {
+    fn name(&self) -> String { "chain".to_string() }
+
+    fn fetch_prices(&self) -> BoxFuture<'_, anyhow::Result<Vec<PriceQuote>>> {
+        async move {
+            let rate = sqlx::query!(
+                r#"SELECT
+                    micro_ccd_per_euro_numerator::FLOAT8 AS "micro_ccd!",
+                    micro_ccd_per_euro_denominator::FLOAT8 AS "euro!"
+                FROM blocks
+                WHERE micro_ccd_per_euro_numerator IS NOT NULL
+                ORDER BY height DESC
+                LIMIT 1"#
+            )
+            .fetch_optional(&self.pool)
+            .await?;
+            let quotes = rate
+                .filter(|rate| rate.micro_ccd > 0.0)
+                .map(|rate| PriceQuote {
+                    asset:    "CCD".to_string(),
+                    currency: "EUR".to_string(),
+                    price:    1_000_000.0 * rate.euro / rate.micro_ccd,
+                })
+                .into_iter()
+                .collect();
+            Ok(quotes)
+        }
+        .boxed()
+    }
+}
+
+/// Combine the quotes of every source, keeping the quote of the first source
+/// for each asset and currency. Returns the quotes with the name of the source
+/// providing them.
+fn merge_quotes(quotes_per_source: Vec<(String, Vec<PriceQuote>)>) -> Vec<(String, PriceQuote)> {
+    let mut merged: Vec<(String, PriceQuote)> = Vec::new();
+    for (source, quotes) in quotes_per_source {
+        for quote in quotes {
+            let quoted = merged.iter().any(|(_, merged_quote)| {
+                merged_quote.asset == quote.asset && merged_quote.currency == quote.currency
+            });
+            if !quoted {
+                merged.push((source.clone(), quote));
+            }
+        }
+    }
+    merged
+}
+
+/// Service recording the prices from the configured price sources.
+pub struct PriceRecorder {
+    db_connect_options: PgConnectOptions,
+    sources:            Vec<Box<dyn PriceSource>>,
+    interval:           Duration,
+    /// Metric counting the prices recorded.
+    recorded:           Counter,
+    /// Metric counting the failures to fetch prices from a source.
+    failed:             Counter,
+}
+
+impl PriceRecorder {
+    pub fn new(
+        db_connect_options: PgConnectOptions,
+        sources: Vec<PriceSourceConfig>,
+        interval: Duration,
+        registry: &mut Registry,
+    ) -> Self {
+        let recorded = Counter::default();
+        registry.register("recorded", "Total number of prices recorded", recorded.clone());
+        let failed = Counter::default();
+        registry.register(
+            "failed",
+            "Total number of failures to fetch the prices of a price source",
+            failed.clone(),
+        );
+        let sources =
+            sources.into_iter().map(|source| source.into_source(&db_connect_options)).collect();
+        Self {
+            db_connect_options,
+            sources,
+            interval,
+            recorded,
+            failed,
+        }
+    }
+
+    /// Run the recorder until signaled by the `stop_signal`. Failures are
+    /// logged and retried, as the prices are not required for indexing blocks.
+    pub async fn run(self, stop_signal: CancellationToken) -> anyhow::Result<()> {
+        info!("Recording prices from {} price sources", self.sources.len());
+        let mut db_connection = None;
+        loop {
+            let delay = match self.record_next(&mut db_connection).await {
+                Ok(()) => self.interval,
+                Err(err) => {
+                    error!("Failed recording prices: {:?}", err);
+                    db_connection = None;
+                    RETRY_DELAY
+                }
+            };
+            tokio::select! {
+                _ = stop_signal.cancelled() => return Ok(()),
+                _ = tokio::time::sleep(delay) => {}
+            }
+        }
+    }
+
+    /// Record the prices, connecting to the database if not connected
+    /// already.
+    async fn record_next(&self, db_connection: &mut Option<PgConnection>) -> anyhow::Result<()> {
+        let db_connection = match db_connection {
+            Some(db_connection) => db_connection,
+            None => {
+                db_connection.insert(PgConnection::connect_with(&self.db_connect_options).await?)
+            }
+        };
+        self.record_prices(db_connection).await
+    }
+
+    /// Fetch the prices from every source and store them in the price
+    /// history. A failing source is skipped, such that the other sources are
+    /// still recorded.
+    async fn record_prices(&self, db_connection: &mut PgConnection) -> anyhow::Result<()> {
+        let recorded_at = Utc::now();
+        let mut quotes_per_source = Vec::new();
+        for source in self.sources.iter() {
+            match source.fetch_prices().await {
+                Ok(quotes) => quotes_per_source.push((source.name(), quotes)),
+                Err(err) => {
+                    self.failed.inc();
+                    warn!("Failed fetching prices from {}: {:?}", source.name(), err);
+                }
+            }
+        }
+        let quotes = merge_quotes(quotes_per_source);
+        let mut assets = Vec::with_capacity(quotes.len());
+        let mut currencies = Vec::with_capacity(quotes.len());
+        let mut prices = Vec::with_capacity(quotes.len());
+        let mut sources = Vec::with_capacity(quotes.len());
+        for (source, quote) in quotes {
+            assets.push(quote.asset);
+            currencies.push(quote.currency);
+            prices.push(quote.price);
+            sources.push(source);
+        }
+        let recorded = sqlx::query!(
+            "INSERT INTO price_history (asset, currency, recorded_at, price, source)
+            SELECT asset, currency, $1, price, source
+            FROM UNNEST($2::TEXT[], $3::TEXT[], $4::FLOAT8[], $5::TEXT[])
+                AS quotes(asset, currency, price, source)
+            ON CONFLICT DO NOTHING",
+            recorded_at,
+            &assets,
+            &currencies,
+            &prices,
+            &sources
+        )
+        .execute(db_connection.as_mut())
+        .await?
+        .rows_affected();
+        self.recorded.inc_by(recorded);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(asset: &str, currency: &str, price: f64) -> PriceQuote {
+        PriceQuote {
+            asset: asset.to_string(),
+            currency: currency.to_string(),
+            price,
+        }
+    }
+
+    #[test]
+    fn test_parse_price_source_config() {
+        assert_eq!(PriceSourceConfig::from_str("chain").unwrap(), PriceSourceConfig::Chain);
+        assert_eq!(
+            PriceSourceConfig::from_str("file:/etc/prices.json").unwrap(),
+            PriceSourceConfig::File(PathBuf::from("/etc/prices.json"))
+        );
+        assert!(matches!(
+            PriceSourceConfig::from_str("https://prices.example.com/latest").unwrap(),
+            PriceSourceConfig::Http(_)
+        ));
+        assert!(PriceSourceConfig::from_str("ftp://prices.example.com").is_err());
+    }
+
+    #[test]
+    fn test_merge_quotes() {
+        let merged = merge_quotes(vec![
+            ("first".to_string(), vec![quote("CCD", "USD", 0.02)]),
+            ("second".to_string(), vec![quote("CCD", "USD", 0.03), quote("CCD", "EUR", 0.018)]),
+        ]);
+        assert_eq!(merged, vec![
+            ("first".to_string(), quote("CCD", "USD", 0.02)),
+            ("second".to_string(), quote("CCD", "EUR", 0.018)),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_http_json_price_source() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/prices")
+            .with_status(200)
+            .with_body(r#"{"CCD": {"usd": 0.02, "EUR": 0.018}, "EURR": {"USD": 1.08}}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let url = reqwest::Url::parse(&format!("{}/prices", server.url())).unwrap();
+        let quotes = HttpJsonPriceSource::new(url).fetch_prices().await.unwrap();
+        assert_eq!(quotes, vec![
+            quote("CCD", "EUR", 0.018),
+            quote("CCD", "USD", 0.02),
+            quote("EURR", "USD", 1.08),
+        ]);
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn test_http_json_price_source_invalid_document() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/prices")
+            .with_status(200)
+            .with_body(r#"{"CCD": 0.02}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let url = reqwest::Url::parse(&format!("{}/prices", server.url())).unwrap();
+        assert!(HttpJsonPriceSource::new(url).fetch_prices().await.is_err());
+        mock.assert();
+    }
+}
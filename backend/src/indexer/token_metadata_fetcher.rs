@@ -0,0 +1,331 @@
+//! Fetching of the metadata JSON of CIS-2 tokens from their metadata URL,
+//! storing the metadata in the `tokens` table, such that the tokens of a
+//! collection can be filtered by the attributes in the metadata.
+//!
+//! The fetcher runs next to the processing of blocks, following the tokens
+//! with a metadata URL different from the URL the metadata was last fetched
+//! from. This includes the tokens indexed before enabling the fetcher and the
+//! tokens getting a new metadata URL.
+//!
+//! Metadata URLs are provided by anyone deploying a contract, so only hosts
+//! resolving to publicly routable addresses are fetched from, including the
+//! hosts redirected to. Failed fetches are retried with an increasing delay.
+
+use anyhow::Context;
+use futures::future::join_all;
+use prometheus_client::{metrics::counter::Counter, registry::Registry};
+use sha2::{Digest, Sha256};
+use sqlx::{postgres::PgConnectOptions, Connection, PgConnection};
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info};
+
+/// The number of tokens fetched concurrently and updated in each database
+/// transaction.
+const BATCH_SIZE: i64 = 20;
+/// Time to wait before checking for new tokens, when the metadata of every
+/// token is fetched.
+const IDLE_DELAY: Duration = Duration::from_secs(30);
+/// Time to wait before retrying after failing to update a batch.
+const RETRY_DELAY: Duration = Duration::from_secs(10);
+/// The most time spent fetching the metadata of a token.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// The largest metadata document accepted.
+const MAX_METADATA_BYTES: usize = 1024 * 1024;
+/// The most redirects followed when fetching the metadata of a token.
+const MAX_REDIRECTS: usize = 5;
+
+/// Service fetching the metadata of CIS-2 tokens.
+pub struct TokenMetadataFetcher {
+    db_connect_options: PgConnectOptions,
+    client:             reqwest::Client,
+    /// Metric counting the tokens with metadata fetched.
+    fetched:            Counter,
+    /// Metric counting the tokens failing to have their metadata fetched.
+    failed:             Counter,
+}
+
+impl TokenMetadataFetcher {
+    pub fn new(
+        db_connect_options: PgConnectOptions,
+        registry: &mut Registry,
+    ) -> anyhow::Result<Self> {
+        let fetched = Counter::default();
+        registry.register(
+            "fetched",
+            "Total number of CIS-2 tokens with metadata fetched",
+            fetched.clone(),
+        );
+        let failed = Counter::default();
+        registry.register(
+            "failed",
+            "Total number of CIS-2 tokens failing to have their metadata fetched",
+            failed.clone(),
+        );
+        let redirect_policy = reqwest::redirect::Policy::custom(|attempt| {
+            if attempt.previous().len() >= MAX_REDIRECTS {
+                attempt.error("Too many redirects")
+            } else if let Err(err) = check_url(attempt.url()) {
+                attempt.error(err)
+            } else {
+                attempt.follow()
+            }
+        });
+        let client = reqwest::Client::builder()
+            .dns_resolver(Arc::new(PublicResolver))
+            .redirect(redirect_policy)
+            .build()
+            .context("Failed building the HTTP client")?;
+        Ok(Self {
+            db_connect_options,
+            client,
+            fetched,
+            failed,
+        })
+    }
+
+    /// Run the fetcher until signaled by the `stop_signal`. Failures are
+    /// logged and retried, as the metadata is not required for indexing
+    /// blocks.
+    pub async fn run(self, stop_signal: CancellationToken) -> anyhow::Result<()> {
+        info!("Fetching CIS-2 token metadata");
+        let mut db_connection = None;
+        loop {
+            let delay = match self.fetch_next(&mut db_connection).await {
+                Ok(processed) if processed == BATCH_SIZE => Duration::ZERO,
+                Ok(_) => IDLE_DELAY,
+                Err(err) => {
+                    error!("Failed fetching CIS-2 token metadata: {:?}", err);
+                    db_connection = None;
+                    RETRY_DELAY
+                }
+            };
+            tokio::select! {
+                _ = stop_signal.cancelled() => return Ok(()),
+                _ = tokio::time::sleep(delay) => {}
+            }
+        }
+    }
+
+    /// Fetch the metadata of the next batch of tokens, connecting to the
+    /// database if not connected already.
+    async fn fetch_next(&self, db_connection: &mut Option<PgConnection>) -> anyhow::Result<i64> {
+        let db_connection = match db_connection {
+            Some(db_connection) => db_connection,
+            None => {
+                db_connection.insert(PgConnection::connect_with(&self.db_connect_options).await?)
+            }
+        };
+        self.fetch_batch(db_connection).await
+    }
+
+    /// Fetch the metadata of the next batch of tokens, returning the number of
+    /// tokens processed.
+    async fn fetch_batch(&self, db_connection: &mut PgConnection) -> anyhow::Result<i64> {
+        let rows = sqlx::query!(
+            r#"SELECT index, metadata_url as "metadata_url!", metadata_hash
+            FROM tokens
+            WHERE metadata_url IS DISTINCT FROM metadata_fetched_url
+                AND metadata_url IS NOT NULL
+                AND (metadata_retry_after IS NULL OR metadata_retry_after <= NOW())
+            ORDER BY index
+            LIMIT $1"#,
+            BATCH_SIZE
+        )
+        .fetch_all(db_connection.as_mut())
+        .await?;
+        let processed = i64::try_from(rows.len())?;
+
+        // The database is not kept in a transaction while fetching.
+        let results = join_all(
+            rows.iter()
+                .map(|row| self.fetch_metadata(&row.metadata_url, row.metadata_hash.as_deref())),
+        )
+        .await;
+        let mut indices = Vec::new();
+        let mut urls = Vec::new();
+        let mut metadata = Vec::new();
+        for (row, result) in rows.into_iter().zip(results) {
+            match result {
+                Ok(value) => {
+                    self.fetched.inc();
+                    metadata.push(Some(value));
+                }
+                Err(err) => {
+                    self.failed.inc();
+                    debug!("Failed fetching metadata of token {}: {:?}", row.index, err);
+                    metadata.push(None);
+                }
+            }
+            indices.push(row.index);
+            urls.push(row.metadata_url);
+        }
+        // Tokens getting a new metadata URL while fetching are left for the next batch.
+        // Tokens failing to have their metadata fetched are retried after a delay,
+        // doubling from a minute up to a day with every failure.
+        sqlx::query!(
+            "UPDATE tokens
+            SET metadata = fetched.metadata,
+                metadata_fetched_url = CASE
+                    WHEN fetched.metadata IS NULL THEN tokens.metadata_fetched_url
+                    ELSE fetched.url
+                END,
+                metadata_fetch_failures = CASE
+                    WHEN fetched.metadata IS NULL THEN tokens.metadata_fetch_failures + 1
+                    ELSE 0
+                END,
+                metadata_retry_after = CASE
+                    WHEN fetched.metadata IS NULL THEN NOW()
+                        + LEAST(2 ^ tokens.metadata_fetch_failures, 1440) * INTERVAL '1 minute'
+                END
+            FROM UNNEST($1::BIGINT[], $2::TEXT[], $3::JSONB[]) AS fetched(index, url, metadata)
+            WHERE tokens.index = fetched.index
+                AND tokens.metadata_url = fetched.url",
+            &indices,
+            &urls,
+            &metadata as &[Option<serde_json::Value>]
+        )
+        .execute(db_connection.as_mut())
+        .await?;
+        Ok(processed)
+    }
+
+    /// Fetch the metadata document at the URL, checking the document against
+    /// the SHA-256 checksum in hex when the token has one.
+    async fn fetch_metadata(
+        &self,
+        url: &str,
+        checksum: Option<&str>,
+    ) -> anyhow::Result<serde_json::Value> {
+        let url = reqwest::Url::parse(url).context("Invalid metadata URL")?;
+        check_url(&url)?;
+        let mut response =
+            self.client.get(url).timeout(REQUEST_TIMEOUT).send().await?.error_for_status()?;
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            body.extend_from_slice(&chunk);
+            anyhow::ensure!(body.len() <= MAX_METADATA_BYTES, "Metadata document too large");
+        }
+        if let Some(checksum) = checksum {
+            anyhow::ensure!(
+                hex::encode(Sha256::digest(&body)).eq_ignore_ascii_case(checksum),
+                "Metadata does not match the checksum {}",
+                checksum
+            );
+        }
+        let metadata: serde_json::Value = serde_json::from_slice(&body)?;
+        anyhow::ensure!(metadata.is_object(), "Metadata is not a JSON object");
+        Ok(metadata)
+    }
+}
+
+/// Check that the metadata URL is an HTTP(S) URL not addressing a host by an
+/// address which is not publicly routable. Hosts given by name are checked
+/// when resolved by the [`PublicResolver`].
+fn check_url(url: &reqwest::Url) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        matches!(url.scheme(), "http" | "https"),
+        "Unsupported metadata URL scheme: {}",
+        url.scheme()
+    );
+    let address = match url.host() {
+        Some(url::Host::Domain(_)) => return Ok(()),
+        Some(url::Host::Ipv4(address)) => IpAddr::V4(address),
+        Some(url::Host::Ipv6(address)) => IpAddr::V6(address),
+        None => anyhow::bail!("Metadata URL without a host"),
+    };
+    anyhow::ensure!(is_public(address), "Metadata URL with the non-public address {}", address);
+    Ok(())
+}
+
+/// Resolver of the hosts of metadata URLs, failing for hosts resolving to any
+/// address which is not publicly routable, such as the loopback, private and
+/// link-local addresses including the cloud metadata endpoint
+/// `169.254.169.254`.
+struct PublicResolver;
+
+impl reqwest::dns::Resolve for PublicResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(async move {
+            let addresses: Vec<SocketAddr> =
+                tokio::net::lookup_host((name.as_str(), 0)).await?.collect();
+            if let Some(address) = addresses.iter().find(|address| !is_public(address.ip())) {
+                return Err(format!(
+                    "Host {} resolves to the non-public address {}",
+                    name.as_str(),
+                    address.ip()
+                )
+                .into());
+            }
+            let addresses: reqwest::dns::Addrs = Box::new(addresses.into_iter());
+            Ok(addresses)
+        })
+    }
+}
+
+/// Whether the address is publicly routable.
+fn is_public(address: IpAddr) -> bool {
+    match address {
+        IpAddr::V4(address) => is_public_v4(address),
+        IpAddr::V6(address) => match address.to_ipv4_mapped() {
+            Some(address) => is_public_v4(address),
+            None => is_public_v6(address),
+        },
+    }
+}
+
+fn is_public_v4(address: Ipv4Addr) -> bool {
+    let [first, second, ..] = address.octets();
+    !(address.is_unspecified()
+        || address.is_loopback()
+        || address.is_private()
+        || address.is_link_local()
+        || address.is_broadcast()
+        || address.is_documentation()
+        || address.is_multicast()
+        // The "this network" block 0.0.0.0/8.
+        || first == 0
+        // The shared address space 100.64.0.0/10 used by carrier-grade NAT.
+        || (first == 100 && second & 0xc0 == 64)
+        // The reserved blocks 240.0.0.0/4.
+        || first >= 240)
+}
+
+fn is_public_v6(address: Ipv6Addr) -> bool {
+    let first = address.segments()[0];
+    !(address.is_unspecified()
+        || address.is_loopback()
+        || address.is_multicast()
+        // Unique local addresses fc00::/7.
+        || first & 0xfe00 == 0xfc00
+        // Link-local addresses fe80::/10.
+        || first & 0xffc0 == 0xfe80)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_url() {
+        for url in [
+            "http://169.254.169.254/latest/meta-data/",
+            "http://127.0.0.1/metadata.json",
+            "http://10.0.0.1/metadata.json",
+            "http://192.168.1.1/metadata.json",
+            "http://[::1]/metadata.json",
+            "http://[::ffff:127.0.0.1]/metadata.json",
+            "http://[fd00::1]/metadata.json",
+            "ftp://example.com/metadata.json",
+        ] {
+            assert!(check_url(&url.parse().unwrap()).is_err(), "{}", url);
+        }
+        for url in ["https://example.com/metadata.json", "http://1.1.1.1/metadata.json"] {
+            assert!(check_url(&url.parse().unwrap()).is_ok(), "{}", url);
+        }
+    }
+}
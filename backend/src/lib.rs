@@ -1,3 +1,7 @@
+// The futures resolving the merged GraphQL query root are nested deeper than
+// the default limit allows for when checking that they are `Send`.
+#![recursion_limit = "256"]
+
 pub mod graphql_api;
 pub mod indexer;
 pub mod migrations;
@@ -9,6 +13,7 @@ mod address;
 mod block_special_event;
 mod connection;
 mod decoded_text;
+mod module_verification;
 mod scalar_types;
 mod transaction_event;
 mod transaction_reject;
@@ -18,6 +18,21 @@ mod m0025_fix_passive_delegator_stake;
 mod m0026_update_genesis_validator_info;
 mod m0027_reindex_credential_deployments;
 mod m0037_update_transaction_type_add_tokenupdate;
+mod m0041_chain_update_queue;
+mod m0042_governance_keys;
+mod m0043_identity_registry;
+mod m0044_credential_details;
+mod m0045_contract_call_tree;
+mod m0046_contract_standards;
+mod m0047_credential_registries;
+mod m0048_smart_contract_wallets;
+mod m0049_sponsored_actions;
+mod m0050_contract_upgrades;
+mod m0054_plt_account_statements;
+mod m0055_plt_token_lists;
+mod m0057_cis2_token_balance_changes;
+mod m0060_euro_costs;
+mod m0062_account_cooldowns;
 
 /// Ensure the current database schema version is compatible with the supported
 /// schema version.
@@ -266,15 +281,58 @@ pub enum SchemaVersion {
     CreatePltTokenAndEventTables,
     #[display("0040: Alter PLT events add event_timestamp and index")]
     AlterPltEventsAddEventTimestampAndIndex,
+    #[display("0041: Track enqueued chain updates and their state")]
+    ChainUpdateQueue,
+    #[display("0042: Track governance key sets and their access structures")]
+    GovernanceKeys,
+    #[display("0043: Identity providers, anonymity revokers and account credentials")]
+    IdentityRegistry,
+    #[display("0044: Account credential details and signature thresholds")]
+    CredentialDetails,
+    #[display("0045: Contract call trees and edges between contracts")]
+    ContractCallTree,
+    #[display("0046: Standards supported by each contract")]
+    ContractStandards,
+    #[display("0047: CIS-4 credential registries")]
+    CredentialRegistries,
+    #[display("0048: CIS-5 smart contract wallets")]
+    SmartContractWallets,
+    #[display("0049: CIS-3 sponsored actions")]
+    SponsoredActions,
+    #[display("0050: Contract upgrade history")]
+    ContractUpgrades,
+    #[display("0051: Uploaded module schemas")]
+    UploadedModuleSchemas,
+    #[display("0052: Module source verification")]
+    ModuleSourceVerification,
+    #[display("0053: Decoded contract events")]
+    DecodedContractEvents,
+    #[display("0054: PLT account statements")]
+    PltAccountStatements,
+    #[display("0055: PLT allow and deny lists")]
+    PltTokenLists,
+    #[display("0056: PLT token supply and holder metrics")]
+    PltTokenMetrics,
+    #[display("0057: CIS-2 token balance history")]
+    Cis2TokenBalanceChanges,
+    #[display("0058: CIS-2 token metadata")]
+    TokenMetadata,
+    #[display("0059: Price history")]
+    PriceHistory,
+    #[display("0060: EUR transaction costs")]
+    EuroCosts,
+    #[display("0061: Module of rejected contract updates")]
+    RejectedReceiveModules,
+    #[display("0062: Stake cooldowns of accounts")]
+    AccountCooldowns,
 }
 impl SchemaVersion {
     /// The minimum supported database schema version for the API.
     /// Fails at startup if any breaking (destructive) database schema versions
     /// have been introduced since this version.
-    pub const API_SUPPORTED_SCHEMA_VERSION: SchemaVersion =
-        SchemaVersion::CreatePltTokenAndEventTables;
+    pub const API_SUPPORTED_SCHEMA_VERSION: SchemaVersion = SchemaVersion::AccountCooldowns;
     /// The latest known version of the schema.
-    const LATEST: SchemaVersion = SchemaVersion::AlterPltEventsAddEventTimestampAndIndex;
+    const LATEST: SchemaVersion = SchemaVersion::AccountCooldowns;
 
     /// Parse version number into a database schema version.
     /// None if the version is unknown.
@@ -334,6 +392,28 @@ impl SchemaVersion {
             SchemaVersion::BakerApyQueryUpdateProtectAgainstOverflow => false,
             SchemaVersion::CreatePltTokenAndEventTables => false,
             SchemaVersion::AlterPltEventsAddEventTimestampAndIndex => false,
+            SchemaVersion::ChainUpdateQueue => false,
+            SchemaVersion::GovernanceKeys => false,
+            SchemaVersion::IdentityRegistry => false,
+            SchemaVersion::CredentialDetails => false,
+            SchemaVersion::ContractCallTree => false,
+            SchemaVersion::ContractStandards => false,
+            SchemaVersion::CredentialRegistries => false,
+            SchemaVersion::SmartContractWallets => false,
+            SchemaVersion::SponsoredActions => false,
+            SchemaVersion::ContractUpgrades => false,
+            SchemaVersion::UploadedModuleSchemas => false,
+            SchemaVersion::ModuleSourceVerification => false,
+            SchemaVersion::DecodedContractEvents => false,
+            SchemaVersion::PltAccountStatements => false,
+            SchemaVersion::PltTokenLists => false,
+            SchemaVersion::PltTokenMetrics => false,
+            SchemaVersion::Cis2TokenBalanceChanges => false,
+            SchemaVersion::TokenMetadata => false,
+            SchemaVersion::PriceHistory => false,
+            SchemaVersion::EuroCosts => false,
+            SchemaVersion::RejectedReceiveModules => false,
+            SchemaVersion::AccountCooldowns => false,
         }
     }
 
@@ -384,6 +464,28 @@ impl SchemaVersion {
             SchemaVersion::BakerApyQueryUpdateProtectAgainstOverflow => false,
             SchemaVersion::CreatePltTokenAndEventTables => false,
             SchemaVersion::AlterPltEventsAddEventTimestampAndIndex => false,
+            SchemaVersion::ChainUpdateQueue => false,
+            SchemaVersion::GovernanceKeys => false,
+            SchemaVersion::IdentityRegistry => false,
+            SchemaVersion::CredentialDetails => false,
+            SchemaVersion::ContractCallTree => false,
+            SchemaVersion::ContractStandards => false,
+            SchemaVersion::CredentialRegistries => false,
+            SchemaVersion::SmartContractWallets => false,
+            SchemaVersion::SponsoredActions => false,
+            SchemaVersion::ContractUpgrades => false,
+            SchemaVersion::UploadedModuleSchemas => false,
+            SchemaVersion::ModuleSourceVerification => false,
+            SchemaVersion::DecodedContractEvents => false,
+            SchemaVersion::PltAccountStatements => false,
+            SchemaVersion::PltTokenLists => false,
+            SchemaVersion::PltTokenMetrics => false,
+            SchemaVersion::Cis2TokenBalanceChanges => false,
+            SchemaVersion::TokenMetadata => false,
+            SchemaVersion::PriceHistory => false,
+            SchemaVersion::EuroCosts => false,
+            SchemaVersion::RejectedReceiveModules => false,
+            SchemaVersion::AccountCooldowns => false,
         }
     }
 
@@ -645,7 +747,121 @@ impl SchemaVersion {
                 SchemaVersion::AlterPltEventsAddEventTimestampAndIndex
             }
 
-            SchemaVersion::AlterPltEventsAddEventTimestampAndIndex => unimplemented!(
+            SchemaVersion::AlterPltEventsAddEventTimestampAndIndex => {
+                m0041_chain_update_queue::run(&mut tx, endpoints, SchemaVersion::ChainUpdateQueue)
+                    .await?
+            }
+            SchemaVersion::ChainUpdateQueue => {
+                m0042_governance_keys::run(&mut tx, endpoints, SchemaVersion::GovernanceKeys)
+                    .await?
+            }
+            SchemaVersion::GovernanceKeys => {
+                m0043_identity_registry::run(&mut tx, endpoints, SchemaVersion::IdentityRegistry)
+                    .await?
+            }
+            SchemaVersion::IdentityRegistry => {
+                m0044_credential_details::run(&mut tx, endpoints, SchemaVersion::CredentialDetails)
+                    .await?
+            }
+            SchemaVersion::CredentialDetails => {
+                m0045_contract_call_tree::run(&mut tx, SchemaVersion::ContractCallTree).await?
+            }
+            SchemaVersion::ContractCallTree => {
+                m0046_contract_standards::run(&mut tx, endpoints, SchemaVersion::ContractStandards)
+                    .await?
+            }
+            SchemaVersion::ContractStandards => {
+                m0047_credential_registries::run(
+                    &mut tx,
+                    endpoints,
+                    SchemaVersion::CredentialRegistries,
+                )
+                .await?
+            }
+            SchemaVersion::CredentialRegistries => {
+                m0048_smart_contract_wallets::run(&mut tx, SchemaVersion::SmartContractWallets)
+                    .await?
+            }
+            SchemaVersion::SmartContractWallets => {
+                m0049_sponsored_actions::run(&mut tx, SchemaVersion::SponsoredActions).await?
+            }
+            SchemaVersion::SponsoredActions => {
+                m0050_contract_upgrades::run(&mut tx, SchemaVersion::ContractUpgrades).await?
+            }
+            SchemaVersion::ContractUpgrades => {
+                tx.as_mut()
+                    .execute(sqlx::raw_sql(include_str!(
+                        "./migrations/m0051_uploaded_module_schemas.sql"
+                    )))
+                    .await?;
+                SchemaVersion::UploadedModuleSchemas
+            }
+            SchemaVersion::UploadedModuleSchemas => {
+                tx.as_mut()
+                    .execute(sqlx::raw_sql(include_str!(
+                        "./migrations/m0052_module_source_verification.sql"
+                    )))
+                    .await?;
+                SchemaVersion::ModuleSourceVerification
+            }
+            SchemaVersion::ModuleSourceVerification => {
+                tx.as_mut()
+                    .execute(sqlx::raw_sql(include_str!(
+                        "./migrations/m0053_decoded_contract_events.sql"
+                    )))
+                    .await?;
+                SchemaVersion::DecodedContractEvents
+            }
+            SchemaVersion::DecodedContractEvents => {
+                m0054_plt_account_statements::run(&mut tx, SchemaVersion::PltAccountStatements)
+                    .await?
+            }
+            SchemaVersion::PltAccountStatements => {
+                m0055_plt_token_lists::run(&mut tx, SchemaVersion::PltTokenLists).await?
+            }
+            SchemaVersion::PltTokenLists => {
+                tx.as_mut()
+                    .execute(sqlx::raw_sql(include_str!(
+                        "./migrations/m0056_plt_token_metrics.sql"
+                    )))
+                    .await?;
+                SchemaVersion::PltTokenMetrics
+            }
+            SchemaVersion::PltTokenMetrics => {
+                m0057_cis2_token_balance_changes::run(
+                    &mut tx,
+                    SchemaVersion::Cis2TokenBalanceChanges,
+                )
+                .await?
+            }
+            SchemaVersion::Cis2TokenBalanceChanges => {
+                tx.as_mut()
+                    .execute(sqlx::raw_sql(include_str!("./migrations/m0058_token_metadata.sql")))
+                    .await?;
+                SchemaVersion::TokenMetadata
+            }
+            SchemaVersion::TokenMetadata => {
+                tx.as_mut()
+                    .execute(sqlx::raw_sql(include_str!("./migrations/m0059_price_history.sql")))
+                    .await?;
+                SchemaVersion::PriceHistory
+            }
+            SchemaVersion::PriceHistory => {
+                m0060_euro_costs::run(&mut tx, endpoints, SchemaVersion::EuroCosts).await?
+            }
+            SchemaVersion::EuroCosts => {
+                tx.as_mut()
+                    .execute(sqlx::raw_sql(include_str!(
+                        "./migrations/m0061_rejected_receive_modules.sql"
+                    )))
+                    .await?;
+                SchemaVersion::RejectedReceiveModules
+            }
+            SchemaVersion::RejectedReceiveModules => {
+                m0062_account_cooldowns::run(&mut tx, endpoints, SchemaVersion::AccountCooldowns)
+                    .await?
+            }
+            SchemaVersion::AccountCooldowns => unimplemented!(
                 "No migration implemented for database schema version {}",
                 self.as_i64()
             ),
@@ -0,0 +1,86 @@
+//! Migration introducing the `chain_update_queue` table, tracking every chain
+//! update enqueued on chain and whether it is still waiting to take effect.
+//!
+//! The update sequence number is only part of the update instruction and not
+//! the outcome stored in the database, so the update instructions are fetched
+//! from the node to fill the table.
+
+use super::SchemaVersion;
+use crate::indexer::{mark_effective_chain_updates, QueuedChainUpdate};
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use concordium_rust_sdk::{
+    types::{AbsoluteBlockHeight, BlockItemSummaryDetails},
+    v2,
+};
+use futures::TryStreamExt;
+use sqlx::Executor;
+use tracing::info;
+
+pub async fn run(
+    tx: &mut sqlx::PgTransaction<'_>,
+    endpoints: &[v2::Endpoint],
+    next_schema_version: SchemaVersion,
+) -> anyhow::Result<SchemaVersion> {
+    tx.as_mut().execute(sqlx::raw_sql(include_str!("./m0041_chain_update_queue.sql"))).await?;
+
+    let endpoint = endpoints.first().context(format!(
+        "Migration '{}' must be provided access to a Concordium node",
+        next_schema_version
+    ))?;
+    let mut client = v2::Client::new(endpoint.clone()).await?;
+
+    let rows = sqlx::query(
+        "SELECT
+            block_height,
+            (SELECT slot_time FROM blocks WHERE height = block_height) as block_slot_time
+        FROM transactions
+        WHERE type = 'Update'
+        GROUP BY block_height
+        ORDER BY block_height ASC",
+    )
+    .fetch_all(tx.as_mut())
+    .await?;
+    info!("Indexing chain updates from {} blocks", rows.len());
+
+    for row in rows {
+        let height: i64 = sqlx::Row::try_get(&row, "block_height")?;
+        let slot_time: DateTime<Utc> = sqlx::Row::try_get(&row, "block_slot_time")?;
+        let block = AbsoluteBlockHeight {
+            height: height.try_into()?,
+        };
+        let summaries = client
+            .get_block_transaction_events(block)
+            .await?
+            .response
+            .try_collect::<Vec<_>>()
+            .await?;
+        let items = client.get_block_items(block).await?.response.try_collect::<Vec<_>>().await?;
+
+        mark_effective_chain_updates(tx, slot_time).await?;
+        for (summary, item) in summaries.into_iter().zip(items) {
+            let BlockItemSummaryDetails::Update(details) = summary.details else {
+                continue;
+            };
+            let transaction_index: i64 =
+                sqlx::query_scalar("SELECT index FROM transactions WHERE hash = $1")
+                    .bind(summary.hash.to_string())
+                    .fetch_one(tx.as_mut())
+                    .await?;
+            QueuedChainUpdate::prepare(&details, &item)?
+                .save(tx, transaction_index, slot_time)
+                .await?;
+        }
+    }
+
+    // Finally mark the updates which have taken effect since the last update was
+    // enqueued.
+    let last_slot_time: Option<DateTime<Utc>> =
+        sqlx::query_scalar("SELECT slot_time FROM blocks ORDER BY height DESC LIMIT 1")
+            .fetch_optional(tx.as_mut())
+            .await?;
+    if let Some(last_slot_time) = last_slot_time {
+        mark_effective_chain_updates(tx, last_slot_time).await?;
+    }
+    Ok(next_schema_version)
+}
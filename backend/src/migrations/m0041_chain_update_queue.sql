@@ -0,0 +1,41 @@
+-- State of a chain update enqueued by an update instruction.
+CREATE TYPE chain_update_state AS ENUM (
+    -- The update is waiting in the queue for its effective time.
+    'Queued',
+    -- The effective time has passed and the update is in force.
+    'Effective',
+    -- The update got replaced by a later update of the same type, before taking effect.
+    'Superseded'
+);
+
+-- Every chain update enqueued on chain, tracking whether it is still waiting to take effect.
+CREATE TABLE chain_update_queue (
+    -- Index of the update transaction enqueuing the update.
+    transaction_index BIGINT PRIMARY KEY REFERENCES transactions,
+    -- The type of update, each type of update has its own queue on chain.
+    update_type update_transaction_type NOT NULL,
+    -- The update sequence number of the update instruction, this is increasing for each update
+    -- of the same type.
+    sequence_number BIGINT NOT NULL,
+    -- The time at which the update takes effect.
+    -- For updates effective immediately, this is the slot time of the block containing it.
+    effective_time TIMESTAMPTZ NOT NULL,
+    -- Slot time of the block where the update got enqueued.
+    enqueued_time TIMESTAMPTZ NOT NULL,
+    -- Current state of the update.
+    state chain_update_state NOT NULL,
+    -- Index of the update transaction which superseded this update.
+    -- Only present when the state is 'Superseded'.
+    superseded_by_transaction_index BIGINT REFERENCES transactions,
+    -- The update payload as it is represented in the GraphQL API.
+    payload JSONB NOT NULL
+);
+
+-- Used when marking updates as effective for every block and when querying the upcoming updates.
+CREATE INDEX chain_update_queue_queued_effective_time_idx
+    ON chain_update_queue (effective_time)
+    WHERE state = 'Queued';
+
+-- Used when looking up the queue for a specific type of update.
+CREATE INDEX chain_update_queue_update_type_sequence_number_idx
+    ON chain_update_queue (update_type, sequence_number);
@@ -0,0 +1,54 @@
+//! Migration introducing the tables tracking the governance key sets.
+//!
+//! The key sets are only part of the chain parameters, so they are fetched
+//! from the node for the genesis block and for every block where an update of
+//! the keys or a protocol update took effect.
+
+use super::SchemaVersion;
+use crate::indexer::GovernanceKeys;
+use anyhow::Context;
+use concordium_rust_sdk::{types::AbsoluteBlockHeight, v2};
+use sqlx::Executor;
+use tracing::info;
+
+pub async fn run(
+    tx: &mut sqlx::PgTransaction<'_>,
+    endpoints: &[v2::Endpoint],
+    next_schema_version: SchemaVersion,
+) -> anyhow::Result<SchemaVersion> {
+    tx.as_mut().execute(sqlx::raw_sql(include_str!("./m0042_governance_keys.sql"))).await?;
+
+    let endpoint = endpoints.first().context(format!(
+        "Migration '{}' must be provided access to a Concordium node",
+        next_schema_version
+    ))?;
+    let mut client = v2::Client::new(endpoint.clone()).await?;
+
+    // The first block where each of the updates took effect.
+    let heights: Vec<i64> = sqlx::query_scalar(
+        "SELECT 0::BIGINT AS height
+        UNION
+        SELECT (SELECT MIN(height) FROM blocks WHERE slot_time >= effective_time)
+        FROM chain_update_queue
+        WHERE state = 'Effective'
+            AND update_type IN (
+                'UpdateRootKeys',
+                'UpdateLevel1Keys',
+                'UpdateLevel2Keys',
+                'UpdateProtocol'
+            )
+        ORDER BY height ASC",
+    )
+    .fetch_all(tx.as_mut())
+    .await?;
+    info!("Reading governance keys from {} blocks", heights.len());
+
+    for height in heights {
+        let block = AbsoluteBlockHeight {
+            height: height.try_into()?,
+        };
+        let chain_parameters = client.get_block_chain_parameters(block).await?.response;
+        GovernanceKeys::from_chain_parameters(&chain_parameters).save(tx, height).await?;
+    }
+    Ok(next_schema_version)
+}
@@ -0,0 +1,72 @@
+-- The level of a set of governance keys authorizing chain updates.
+CREATE TYPE governance_key_level AS ENUM (
+    'Root',
+    'Level1',
+    'Level2'
+);
+
+-- The kinds of chain updates which each have their own access structure among the level 2 keys.
+-- The order must match the order of the variants in `Level2Authorization`.
+CREATE TYPE level2_authorization AS ENUM (
+    'Emergency',
+    'Protocol',
+    'ElectionDifficulty',
+    'EuroPerEnergy',
+    'MicroCcdPerEuro',
+    'FoundationAccount',
+    'MintDistribution',
+    'TransactionFeeDistribution',
+    'GasRewards',
+    'PoolParameters',
+    'AddAnonymityRevoker',
+    'AddIdentityProvider',
+    'CooldownParameters',
+    'TimeParameters',
+    'CreatePlt'
+);
+
+-- Every version of the governance key sets, a new version is added whenever a key set changes.
+CREATE TABLE governance_key_sets (
+    -- Internal identifier of the key set version.
+    id BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    -- The level of the key set.
+    level governance_key_level NOT NULL,
+    -- Height of the first block where this version of the key set is in effect.
+    effective_block_height BIGINT NOT NULL REFERENCES blocks,
+    -- Height of the first block where this version of the key set got replaced.
+    -- NULL for the current version.
+    replaced_block_height BIGINT REFERENCES blocks,
+    -- Hex encoded verify keys, where the position in the array (starting from 0) is the key index.
+    keys TEXT[] NOT NULL,
+    -- Number of keys needed to authorize an update.
+    -- Only present for root and level 1 key sets, since level 2 keys use an access structure
+    -- for each kind of update.
+    threshold INT
+);
+
+-- Used for looking up the current version of each key set.
+CREATE UNIQUE INDEX governance_key_sets_current_idx
+    ON governance_key_sets (level)
+    WHERE replaced_block_height IS NULL;
+
+-- The access structures of level 2 key sets.
+CREATE TABLE governance_level2_access_structures (
+    -- The level 2 key set version.
+    key_set_id BIGINT NOT NULL REFERENCES governance_key_sets,
+    -- The kind of chain updates authorized.
+    authorization_kind level2_authorization NOT NULL,
+    -- Indices of the keys from the key set which can sign this kind of update.
+    key_indices INT[] NOT NULL,
+    -- Number of the keys needed to authorize this kind of update.
+    threshold INT NOT NULL,
+
+    PRIMARY KEY (key_set_id, authorization_kind)
+);
+
+-- Updates of the governance keys were stored with the type of the keys signing the update, rather
+-- than the key set being updated, which is found in the chain update queue.
+UPDATE transactions
+SET type_update = chain_update_queue.update_type
+FROM chain_update_queue
+WHERE chain_update_queue.transaction_index = transactions.index
+    AND transactions.type_update IS DISTINCT FROM chain_update_queue.update_type;
@@ -0,0 +1,174 @@
+//! Migration introducing the tables for identity providers, anonymity revokers
+//! and the credentials of accounts.
+//!
+//! The details of identity providers and anonymity revokers, and the
+//! credentials of accounts, are not part of the outcomes stored in the
+//! database, so these are fetched from the node.
+
+use super::SchemaVersion;
+use crate::indexer::{
+    PreparedAccountCredential, PreparedAnonymityRevoker, PreparedIdentityProvider,
+};
+use anyhow::Context;
+use concordium_rust_sdk::{
+    base::{transactions::BlockItem, updates::UpdatePayload},
+    types::{AbsoluteBlockHeight, AccountIndex},
+    v2,
+};
+use futures::TryStreamExt;
+use sqlx::Executor;
+use tracing::info;
+
+pub async fn run(
+    tx: &mut sqlx::PgTransaction<'_>,
+    endpoints: &[v2::Endpoint],
+    next_schema_version: SchemaVersion,
+) -> anyhow::Result<SchemaVersion> {
+    tx.as_mut().execute(sqlx::raw_sql(include_str!("./m0043_identity_registry.sql"))).await?;
+
+    // Get the last processed block height.
+    let last_height: Option<i64> =
+        sqlx::query_scalar("SELECT height FROM blocks ORDER BY height DESC LIMIT 1")
+            .fetch_optional(tx.as_mut())
+            .await?;
+    let Some(last_height) = last_height else {
+        // Nothing is indexed yet, the registry is filled when indexing genesis.
+        return Ok(next_schema_version);
+    };
+    let last_block = AbsoluteBlockHeight {
+        height: last_height.try_into()?,
+    };
+    let genesis_block = AbsoluteBlockHeight {
+        height: 0,
+    };
+
+    let endpoint = endpoints.first().context(format!(
+        "Migration '{}' must be provided access to a Concordium node",
+        next_schema_version
+    ))?;
+    let mut client = v2::Client::new(endpoint.clone()).await?;
+
+    let mut identity_providers = client.get_identity_providers(genesis_block).await?.response;
+    while let Some(info) = identity_providers.try_next().await? {
+        PreparedIdentityProvider::prepare(&info).save(tx, 0, None).await?;
+    }
+    let mut anonymity_revokers = client.get_anonymity_revokers(genesis_block).await?.response;
+    while let Some(info) = anonymity_revokers.try_next().await? {
+        PreparedAnonymityRevoker::prepare(&info).save(tx, 0, None).await?;
+    }
+
+    // Identity providers and anonymity revokers added by chain updates, which were
+    // not superseded before taking effect.
+    let rows = sqlx::query(
+        "SELECT
+            chain_update_queue.transaction_index,
+            transactions.block_height,
+            transactions.hash
+        FROM chain_update_queue
+            JOIN transactions ON transactions.index = chain_update_queue.transaction_index
+        WHERE chain_update_queue.update_type IN ('UpdateAddIdentityProvider', \
+         'UpdateAddAnonymityRevoker')
+            AND chain_update_queue.state != 'Superseded'
+        ORDER BY chain_update_queue.transaction_index ASC",
+    )
+    .fetch_all(tx.as_mut())
+    .await?;
+    info!("Reading {} identity provider and anonymity revoker updates", rows.len());
+    for row in rows {
+        let transaction_index: i64 = sqlx::Row::try_get(&row, "transaction_index")?;
+        let height: i64 = sqlx::Row::try_get(&row, "block_height")?;
+        let hash: String = sqlx::Row::try_get(&row, "hash")?;
+        let block = AbsoluteBlockHeight {
+            height: height.try_into()?,
+        };
+        let items = client.get_block_items(block).await?.response.try_collect::<Vec<_>>().await?;
+        let item = items
+            .into_iter()
+            .find(|item| item.hash().to_string() == hash)
+            .context(format!("Block item {} not found in block {}", hash, height))?;
+        let BlockItem::UpdateInstruction(instruction) = item else {
+            anyhow::bail!("Block item {} is not an update instruction", hash);
+        };
+        match instruction.payload {
+            UpdatePayload::AddIdentityProvider(info) => {
+                PreparedIdentityProvider::prepare(&info)
+                    .save(tx, height, Some(transaction_index))
+                    .await?
+            }
+            UpdatePayload::AddAnonymityRevoker(info) => {
+                PreparedAnonymityRevoker::prepare(&info)
+                    .save(tx, height, Some(transaction_index))
+                    .await?
+            }
+            payload => {
+                anyhow::bail!("Unexpected payload for update instruction {}: {:?}", hash, payload)
+            }
+        }
+    }
+
+    // The current credentials of every account. Only the credential creating the
+    // account can be linked to its transaction.
+    let accounts = sqlx::query("SELECT index, transaction_index FROM accounts ORDER BY index ASC")
+        .fetch_all(tx.as_mut())
+        .await?;
+    info!("Reading credentials of {} accounts", accounts.len());
+    for account in accounts {
+        let account_index: i64 = sqlx::Row::try_get(&account, "index")?;
+        let account_transaction_index: Option<i64> =
+            sqlx::Row::try_get(&account, "transaction_index")?;
+        let info = client
+            .get_account_info(
+                &v2::AccountIdentifier::Index(AccountIndex::from(u64::try_from(account_index)?)),
+                last_block,
+            )
+            .await?
+            .response;
+
+        let mut registration_ids = Vec::new();
+        let mut credential_indices = Vec::new();
+        let mut identity_provider_ids = Vec::new();
+        let mut transaction_indices = Vec::new();
+        for (credential_index, credential) in info.account_credentials.iter() {
+            let credential = PreparedAccountCredential::from_account_info(
+                credential_index.index,
+                &credential.value,
+            )?;
+            registration_ids.push(credential.credential_registration_id().to_string());
+            credential_indices.push(i16::from(credential_index.index));
+            identity_provider_ids.push(credential.identity_provider_id());
+            transaction_indices.push(
+                if credential_index.index == 0 {
+                    account_transaction_index
+                } else {
+                    None
+                },
+            );
+        }
+        sqlx::query(
+            "INSERT INTO account_credentials (
+                credential_registration_id,
+                account_index,
+                credential_index,
+                identity_provider_id,
+                transaction_index
+            )
+            SELECT
+                registration_id,
+                $1,
+                credential_index,
+                identity_provider_id,
+                transaction_index
+            FROM UNNEST($2::TEXT[], $3::SMALLINT[], $4::BIGINT[], $5::BIGINT[])
+                AS input(registration_id, credential_index, identity_provider_id, \
+             transaction_index)",
+        )
+        .bind(account_index)
+        .bind(&registration_ids)
+        .bind(&credential_indices)
+        .bind(&identity_provider_ids)
+        .bind(&transaction_indices)
+        .execute(tx.as_mut())
+        .await?;
+    }
+    Ok(next_schema_version)
+}
@@ -0,0 +1,64 @@
+-- Identity providers registered on chain, either in genesis or added by a chain update.
+CREATE TABLE identity_providers (
+    -- The identity of the identity provider.
+    id BIGINT PRIMARY KEY,
+    -- Name of the identity provider.
+    name TEXT NOT NULL,
+    -- URL of the identity provider.
+    url TEXT NOT NULL,
+    -- Free form description of the identity provider.
+    description TEXT NOT NULL,
+    -- Hex encoded public key used to verify the signatures of the identity provider on identity
+    -- objects.
+    verify_key TEXT NOT NULL,
+    -- Hex encoded Ed25519 public key used to verify initial account creations.
+    cdi_verify_key TEXT NOT NULL,
+    -- Height of the block where the identity provider got registered.
+    creation_block_height BIGINT NOT NULL REFERENCES blocks,
+    -- Index of the chain update adding the identity provider.
+    -- NULL for identity providers present in genesis.
+    transaction_index BIGINT REFERENCES transactions
+);
+
+-- Anonymity revokers registered on chain, either in genesis or added by a chain update.
+CREATE TABLE anonymity_revokers (
+    -- The identity of the anonymity revoker.
+    id BIGINT PRIMARY KEY,
+    -- Name of the anonymity revoker.
+    name TEXT NOT NULL,
+    -- URL of the anonymity revoker.
+    url TEXT NOT NULL,
+    -- Free form description of the anonymity revoker.
+    description TEXT NOT NULL,
+    -- Hex encoded public key used for encrypting data for the anonymity revoker.
+    public_key TEXT NOT NULL,
+    -- Height of the block where the anonymity revoker got registered.
+    creation_block_height BIGINT NOT NULL REFERENCES blocks,
+    -- Index of the chain update adding the anonymity revoker.
+    -- NULL for anonymity revokers present in genesis.
+    transaction_index BIGINT REFERENCES transactions
+);
+
+-- The current credentials of each account.
+CREATE TABLE account_credentials (
+    -- Hex encoded credential registration ID.
+    credential_registration_id TEXT PRIMARY KEY,
+    -- The account holding the credential.
+    account_index BIGINT NOT NULL REFERENCES accounts,
+    -- Index of the credential on the account, the credential creating the account has index 0.
+    credential_index SMALLINT NOT NULL,
+    -- The identity provider which issued the identity behind the credential.
+    identity_provider_id BIGINT NOT NULL REFERENCES identity_providers,
+    -- Index of the transaction deploying the credential.
+    -- NULL for credentials of genesis accounts and for credentials added to an account before
+    -- this table got introduced.
+    transaction_index BIGINT REFERENCES transactions
+);
+
+-- Used for looking up the credentials of an account.
+CREATE INDEX account_credentials_account_index_idx ON account_credentials (account_index);
+
+-- Used for counting the accounts created through an identity provider.
+CREATE INDEX account_credentials_identity_provider_created_idx
+    ON account_credentials (identity_provider_id, transaction_index)
+    WHERE credential_index = 0;
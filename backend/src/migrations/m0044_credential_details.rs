@@ -0,0 +1,73 @@
+//! Migration adding the details of account credentials and the signature
+//! threshold of accounts.
+//!
+//! The details are only part of the credential deployments and not the
+//! outcomes stored in the database, so the current credentials of every
+//! account are fetched from the node.
+
+use super::SchemaVersion;
+use crate::indexer::PreparedAccountCredential;
+use anyhow::Context;
+use concordium_rust_sdk::{
+    types::{AbsoluteBlockHeight, AccountIndex},
+    v2,
+};
+use sqlx::Executor;
+use tracing::info;
+
+pub async fn run(
+    tx: &mut sqlx::PgTransaction<'_>,
+    endpoints: &[v2::Endpoint],
+    next_schema_version: SchemaVersion,
+) -> anyhow::Result<SchemaVersion> {
+    tx.as_mut().execute(sqlx::raw_sql(include_str!("./m0044_pre_credential_details.sql"))).await?;
+
+    // Get the last processed block height.
+    let last_height: Option<i64> =
+        sqlx::query_scalar("SELECT height FROM blocks ORDER BY height DESC LIMIT 1")
+            .fetch_optional(tx.as_mut())
+            .await?;
+    if let Some(last_height) = last_height {
+        let last_block = AbsoluteBlockHeight {
+            height: last_height.try_into()?,
+        };
+        let endpoint = endpoints.first().context(format!(
+            "Migration '{}' must be provided access to a Concordium node",
+            next_schema_version
+        ))?;
+        let mut client = v2::Client::new(endpoint.clone()).await?;
+
+        let account_indices: Vec<i64> =
+            sqlx::query_scalar("SELECT index FROM accounts ORDER BY index ASC")
+                .fetch_all(tx.as_mut())
+                .await?;
+        info!("Reading credential details of {} accounts", account_indices.len());
+        for account_index in account_indices {
+            let info = client
+                .get_account_info(
+                    &v2::AccountIdentifier::Index(AccountIndex::from(u64::try_from(
+                        account_index,
+                    )?)),
+                    last_block,
+                )
+                .await?
+                .response;
+            sqlx::query("UPDATE accounts SET signature_threshold = $2 WHERE index = $1")
+                .bind(account_index)
+                .bind(i16::from(u8::from(info.account_threshold)))
+                .execute(tx.as_mut())
+                .await?;
+            for (credential_index, credential) in info.account_credentials.iter() {
+                PreparedAccountCredential::from_account_info(
+                    credential_index.index,
+                    &credential.value,
+                )?
+                .save_details(tx)
+                .await?;
+            }
+        }
+    }
+
+    tx.as_mut().execute(sqlx::raw_sql(include_str!("./m0044_post_credential_details.sql"))).await?;
+    Ok(next_schema_version)
+}
@@ -0,0 +1,10 @@
+ALTER TABLE account_credentials
+    ALTER COLUMN is_initial SET NOT NULL,
+    ALTER COLUMN anonymity_revoker_ids SET NOT NULL,
+    ALTER COLUMN valid_from SET NOT NULL,
+    ALTER COLUMN valid_to SET NOT NULL,
+    ALTER COLUMN key_threshold SET NOT NULL,
+    ALTER COLUMN keys SET NOT NULL;
+
+-- Used for querying the credentials expiring within a period.
+CREATE INDEX account_credentials_valid_to_idx ON account_credentials (valid_to);
@@ -0,0 +1,24 @@
+-- Details of the credentials of accounts, filled by the migration before being made non-nullable.
+ALTER TABLE account_credentials
+    -- Whether this is an initial credential, which is created by the identity provider and has no
+    -- anonymity revokers.
+    ADD COLUMN is_initial BOOLEAN,
+    -- The anonymity revokers able to reveal the identity behind the credential.
+    -- Empty for initial credentials.
+    ADD COLUMN anonymity_revoker_ids BIGINT[],
+    -- Number of anonymity revokers needed to reveal the identity behind the credential.
+    -- NULL for initial credentials.
+    ADD COLUMN anonymity_revoker_threshold SMALLINT,
+    -- First day of the month where the credential got created.
+    ADD COLUMN valid_from DATE,
+    -- First day of the last month where the credential is valid. The credential expires at the
+    -- end of this month.
+    ADD COLUMN valid_to DATE,
+    -- Number of keys needed to sign on behalf of the credential.
+    ADD COLUMN key_threshold SMALLINT,
+    -- Hex encoded Ed25519 verify keys of the credential, ordered by key index.
+    ADD COLUMN keys TEXT[];
+
+-- Number of credentials needed to sign on behalf of the account.
+-- Accounts are created with a single credential and a threshold of 1.
+ALTER TABLE accounts ADD COLUMN signature_threshold SMALLINT NOT NULL DEFAULT 1;
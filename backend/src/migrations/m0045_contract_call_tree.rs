@@ -0,0 +1,56 @@
+//! Migration introducing the tables for the tree of contract calls made by
+//! each contract update transaction, and the aggregated edges between
+//! contracts calling each other.
+//!
+//! The trees are reconstructed from the events already stored for the
+//! transactions.
+
+use super::SchemaVersion;
+use crate::{
+    indexer::{CallTraceStep, PreparedContractCallTree},
+    transaction_event::Event,
+};
+use sqlx::Executor;
+use tracing::info;
+
+/// Number of transactions to read from the database at a time.
+const BATCH_SIZE: i64 = 1000;
+
+pub async fn run(
+    tx: &mut sqlx::PgTransaction<'_>,
+    next_schema_version: SchemaVersion,
+) -> anyhow::Result<SchemaVersion> {
+    tx.as_mut().execute(sqlx::raw_sql(include_str!("./m0045_contract_call_tree.sql"))).await?;
+
+    let mut processed = 0;
+    let mut last_index = -1;
+    loop {
+        let rows = sqlx::query(
+            "SELECT index, events
+            FROM transactions
+            WHERE type_account = 'UpdateSmartContractInstance'
+                AND success
+                AND index > $1
+            ORDER BY index ASC
+            LIMIT $2",
+        )
+        .bind(last_index)
+        .bind(BATCH_SIZE)
+        .fetch_all(tx.as_mut())
+        .await?;
+        if rows.is_empty() {
+            break;
+        }
+        for row in rows.iter() {
+            let index: i64 = sqlx::Row::try_get(row, "index")?;
+            let events: sqlx::types::Json<Vec<Event>> = sqlx::Row::try_get(row, "events")?;
+            let steps =
+                events.0.iter().map(CallTraceStep::try_from).collect::<anyhow::Result<Vec<_>>>()?;
+            PreparedContractCallTree::build(steps)?.save(tx, index).await?;
+            last_index = index;
+        }
+        processed += rows.len();
+        info!("Built contract call trees for {} transactions", processed);
+    }
+    Ok(next_schema_version)
+}
@@ -0,0 +1,60 @@
+-- The contract calls made by contract update transactions, forming a tree for each transaction.
+CREATE TABLE contract_calls (
+    -- The contract update transaction.
+    transaction_index BIGINT NOT NULL REFERENCES transactions,
+    -- Index of the trace element reporting the completed call, among the events of the
+    -- transaction.
+    trace_element_index BIGINT NOT NULL,
+    -- Index of the trace element reporting the call making this call.
+    -- NULL for calls made directly by the transaction.
+    parent_trace_element_index BIGINT,
+    -- Number of calls between the transaction and this call, 0 for calls made directly by the
+    -- transaction.
+    depth SMALLINT NOT NULL,
+    -- The account making the call. Only present for calls made directly by the transaction.
+    caller_account_index BIGINT REFERENCES accounts,
+    -- The contract making the call. Only present for nested calls.
+    caller_contract_index BIGINT,
+    caller_contract_sub_index BIGINT,
+    -- The contract being called.
+    contract_index BIGINT NOT NULL,
+    contract_sub_index BIGINT NOT NULL,
+    -- The receive name of the entrypoint called.
+    entrypoint TEXT NOT NULL,
+    -- Amount of CCD sent with the call (in microCCD).
+    amount BIGINT NOT NULL,
+    PRIMARY KEY (transaction_index, trace_element_index),
+    FOREIGN KEY (caller_contract_index, caller_contract_sub_index)
+        REFERENCES contracts (index, sub_index),
+    FOREIGN KEY (contract_index, contract_sub_index) REFERENCES contracts (index, sub_index)
+);
+
+-- Contracts calling other contracts, aggregated over every contract call.
+CREATE TABLE contract_call_edges (
+    -- Used as cursor when paginating the edges, ordered by when the edge was first seen.
+    id BIGINT GENERATED ALWAYS AS IDENTITY UNIQUE,
+    -- The contract making the calls.
+    caller_contract_index BIGINT NOT NULL,
+    caller_contract_sub_index BIGINT NOT NULL,
+    -- The contract being called.
+    contract_index BIGINT NOT NULL,
+    contract_sub_index BIGINT NOT NULL,
+    -- Number of calls made.
+    call_count BIGINT NOT NULL,
+    -- The first transaction making a call.
+    first_transaction_index BIGINT NOT NULL REFERENCES transactions,
+    -- The latest transaction making a call.
+    last_transaction_index BIGINT NOT NULL REFERENCES transactions,
+    PRIMARY KEY (caller_contract_index, caller_contract_sub_index, contract_index, contract_sub_index),
+    FOREIGN KEY (caller_contract_index, caller_contract_sub_index)
+        REFERENCES contracts (index, sub_index),
+    FOREIGN KEY (contract_index, contract_sub_index) REFERENCES contracts (index, sub_index)
+);
+
+-- Used for querying the contracts calling a contract.
+CREATE INDEX contract_call_edges_callee_idx
+    ON contract_call_edges (contract_index, contract_sub_index, id);
+
+-- Used for querying the contracts called by a contract.
+CREATE INDEX contract_call_edges_caller_idx
+    ON contract_call_edges (caller_contract_index, caller_contract_sub_index, id);
@@ -0,0 +1,65 @@
+//! Migration adding the standards supported by each contract.
+//!
+//! The standards are queried from the node using the CIS-0 `supports`
+//! entrypoint of every contract, in the state of the last processed block.
+
+use super::SchemaVersion;
+use crate::indexer::{standard_identifiers, PreparedContractStandards};
+use anyhow::Context;
+use concordium_rust_sdk::{
+    types::{smart_contracts::OwnedContractName, AbsoluteBlockHeight, ContractAddress},
+    v2,
+};
+use sqlx::Executor;
+use tracing::info;
+
+pub async fn run(
+    tx: &mut sqlx::PgTransaction<'_>,
+    endpoints: &[v2::Endpoint],
+    next_schema_version: SchemaVersion,
+) -> anyhow::Result<SchemaVersion> {
+    tx.as_mut().execute(sqlx::raw_sql(include_str!("./m0046_contract_standards.sql"))).await?;
+
+    // Get the last processed block height.
+    let last_height: Option<i64> =
+        sqlx::query_scalar("SELECT height FROM blocks ORDER BY height DESC LIMIT 1")
+            .fetch_optional(tx.as_mut())
+            .await?;
+    let Some(last_height) = last_height else {
+        return Ok(next_schema_version);
+    };
+    let last_block = AbsoluteBlockHeight {
+        height: last_height.try_into()?,
+    };
+    let endpoint = endpoints.first().context(format!(
+        "Migration '{}' must be provided access to a Concordium node",
+        next_schema_version
+    ))?;
+    let mut client = v2::Client::new(endpoint.clone()).await?;
+
+    let contracts = sqlx::query("SELECT index, sub_index, name FROM contracts ORDER BY index ASC")
+        .fetch_all(tx.as_mut())
+        .await?;
+    info!("Querying the supported standards of {} contracts", contracts.len());
+    // Only the standards tracked by default, since the migration is not provided
+    // the indexer configuration.
+    let standard_identifiers = standard_identifiers(&[]);
+    for row in contracts.iter() {
+        let index: i64 = sqlx::Row::try_get(row, "index")?;
+        let sub_index: i64 = sqlx::Row::try_get(row, "sub_index")?;
+        let name: String = sqlx::Row::try_get(row, "name")?;
+        // The name is stored without the `init_` prefix of the init function.
+        let init_name = OwnedContractName::new(format!("init_{}", name))?;
+        PreparedContractStandards::prepare(
+            &mut client,
+            last_block,
+            ContractAddress::new(index.try_into()?, sub_index.try_into()?),
+            init_name.as_contract_name(),
+            &standard_identifiers,
+        )
+        .await?
+        .save(tx)
+        .await?;
+    }
+    Ok(next_schema_version)
+}
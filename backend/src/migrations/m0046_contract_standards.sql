@@ -0,0 +1,6 @@
+-- Identifiers of the standards supported by each contract, as reported by the CIS-0 `supports`
+-- entrypoint of the contract, such as 'CIS-2'. Updated when the contract is upgraded.
+ALTER TABLE contracts ADD COLUMN supported_standards TEXT[] NOT NULL DEFAULT '{}';
+
+-- Important for filtering contracts by the standards they support.
+CREATE INDEX contracts_supported_standards_idx ON contracts USING GIN (supported_standards);
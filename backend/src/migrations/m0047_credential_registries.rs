@@ -0,0 +1,125 @@
+//! Migration introducing the tables for CIS-4 credential registries.
+//!
+//! The events are parsed from the contract logs already stored for the
+//! contracts supporting CIS-4, while the entries of the registered credentials
+//! are queried from the registries using a node.
+
+use super::SchemaVersion;
+use crate::{
+    indexer::{Cis4Event, PreparedCredentialRegistryEvents},
+    transaction_event::Event,
+};
+use anyhow::Context;
+use concordium_rust_sdk::{
+    types::{AbsoluteBlockHeight, ContractAddress},
+    v2,
+};
+use sqlx::Executor;
+use tracing::info;
+
+pub async fn run(
+    tx: &mut sqlx::PgTransaction<'_>,
+    endpoints: &[v2::Endpoint],
+    next_schema_version: SchemaVersion,
+) -> anyhow::Result<SchemaVersion> {
+    tx.as_mut().execute(sqlx::raw_sql(include_str!("./m0047_credential_registries.sql"))).await?;
+
+    let registries = sqlx::query(
+        "SELECT index, sub_index, name, transaction_index
+        FROM contracts
+        WHERE supported_standards @> ARRAY['CIS-4']
+        ORDER BY index ASC",
+    )
+    .fetch_all(tx.as_mut())
+    .await?;
+    if registries.is_empty() {
+        return Ok(next_schema_version);
+    }
+    let last_height: i64 =
+        sqlx::query_scalar("SELECT height FROM blocks ORDER BY height DESC LIMIT 1")
+            .fetch_one(tx.as_mut())
+            .await?;
+    let last_block = AbsoluteBlockHeight {
+        height: last_height.try_into()?,
+    };
+    let endpoint = endpoints.first().context(format!(
+        "Migration '{}' must be provided access to a Concordium node",
+        next_schema_version
+    ))?;
+    let mut client = v2::Client::new(endpoint.clone()).await?;
+
+    info!("Reading the CIS-4 events of {} contracts", registries.len());
+    for registry in registries.iter() {
+        let index: i64 = sqlx::Row::try_get(registry, "index")?;
+        let sub_index: i64 = sqlx::Row::try_get(registry, "sub_index")?;
+        let name: String = sqlx::Row::try_get(registry, "name")?;
+        let init_transaction_index: i64 = sqlx::Row::try_get(registry, "transaction_index")?;
+        let address = ContractAddress::new(index.try_into()?, sub_index.try_into()?);
+
+        let logs_per_transaction =
+            contract_logs(tx, index, sub_index, init_transaction_index).await?;
+        for (transaction_index, logs) in logs_per_transaction {
+            let events: Vec<_> = logs.iter().filter_map(|log| Cis4Event::parse(log)).collect();
+            if events.is_empty() {
+                continue;
+            }
+            PreparedCredentialRegistryEvents::prepare(
+                &mut client,
+                last_block,
+                address,
+                &name,
+                events,
+            )
+            .await?
+            .save(tx, transaction_index)
+            .await?;
+        }
+    }
+    Ok(next_schema_version)
+}
+
+/// The logs of the contract initialization, followed by the logs of every
+/// trace element affecting the contract, together with the index of the
+/// transaction logging them. Also used by later migrations indexing contract
+/// logs.
+pub(super) async fn contract_logs(
+    tx: &mut sqlx::PgTransaction<'_>,
+    index: i64,
+    sub_index: i64,
+    init_transaction_index: i64,
+) -> anyhow::Result<Vec<(i64, Vec<Vec<u8>>)>> {
+    let mut logs_per_transaction: Vec<(i64, Vec<Vec<u8>>)> = Vec::new();
+    let init_events: sqlx::types::Json<Vec<Event>> =
+        sqlx::query_scalar("SELECT events FROM transactions WHERE index = $1")
+            .bind(init_transaction_index)
+            .fetch_one(tx.as_mut())
+            .await?;
+    for event in init_events.0 {
+        if let Event::ContractInitialized(initialized) = event {
+            logs_per_transaction.push((init_transaction_index, initialized.contract_logs_raw));
+        }
+    }
+    let trace_elements = sqlx::query(
+        "SELECT contract_events.transaction_index, trace_element_index, transactions.events
+        FROM contract_events
+            JOIN transactions ON transactions.index = contract_events.transaction_index
+        WHERE contract_index = $1 AND contract_sub_index = $2
+        ORDER BY contract_events.transaction_index ASC, trace_element_index ASC",
+    )
+    .bind(index)
+    .bind(sub_index)
+    .fetch_all(tx.as_mut())
+    .await?;
+    for row in trace_elements.iter() {
+        let transaction_index: i64 = sqlx::Row::try_get(row, "transaction_index")?;
+        let trace_element_index: i64 = sqlx::Row::try_get(row, "trace_element_index")?;
+        let events: sqlx::types::Json<Vec<Event>> = sqlx::Row::try_get(row, "events")?;
+        let logs = match events.0.into_iter().nth(trace_element_index.try_into()?) {
+            Some(Event::ContractUpdated(updated)) => updated.contract_logs_raw,
+            Some(Event::ContractInterrupted(interrupted)) => interrupted.contract_logs_raw,
+            _ => continue,
+        };
+        logs_per_transaction.push((transaction_index, logs));
+    }
+    Ok(logs_per_transaction)
+}
@@ -0,0 +1,91 @@
+-- The kind of change to a credential in a CIS-4 credential registry.
+CREATE TYPE cis4_credential_event_type AS ENUM (
+    'Registered',
+    'Revoked',
+    'Restored',
+    'MetadataUpdated'
+);
+
+-- Contracts acting as CIS-4 credential registries, meaning contracts supporting CIS-4 and logging
+-- CIS-4 events.
+CREATE TABLE cis4_registries (
+    contract_index BIGINT NOT NULL,
+    contract_sub_index BIGINT NOT NULL,
+    -- The transaction logging the first CIS-4 event of the registry.
+    transaction_index BIGINT NOT NULL REFERENCES transactions,
+    -- URL of the metadata of the issuer. NULL until the registry logs it.
+    issuer_metadata_url TEXT,
+    -- Hex encoded SHA256 hash of the issuer metadata, if provided by the registry.
+    issuer_metadata_hash TEXT,
+    -- The type of the credentials in the registry. NULL until the registry logs a schema.
+    credential_type TEXT,
+    -- URL of the schema of the credentials in the registry.
+    schema_url TEXT,
+    PRIMARY KEY (contract_index, contract_sub_index),
+    FOREIGN KEY (contract_index, contract_sub_index) REFERENCES contracts (index, sub_index)
+);
+
+-- The credentials registered in CIS-4 credential registries.
+CREATE TABLE cis4_credentials (
+    -- Used as cursor when paginating the credentials of a registry.
+    id BIGINT GENERATED ALWAYS AS IDENTITY UNIQUE,
+    contract_index BIGINT NOT NULL,
+    contract_sub_index BIGINT NOT NULL,
+    -- Hex encoded Ed25519 public key identifying the credential holder.
+    holder_id TEXT NOT NULL,
+    credential_type TEXT NOT NULL,
+    -- URL of the schema of the credential.
+    schema_url TEXT NOT NULL,
+    -- The following columns are read from the credential entry in the registry and are NULL if
+    -- the registry did not provide the entry.
+    -- Whether the holder is able to revoke the credential.
+    holder_revocable BOOLEAN,
+    -- Start of the validity window of the credential.
+    valid_from TIMESTAMPTZ,
+    -- End of the validity window of the credential. NULL when the credential never expires.
+    valid_until TIMESTAMPTZ,
+    -- URL of the metadata of the credential.
+    metadata_url TEXT,
+    -- Whether the credential is revoked.
+    revoked BOOLEAN NOT NULL DEFAULT FALSE,
+    -- The transaction registering the credential.
+    transaction_index BIGINT NOT NULL REFERENCES transactions,
+    PRIMARY KEY (contract_index, contract_sub_index, holder_id),
+    FOREIGN KEY (contract_index, contract_sub_index)
+        REFERENCES cis4_registries (contract_index, contract_sub_index)
+);
+
+-- Used for paginating the credentials of a registry.
+CREATE INDEX cis4_credentials_registry_idx ON cis4_credentials (contract_index, contract_sub_index, id);
+
+-- The history of each credential in CIS-4 credential registries.
+CREATE TABLE cis4_credential_events (
+    id BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    credential_id BIGINT NOT NULL REFERENCES cis4_credentials (id),
+    transaction_index BIGINT NOT NULL REFERENCES transactions,
+    event_type cis4_credential_event_type NOT NULL,
+    -- The party revoking the credential: 'Issuer', 'Holder' or the hex encoded revocation key.
+    -- Only present for revocations.
+    revoker TEXT,
+    -- The reason for revoking the credential, if provided.
+    reason TEXT
+);
+
+-- Used for querying the history of a credential.
+CREATE INDEX cis4_credential_events_credential_idx ON cis4_credential_events (credential_id, id);
+
+-- The revocation keys of CIS-4 credential registries, able to revoke credentials on behalf of the
+-- issuer.
+CREATE TABLE cis4_revocation_keys (
+    contract_index BIGINT NOT NULL,
+    contract_sub_index BIGINT NOT NULL,
+    -- Hex encoded Ed25519 public key.
+    key TEXT NOT NULL,
+    -- Whether the key is registered, false when the key got removed.
+    active BOOLEAN NOT NULL,
+    -- The latest transaction registering or removing the key.
+    transaction_index BIGINT NOT NULL REFERENCES transactions,
+    PRIMARY KEY (contract_index, contract_sub_index, key),
+    FOREIGN KEY (contract_index, contract_sub_index)
+        REFERENCES cis4_registries (contract_index, contract_sub_index)
+);
@@ -0,0 +1,46 @@
+//! Migration introducing the tables for CIS-5 smart contract wallets.
+//!
+//! The events are parsed from the contract logs already stored for the
+//! contracts supporting CIS-5.
+
+use super::{m0047_credential_registries::contract_logs, SchemaVersion};
+use crate::indexer::{Cis5Event, PreparedSmartContractWalletEvents};
+use concordium_rust_sdk::types::ContractAddress;
+use sqlx::Executor;
+use tracing::info;
+
+pub async fn run(
+    tx: &mut sqlx::PgTransaction<'_>,
+    next_schema_version: SchemaVersion,
+) -> anyhow::Result<SchemaVersion> {
+    tx.as_mut().execute(sqlx::raw_sql(include_str!("./m0048_smart_contract_wallets.sql"))).await?;
+
+    let wallets = sqlx::query(
+        "SELECT index, sub_index, transaction_index
+        FROM contracts
+        WHERE supported_standards @> ARRAY['CIS-5']
+        ORDER BY index ASC",
+    )
+    .fetch_all(tx.as_mut())
+    .await?;
+    info!("Reading the CIS-5 events of {} contracts", wallets.len());
+    for wallet in wallets.iter() {
+        let index: i64 = sqlx::Row::try_get(wallet, "index")?;
+        let sub_index: i64 = sqlx::Row::try_get(wallet, "sub_index")?;
+        let init_transaction_index: i64 = sqlx::Row::try_get(wallet, "transaction_index")?;
+        let address = ContractAddress::new(index.try_into()?, sub_index.try_into()?);
+
+        let logs_per_transaction =
+            contract_logs(tx, index, sub_index, init_transaction_index).await?;
+        for (transaction_index, logs) in logs_per_transaction {
+            let events: Vec<_> = logs.iter().filter_map(|log| Cis5Event::parse(log)).collect();
+            if events.is_empty() {
+                continue;
+            }
+            PreparedSmartContractWalletEvents::prepare(address, events)?
+                .save(tx, transaction_index)
+                .await?;
+        }
+    }
+    Ok(next_schema_version)
+}
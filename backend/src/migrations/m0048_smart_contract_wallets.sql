@@ -0,0 +1,62 @@
+-- The kind of change to the balance of a public key in a CIS-5 smart contract wallet.
+CREATE TYPE cis5_balance_change_type AS ENUM (
+    'Deposit',
+    'Withdraw',
+    'TransferIn',
+    'TransferOut'
+);
+
+-- The public keys holding funds in CIS-5 smart contract wallets.
+CREATE TABLE cis5_public_key_holders (
+    id BIGINT GENERATED ALWAYS AS IDENTITY UNIQUE,
+    contract_index BIGINT NOT NULL,
+    contract_sub_index BIGINT NOT NULL,
+    -- Hex encoded Ed25519 public key.
+    public_key TEXT NOT NULL,
+    -- The balance of CCD held for the public key (in microCCD).
+    ccd_balance BIGINT NOT NULL DEFAULT 0,
+    -- The latest nonce logged for the public key by a sponsored action. NULL if none is logged.
+    nonce BIGINT,
+    -- The transaction logging the first event for the public key.
+    transaction_index BIGINT NOT NULL REFERENCES transactions,
+    PRIMARY KEY (contract_index, contract_sub_index, public_key),
+    FOREIGN KEY (contract_index, contract_sub_index) REFERENCES contracts (index, sub_index)
+);
+
+-- Used for looking up a public key across wallets.
+CREATE INDEX cis5_public_key_holders_public_key_idx ON cis5_public_key_holders (public_key);
+
+-- The balances of CIS-2 tokens held for public keys in CIS-5 smart contract wallets.
+CREATE TABLE cis5_token_balances (
+    holder_id BIGINT NOT NULL REFERENCES cis5_public_key_holders (id),
+    -- The CIS-2 contract of the token.
+    token_contract_index BIGINT NOT NULL,
+    token_contract_sub_index BIGINT NOT NULL,
+    -- Hex encoded token ID.
+    token_id TEXT NOT NULL,
+    balance NUMERIC NOT NULL,
+    PRIMARY KEY (holder_id, token_contract_index, token_contract_sub_index, token_id)
+);
+
+-- The history of the balances of public keys in CIS-5 smart contract wallets.
+CREATE TABLE cis5_balance_changes (
+    id BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    holder_id BIGINT NOT NULL REFERENCES cis5_public_key_holders (id),
+    transaction_index BIGINT NOT NULL REFERENCES transactions,
+    change_type cis5_balance_change_type NOT NULL,
+    -- The CIS-2 token changing balance. NULL for changes to the CCD balance.
+    token_contract_index BIGINT,
+    token_contract_sub_index BIGINT,
+    token_id TEXT,
+    -- The amount deposited, withdrawn or transferred (in microCCD for CCD).
+    amount NUMERIC NOT NULL,
+    -- The other party of the change: an account or contract for deposits and withdrawals, and a
+    -- hex encoded public key for transfers.
+    counterparty_account_index BIGINT REFERENCES accounts,
+    counterparty_contract_index BIGINT,
+    counterparty_contract_sub_index BIGINT,
+    counterparty_public_key TEXT
+);
+
+-- Used for paginating the history of a public key.
+CREATE INDEX cis5_balance_changes_holder_idx ON cis5_balance_changes (holder_id, id);
@@ -0,0 +1,64 @@
+//! Migration introducing the table for CIS-3 sponsored actions.
+//!
+//! The sponsored actions are detected from the contract calls already stored
+//! for the contracts supporting CIS-3.
+
+use super::SchemaVersion;
+use crate::{
+    indexer::{CallTraceStep, PreparedSponsoredAction},
+    transaction_event::Event,
+};
+use sqlx::Executor;
+use tracing::info;
+
+pub async fn run(
+    tx: &mut sqlx::PgTransaction<'_>,
+    next_schema_version: SchemaVersion,
+) -> anyhow::Result<SchemaVersion> {
+    tx.as_mut().execute(sqlx::raw_sql(include_str!("./m0049_sponsored_actions.sql"))).await?;
+
+    let calls = sqlx::query(
+        "SELECT contract_events.transaction_index, trace_element_index, transactions.events
+        FROM contract_events
+            JOIN contracts ON contracts.index = contract_events.contract_index
+                AND contracts.sub_index = contract_events.contract_sub_index
+            JOIN transactions ON transactions.index = contract_events.transaction_index
+        WHERE contracts.supported_standards @> ARRAY['CIS-3']
+        ORDER BY contract_events.transaction_index ASC, trace_element_index ASC",
+    )
+    .fetch_all(tx.as_mut())
+    .await?;
+    info!("Detecting sponsored actions among {} contract events", calls.len());
+    for row in calls.iter() {
+        let transaction_index: i64 = sqlx::Row::try_get(row, "transaction_index")?;
+        let trace_element_index: i64 = sqlx::Row::try_get(row, "trace_element_index")?;
+        let events: sqlx::types::Json<Vec<Event>> = sqlx::Row::try_get(row, "events")?;
+        let Some(event @ Event::ContractUpdated(updated)) =
+            events.0.get(usize::try_from(trace_element_index)?)
+        else {
+            continue;
+        };
+        let CallTraceStep::Updated {
+            caller,
+            contract_index,
+            contract_sub_index,
+            entrypoint,
+            ..
+        } = CallTraceStep::try_from(event)?
+        else {
+            continue;
+        };
+        let action = PreparedSponsoredAction::detect(
+            trace_element_index,
+            contract_index,
+            contract_sub_index,
+            caller,
+            &entrypoint,
+            &updated.input_parameter,
+        );
+        if let Some(action) = action {
+            action.save(tx, transaction_index).await?;
+        }
+    }
+    Ok(next_schema_version)
+}
@@ -0,0 +1,29 @@
+-- CIS-3 sponsored actions, where a sponsor calls the `permit` entrypoint of a contract to execute
+-- an action signed by another account.
+CREATE TABLE sponsored_actions (
+    -- Used as cursor when paginating the sponsored actions of an account.
+    id BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    transaction_index BIGINT NOT NULL REFERENCES transactions,
+    -- Index of the trace element reporting the call to `permit`.
+    trace_element_index BIGINT NOT NULL,
+    contract_index BIGINT NOT NULL,
+    contract_sub_index BIGINT NOT NULL,
+    -- The account signing the action. NULL if the signer is not an account on chain.
+    signer_account_index BIGINT REFERENCES accounts,
+    -- The account or contract calling `permit`, paying for the action. Exactly one of the account
+    -- and the contract is present.
+    sponsor_account_index BIGINT REFERENCES accounts,
+    sponsor_contract_index BIGINT,
+    sponsor_contract_sub_index BIGINT,
+    -- The entrypoint executed on behalf of the signer.
+    entrypoint TEXT NOT NULL,
+    nonce BIGINT NOT NULL,
+    -- Expiry of the signature of the signer.
+    signature_expiry TIMESTAMPTZ NOT NULL,
+    FOREIGN KEY (contract_index, contract_sub_index) REFERENCES contracts (index, sub_index)
+);
+
+-- Used for querying the sponsored actions of a transaction.
+CREATE INDEX sponsored_actions_transaction_idx ON sponsored_actions (transaction_index);
+-- Used for paginating the sponsored actions signed by an account.
+CREATE INDEX sponsored_actions_signer_idx ON sponsored_actions (signer_account_index, id);
@@ -0,0 +1,47 @@
+//! Migration introducing the upgrade history of contracts.
+//!
+//! The history is rebuilt from the upgrade events already stored for the
+//! contracts which got upgraded.
+
+use super::SchemaVersion;
+use sqlx::Executor;
+use tracing::info;
+
+pub async fn run(
+    tx: &mut sqlx::PgTransaction<'_>,
+    next_schema_version: SchemaVersion,
+) -> anyhow::Result<SchemaVersion> {
+    tx.as_mut().execute(sqlx::raw_sql(include_str!("./m0050_contract_upgrades.sql"))).await?;
+
+    // The trace element reporting the upgrade is looked up among the events of the
+    // transaction in the database, such that only the upgrade events are read.
+    let upgrades = sqlx::query(
+        "INSERT INTO contract_upgrades (
+            contract_index,
+            contract_sub_index,
+            transaction_index,
+            trace_element_index,
+            from_module_reference,
+            to_module_reference
+        )
+        SELECT
+            contract_index,
+            contract_sub_index,
+            contract_events.transaction_index,
+            trace_element_index,
+            transactions.events -> trace_element_index::INT -> 'ContractUpgraded' ->> 'from',
+            transactions.events -> trace_element_index::INT -> 'ContractUpgraded' ->> 'to'
+        FROM contract_events
+            JOIN contracts ON contracts.index = contract_events.contract_index
+                AND contracts.sub_index = contract_events.contract_sub_index
+            JOIN transactions ON transactions.index = contract_events.transaction_index
+        WHERE contracts.last_upgrade_transaction_index IS NOT NULL
+            AND transactions.events -> trace_element_index::INT ? 'ContractUpgraded'
+        ORDER BY contract_events.transaction_index ASC, trace_element_index ASC",
+    )
+    .execute(tx.as_mut())
+    .await?
+    .rows_affected();
+    info!("Recorded {} contract upgrades", upgrades);
+    Ok(next_schema_version)
+}
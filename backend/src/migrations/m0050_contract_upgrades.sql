@@ -0,0 +1,19 @@
+-- The history of upgrades of each contract.
+CREATE TABLE contract_upgrades (
+    -- Used as cursor when paginating the upgrades of a contract.
+    id BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    contract_index BIGINT NOT NULL,
+    contract_sub_index BIGINT NOT NULL,
+    -- The transaction upgrading the contract.
+    transaction_index BIGINT NOT NULL REFERENCES transactions,
+    -- Index of the trace element reporting the upgrade, among the events of the transaction.
+    trace_element_index BIGINT NOT NULL,
+    -- The module of the contract before the upgrade.
+    from_module_reference CHAR(64) NOT NULL REFERENCES smart_contract_modules,
+    -- The module of the contract after the upgrade.
+    to_module_reference CHAR(64) NOT NULL REFERENCES smart_contract_modules,
+    FOREIGN KEY (contract_index, contract_sub_index) REFERENCES contracts (index, sub_index)
+);
+
+-- Used for paginating the upgrades of a contract.
+CREATE INDEX contract_upgrades_contract_idx ON contract_upgrades (contract_index, contract_sub_index, id);
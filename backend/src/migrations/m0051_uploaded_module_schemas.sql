@@ -0,0 +1,7 @@
+-- Time at which the schema of the module was uploaded through the REST API, after verifying the
+-- uploaded Wasm module against the module reference.
+-- Is NULL for modules with a schema embedded in the module, or without any schema.
+ALTER TABLE smart_contract_modules
+    ADD COLUMN schema_uploaded_at
+        TIMESTAMPTZ
+        NULL;
@@ -0,0 +1,72 @@
+-- The build information embedded in modules built using the verifiable builds of `cargo concordium`.
+-- The columns are NULL for modules without build information.
+ALTER TABLE smart_contract_modules
+    -- Hex encoded SHA-256 hash of the source archive.
+    ADD COLUMN build_archive_hash
+        CHAR(64)
+        NULL,
+    -- The Docker image used for building.
+    ADD COLUMN build_image
+        TEXT
+        NULL,
+    -- The command used for building, run in the extracted source archive.
+    ADD COLUMN build_command
+        TEXT[]
+        NULL,
+    -- Link to where the source archive is published.
+    ADD COLUMN build_source_link
+        TEXT
+        NULL,
+    -- Whether the build information is yet to be read from the module source. The modules
+    -- deployed before this migration are read by the indexer in the background.
+    ADD COLUMN build_info_pending
+        BOOLEAN
+        NOT NULL
+        DEFAULT TRUE;
+
+-- The indexer reads the build information of modules when they are deployed.
+ALTER TABLE smart_contract_modules ALTER COLUMN build_info_pending SET DEFAULT FALSE;
+
+CREATE TYPE module_verification_method AS ENUM (
+    'LocalBuild',
+    'Attestation'
+);
+
+-- Modules with a source archive verified against the module.
+CREATE TABLE module_source_verifications(
+    module_reference
+        CHAR(64)
+        PRIMARY KEY
+        REFERENCES smart_contract_modules,
+    method
+        module_verification_method
+        NOT NULL,
+    verified_at
+        TIMESTAMPTZ
+        NOT NULL,
+    -- Hex encoded Ed25519 public key of the attester, for verifications by attestation.
+    attester_key
+        CHAR(64)
+        NULL,
+    -- Hex encoded signature of the attester, for verifications by attestation.
+    attestation_signature
+        CHAR(128)
+        NULL
+);
+
+-- The text files of the verified source archives.
+CREATE TABLE module_source_files(
+    module_reference
+        CHAR(64)
+        NOT NULL
+        REFERENCES module_source_verifications
+        ON DELETE CASCADE,
+    -- Path of the file in the source archive.
+    path
+        TEXT
+        NOT NULL,
+    content
+        TEXT
+        NOT NULL,
+    PRIMARY KEY (module_reference, path)
+);
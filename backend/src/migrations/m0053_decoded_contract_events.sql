@@ -0,0 +1,38 @@
+-- Parameter and events of the contract event decoded using the schema of the contract module at the
+-- time of the event, stored as an object `{"parameter": ..., "events": [...]}` with NULL in place
+-- of values failing to decode.
+-- Is NULL for events without a module schema, or when the decoding of contract events is disabled
+-- in the indexer.
+ALTER TABLE contract_events
+    ADD COLUMN decoded
+        JSONB
+        NULL;
+
+-- Allows for filtering contract events by the decoded values using the containment operator `@>`.
+CREATE INDEX contract_events_decoded_idx ON contract_events USING GIN (decoded jsonb_path_ops);
+
+-- Progress of decoding contract events, tracked by the indexer when decoding is enabled. Events
+-- indexed before the decoding got enabled are decoded starting from the first event.
+CREATE TABLE contract_event_decoding (
+    -- Index of the last contract event processed.
+    last_index
+        BIGINT
+        NOT NULL
+);
+
+INSERT INTO contract_event_decoding (last_index) VALUES (-1);
+
+-- Modules with a schema uploaded after events of the module were processed by the decoder, such that
+-- the events of the contracts using the module are decoded again. Rows are removed once every event
+-- up to the progress of the decoder is decoded again.
+CREATE TABLE contract_event_redecoding (
+    -- The module with the uploaded schema.
+    module_reference
+        CHAR(64)
+        PRIMARY KEY
+        REFERENCES smart_contract_modules,
+    -- Index of the last contract event processed for the module.
+    last_index
+        BIGINT
+        NOT NULL
+);
@@ -0,0 +1,149 @@
+//! Migration introducing the statements of protocol level tokens (PLTs) for
+//! accounts.
+//!
+//! The statements are rebuilt from the stored token events, replaying the
+//! balance of every account and token from the first event. The events are
+//! read and the statement entries inserted in batches, keeping only the
+//! running balances in memory.
+
+use super::SchemaVersion;
+use crate::transaction_event::protocol_level_tokens::{
+    account_statement_changes, canonical_address, TokenEventDetails,
+};
+use bigdecimal::BigDecimal;
+use sqlx::Executor;
+use std::collections::HashMap;
+use tracing::info;
+
+/// The number of token events read at a time.
+const BATCH_SIZE: i64 = 10_000;
+
+pub async fn run(
+    tx: &mut sqlx::PgTransaction<'_>,
+    next_schema_version: SchemaVersion,
+) -> anyhow::Result<SchemaVersion> {
+    tx.as_mut().execute(sqlx::raw_sql(include_str!("./m0054_plt_account_statements.sql"))).await?;
+
+    // Balances by canonical account address and token index.
+    let mut balances: HashMap<(Vec<u8>, i64), BigDecimal> = HashMap::new();
+    let mut last_event_id = i64::MIN;
+    let mut inserted_entries = 0;
+    loop {
+        let events = sqlx::query(
+            "SELECT
+                plt_events.id,
+                plt_events.transaction_index,
+                plt_events.token_index,
+                plt_events.token_event,
+                blocks.slot_time
+            FROM plt_events
+                JOIN transactions ON transactions.index = plt_events.transaction_index
+                JOIN blocks ON blocks.height = transactions.block_height
+            WHERE plt_events.event_type IN ('Mint', 'Burn', 'Transfer')
+                AND plt_events.id > $1
+            ORDER BY plt_events.id ASC
+            LIMIT $2",
+        )
+        .bind(last_event_id)
+        .bind(BATCH_SIZE)
+        .fetch_all(tx.as_mut())
+        .await?;
+        let Some(last_event) = events.last() else {
+            break;
+        };
+        last_event_id = sqlx::Row::try_get(last_event, "id")?;
+
+        let mut canonical_addresses = Vec::new();
+        let mut token_indices = Vec::new();
+        let mut entry_types = Vec::new();
+        let mut amounts = Vec::new();
+        let mut account_balances = Vec::new();
+        let mut plt_event_ids = Vec::new();
+        let mut transaction_indices = Vec::new();
+        let mut slot_times = Vec::new();
+        for row in events.iter() {
+            let plt_event_id: i64 = sqlx::Row::try_get(row, "id")?;
+            let transaction_index: i64 = sqlx::Row::try_get(row, "transaction_index")?;
+            let token_index: i64 = sqlx::Row::try_get(row, "token_index")?;
+            let token_event: sqlx::types::Json<TokenEventDetails> =
+                sqlx::Row::try_get(row, "token_event")?;
+            let slot_time: chrono::DateTime<chrono::Utc> = sqlx::Row::try_get(row, "slot_time")?;
+            for (address, entry_type, amount) in account_statement_changes(&token_event.0)? {
+                let canonical_address = canonical_address(&address)?;
+                let balance = balances.entry((canonical_address.clone(), token_index)).or_default();
+                *balance += &amount;
+                canonical_addresses.push(canonical_address);
+                token_indices.push(token_index);
+                entry_types.push(entry_type.to_string());
+                amounts.push(amount);
+                account_balances.push(balance.clone());
+                plt_event_ids.push(plt_event_id);
+                transaction_indices.push(transaction_index);
+                slot_times.push(slot_time);
+            }
+        }
+        let entries = canonical_addresses.len();
+        let inserted = sqlx::query(
+            "INSERT INTO plt_account_statements (
+                account_index,
+                token_index,
+                entry_type,
+                amount,
+                account_balance,
+                plt_event_id,
+                transaction_index,
+                slot_time
+            )
+            SELECT
+                accounts.index,
+                entries.token_index,
+                entries.entry_type::plt_account_statement_entry_type,
+                entries.amount,
+                entries.account_balance,
+                entries.plt_event_id,
+                entries.transaction_index,
+                entries.slot_time
+            FROM UNNEST(
+                $1::BYTEA[],
+                $2::BIGINT[],
+                $3::TEXT[],
+                $4::NUMERIC[],
+                $5::NUMERIC[],
+                $6::BIGINT[],
+                $7::BIGINT[],
+                $8::TIMESTAMPTZ[]
+            ) WITH ORDINALITY AS entries(
+                canonical_address,
+                token_index,
+                entry_type,
+                amount,
+                account_balance,
+                plt_event_id,
+                transaction_index,
+                slot_time,
+                position
+            )
+                JOIN accounts ON accounts.canonical_address = entries.canonical_address
+            ORDER BY entries.position",
+        )
+        .bind(canonical_addresses)
+        .bind(token_indices)
+        .bind(entry_types)
+        .bind(amounts)
+        .bind(account_balances)
+        .bind(plt_event_ids)
+        .bind(transaction_indices)
+        .bind(slot_times)
+        .execute(tx.as_mut())
+        .await?
+        .rows_affected();
+        anyhow::ensure!(
+            inserted == u64::try_from(entries)?,
+            "Token events up to event {} refer to accounts which do not exist",
+            last_event_id
+        );
+        inserted_entries += inserted;
+    }
+    info!("Built the PLT account statements of {} entries", inserted_entries);
+    Ok(next_schema_version)
+}
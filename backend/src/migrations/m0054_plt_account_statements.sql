@@ -0,0 +1,64 @@
+-- Type of an entry in the statement of a protocol level token (PLT) for an account.
+CREATE TYPE plt_account_statement_entry_type AS ENUM (
+    'Mint',
+    'Burn',
+    'TransferIn',
+    'TransferOut'
+);
+
+-- Ledger of the changes to the PLT balance of each account, with an entry for every mint, burn and
+-- transfer. A transfer results in an entry for both the sender and the receiver.
+CREATE TABLE plt_account_statements (
+    -- Unique identifier for each statement entry, ordering the entries of an account and token.
+    id
+        BIGINT
+        GENERATED ALWAYS AS IDENTITY
+        PRIMARY KEY,
+    -- Index of the account associated with this entry.
+    account_index
+        BIGINT
+        NOT NULL
+        REFERENCES accounts(index),
+    -- Index of the token associated with this entry.
+    token_index
+        BIGINT
+        NOT NULL
+        REFERENCES plt_tokens(index),
+    -- Type of the statement entry.
+    entry_type
+        plt_account_statement_entry_type
+        NOT NULL,
+    -- Change of the balance in the smallest unit of the token.
+    -- Will be negative when an amount is being subtracted from the account.
+    amount
+        NUMERIC
+        NOT NULL,
+    -- The resulting balance of the account after applying this entry, in the smallest unit of the
+    -- token.
+    account_balance
+        NUMERIC
+        NOT NULL,
+    -- The token event causing the entry.
+    plt_event_id
+        BIGINT
+        NOT NULL
+        REFERENCES plt_events(id),
+    -- Index of the transaction with the token event.
+    transaction_index
+        BIGINT
+        NOT NULL
+        REFERENCES transactions(index),
+    -- Slot time of the block with the transaction.
+    slot_time
+        TIMESTAMPTZ
+        NOT NULL
+);
+
+-- Index for the statements of an account, optionally for one token.
+CREATE INDEX plt_account_statements_account_token_idx
+    ON plt_account_statements (account_index, token_index, id);
+CREATE INDEX plt_account_statements_account_idx
+    ON plt_account_statements (account_index, id);
+-- Index for exporting the statements of an account and token within a time span.
+CREATE INDEX plt_account_statements_account_token_slot_time_idx
+    ON plt_account_statements (account_index, token_index, slot_time DESC);
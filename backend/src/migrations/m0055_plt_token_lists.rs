@@ -0,0 +1,59 @@
+//! Migration introducing the allow and deny lists and the pause state of
+//! protocol level tokens.
+//!
+//! The lists and pause state are rebuilt from the stored token module events.
+
+use super::SchemaVersion;
+use crate::transaction_event::protocol_level_tokens::{
+    save_token_module_state, TokenEventDetails, TokenUpdateModuleType,
+};
+use sqlx::Executor;
+use tracing::info;
+
+pub async fn run(
+    tx: &mut sqlx::PgTransaction<'_>,
+    next_schema_version: SchemaVersion,
+) -> anyhow::Result<SchemaVersion> {
+    tx.as_mut().execute(sqlx::raw_sql(include_str!("./m0055_plt_token_lists.sql"))).await?;
+
+    let events = sqlx::query(
+        "SELECT
+            plt_events.id,
+            plt_events.transaction_index,
+            plt_events.token_index,
+            plt_events.token_module_type,
+            plt_events.token_event,
+            blocks.slot_time
+        FROM plt_events
+            JOIN transactions ON transactions.index = plt_events.transaction_index
+            JOIN blocks ON blocks.height = transactions.block_height
+        WHERE plt_events.token_module_type IS NOT NULL
+        ORDER BY plt_events.id ASC",
+    )
+    .fetch_all(tx.as_mut())
+    .await?;
+    info!("Replaying {} token module events", events.len());
+    for row in events.iter() {
+        let plt_event_id: i64 = sqlx::Row::try_get(row, "id")?;
+        let transaction_index: i64 = sqlx::Row::try_get(row, "transaction_index")?;
+        let token_index: i64 = sqlx::Row::try_get(row, "token_index")?;
+        let module_type: TokenUpdateModuleType = sqlx::Row::try_get(row, "token_module_type")?;
+        let token_event: sqlx::types::Json<TokenEventDetails> =
+            sqlx::Row::try_get(row, "token_event")?;
+        let slot_time: chrono::DateTime<chrono::Utc> = sqlx::Row::try_get(row, "slot_time")?;
+        let TokenEventDetails::Module(event) = token_event.0 else {
+            continue;
+        };
+        save_token_module_state(
+            tx,
+            token_index,
+            module_type,
+            &event.details,
+            plt_event_id,
+            transaction_index,
+            slot_time,
+        )
+        .await?;
+    }
+    Ok(next_schema_version)
+}
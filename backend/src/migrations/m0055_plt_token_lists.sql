@@ -0,0 +1,113 @@
+-- List of accounts maintained by the token module of a protocol level token (PLT).
+CREATE TYPE plt_token_list AS ENUM (
+    'Allow',
+    'Deny'
+);
+
+-- Whether the operations of the token are paused by the token module.
+ALTER TABLE plt_tokens
+    ADD COLUMN paused BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Current members of the allow and deny lists of each token.
+CREATE TABLE plt_token_list_members (
+    -- Index of the token maintaining the list.
+    token_index
+        BIGINT
+        NOT NULL
+        REFERENCES plt_tokens(index),
+    -- The list with the account.
+    list
+        plt_token_list
+        NOT NULL,
+    -- Index of the account on the list.
+    account_index
+        BIGINT
+        NOT NULL
+        REFERENCES accounts(index),
+    -- The token event adding the account to the list.
+    plt_event_id
+        BIGINT
+        NOT NULL
+        REFERENCES plt_events(id),
+    PRIMARY KEY (token_index, list, account_index)
+);
+
+-- Index for the members of a list, ordered by the time they got added.
+CREATE INDEX plt_token_list_members_list_idx
+    ON plt_token_list_members (token_index, list, plt_event_id);
+-- Index for the list memberships of an account.
+CREATE INDEX plt_token_list_members_account_idx
+    ON plt_token_list_members (account_index);
+
+-- History of the accounts added to and removed from the allow and deny lists of each token.
+CREATE TABLE plt_token_list_changes (
+    id
+        BIGINT
+        GENERATED ALWAYS AS IDENTITY
+        PRIMARY KEY,
+    token_index
+        BIGINT
+        NOT NULL
+        REFERENCES plt_tokens(index),
+    list
+        plt_token_list
+        NOT NULL,
+    account_index
+        BIGINT
+        NOT NULL
+        REFERENCES accounts(index),
+    -- Whether the account got added to the list, otherwise it got removed.
+    added
+        BOOLEAN
+        NOT NULL,
+    -- The token event changing the list.
+    plt_event_id
+        BIGINT
+        NOT NULL
+        REFERENCES plt_events(id),
+    -- Index of the transaction with the token event.
+    transaction_index
+        BIGINT
+        NOT NULL
+        REFERENCES transactions(index),
+    -- Slot time of the block with the transaction.
+    slot_time
+        TIMESTAMPTZ
+        NOT NULL
+);
+
+CREATE INDEX plt_token_list_changes_token_idx
+    ON plt_token_list_changes (token_index, id);
+
+-- History of the pausing and unpausing of each token.
+CREATE TABLE plt_token_pause_changes (
+    id
+        BIGINT
+        GENERATED ALWAYS AS IDENTITY
+        PRIMARY KEY,
+    token_index
+        BIGINT
+        NOT NULL
+        REFERENCES plt_tokens(index),
+    -- Whether the token got paused, otherwise it got unpaused.
+    paused
+        BOOLEAN
+        NOT NULL,
+    -- The token event pausing or unpausing the token.
+    plt_event_id
+        BIGINT
+        NOT NULL
+        REFERENCES plt_events(id),
+    -- Index of the transaction with the token event.
+    transaction_index
+        BIGINT
+        NOT NULL
+        REFERENCES transactions(index),
+    -- Slot time of the block with the transaction.
+    slot_time
+        TIMESTAMPTZ
+        NOT NULL
+);
+
+CREATE INDEX plt_token_pause_changes_token_idx
+    ON plt_token_pause_changes (token_index, id);
@@ -0,0 +1,74 @@
+-- Cumulative supply and holder metrics per protocol level token, with a row for every block
+-- minting, burning or transferring the token.
+CREATE TABLE metrics_plt_token (
+    event_timestamp TIMESTAMPTZ NOT NULL,
+    token_index BIGINT NOT NULL,
+    -- Total amount minted of the token up to and including the block.
+    cumulative_minted NUMERIC NOT NULL,
+    -- Total amount burned of the token up to and including the block.
+    cumulative_burned NUMERIC NOT NULL,
+    -- Number of accounts holding a non-zero balance of the token after the block.
+    holder_count BIGINT NOT NULL,
+    PRIMARY KEY (token_index, event_timestamp)
+);
+
+-- Rebuild the metrics from the account statements. The balance of an account at the end of a
+-- block is the balance of its last statement entry in the block.
+WITH block_balances AS (
+    SELECT DISTINCT ON (token_index, account_index, slot_time)
+        token_index,
+        account_index,
+        slot_time,
+        account_balance
+    FROM plt_account_statements
+    ORDER BY token_index, account_index, slot_time, id DESC
+),
+changes AS (
+    SELECT
+        token_index,
+        slot_time,
+        0 AS minted,
+        0 AS burned,
+        (account_balance > 0)::INT
+            - (COALESCE(
+                LAG(account_balance) OVER (
+                    PARTITION BY token_index, account_index ORDER BY slot_time
+                ),
+                0
+            ) > 0)::INT AS holder_change
+    FROM block_balances
+    UNION ALL
+    SELECT
+        token_index,
+        slot_time,
+        CASE WHEN entry_type = 'Mint' THEN amount ELSE 0 END,
+        CASE WHEN entry_type = 'Burn' THEN -amount ELSE 0 END,
+        0
+    FROM plt_account_statements
+    WHERE entry_type IN ('Mint', 'Burn')
+),
+block_changes AS (
+    SELECT
+        token_index,
+        slot_time,
+        SUM(minted) AS minted,
+        SUM(burned) AS burned,
+        SUM(holder_change) AS holder_change
+    FROM changes
+    GROUP BY token_index, slot_time
+)
+INSERT INTO metrics_plt_token (
+    event_timestamp,
+    token_index,
+    cumulative_minted,
+    cumulative_burned,
+    holder_count
+)
+SELECT
+    slot_time,
+    token_index,
+    SUM(minted) OVER token_time,
+    SUM(burned) OVER token_time,
+    SUM(holder_change) OVER token_time
+FROM block_changes
+WINDOW token_time AS (PARTITION BY token_index ORDER BY slot_time);
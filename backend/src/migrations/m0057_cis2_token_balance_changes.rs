@@ -0,0 +1,142 @@
+//! Migration introducing the balance history of CIS-2 tokens for accounts.
+//!
+//! The history is rebuilt from the stored CIS-2 token events, replaying the
+//! balance of every account and token from the first event. The events are
+//! read and the changes inserted in batches, keeping only the running balances
+//! in memory.
+
+use super::SchemaVersion;
+use crate::transaction_event::CisEvent;
+use bigdecimal::BigDecimal;
+use sqlx::Executor;
+use std::collections::HashMap;
+use tracing::info;
+
+/// The number of token events read at a time.
+const BATCH_SIZE: i64 = 10_000;
+
+pub async fn run(
+    tx: &mut sqlx::PgTransaction<'_>,
+    next_schema_version: SchemaVersion,
+) -> anyhow::Result<SchemaVersion> {
+    tx.as_mut()
+        .execute(sqlx::raw_sql(include_str!("./m0057_cis2_token_balance_changes.sql")))
+        .await?;
+
+    // Balances by canonical account address and token index.
+    let mut balances: HashMap<(Vec<u8>, i64), BigDecimal> = HashMap::new();
+    let mut last_event_index = i64::MIN;
+    let mut inserted_changes = 0;
+    loop {
+        let events = sqlx::query(
+            "SELECT
+                cis2_token_events.index,
+                cis2_token_events.transaction_index,
+                cis2_token_events.token_index,
+                cis2_token_events.cis2_token_event,
+                blocks.height,
+                blocks.slot_time
+            FROM cis2_token_events
+                JOIN transactions ON transactions.index = cis2_token_events.transaction_index
+                JOIN blocks ON blocks.height = transactions.block_height
+            WHERE cis2_token_events.index > $1
+            ORDER BY cis2_token_events.index ASC
+            LIMIT $2",
+        )
+        .bind(last_event_index)
+        .bind(BATCH_SIZE)
+        .fetch_all(tx.as_mut())
+        .await?;
+        let Some(last_event) = events.last() else {
+            break;
+        };
+        last_event_index = sqlx::Row::try_get(last_event, "index")?;
+
+        let mut canonical_addresses = Vec::new();
+        let mut token_indices = Vec::new();
+        let mut change_types = Vec::new();
+        let mut amounts = Vec::new();
+        let mut account_balances = Vec::new();
+        let mut transaction_indices = Vec::new();
+        let mut block_heights = Vec::new();
+        let mut slot_times = Vec::new();
+        for row in events.iter() {
+            let transaction_index: i64 = sqlx::Row::try_get(row, "transaction_index")?;
+            let token_index: i64 = sqlx::Row::try_get(row, "token_index")?;
+            let event: sqlx::types::Json<CisEvent> = sqlx::Row::try_get(row, "cis2_token_event")?;
+            let block_height: i64 = sqlx::Row::try_get(row, "height")?;
+            let slot_time: chrono::DateTime<chrono::Utc> = sqlx::Row::try_get(row, "slot_time")?;
+            for (address, change_type, amount) in event.0.account_balance_changes() {
+                let canonical_address = address.get_canonical_address().0.to_vec();
+                let balance = balances.entry((canonical_address.clone(), token_index)).or_default();
+                *balance += &amount;
+                canonical_addresses.push(canonical_address);
+                token_indices.push(token_index);
+                change_types.push(change_type.to_string());
+                amounts.push(amount);
+                account_balances.push(balance.clone());
+                transaction_indices.push(transaction_index);
+                block_heights.push(block_height);
+                slot_times.push(slot_time);
+            }
+        }
+        // Accounts are only tracked when they exist, matching the balances in
+        // `account_tokens`.
+        inserted_changes += sqlx::query(
+            "INSERT INTO cis2_token_balance_changes (
+                account_index,
+                token_index,
+                change_type,
+                amount,
+                account_balance,
+                transaction_index,
+                block_height,
+                slot_time
+            )
+            SELECT
+                accounts.index,
+                changes.token_index,
+                changes.change_type::cis2_balance_change_type,
+                changes.amount,
+                changes.account_balance,
+                changes.transaction_index,
+                changes.block_height,
+                changes.slot_time
+            FROM UNNEST(
+                $1::BYTEA[],
+                $2::BIGINT[],
+                $3::TEXT[],
+                $4::NUMERIC[],
+                $5::NUMERIC[],
+                $6::BIGINT[],
+                $7::BIGINT[],
+                $8::TIMESTAMPTZ[]
+            ) WITH ORDINALITY AS changes(
+                canonical_address,
+                token_index,
+                change_type,
+                amount,
+                account_balance,
+                transaction_index,
+                block_height,
+                slot_time,
+                position
+            )
+                JOIN accounts ON accounts.canonical_address = changes.canonical_address
+            ORDER BY changes.position",
+        )
+        .bind(canonical_addresses)
+        .bind(token_indices)
+        .bind(change_types)
+        .bind(amounts)
+        .bind(account_balances)
+        .bind(transaction_indices)
+        .bind(block_heights)
+        .bind(slot_times)
+        .execute(tx.as_mut())
+        .await?
+        .rows_affected();
+    }
+    info!("Built the CIS-2 token balance history of {} changes", inserted_changes);
+    Ok(next_schema_version)
+}
@@ -0,0 +1,60 @@
+-- Type of a change to the CIS-2 token balance of an account.
+CREATE TYPE cis2_balance_change_type AS ENUM (
+    'Mint',
+    'Burn',
+    'TransferIn',
+    'TransferOut'
+);
+
+-- Ledger of the changes to the CIS-2 token balance of each account, with a change for every mint,
+-- burn and transfer event. A transfer results in a change for both the sender and the receiver.
+-- Only balances of accounts are tracked, matching `account_tokens`.
+CREATE TABLE cis2_token_balance_changes (
+    -- Unique identifier for each change, ordering the changes of an account and token.
+    id
+        BIGINT
+        GENERATED ALWAYS AS IDENTITY
+        PRIMARY KEY,
+    -- Index of the account holding the token.
+    account_index
+        BIGINT
+        NOT NULL
+        REFERENCES accounts(index),
+    -- Index of the token.
+    token_index
+        BIGINT
+        NOT NULL
+        REFERENCES tokens(index),
+    -- Type of the change.
+    change_type
+        cis2_balance_change_type
+        NOT NULL,
+    -- Change of the balance, negative when tokens left the account.
+    amount
+        NUMERIC
+        NOT NULL,
+    -- The balance of the account after the change.
+    account_balance
+        NUMERIC
+        NOT NULL,
+    -- Index of the transaction with the token event.
+    transaction_index
+        BIGINT
+        NOT NULL
+        REFERENCES transactions(index),
+    -- Height of the block with the transaction.
+    block_height
+        BIGINT
+        NOT NULL,
+    -- Slot time of the block with the transaction.
+    slot_time
+        TIMESTAMPTZ
+        NOT NULL
+);
+
+-- Index for the balance history of an account, optionally for one token, and for looking up the
+-- balance of an account and token at a given height or time.
+CREATE INDEX cis2_token_balance_changes_account_token_idx
+    ON cis2_token_balance_changes (account_index, token_index, id);
+CREATE INDEX cis2_token_balance_changes_account_idx
+    ON cis2_token_balance_changes (account_index, id);
@@ -0,0 +1,48 @@
+-- The metadata JSON of the CIS-2 token fetched from the metadata URL by the indexer, when fetching
+-- token metadata is enabled. Is NULL until fetched, and when the metadata failed to be fetched or
+-- is not a JSON object.
+ALTER TABLE tokens
+    ADD COLUMN metadata
+        JSONB
+        NULL,
+    -- The metadata URL the metadata got fetched from. The metadata is fetched again when the token
+    -- gets a new metadata URL.
+    ADD COLUMN metadata_fetched_url
+        TEXT
+        NULL,
+    -- The SHA-256 checksum in hex of the metadata at the metadata URL, when provided by the
+    -- contract. Metadata not matching the checksum is not stored.
+    ADD COLUMN metadata_hash
+        TEXT
+        NULL,
+    -- The number of successive failures fetching the metadata from the metadata URL.
+    ADD COLUMN metadata_fetch_failures
+        INT
+        NOT NULL
+        DEFAULT 0,
+    -- When to fetch the metadata again after failing to fetch it. NULL when not failed.
+    ADD COLUMN metadata_retry_after
+        TIMESTAMPTZ
+        NULL;
+
+-- The checksum of the metadata of the tokens indexed before, from their last metadata event.
+UPDATE tokens
+SET metadata_hash = (
+    SELECT cis2_token_event -> 'TokenMetadata' -> 'metadata_url' ->> 'hash'
+    FROM cis2_token_events
+    WHERE cis2_token_events.token_index = tokens.index
+        AND cis2_token_event ? 'TokenMetadata'
+    ORDER BY index_per_token DESC
+    LIMIT 1
+)
+WHERE metadata_url IS NOT NULL;
+
+-- Allows for filtering the tokens of a collection by the attributes in the metadata using the
+-- containment operator `@>`.
+CREATE INDEX tokens_metadata_attributes_idx
+    ON tokens USING GIN ((metadata -> 'attributes') jsonb_path_ops);
+
+-- Index for the tokens waiting for their metadata to be fetched.
+CREATE INDEX tokens_metadata_pending_idx
+    ON tokens (index)
+    WHERE metadata_url IS DISTINCT FROM metadata_fetched_url;
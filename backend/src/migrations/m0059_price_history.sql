@@ -0,0 +1,16 @@
+-- Prices of CCD and tokens recorded by the indexer from the configured price sources, with a row
+-- per asset and currency every time prices are recorded.
+CREATE TABLE price_history (
+    -- The asset being priced: `CCD`, the token id of a protocol level token, the token address of a
+    -- CIS-2 token or the ISO 4217 code of a fiat currency.
+    asset TEXT NOT NULL,
+    -- ISO 4217 code of the fiat currency of the price.
+    currency TEXT NOT NULL,
+    -- Time at which the price got recorded.
+    recorded_at TIMESTAMPTZ NOT NULL,
+    -- Price of one whole unit of the asset in the currency.
+    price DOUBLE PRECISION NOT NULL,
+    -- Name of the price source providing the price.
+    source TEXT NOT NULL,
+    PRIMARY KEY (asset, currency, recorded_at)
+);
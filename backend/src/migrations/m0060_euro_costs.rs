@@ -0,0 +1,46 @@
+//! Migration adding the MicroCcdPerEuro exchange rate in force at each block
+//! and the cumulative transaction fees in CCD and EUR to the blocks.
+//!
+//! The exchange rate at genesis is not stored in the database and is fetched
+//! from the node, while later changes of the rate are found in the effective
+//! chain updates.
+
+use super::SchemaVersion;
+use crate::indexer::micro_ccd_per_euro;
+use anyhow::Context;
+use bigdecimal::BigDecimal;
+use concordium_rust_sdk::v2::{self, BlockIdentifier};
+use sqlx::Executor;
+
+/// Run database migration adding the exchange rate and cumulative fees to the
+/// blocks.
+pub async fn run(
+    tx: &mut sqlx::PgTransaction<'_>,
+    endpoints: &[v2::Endpoint],
+    next_schema_version: SchemaVersion,
+) -> anyhow::Result<SchemaVersion> {
+    tx.as_mut().execute(sqlx::raw_sql(include_str!("./m0060_euro_costs.sql"))).await?;
+
+    let endpoint = endpoints.first().context(format!(
+        "Migration '{}' must be provided access to a Concordium node",
+        next_schema_version
+    ))?;
+    let mut client = v2::Client::new(endpoint.clone()).await?;
+    let genesis_chain_parameters = client
+        .get_block_chain_parameters(BlockIdentifier::AbsoluteHeight(0.into()))
+        .await?
+        .response;
+    let genesis_rate = micro_ccd_per_euro(&genesis_chain_parameters);
+
+    sqlx::query(
+        "INSERT INTO micro_ccd_per_euro_changes (effective_time, numerator, denominator)
+        VALUES ('-infinity', $1, $2)",
+    )
+    .bind(BigDecimal::from(genesis_rate.numerator()))
+    .bind(BigDecimal::from(genesis_rate.denominator()))
+    .execute(tx.as_mut())
+    .await?;
+
+    tx.as_mut().execute(sqlx::raw_sql(include_str!("./m0060_euro_costs_backfill.sql"))).await?;
+    Ok(next_schema_version)
+}
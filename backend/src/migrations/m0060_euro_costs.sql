@@ -0,0 +1,29 @@
+-- The MicroCcdPerEuro exchange rate in force at the block, as a fraction of microCCD per EUR, and
+-- the total transaction fees up to and including the block in CCD and in EUR, where the fees of
+-- each block are converted using the exchange rate of the block.
+ALTER TABLE blocks
+    ADD COLUMN micro_ccd_per_euro_numerator
+        NUMERIC,
+    ADD COLUMN micro_ccd_per_euro_denominator
+        NUMERIC,
+    ADD COLUMN cumulative_ccd_fees
+        BIGINT,
+    ADD COLUMN cumulative_euro_fees
+        NUMERIC;
+
+-- The exchange rates in force from the effective time, filled with the rate at genesis and the
+-- effective chain updates of the rate by the migration.
+CREATE TEMPORARY TABLE micro_ccd_per_euro_changes (
+    effective_time TIMESTAMPTZ NOT NULL,
+    numerator NUMERIC NOT NULL,
+    denominator NUMERIC NOT NULL
+) ON COMMIT DROP;
+
+INSERT INTO micro_ccd_per_euro_changes
+SELECT
+    effective_time,
+    (payload -> 'MicroCcdPerEuro' -> 'exchange_rate' ->> 'numerator')::NUMERIC,
+    (payload -> 'MicroCcdPerEuro' -> 'exchange_rate' ->> 'denominator')::NUMERIC
+FROM chain_update_queue
+WHERE update_type = 'UpdateMicroGtuPerEuro'
+    AND state = 'Effective';
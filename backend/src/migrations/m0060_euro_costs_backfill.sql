@@ -0,0 +1,40 @@
+-- Set the exchange rate of every block to the last rate effective at or before the block.
+UPDATE blocks
+SET (micro_ccd_per_euro_numerator, micro_ccd_per_euro_denominator) = (
+    SELECT numerator, denominator
+    FROM micro_ccd_per_euro_changes
+    WHERE micro_ccd_per_euro_changes.effective_time <= blocks.slot_time
+    ORDER BY micro_ccd_per_euro_changes.effective_time DESC
+    LIMIT 1
+);
+
+-- Accumulate the transaction fees of every block.
+WITH block_fees AS (
+    SELECT block_height, SUM(ccd_cost) AS fees
+    FROM transactions
+    GROUP BY block_height
+),
+cumulative_fees AS (
+    SELECT
+        blocks.height,
+        SUM(COALESCE(block_fees.fees, 0)) OVER (ORDER BY blocks.height) AS ccd_fees,
+        SUM(ROUND(
+            COALESCE(block_fees.fees, 0) * blocks.micro_ccd_per_euro_denominator
+                / blocks.micro_ccd_per_euro_numerator,
+            12
+        )) OVER (ORDER BY blocks.height) AS euro_fees
+    FROM blocks
+        LEFT JOIN block_fees ON block_fees.block_height = blocks.height
+)
+UPDATE blocks
+SET
+    cumulative_ccd_fees = cumulative_fees.ccd_fees,
+    cumulative_euro_fees = cumulative_fees.euro_fees
+FROM cumulative_fees
+WHERE blocks.height = cumulative_fees.height;
+
+ALTER TABLE blocks
+    ALTER COLUMN micro_ccd_per_euro_numerator SET NOT NULL,
+    ALTER COLUMN micro_ccd_per_euro_denominator SET NOT NULL,
+    ALTER COLUMN cumulative_ccd_fees SET NOT NULL,
+    ALTER COLUMN cumulative_euro_fees SET NOT NULL;
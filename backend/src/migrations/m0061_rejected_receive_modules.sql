@@ -0,0 +1,28 @@
+-- Store the module of the contract instance at the time of the transaction in the reject reason of
+-- rejected contract updates, used for decoding the message with the schema of that module. The
+-- module is the one replaced by the first upgrade of the contract after the transaction, or the
+-- current module when the contract was not upgraded since.
+UPDATE transactions
+SET reject = jsonb_set(
+    reject,
+    '{RejectedReceive,module_reference}',
+    to_jsonb(COALESCE(
+        (
+            SELECT from_module_reference
+            FROM contract_upgrades
+            WHERE contract_index = (reject->'RejectedReceive'->'contract_address'->>'index')::BIGINT
+                AND contract_sub_index = (reject->'RejectedReceive'->'contract_address'->>'sub_index')::BIGINT
+                AND contract_upgrades.transaction_index > transactions.index
+            ORDER BY contract_upgrades.transaction_index, trace_element_index
+            LIMIT 1
+        ),
+        (
+            SELECT module_reference
+            FROM contracts
+            WHERE index = (reject->'RejectedReceive'->'contract_address'->>'index')::BIGINT
+                AND sub_index = (reject->'RejectedReceive'->'contract_address'->>'sub_index')::BIGINT
+        )
+    )::TEXT)
+)
+WHERE index IN (SELECT transaction_index FROM contract_reject_transactions)
+    AND reject ? 'RejectedReceive';
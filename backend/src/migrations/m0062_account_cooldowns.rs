@@ -0,0 +1,66 @@
+//! Migration adding the stake of accounts in cooldown.
+//!
+//! The cooldowns of the accounts with stake in cooldown at the latest indexed
+//! block are read from the node, while later cooldowns are read by the indexer.
+
+use super::SchemaVersion;
+use crate::indexer::PreparedAccountCooldowns;
+use anyhow::Context;
+use concordium_rust_sdk::{
+    types::ProtocolVersion,
+    v2::{self, BlockIdentifier},
+};
+use futures::TryStreamExt;
+use sqlx::Executor;
+use std::collections::BTreeSet;
+
+/// Run database migration adding the stake of accounts in cooldown.
+pub async fn run(
+    tx: &mut sqlx::PgTransaction<'_>,
+    endpoints: &[v2::Endpoint],
+    next_schema_version: SchemaVersion,
+) -> anyhow::Result<SchemaVersion> {
+    tx.as_mut().execute(sqlx::raw_sql(include_str!("./m0062_account_cooldowns.sql"))).await?;
+
+    let latest_height: Option<i64> =
+        sqlx::query_scalar("SELECT height FROM blocks ORDER BY height DESC LIMIT 1")
+            .fetch_optional(tx.as_mut())
+            .await?;
+    let Some(latest_height) = latest_height else {
+        // No blocks processed yet, meaning no data to migrate.
+        return Ok(next_schema_version);
+    };
+    let latest_block = BlockIdentifier::AbsoluteHeight(u64::try_from(latest_height)?.into());
+    let endpoint = endpoints.first().context(format!(
+        "Migration '{}' must be provided access to a Concordium node",
+        next_schema_version
+    ))?;
+    let mut client = v2::Client::new(endpoint.clone()).await?;
+    if client.get_block_info(latest_block).await?.response.protocol_version < ProtocolVersion::P7 {
+        // Stake cooldowns were introduced in protocol version 7.
+        return Ok(next_schema_version);
+    }
+
+    let mut accounts = BTreeSet::new();
+    let cooldown_accounts: Vec<_> =
+        client.get_cooldown_accounts(latest_block).await?.response.try_collect().await?;
+    accounts.extend(cooldown_accounts.into_iter().map(|pending| pending.account_index));
+    let pre_cooldown_accounts: Vec<_> =
+        client.get_pre_cooldown_accounts(latest_block).await?.response.try_collect().await?;
+    accounts.extend(pre_cooldown_accounts);
+    let pre_pre_cooldown_accounts: Vec<_> =
+        client.get_pre_pre_cooldown_accounts(latest_block).await?.response.try_collect().await?;
+    accounts.extend(pre_pre_cooldown_accounts);
+
+    for account in accounts {
+        PreparedAccountCooldowns::prepare(
+            &mut client,
+            v2::AccountIdentifier::Index(account),
+            latest_block,
+        )
+        .await?
+        .save(tx)
+        .await?;
+    }
+    Ok(next_schema_version)
+}
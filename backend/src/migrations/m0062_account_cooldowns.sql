@@ -0,0 +1,20 @@
+-- The stake of accounts in cooldown, read from the node whenever the account configures its stake.
+-- Stake removed from a validator or delegator from protocol version 7 is in cooldown until released
+-- at the first payday after the end time.
+CREATE TABLE account_cooldowns (
+    -- The account index (row in the `accounts` table) of the account with the stake in cooldown.
+    account_index
+        BIGINT
+        NOT NULL
+        REFERENCES accounts,
+    -- The time the cooldown ends, which is projected until the cooldown starts at the next payday.
+    end_time
+        TIMESTAMPTZ
+        NOT NULL,
+    -- The amount of stake in cooldown in microCCD.
+    amount
+        BIGINT
+        NOT NULL
+);
+
+CREATE INDEX account_cooldowns_idx ON account_cooldowns (account_index, end_time);
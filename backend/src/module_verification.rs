@@ -0,0 +1,495 @@
+//! Verification of the source code behind smart contract modules built using
+//! the verifiable builds of `cargo concordium`.
+//!
+//! A verifiable build embeds the build information in the custom section
+//! `concordium-build-info` of the module: the SHA-256 hash of the source
+//! archive, the Docker image and the command used for building. A source
+//! archive is verified by checking it against the hash and then either:
+//!
+//! - Building the archive using the [`ModuleBuilder`] and checking the built
+//!   module against the module reference.
+//! - Checking an attestation signed by a trusted attester, where the attester
+//!   signs the module reference followed by the hash of the archive, each as 32
+//!   bytes.
+
+use crate::graphql_api::ModuleVerificationMethod;
+use concordium_rust_sdk::{
+    common,
+    smart_contracts::engine::utils::{get_build_info, VersionedBuildInfo},
+    types::smart_contracts::WasmModule,
+};
+use ed25519_dalek::{Signature, VerifyingKey};
+use sha2::{Digest, Sha256};
+use std::{
+    fmt::Debug,
+    fs::File,
+    io::Read,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+};
+use tonic::async_trait;
+
+/// Path in the build container where the built module is written.
+const BUILT_MODULE_PATH: &str = "/b/out/module.wasm.v1";
+/// Memory available to a build container.
+const BUILD_MEMORY_LIMIT: &str = "4g";
+/// Number of CPUs available to a build container.
+const BUILD_CPU_LIMIT: &str = "2";
+/// Number of processes allowed in a build container.
+const BUILD_PIDS_LIMIT: &str = "512";
+/// User and group running the build in the container, the unprivileged
+/// `nobody` user.
+const BUILD_USER: &str = "65534:65534";
+/// The most time spent building, after which the build container is killed.
+const BUILD_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+/// Time between checking whether a build has finished.
+const BUILD_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// The most bytes of the build output included in the error of a failed build.
+const BUILD_ERROR_OUTPUT_LIMIT: usize = 16 * 1024;
+
+/// The build information embedded in a module by a verifiable build.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleBuildInfo {
+    /// Hex encoded SHA-256 hash of the source archive.
+    pub archive_hash:  String,
+    /// The Docker image used for building.
+    pub image:         String,
+    /// The command used for building, run in the extracted archive.
+    pub build_command: Vec<String>,
+    /// Link to where the source archive is published, if provided.
+    pub source_link:   Option<String>,
+}
+
+impl ModuleBuildInfo {
+    /// Read the build information from the source of a module. `None` if the
+    /// module is not built using a verifiable build.
+    pub fn from_module_source(source: &[u8]) -> Option<Self> {
+        let VersionedBuildInfo::V0(build_info) = get_build_info(source).ok()?;
+        Some(Self {
+            archive_hash:  build_info.archive_hash.to_string(),
+            image:         build_info.image,
+            build_command: build_info.build_command,
+            source_link:   build_info.source_link,
+        })
+    }
+}
+
+/// Build step producing a module from a source archive.
+#[async_trait]
+pub trait ModuleBuilder: Debug + Send + Sync {
+    /// Build the source archive using the build information, returning the
+    /// versioned Wasm module.
+    async fn build(
+        &self,
+        build_info: &ModuleBuildInfo,
+        source_archive: &[u8],
+    ) -> anyhow::Result<Vec<u8>>;
+}
+
+/// Builds modules in a Docker container without network access, using the
+/// image from the build information. The image is chosen by whoever deployed
+/// the module, so only images matching the allowed images are used and images
+/// are never pulled. The build runs as an unprivileged user without
+/// capabilities, and the container has a read-only root filesystem, limited
+/// memory, CPUs and processes, and is killed when the build takes too long.
+#[derive(Debug)]
+pub struct DockerModuleBuilder {
+    /// The Docker executable.
+    docker:         PathBuf,
+    /// The images allowed for builds, where a trailing `*` matches any
+    /// suffix, such as `docker.io/concordium/verifiable-sc:*`.
+    allowed_images: Vec<String>,
+}
+
+impl DockerModuleBuilder {
+    pub fn new(docker: PathBuf, allowed_images: Vec<String>) -> Self {
+        Self {
+            docker,
+            allowed_images,
+        }
+    }
+
+    /// Whether the image matches one of the allowed images.
+    fn is_allowed_image(&self, image: &str) -> bool {
+        self.allowed_images.iter().any(|allowed| match allowed.strip_suffix('*') {
+            Some(prefix) => image.starts_with(prefix),
+            None => image == allowed,
+        })
+    }
+}
+
+#[async_trait]
+impl ModuleBuilder for DockerModuleBuilder {
+    async fn build(
+        &self,
+        build_info: &ModuleBuildInfo,
+        source_archive: &[u8],
+    ) -> anyhow::Result<Vec<u8>> {
+        if !self.is_allowed_image(&build_info.image) {
+            anyhow::bail!("Image {} is not allowed for builds", build_info.image);
+        }
+        let docker = self.docker.clone();
+        let build_info = build_info.clone();
+        let source_archive = source_archive.to_vec();
+        tokio::task::spawn_blocking(move || build_in_docker(&docker, &build_info, &source_archive))
+            .await?
+    }
+}
+
+fn build_in_docker(
+    docker: &Path,
+    build_info: &ModuleBuildInfo,
+    source_archive: &[u8],
+) -> anyhow::Result<Vec<u8>> {
+    let name = format!(
+        "ccdscan-build-{}-{}",
+        build_info.archive_hash,
+        chrono::Utc::now().timestamp_micros()
+    );
+    let dir = std::env::temp_dir().join(&name);
+    std::fs::create_dir_all(dir.join("out"))?;
+    let result = (|| {
+        // The build user of the container extracts the archive into the directory and
+        // writes the built module.
+        for writable in [dir.clone(), dir.join("out")] {
+            std::fs::set_permissions(writable, std::fs::Permissions::from_mode(0o777))?;
+        }
+        std::fs::write(dir.join("source.tar"), source_archive)?;
+        let build_command = build_info
+            .build_command
+            .iter()
+            .map(|argument| shell_quote(argument))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let script = format!(
+            "mkdir /b/source && cd /b/source && tar -xf /b/source.tar && {} --out {}",
+            build_command, BUILT_MODULE_PATH
+        );
+        // The output is written to a file, such that a build cannot block on a full
+        // pipe while waiting for it to finish.
+        let output_path = dir.join("output.log");
+        let output = File::create(&output_path)?;
+        let mut child = Command::new(docker)
+            .args(["run", "--rm", "--name", &name, "--network", "none", "--read-only"])
+            .args(["--tmpfs", "/tmp", "--memory", BUILD_MEMORY_LIMIT, "--cpus", BUILD_CPU_LIMIT])
+            .args(["--pids-limit", BUILD_PIDS_LIMIT, "--pull", "never", "--user", BUILD_USER])
+            .args(["--cap-drop", "ALL", "--security-opt", "no-new-privileges", "--volume"])
+            .arg(format!("{}:/b", dir.display()))
+            .arg(&build_info.image)
+            .args(["/bin/sh", "-c", &script])
+            .stdin(Stdio::null())
+            .stdout(output.try_clone()?)
+            .stderr(output)
+            .spawn()?;
+        let deadline = Instant::now() + BUILD_TIMEOUT;
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                // Killing the client does not stop the container.
+                let _ = Command::new(docker).args(["kill", &name]).output();
+                let _ = child.kill();
+                let _ = child.wait();
+                anyhow::bail!("Build timed out after {} seconds", BUILD_TIMEOUT.as_secs());
+            }
+            std::thread::sleep(BUILD_POLL_INTERVAL);
+        };
+        if !status.success() {
+            let mut output = Vec::new();
+            File::open(&output_path)?.read_to_end(&mut output)?;
+            let tail = &output[output.len().saturating_sub(BUILD_ERROR_OUTPUT_LIMIT)..];
+            anyhow::bail!("Build failed: {}", String::from_utf8_lossy(tail));
+        }
+        Ok(std::fs::read(dir.join("out/module.wasm.v1"))?)
+    })();
+    let _ = std::fs::remove_dir_all(&dir);
+    result
+}
+
+fn shell_quote(argument: &str) -> String { format!("'{}'", argument.replace('\'', "'\\''")) }
+
+/// Attestation of a source archive, signed by an external attester.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attestation {
+    /// Hex encoded Ed25519 public key of the attester.
+    pub attester_key: String,
+    /// Hex encoded Ed25519 signature of the module reference followed by the
+    /// hash of the source archive.
+    pub signature:    String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum VerificationError {
+    #[error("Source archive does not match the archive hash in the build information.")]
+    ArchiveHashMismatch,
+    #[error("Source archive is not a valid tar archive.")]
+    InvalidArchive,
+    #[error("Attester is not trusted.")]
+    UntrustedAttester,
+    #[error("Invalid attestation signature.")]
+    InvalidSignature,
+    #[error("Local builds are not enabled.")]
+    LocalBuildDisabled,
+    #[error("Failed building the source archive: {0}")]
+    BuildFailed(anyhow::Error),
+    #[error("Built module does not match the module reference.")]
+    BuiltModuleMismatch,
+}
+
+/// A text file in a verified source archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceFile {
+    pub path:    String,
+    pub content: String,
+}
+
+/// A source archive verified against a module.
+#[derive(Debug)]
+pub struct VerifiedSource {
+    pub method:      ModuleVerificationMethod,
+    pub attestation: Option<Attestation>,
+    /// The text files of the archive, ordered by path. Binary files are left
+    /// out.
+    pub files:       Vec<SourceFile>,
+}
+
+/// Verify a source archive against a module reference and the build
+/// information embedded in the module. The attestation is checked when
+/// provided, otherwise the archive is built.
+pub async fn verify_source(
+    builder: Option<&dyn ModuleBuilder>,
+    trusted_attesters: &[String],
+    module_reference: &str,
+    build_info: &ModuleBuildInfo,
+    source_archive: &[u8],
+    attestation: Option<Attestation>,
+) -> Result<VerifiedSource, VerificationError> {
+    let archive_hash: [u8; 32] = Sha256::digest(source_archive).into();
+    if hex::encode(archive_hash) != build_info.archive_hash {
+        return Err(VerificationError::ArchiveHashMismatch);
+    }
+    let files = read_source_files(source_archive)?;
+    let method = if let Some(attestation) = &attestation {
+        verify_attestation(trusted_attesters, module_reference, &archive_hash, attestation)?;
+        ModuleVerificationMethod::Attestation
+    } else {
+        let builder = builder.ok_or(VerificationError::LocalBuildDisabled)?;
+        let built = builder
+            .build(build_info, source_archive)
+            .await
+            .map_err(VerificationError::BuildFailed)?;
+        let built_module: WasmModule = common::from_bytes(&mut std::io::Cursor::new(&built))
+            .map_err(VerificationError::BuildFailed)?;
+        if built_module.get_module_ref().to_string() != module_reference {
+            return Err(VerificationError::BuiltModuleMismatch);
+        }
+        ModuleVerificationMethod::LocalBuild
+    };
+    Ok(VerifiedSource {
+        method,
+        attestation,
+        files,
+    })
+}
+
+fn verify_attestation(
+    trusted_attesters: &[String],
+    module_reference: &str,
+    archive_hash: &[u8; 32],
+    attestation: &Attestation,
+) -> Result<(), VerificationError> {
+    let attester_key = attestation.attester_key.to_lowercase();
+    if !trusted_attesters.iter().any(|trusted| trusted.to_lowercase() == attester_key) {
+        return Err(VerificationError::UntrustedAttester);
+    }
+    let attester_key: [u8; 32] = hex::decode(&attester_key)
+        .ok()
+        .and_then(|key| key.try_into().ok())
+        .ok_or(VerificationError::UntrustedAttester)?;
+    let attester_key = VerifyingKey::from_bytes(&attester_key)
+        .map_err(|_| VerificationError::UntrustedAttester)?;
+    let signature = hex::decode(&attestation.signature)
+        .ok()
+        .and_then(|signature| Signature::from_slice(&signature).ok())
+        .ok_or(VerificationError::InvalidSignature)?;
+    let mut message =
+        hex::decode(module_reference).map_err(|_| VerificationError::InvalidSignature)?;
+    message.extend_from_slice(archive_hash);
+    attester_key
+        .verify_strict(&message, &signature)
+        .map_err(|_| VerificationError::InvalidSignature)
+}
+
+/// Read the text files of a tar archive.
+fn read_source_files(archive: &[u8]) -> Result<Vec<SourceFile>, VerificationError> {
+    let mut files = Vec::new();
+    let mut archive = tar::Archive::new(archive);
+    for entry in archive.entries().map_err(|_| VerificationError::InvalidArchive)? {
+        let mut entry = entry.map_err(|_| VerificationError::InvalidArchive)?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry
+            .path()
+            .map_err(|_| VerificationError::InvalidArchive)?
+            .to_string_lossy()
+            .into_owned();
+        let mut content = Vec::new();
+        entry.read_to_end(&mut content).map_err(|_| VerificationError::InvalidArchive)?;
+        // Binary files are left out.
+        if let Ok(content) = String::from_utf8(content) {
+            // PostgreSQL text does not support NUL characters.
+            if !content.contains('\0') {
+                files.push(SourceFile {
+                    path,
+                    content,
+                });
+            }
+        }
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    /// Stub build step returning a fixed module.
+    #[derive(Debug)]
+    struct StubBuilder(Vec<u8>);
+
+    #[async_trait]
+    impl ModuleBuilder for StubBuilder {
+        async fn build(&self, _: &ModuleBuildInfo, _: &[u8]) -> anyhow::Result<Vec<u8>> {
+            Ok(self.0.clone())
+        }
+    }
+
+    fn archive() -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut append = |path: &str, content: &[u8]| {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len().try_into().unwrap());
+            builder.append_data(&mut header, path, content).unwrap();
+        };
+        append("src/lib.rs", b"fn main() {}");
+        // Paths longer than 100 bytes are stored in a separate entry.
+        append(&format!("{}/Cargo.toml", "very-long".repeat(12)), b"[package]");
+        append("logo.png", &[0xff, 0xd8]);
+        builder.into_inner().unwrap()
+    }
+
+    fn build_info(archive: &[u8]) -> ModuleBuildInfo {
+        ModuleBuildInfo {
+            archive_hash:  hex::encode(Sha256::digest(archive)),
+            image:         "docker.io/concordium/verifiable-sc:1.70.0".to_string(),
+            build_command: vec!["cargo".to_string(), "concordium".to_string()],
+            source_link:   None,
+        }
+    }
+
+    /// Versioned module with an empty Wasm module as source.
+    fn module() -> (Vec<u8>, String) {
+        let module = vec![0, 0, 0, 1, 0, 0, 0, 8, 0x00, 0x61, 0x73, 0x6d, 1, 0, 0, 0];
+        let parsed: WasmModule = common::from_bytes(&mut std::io::Cursor::new(&module)).unwrap();
+        let module_reference = parsed.get_module_ref().to_string();
+        (module, module_reference)
+    }
+
+    #[test]
+    fn test_allowed_images() {
+        let builder = DockerModuleBuilder::new(PathBuf::from("docker"), vec![
+            "docker.io/concordium/verifiable-sc:*".to_string(),
+            "localhost/builder:1.0".to_string(),
+        ]);
+        assert!(builder.is_allowed_image("docker.io/concordium/verifiable-sc:1.70.0"));
+        assert!(builder.is_allowed_image("localhost/builder:1.0"));
+        assert!(!builder.is_allowed_image("localhost/builder:1.1"));
+        assert!(!builder.is_allowed_image("docker.io/attacker/verifiable-sc:1.70.0"));
+    }
+
+    #[test]
+    fn test_read_source_files() {
+        let files = read_source_files(&archive()).unwrap();
+        assert_eq!(files, vec![
+            SourceFile {
+                path:    "src/lib.rs".to_string(),
+                content: "fn main() {}".to_string(),
+            },
+            SourceFile {
+                path:    format!("{}/Cargo.toml", "very-long".repeat(12)),
+                content: "[package]".to_string(),
+            },
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_verify_local_build() {
+        let archive = archive();
+        let (module, module_reference) = module();
+        let builder = StubBuilder(module);
+        let builder: Option<&dyn ModuleBuilder> = Some(&builder);
+        let verified =
+            verify_source(builder, &[], &module_reference, &build_info(&archive), &archive, None)
+                .await
+                .unwrap();
+        assert_eq!(verified.method, ModuleVerificationMethod::LocalBuild);
+        assert_eq!(verified.files.len(), 2);
+
+        // Archives not matching the build information are rejected before building.
+        let result =
+            verify_source(builder, &[], &module_reference, &build_info(b"other"), &archive, None)
+                .await;
+        assert!(matches!(result, Err(VerificationError::ArchiveHashMismatch)));
+
+        // Built modules must match the module reference.
+        let other_reference = "00".repeat(32);
+        let result =
+            verify_source(builder, &[], &other_reference, &build_info(&archive), &archive, None)
+                .await;
+        assert!(matches!(result, Err(VerificationError::BuiltModuleMismatch)));
+    }
+
+    #[tokio::test]
+    async fn test_verify_attestation() {
+        let archive = archive();
+        let (_, module_reference) = module();
+        let signing_key = SigningKey::from_bytes(&[7; 32]);
+        let attester_key = hex::encode(signing_key.verifying_key().as_bytes());
+        let mut message = hex::decode(&module_reference).unwrap();
+        message.extend(Sha256::digest(&archive));
+        let attestation = Attestation {
+            attester_key: attester_key.clone(),
+            signature:    hex::encode(signing_key.sign(&message).to_bytes()),
+        };
+
+        let verified = verify_source(
+            None,
+            &[attester_key],
+            &module_reference,
+            &build_info(&archive),
+            &archive,
+            Some(attestation.clone()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(verified.method, ModuleVerificationMethod::Attestation);
+
+        let result = verify_source(
+            None,
+            &[],
+            &module_reference,
+            &build_info(&archive),
+            &archive,
+            Some(attestation),
+        )
+        .await;
+        assert!(matches!(result, Err(VerificationError::UntrustedAttester)));
+    }
+}
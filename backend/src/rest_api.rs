@@ -1,24 +1,51 @@
 //! Module containing the implementation of a service providing the public
 //! facing REST API for `ccdscan-api`.
 
-use crate::graphql_api::{AccountStatementEntryType, ApiServiceConfig};
+use crate::{
+    graphql_api::{
+        schema_cache::ModuleSchemaCache, token_units, AccountStatementEntryType, ApiServiceConfig,
+        ModuleVerificationMethod, PltAccountStatementEntryType,
+    },
+    module_verification::{
+        verify_source, Attestation, DockerModuleBuilder, ModuleBuildInfo, ModuleBuilder,
+        VerificationError,
+    },
+};
 use axum::{
-    extract::{Query, State},
-    http::HeaderName,
+    extract::{DefaultBodyLimit, Path, Query, State},
+    http::{HeaderMap, HeaderName},
     response::{AppendHeaders, IntoResponse},
-    routing::get,
-    Router,
+    routing::{get, post},
+    Json, Router,
 };
 use chrono::{DateTime, TimeDelta, Utc};
-use concordium_rust_sdk::{common::types::Amount, id::types::AccountAddress};
+use concordium_rust_sdk::{
+    base::contracts_common::{schema::VersionedModuleSchema, to_bytes},
+    common::{self, types::Amount},
+    id::types::AccountAddress,
+    smart_contracts::engine::{
+        utils::get_inits,
+        v0, v1,
+        wasm::{
+            parse::parse_skeleton,
+            validate::{validate_module, ValidationConfig},
+        },
+    },
+    types::smart_contracts::{WasmModule, WasmVersion},
+};
 use futures::TryStreamExt as _;
 use prometheus_client::registry::Registry;
 use reqwest::StatusCode;
 use sqlx::PgPool;
-use std::sync::Arc;
+use std::{collections::BTreeSet, sync::Arc};
+use subtle::ConstantTimeEq;
 use tower_http::cors::{Any, CorsLayer};
 use tracing::error;
 
+/// Maximum size of the body when verifying a module source, holding the hex
+/// encoded source archive.
+const MODULE_SOURCE_BODY_LIMIT: usize = 64 * 1024 * 1024;
+
 /// Service providing the router for the REST API.
 #[derive(Debug)]
 pub struct Service {
@@ -32,17 +59,33 @@ pub struct Service {
 #[derive(Debug, Clone)]
 struct RouterState {
     /// Database connection pool.
-    pool:   PgPool,
+    pool:           PgPool,
     /// Configurations for the API.
-    config: Arc<ApiServiceConfig>,
+    config:         Arc<ApiServiceConfig>,
+    /// Build step for verifying module sources, `None` if local builds are
+    /// disabled.
+    module_builder: Option<Arc<dyn ModuleBuilder>>,
+    /// Cache of parsed module schemas shared with the GraphQL API.
+    schema_cache:   Arc<ModuleSchemaCache>,
 }
 
 impl Service {
-    pub fn new(pool: PgPool, config: Arc<ApiServiceConfig>, registry: &mut Registry) -> Self {
+    pub fn new(
+        pool: PgPool,
+        config: Arc<ApiServiceConfig>,
+        schema_cache: Arc<ModuleSchemaCache>,
+        registry: &mut Registry,
+    ) -> Self {
+        let module_builder = config.module_verification_docker.clone().map(|docker| {
+            Arc::new(DockerModuleBuilder::new(docker, config.module_verification_images.clone()))
+                as Arc<dyn ModuleBuilder>
+        });
         Self {
             state:         RouterState {
                 pool,
                 config,
+                module_builder,
+                schema_cache,
             },
             monitor_layer: monitor::MonitorLayer::new(registry.sub_registry_with_prefix("rest")),
         }
@@ -56,6 +99,13 @@ impl Service {
         Router::new()
             .route("/rest/balance-statistics/latest", get(Self::latest_balance_statistics))
             .route("/rest/export/statement", get(Self::export_account_statements))
+            .route("/rest/export/plt-statement", get(Self::export_plt_account_statements))
+            .route("/rest/modules/{module_reference}/schema", post(Self::upload_module_schema))
+            .route(
+                "/rest/modules/{module_reference}/source",
+                post(Self::verify_module_source)
+                    .layer(DefaultBodyLimit::max(MODULE_SOURCE_BODY_LIMIT)),
+            )
             .layer(cors_layer)
             .layer(self.monitor_layer)
             .with_state(self.state)
@@ -111,17 +161,7 @@ impl Service {
         Query(params): Query<ExportAccountStatement>,
         State(state): State<RouterState>,
     ) -> ApiResult<(AppendHeaders<[(HeaderName, String); 2]>, String)> {
-        let to = params.to_time.unwrap_or_else(Utc::now);
-        let account_statements_export_max_days =
-            i64::try_from(state.config.export_statement_max_days).unwrap_or(32);
-        let from = params
-            .from_time
-            .unwrap_or_else(|| to - TimeDelta::days(account_statements_export_max_days));
-        if to - from > TimeDelta::days(account_statements_export_max_days) {
-            return Err(ApiError::ExceedsMaxAllowedDaysForAccountStatementExport(
-                account_statements_export_max_days,
-            ));
-        }
+        let (from, to) = export_period(&state.config, params.from_time, params.to_time)?;
 
         let mut rows = sqlx::query_as!(
             ExportAccountStatementEntry,
@@ -176,6 +216,315 @@ impl Service {
         ]);
         Ok((headers, csv))
     }
+
+    async fn export_plt_account_statements(
+        Query(params): Query<ExportPltAccountStatement>,
+        State(state): State<RouterState>,
+    ) -> ApiResult<(AppendHeaders<[(HeaderName, String); 2]>, String)> {
+        let (from, to) = export_period(&state.config, params.from_time, params.to_time)?;
+        let token = sqlx::query!(
+            "SELECT index, token_id, decimal FROM plt_tokens WHERE LOWER(token_id) = LOWER($1)",
+            params.token_id
+        )
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+
+        let mut rows = sqlx::query!(
+            r#"SELECT
+                plt_account_statements.slot_time as timestamp,
+                plt_account_statements.amount,
+                plt_account_statements.account_balance,
+                plt_account_statements.entry_type as "entry_type: PltAccountStatementEntryType",
+                transactions.hash as transaction_hash
+            FROM plt_account_statements
+                JOIN transactions ON transactions.index = plt_account_statements.transaction_index
+            WHERE
+                plt_account_statements.account_index =
+                    (SELECT index FROM accounts WHERE address = $1)
+                AND plt_account_statements.token_index = $2
+                AND plt_account_statements.slot_time between $3 and $4
+            ORDER BY plt_account_statements.slot_time DESC, plt_account_statements.id DESC"#,
+            params.account_address.to_string(),
+            token.index,
+            from,
+            to
+        )
+        .fetch(&state.pool);
+        let mut csv =
+            format!("Time,Amount ({0}),Balance ({0}),Label,Transaction\n", token.token_id);
+        while let Some(row) = rows.try_next().await? {
+            csv.push_str(
+                format!(
+                    "{},{},{},{},{}\n",
+                    row.timestamp.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+                    token_units(&row.amount, token.decimal).to_plain_string(),
+                    token_units(&row.account_balance, token.decimal).to_plain_string(),
+                    row.entry_type,
+                    row.transaction_hash
+                )
+                .as_str(),
+            )
+        }
+        let filename = format!(
+            "plt-statement-{}-{}_{}-{}.csv",
+            token.token_id,
+            params.account_address,
+            from.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            to.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+        );
+        let headers = AppendHeaders([
+            (axum::http::header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                axum::http::header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ]);
+        Ok((headers, csv))
+    }
+
+    /// Upload a schema for a module deployed without an embedded schema. The
+    /// uploaded Wasm module must hash to the module reference, ensuring the
+    /// schema is provided by someone holding the deployed module, and the
+    /// schema may only describe contracts defined by the module. Values are
+    /// decoded when queried, so the schema applies to past events and
+    /// parameters of every contract using the module as well, and the events
+    /// decoded by the indexer are queued for decoding again.
+    async fn upload_module_schema(
+        Path(module_reference): Path<String>,
+        State(state): State<RouterState>,
+        headers: HeaderMap,
+        Json(body): Json<UploadModuleSchema>,
+    ) -> ApiResult<StatusCode> {
+        authorize(&headers, state.config.schema_upload_token.as_deref())?;
+        let (verified_module_reference, schema) = check_schema_upload(&module_reference, &body)?;
+
+        // Schemas embedded in the module take precedence, while earlier uploads are
+        // replaced.
+        let mut tx = state.pool.begin().await?;
+        let updated = sqlx::query!(
+            "UPDATE smart_contract_modules
+            SET schema = $2, schema_uploaded_at = NOW()
+            WHERE module_reference = $1
+                AND (schema IS NULL OR schema_uploaded_at IS NOT NULL)",
+            verified_module_reference,
+            to_bytes(&schema)
+        )
+        .execute(tx.as_mut())
+        .await?
+        .rows_affected();
+        if updated == 0 {
+            let deployed = sqlx::query_scalar!(
+                "SELECT EXISTS(SELECT 1 FROM smart_contract_modules WHERE module_reference = $1)",
+                verified_module_reference
+            )
+            .fetch_one(&state.pool)
+            .await?
+            .unwrap_or(false);
+            return Err(if deployed {
+                ApiError::EmbeddedSchema
+            } else {
+                ApiError::NotFound
+            });
+        }
+        // Decode the events indexed before the upload again using the schema.
+        sqlx::query!(
+            "INSERT INTO contract_event_redecoding (module_reference, last_index)
+            VALUES ($1, -1)
+            ON CONFLICT (module_reference) DO UPDATE SET last_index = -1",
+            verified_module_reference
+        )
+        .execute(tx.as_mut())
+        .await?;
+        tx.commit().await?;
+        state.schema_cache.invalidate(&verified_module_reference);
+        Ok(StatusCode::NO_CONTENT)
+    }
+
+    /// Verify the source archive of a module built using a verifiable build,
+    /// either by building the archive or by checking the attestation of a
+    /// trusted attester. A new verification replaces the earlier one.
+    async fn verify_module_source(
+        Path(module_reference): Path<String>,
+        State(state): State<RouterState>,
+        headers: HeaderMap,
+        Json(body): Json<VerifyModuleSource>,
+    ) -> ApiResult<StatusCode> {
+        authorize(&headers, state.config.module_verification_token.as_deref())?;
+        let module_reference = module_reference.to_lowercase();
+        let row = sqlx::query!(
+            "SELECT build_archive_hash, build_image, build_command, build_source_link
+            FROM smart_contract_modules
+            WHERE module_reference = $1",
+            module_reference
+        )
+        .fetch_optional(&state.pool)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+        let (Some(archive_hash), Some(image), Some(build_command)) =
+            (row.build_archive_hash, row.build_image, row.build_command)
+        else {
+            return Err(ApiError::MissingBuildInfo);
+        };
+        let build_info = ModuleBuildInfo {
+            archive_hash,
+            image,
+            build_command,
+            source_link: row.build_source_link,
+        };
+        let source_archive = hex::decode(&body.source_archive)
+            .map_err(|_| ApiError::from(VerificationError::InvalidArchive))?;
+        let verified = verify_source(
+            state.module_builder.as_deref(),
+            &state.config.module_verification_attesters,
+            &module_reference,
+            &build_info,
+            &source_archive,
+            body.attestation,
+        )
+        .await?;
+
+        let (paths, contents): (Vec<String>, Vec<String>) =
+            verified.files.into_iter().map(|file| (file.path, file.content)).unzip();
+        let mut tx = state.pool.begin().await?;
+        sqlx::query!(
+            "DELETE FROM module_source_verifications WHERE module_reference = $1",
+            module_reference
+        )
+        .execute(tx.as_mut())
+        .await?;
+        sqlx::query!(
+            "INSERT INTO module_source_verifications (
+                module_reference,
+                method,
+                verified_at,
+                attester_key,
+                attestation_signature
+            ) VALUES ($1, $2, NOW(), $3, $4)",
+            module_reference,
+            verified.method as ModuleVerificationMethod,
+            verified
+                .attestation
+                .as_ref()
+                .map(|attestation| attestation.attester_key.to_lowercase()),
+            verified.attestation.as_ref().map(|attestation| attestation.signature.to_lowercase())
+        )
+        .execute(tx.as_mut())
+        .await?;
+        sqlx::query!(
+            "INSERT INTO module_source_files (module_reference, path, content)
+            SELECT $1, * FROM UNNEST($2::TEXT[], $3::TEXT[])",
+            module_reference,
+            &paths,
+            &contents
+        )
+        .execute(tx.as_mut())
+        .await?;
+        tx.commit().await?;
+        Ok(StatusCode::NO_CONTENT)
+    }
+}
+
+/// Check the bearer token of a request against the configured token, where
+/// requests are rejected as not found when no token is configured.
+fn authorize(headers: &HeaderMap, token: Option<&str>) -> ApiResult<()> {
+    let Some(token) = token else {
+        return Err(ApiError::NotFound);
+    };
+    let authorized = headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|provided| bool::from(provided.as_bytes().ct_eq(token.as_bytes())));
+    if !authorized {
+        return Err(ApiError::Unauthorized);
+    }
+    Ok(())
+}
+
+/// Check that an uploaded schema is a valid schema for the uploaded module,
+/// and that the module is the one referenced. Returns the module reference and
+/// the parsed schema.
+fn check_schema_upload(
+    module_reference: &str,
+    upload: &UploadModuleSchema,
+) -> ApiResult<(String, VersionedModuleSchema)> {
+    let module_source = hex::decode(&upload.module_source).map_err(|_| ApiError::InvalidModule)?;
+    let module: WasmModule = common::from_bytes(&mut std::io::Cursor::new(&module_source))
+        .map_err(|_| ApiError::InvalidModule)?;
+    let verified_module_reference = module.get_module_ref().to_string();
+    if verified_module_reference != module_reference.to_lowercase() {
+        return Err(ApiError::ModuleReferenceMismatch);
+    }
+    let schema = hex::decode(&upload.schema)
+        .ok()
+        .and_then(|schema| VersionedModuleSchema::new(&schema, &None).ok())
+        .ok_or(ApiError::InvalidSchema)?;
+    let contract_names = module_contract_names(&module).ok_or(ApiError::InvalidModule)?;
+    if let Some(unknown) =
+        schema_contract_names(&schema).find(|name| !contract_names.contains(name.as_str()))
+    {
+        return Err(ApiError::UnknownSchemaContract(unknown.clone()));
+    }
+    Ok((verified_module_reference, schema))
+}
+
+/// The names of the contracts defined by a module, read from the init
+/// functions it exports. `None` if the module is not a valid smart contract
+/// module.
+fn module_contract_names(module: &WasmModule) -> Option<BTreeSet<String>> {
+    let skeleton = parse_skeleton(module.source.as_ref()).ok()?;
+    let module = match module.version {
+        WasmVersion::V0 => {
+            validate_module(ValidationConfig::V0, &v0::ConcordiumAllowedImports, &skeleton)
+        }
+        WasmVersion::V1 => validate_module(
+            ValidationConfig::V1,
+            &v1::ConcordiumAllowedImports {
+                support_upgrade: true,
+                enable_debug:    false,
+            },
+            &skeleton,
+        ),
+    }
+    .ok()?;
+    Some(
+        get_inits(&module)
+            .into_iter()
+            .filter_map(|name| name.as_ref().strip_prefix("init_"))
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+/// The names of the contracts described by a schema.
+fn schema_contract_names(schema: &VersionedModuleSchema) -> Box<dyn Iterator<Item = &String> + '_> {
+    match schema {
+        VersionedModuleSchema::V0(module) => Box::new(module.contracts.keys()),
+        VersionedModuleSchema::V1(module) => Box::new(module.contracts.keys()),
+        VersionedModuleSchema::V2(module) => Box::new(module.contracts.keys()),
+        VersionedModuleSchema::V3(module) => Box::new(module.contracts.keys()),
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VerifyModuleSource {
+    /// Hex encoded tar archive of the source, as produced by the verifiable
+    /// build.
+    source_archive: String,
+    /// Attestation of the source archive. The archive is built when not
+    /// provided.
+    attestation:    Option<Attestation>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UploadModuleSchema {
+    /// Hex encoded versioned Wasm module, as deployed on chain.
+    module_source: String,
+    /// Hex encoded versioned module schema.
+    schema:        String,
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -186,6 +535,35 @@ struct ExportAccountStatement {
     to_time:         Option<DateTime<Utc>>,
 }
 
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportPltAccountStatement {
+    account_address: AccountAddress,
+    token_id:        String,
+    from_time:       Option<DateTime<Utc>>,
+    to_time:         Option<DateTime<Utc>>,
+}
+
+/// The period of a statement export, ending now and spanning the most days
+/// allowed when not provided.
+fn export_period(
+    config: &ApiServiceConfig,
+    from_time: Option<DateTime<Utc>>,
+    to_time: Option<DateTime<Utc>>,
+) -> ApiResult<(DateTime<Utc>, DateTime<Utc>)> {
+    let to = to_time.unwrap_or_else(Utc::now);
+    let account_statements_export_max_days =
+        i64::try_from(config.export_statement_max_days).unwrap_or(32);
+    let from =
+        from_time.unwrap_or_else(|| to - TimeDelta::days(account_statements_export_max_days));
+    if to - from > TimeDelta::days(account_statements_export_max_days) {
+        return Err(ApiError::ExceedsMaxAllowedDaysForAccountStatementExport(
+            account_statements_export_max_days,
+        ));
+    }
+    Ok((from, to))
+}
+
 struct ExportAccountStatementEntry {
     timestamp:       DateTime<Utc>,
     amount:          i64,
@@ -240,10 +618,29 @@ enum ApiError {
     FailedDatabaseQuery(Arc<sqlx::Error>),
     #[error("Invalid integer: {0}")]
     InvalidInt(#[from] std::num::TryFromIntError),
+    #[error("Missing or invalid bearer token.")]
+    Unauthorized,
+    #[error("Module source is not a valid versioned Wasm module.")]
+    InvalidModule,
+    #[error("Module source does not match the module reference.")]
+    ModuleReferenceMismatch,
+    #[error("Schema is not a valid versioned module schema.")]
+    InvalidSchema,
+    #[error("Schema describes contract '{0}', which is not defined by the module.")]
+    UnknownSchemaContract(String),
+    #[error("The module already has an embedded schema.")]
+    EmbeddedSchema,
+    #[error("The module has no build information from a verifiable build.")]
+    MissingBuildInfo,
+    #[error("Verification failed: {0}")]
+    Verification(Arc<VerificationError>),
 }
 impl From<sqlx::Error> for ApiError {
     fn from(value: sqlx::Error) -> Self { ApiError::FailedDatabaseQuery(Arc::new(value)) }
 }
+impl From<VerificationError> for ApiError {
+    fn from(value: VerificationError) -> Self { ApiError::Verification(Arc::new(value)) }
+}
 
 type ApiResult<A> = Result<A, ApiError>;
 
@@ -254,6 +651,17 @@ impl IntoResponse for ApiError {
             ApiError::FailedDatabaseQuery(_) => StatusCode::INTERNAL_SERVER_ERROR,
             ApiError::InvalidInt(_) => StatusCode::INTERNAL_SERVER_ERROR,
             ApiError::NotFound => StatusCode::NOT_FOUND,
+            ApiError::Unauthorized => StatusCode::UNAUTHORIZED,
+            ApiError::InvalidModule
+            | ApiError::ModuleReferenceMismatch
+            | ApiError::InvalidSchema
+            | ApiError::UnknownSchemaContract(_) => StatusCode::BAD_REQUEST,
+            ApiError::EmbeddedSchema => StatusCode::CONFLICT,
+            ApiError::MissingBuildInfo => StatusCode::BAD_REQUEST,
+            ApiError::Verification(ref error) => match error.as_ref() {
+                VerificationError::BuildFailed(_) => StatusCode::UNPROCESSABLE_ENTITY,
+                _ => StatusCode::BAD_REQUEST,
+            },
         };
         (status, self.to_string()).into_response()
     }
@@ -365,3 +773,91 @@ mod monitor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use concordium_rust_sdk::base::contracts_common::schema::{ContractV3, ModuleV3};
+
+    /// A minimal V1 module defining the contract `counter`, with an init
+    /// function returning success.
+    fn counter_module() -> WasmModule {
+        let mut source = b"\0asm\x01\0\0\0".to_vec();
+        // Type section with the type of init functions, `(i64) -> i32`.
+        source.extend([1, 6, 1, 0x60, 1, 0x7e, 1, 0x7f]);
+        // Function section.
+        source.extend([3, 2, 1, 0]);
+        // Export section exporting `init_counter`.
+        source.extend([7, 16, 1, 12]);
+        source.extend(b"init_counter");
+        source.extend([0, 0]);
+        // Code section, returning zero.
+        source.extend([10, 6, 1, 4, 0, 0x41, 0, 0x0b]);
+        WasmModule {
+            version: WasmVersion::V1,
+            source:  source.into(),
+        }
+    }
+
+    fn schema_upload(module: &WasmModule, contract_name: &str) -> UploadModuleSchema {
+        let schema = VersionedModuleSchema::V3(ModuleV3 {
+            contracts: [(contract_name.to_string(), ContractV3::default())].into(),
+        });
+        UploadModuleSchema {
+            module_source: hex::encode(common::to_bytes(module)),
+            schema:        hex::encode(to_bytes(&schema)),
+        }
+    }
+
+    fn bearer(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers
+            .insert(axum::http::header::AUTHORIZATION, format!("Bearer {token}").parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_authorize() {
+        assert!(authorize(&bearer("secret"), Some("secret")).is_ok());
+        assert!(matches!(authorize(&bearer("wrong"), Some("secret")), Err(ApiError::Unauthorized)));
+        assert!(matches!(
+            authorize(&HeaderMap::new(), Some("secret")),
+            Err(ApiError::Unauthorized)
+        ));
+        // Uploads are disabled when no token is configured.
+        assert!(matches!(authorize(&bearer("secret"), None), Err(ApiError::NotFound)));
+    }
+
+    #[test]
+    fn test_check_schema_upload() {
+        let module = counter_module();
+        let module_reference = module.get_module_ref().to_string();
+        let (verified_module_reference, schema) = check_schema_upload(
+            &module_reference.to_uppercase(),
+            &schema_upload(&module, "counter"),
+        )
+        .unwrap();
+        assert_eq!(verified_module_reference, module_reference);
+        assert_eq!(schema_contract_names(&schema).collect::<Vec<_>>(), vec!["counter"]);
+    }
+
+    #[test]
+    fn test_check_schema_upload_module_mismatch() {
+        let module = counter_module();
+        let other_module_reference = "0".repeat(64);
+        assert!(matches!(
+            check_schema_upload(&other_module_reference, &schema_upload(&module, "counter")),
+            Err(ApiError::ModuleReferenceMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_check_schema_upload_unknown_contract() {
+        let module = counter_module();
+        let module_reference = module.get_module_ref().to_string();
+        assert!(matches!(
+            check_schema_upload(&module_reference, &schema_upload(&module, "token")),
+            Err(ApiError::UnknownSchemaContract(name)) if name == "token"
+        ));
+    }
+}
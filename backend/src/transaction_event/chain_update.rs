@@ -2,8 +2,9 @@ use crate::{
     address::AccountAddress,
     scalar_types::{DateTime, Decimal, UnsignedInt, UnsignedLong},
     transaction_event::protocol_level_tokens::InitializationParameters,
+    transaction_type::UpdateTransactionType,
 };
-use async_graphql::{SimpleObject, Union};
+use async_graphql::{Enum, SimpleObject, Union};
 use concordium_rust_sdk::{
     common::cbor,
     protocol_level_tokens::TokenModuleInitializationParameters,
@@ -35,6 +36,145 @@ pub struct ChainUpdateEnqueued {
     pub payload:        ChainUpdatePayload,
 }
 
+/// The state of a chain update which got enqueued by an update instruction.
+#[derive(Debug, Enum, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "chain_update_state")]
+pub enum ChainUpdateState {
+    /// The update is waiting in the queue for its effective time.
+    Queued,
+    /// The effective time of the update has passed and the update is in force.
+    Effective,
+    /// The update got replaced by a later update of the same type.
+    Superseded,
+}
+
+/// The level of a set of governance keys authorizing chain updates.
+#[derive(Debug, Enum, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "governance_key_level")]
+pub enum GovernanceKeyLevel {
+    /// Root keys, authorizing updates of every governance key set.
+    Root,
+    /// Level 1 keys, authorizing updates of the level 1 and level 2 keys.
+    Level1,
+    /// Level 2 keys, authorizing updates of the chain parameters.
+    Level2,
+}
+
+impl GovernanceKeyLevel {
+    /// The types of chain updates which can be authorized by key sets at this
+    /// level. Updates of the level 1 and level 2 keys can be authorized by
+    /// both the root and the level 1 keys, which is told apart by
+    /// `authorized_payload_variant`.
+    pub fn authorized_update_types(self) -> Vec<UpdateTransactionType> {
+        match self {
+            GovernanceKeyLevel::Root => vec![
+                UpdateTransactionType::UpdateRootKeys,
+                UpdateTransactionType::UpdateLevel1Keys,
+                UpdateTransactionType::UpdateLevel2Keys,
+            ],
+            GovernanceKeyLevel::Level1 => vec![
+                UpdateTransactionType::UpdateLevel1Keys,
+                UpdateTransactionType::UpdateLevel2Keys,
+            ],
+            GovernanceKeyLevel::Level2 => Level2Authorization::ALL
+                .iter()
+                .flat_map(|authorization| authorization.authorized_update_types())
+                .copied()
+                .collect(),
+        }
+    }
+
+    /// The variant of `ChainUpdatePayload` of the updates authorized by key
+    /// sets at this level, given by whether the update is signed by the root
+    /// or the level 1 keys. None for level 2 keys, since these only authorize
+    /// updates which are not signed by other levels.
+    pub fn authorized_payload_variant(self) -> Option<&'static str> {
+        match self {
+            GovernanceKeyLevel::Root => Some("RootKeys"),
+            GovernanceKeyLevel::Level1 => Some("Level1Keys"),
+            GovernanceKeyLevel::Level2 => None,
+        }
+    }
+}
+
+/// The kinds of chain updates which each have their own access structure among
+/// the level 2 keys.
+#[derive(Debug, Enum, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "level2_authorization")]
+pub enum Level2Authorization {
+    Emergency,
+    Protocol,
+    ElectionDifficulty,
+    EuroPerEnergy,
+    MicroCcdPerEuro,
+    FoundationAccount,
+    MintDistribution,
+    TransactionFeeDistribution,
+    GasRewards,
+    PoolParameters,
+    AddAnonymityRevoker,
+    AddIdentityProvider,
+    CooldownParameters,
+    TimeParameters,
+    CreatePlt,
+}
+
+impl Level2Authorization {
+    const ALL: [Level2Authorization; 15] = [
+        Level2Authorization::Emergency,
+        Level2Authorization::Protocol,
+        Level2Authorization::ElectionDifficulty,
+        Level2Authorization::EuroPerEnergy,
+        Level2Authorization::MicroCcdPerEuro,
+        Level2Authorization::FoundationAccount,
+        Level2Authorization::MintDistribution,
+        Level2Authorization::TransactionFeeDistribution,
+        Level2Authorization::GasRewards,
+        Level2Authorization::PoolParameters,
+        Level2Authorization::AddAnonymityRevoker,
+        Level2Authorization::AddIdentityProvider,
+        Level2Authorization::CooldownParameters,
+        Level2Authorization::TimeParameters,
+        Level2Authorization::CreatePlt,
+    ];
+
+    /// The types of chain updates authorized by the access structure.
+    /// Parameters introduced in later protocol versions are authorized by the
+    /// access structure of the closest related parameter.
+    pub fn authorized_update_types(self) -> &'static [UpdateTransactionType] {
+        use UpdateTransactionType::*;
+        match self {
+            Level2Authorization::Emergency => &[],
+            Level2Authorization::Protocol => &[UpdateProtocol],
+            Level2Authorization::ElectionDifficulty => &[
+                UpdateElectionDifficulty,
+                TimeoutParametersUpdate,
+                MinBlockTimeUpdate,
+                BlockEnergyLimitUpdate,
+            ],
+            Level2Authorization::EuroPerEnergy => &[UpdateEuroPerEnergy],
+            Level2Authorization::MicroCcdPerEuro => &[UpdateMicroGtuPerEuro],
+            Level2Authorization::FoundationAccount => &[UpdateFoundationAccount],
+            Level2Authorization::MintDistribution => {
+                &[UpdateMintDistribution, MintDistributionCpv1Update]
+            }
+            Level2Authorization::TransactionFeeDistribution => &[UpdateTransactionFeeDistribution],
+            Level2Authorization::GasRewards => &[UpdateGasRewards, GasRewardsCpv2Update],
+            Level2Authorization::PoolParameters => &[
+                UpdateBakerStakeThreshold,
+                UpdatePoolParameters,
+                FinalizationCommitteeParametersUpdate,
+                ValidatorScoreParametersUpdate,
+            ],
+            Level2Authorization::AddAnonymityRevoker => &[UpdateAddAnonymityRevoker],
+            Level2Authorization::AddIdentityProvider => &[UpdateAddIdentityProvider],
+            Level2Authorization::CooldownParameters => &[UpdateCooldownParameters],
+            Level2Authorization::TimeParameters => &[UpdateTimeParameters],
+            Level2Authorization::CreatePlt => &[CreatePltUpdate],
+        }
+    }
+}
+
 #[derive(Union, serde::Serialize, serde::Deserialize)]
 pub enum ChainUpdatePayload {
     Protocol(ProtocolChainUpdatePayload),
@@ -450,3 +590,83 @@ impl From<ExchangeRate> for Ratio {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use concordium_rust_sdk::types::{Level1Update, RootUpdate};
+
+    /// The levels of the key sets listing the update as authorized, following
+    /// the filter used when querying the updates authorized by a key set.
+    fn authorizing_levels(payload: UpdatePayload) -> Vec<GovernanceKeyLevel> {
+        let update_type = UpdateTransactionType::from(&payload);
+        let payload = serde_json::to_value(ChainUpdatePayload::from(payload)).unwrap();
+        [GovernanceKeyLevel::Root, GovernanceKeyLevel::Level1, GovernanceKeyLevel::Level2]
+            .into_iter()
+            .filter(|level| {
+                level.authorized_update_types().contains(&update_type)
+                    && level
+                        .authorized_payload_variant()
+                        .is_none_or(|variant| payload.get(variant).is_some())
+            })
+            .collect()
+    }
+
+    fn access_structure() -> serde_json::Value {
+        serde_json::json!({"authorizedKeys": [], "threshold": 1})
+    }
+
+    fn higher_level_keys<Kind>() -> concordium_rust_sdk::types::HigherLevelAccessStructure<Kind> {
+        serde_json::from_value(serde_json::json!({"keys": [], "threshold": 1})).unwrap()
+    }
+
+    fn level2_keys() -> Box<concordium_rust_sdk::types::AuthorizationsV0> {
+        let mut authorizations = serde_json::json!({"keys": []});
+        for name in [
+            "emergency",
+            "protocol",
+            "electionDifficulty",
+            "euroPerEnergy",
+            "microGTUPerEuro",
+            "foundationAccount",
+            "mintDistribution",
+            "transactionFeeDistribution",
+            "paramGASRewards",
+            "poolParameters",
+            "addAnonymityRevoker",
+            "addIdentityProvider",
+        ] {
+            authorizations[name] = access_structure();
+        }
+        Box::new(serde_json::from_value(authorizations).unwrap())
+    }
+
+    #[test]
+    fn test_root_keys_update() {
+        let payload = UpdatePayload::Root(RootUpdate::RootKeysUpdate(higher_level_keys()));
+        assert_eq!(UpdateTransactionType::from(&payload), UpdateTransactionType::UpdateRootKeys);
+        assert_eq!(authorizing_levels(payload), vec![GovernanceKeyLevel::Root]);
+    }
+
+    #[test]
+    fn test_level1_keys_update() {
+        let payload = UpdatePayload::Root(RootUpdate::Level1KeysUpdate(higher_level_keys()));
+        assert_eq!(UpdateTransactionType::from(&payload), UpdateTransactionType::UpdateLevel1Keys);
+        assert_eq!(authorizing_levels(payload), vec![GovernanceKeyLevel::Root]);
+
+        let payload = UpdatePayload::Level1(Level1Update::Level1KeysUpdate(higher_level_keys()));
+        assert_eq!(UpdateTransactionType::from(&payload), UpdateTransactionType::UpdateLevel1Keys);
+        assert_eq!(authorizing_levels(payload), vec![GovernanceKeyLevel::Level1]);
+    }
+
+    #[test]
+    fn test_level2_keys_update() {
+        let payload = UpdatePayload::Root(RootUpdate::Level2KeysUpdate(level2_keys()));
+        assert_eq!(UpdateTransactionType::from(&payload), UpdateTransactionType::UpdateLevel2Keys);
+        assert_eq!(authorizing_levels(payload), vec![GovernanceKeyLevel::Root]);
+
+        let payload = UpdatePayload::Level1(Level1Update::Level2KeysUpdate(level2_keys()));
+        assert_eq!(UpdateTransactionType::from(&payload), UpdateTransactionType::UpdateLevel2Keys);
+        assert_eq!(authorizing_levels(payload), vec![GovernanceKeyLevel::Level1]);
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{
     address::Address as ScalarAddress,
     decoded_text::DecodedText,
-    graphql_api::{ApiResult, InternalError},
+    graphql_api::{ApiResult, Cis2BalanceChangeType, InternalError},
     scalar_types::{BigInteger, Byte, DateTime, UnsignedLong},
     transaction_event::{protocol_level_tokens::CreatePlt, transfers::TimestampedAmount},
 };
@@ -9,7 +9,8 @@ use anyhow::Context;
 use async_graphql::{ComplexObject, Object, SimpleObject, Union};
 use bigdecimal::BigDecimal;
 use concordium_rust_sdk::{
-    cis2, common::cbor, protocol_level_tokens::TokenModuleInitializationParameters, types::Address,
+    cis2, common::cbor, id::types::AccountAddress,
+    protocol_level_tokens::TokenModuleInitializationParameters, types::Address,
 };
 use tracing::error;
 
@@ -760,6 +761,45 @@ pub enum CisEvent {
     Unknown(CisUnknownEvent),
 }
 
+impl CisEvent {
+    /// The changes to the token balances of accounts caused by the event, in
+    /// the order they are applied. A transfer changes the balance of the sender
+    /// before the balance of the receiver.
+    pub fn account_balance_changes(
+        &self,
+    ) -> Vec<(AccountAddress, Cis2BalanceChangeType, BigDecimal)> {
+        let amount = |amount: &cis2::TokenAmount| BigDecimal::from_biguint(amount.0.clone(), 0);
+        let mut changes = Vec::new();
+        match self {
+            CisEvent::Mint(CisMintEvent {
+                amount: minted,
+                owner: Address::Account(owner),
+                ..
+            }) => changes.push((*owner, Cis2BalanceChangeType::Mint, amount(minted))),
+            CisEvent::Burn(CisBurnEvent {
+                amount: burned,
+                owner: Address::Account(owner),
+                ..
+            }) => changes.push((*owner, Cis2BalanceChangeType::Burn, -amount(burned))),
+            CisEvent::Transfer(CisTransferEvent {
+                amount: transferred,
+                from,
+                to,
+                ..
+            }) => {
+                if let Address::Account(from) = from {
+                    changes.push((*from, Cis2BalanceChangeType::TransferOut, -amount(transferred)));
+                }
+                if let Address::Account(to) = to {
+                    changes.push((*to, Cis2BalanceChangeType::TransferIn, amount(transferred)));
+                }
+            }
+            _ => (),
+        }
+        changes
+    }
+}
+
 impl From<cis2::Event> for CisEvent {
     fn from(event: cis2::Event) -> Self {
         match event {
@@ -1,4 +1,9 @@
-use crate::address::AccountAddress;
+use crate::{
+    address::AccountAddress,
+    graphql_api::{PltAccountStatementEntryType, PltTokenList},
+    indexer::EnsureAffectedRows,
+};
+use anyhow::Context;
 use async_graphql::{Enum, SimpleObject, Union};
 use bigdecimal::BigDecimal;
 use std::str::FromStr;
@@ -517,6 +522,166 @@ impl TokenUpdate {
     }
 }
 
+/// The changes to the balances of accounts caused by a token event, as the
+/// account address, the type of the statement entry and the signed change in
+/// the smallest unit of the token. A transfer changes the balance of the
+/// sender before the receiver.
+pub fn account_statement_changes(
+    event: &TokenEventDetails,
+) -> anyhow::Result<Vec<(String, PltAccountStatementEntryType, BigDecimal)>> {
+    let parse_amount = |amount: &TokenAmount| {
+        BigDecimal::from_str(&amount.value)
+            .map_err(|e| anyhow::anyhow!("Failed to parse token amount value: {}", e))
+    };
+    let changes = match event {
+        TokenEventDetails::Module(_) => Vec::new(),
+        TokenEventDetails::Mint(e) => vec![(
+            e.target.address.to_string(),
+            PltAccountStatementEntryType::Mint,
+            parse_amount(&e.amount)?,
+        )],
+        TokenEventDetails::Burn(e) => vec![(
+            e.target.address.to_string(),
+            PltAccountStatementEntryType::Burn,
+            -parse_amount(&e.amount)?,
+        )],
+        TokenEventDetails::Transfer(e) => {
+            let amount = parse_amount(&e.amount)?;
+            vec![
+                (e.from.address.to_string(), PltAccountStatementEntryType::TransferOut, -&amount),
+                (e.to.address.to_string(), PltAccountStatementEntryType::TransferIn, amount),
+            ]
+        }
+    };
+    Ok(changes)
+}
+
+/// The canonical address of an account, used for matching account aliases.
+pub fn canonical_address(account_address: &str) -> anyhow::Result<Vec<u8>> {
+    let account_address =
+        concordium_rust_sdk::base::contracts_common::AccountAddress::from_str(account_address)
+            .map_err(|_| {
+                anyhow::anyhow!(
+                    "Failed to convert string into account address type: {}",
+                    account_address
+                )
+            })?;
+    Ok(account_address.get_canonical_address().0.to_vec())
+}
+
+/// The account added to or removed from an allow or deny list by a token
+/// module event. The details hold the decoded event, `None` for other events.
+fn module_event_target(details: &serde_json::Value) -> Option<String> {
+    use protocol_level_tokens::{CborTokenHolder, TokenModuleEventType};
+    let event = TokenModuleEventType::deserialize(details).ok()?;
+    match event {
+        TokenModuleEventType::AddAllowList(list_update)
+        | TokenModuleEventType::RemoveAllowList(list_update)
+        | TokenModuleEventType::AddDenyList(list_update)
+        | TokenModuleEventType::RemoveDenyList(list_update) => match list_update.target {
+            CborTokenHolder::Account(account) => Some(account.address.to_string()),
+        },
+        _ => None,
+    }
+}
+
+/// Update the allow and deny lists and the pause state of a token from a token
+/// module event, recording the change in the history of the token.
+pub async fn save_token_module_state(
+    tx: &mut sqlx::PgTransaction<'_>,
+    token_index: i64,
+    module_type: TokenUpdateModuleType,
+    details: &serde_json::Value,
+    plt_event_id: i64,
+    transaction_index: i64,
+    slot_time: chrono::DateTime<chrono::Utc>,
+) -> anyhow::Result<()> {
+    let (list, added) = match module_type {
+        TokenUpdateModuleType::AddAllowList => (PltTokenList::Allow, true),
+        TokenUpdateModuleType::RemoveAllowList => (PltTokenList::Allow, false),
+        TokenUpdateModuleType::AddDenyList => (PltTokenList::Deny, true),
+        TokenUpdateModuleType::RemoveDenyList => (PltTokenList::Deny, false),
+        TokenUpdateModuleType::Pause | TokenUpdateModuleType::Unpause => {
+            let paused = module_type == TokenUpdateModuleType::Pause;
+            sqlx::query!("UPDATE plt_tokens SET paused = $2 WHERE index = $1", token_index, paused)
+                .execute(tx.as_mut())
+                .await?;
+            sqlx::query!(
+                "INSERT INTO plt_token_pause_changes (
+                    token_index,
+                    paused,
+                    plt_event_id,
+                    transaction_index,
+                    slot_time
+                ) VALUES ($1, $2, $3, $4, $5)",
+                token_index,
+                paused,
+                plt_event_id,
+                transaction_index,
+                slot_time
+            )
+            .execute(tx.as_mut())
+            .await?;
+            return Ok(());
+        }
+    };
+    let Some(address) = module_event_target(details) else {
+        tracing::warn!("Token module event {} without a target account", plt_event_id);
+        return Ok(());
+    };
+    let canonical_address = canonical_address(&address)?;
+    let account_index = sqlx::query_scalar!(
+        "SELECT index FROM accounts WHERE canonical_address = $1",
+        canonical_address.as_slice()
+    )
+    .fetch_one(tx.as_mut())
+    .await?;
+    if added {
+        sqlx::query!(
+            "INSERT INTO plt_token_list_members (token_index, list, account_index, plt_event_id)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT DO NOTHING",
+            token_index,
+            list as PltTokenList,
+            account_index,
+            plt_event_id
+        )
+        .execute(tx.as_mut())
+        .await?;
+    } else {
+        sqlx::query!(
+            "DELETE FROM plt_token_list_members
+            WHERE token_index = $1 AND list = $2 AND account_index = $3",
+            token_index,
+            list as PltTokenList,
+            account_index
+        )
+        .execute(tx.as_mut())
+        .await?;
+    }
+    sqlx::query!(
+        "INSERT INTO plt_token_list_changes (
+            token_index,
+            list,
+            account_index,
+            added,
+            plt_event_id,
+            transaction_index,
+            slot_time
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        token_index,
+        list as PltTokenList,
+        account_index,
+        added,
+        plt_event_id,
+        transaction_index,
+        slot_time
+    )
+    .execute(tx.as_mut())
+    .await?;
+    Ok(())
+}
+
 impl TryFrom<TokenUpdate> for PreparedTokenUpdate {
     type Error = anyhow::Error;
 
@@ -594,7 +759,7 @@ impl PreparedTokenUpdate {
         let token_event: serde_json::Value =
             serde_json::to_value(&self.event).unwrap_or(serde_json::Value::Null);
 
-        sqlx::query!(
+        let plt_event = sqlx::query!(
             "
             INSERT INTO plt_events (
                 id,
@@ -613,6 +778,7 @@ impl PreparedTokenUpdate {
                 $5
               
                 )
+            RETURNING id, token_index
             ",
             transaction_index,
             self.event_type as TokenUpdateEventType,
@@ -620,7 +786,7 @@ impl PreparedTokenUpdate {
             self.token_id,
             token_event
         )
-        .execute(tx.as_mut())
+        .fetch_one(tx.as_mut())
         .await?;
 
         // Update cumulative event count metrics (metrics_plt table)
@@ -767,12 +933,170 @@ impl PreparedTokenUpdate {
                     }
                 }
             }
-            TokenUpdateEventType::TokenModule => {}
+            TokenUpdateEventType::TokenModule => {
+                if let (Some(module_type), TokenEventDetails::Module(e)) =
+                    (self.token_module_type, &self.event)
+                {
+                    save_token_module_state(
+                        tx,
+                        plt_event.token_index,
+                        module_type,
+                        &e.details,
+                        plt_event.id,
+                        transaction_index,
+                        slot_time,
+                    )
+                    .await?;
+                }
+            }
         }
+        self.insert_account_statements(tx, plt_event.id, transaction_index, slot_time).await?;
+        self.update_metrics_plt_token(tx, plt_event.token_index, slot_time).await?;
 
         Ok(())
     }
 
+    /// Record the changes to the account balances in the PLT account
+    /// statements, after the balances got updated.
+    async fn insert_account_statements(
+        &self,
+        tx: &mut sqlx::PgTransaction<'_>,
+        plt_event_id: i64,
+        transaction_index: i64,
+        slot_time: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<()> {
+        let changes = account_statement_changes(&self.event)?
+            .into_iter()
+            .map(|(address, entry_type, amount)| {
+                Ok((canonical_address(&address)?, entry_type, amount))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        for (i, (canonical_address, entry_type, amount)) in changes.iter().enumerate() {
+            // The balance after an entry excludes the later changes of the event to the
+            // same account, such as the receiving side of a transfer to oneself.
+            let later_changes: BigDecimal = changes[i + 1..]
+                .iter()
+                .filter(|(later_address, ..)| later_address == canonical_address)
+                .map(|(.., later_amount)| later_amount)
+                .sum();
+            sqlx::query!(
+                "INSERT INTO plt_account_statements (
+                    account_index,
+                    token_index,
+                    entry_type,
+                    amount,
+                    account_balance,
+                    plt_event_id,
+                    transaction_index,
+                    slot_time
+                )
+                SELECT
+                    plt_accounts.account_index,
+                    plt_accounts.token_index,
+                    $3,
+                    $4,
+                    COALESCE(plt_accounts.amount, 0) - $5,
+                    $6,
+                    $7,
+                    $8
+                FROM plt_accounts
+                WHERE plt_accounts.account_index =
+                        (SELECT index FROM accounts WHERE canonical_address = $1::bytea)
+                    AND plt_accounts.token_index =
+                        (SELECT index FROM plt_tokens WHERE token_id = $2)",
+                canonical_address.as_slice(),
+                self.token_id,
+                *entry_type as PltAccountStatementEntryType,
+                amount,
+                later_changes,
+                plt_event_id,
+                transaction_index,
+                slot_time
+            )
+            .execute(tx.as_mut())
+            .await?
+            .ensure_affected_one_row()
+            .context("Failed to record the PLT account statement entry")?;
+        }
+        Ok(())
+    }
+
+    /// Record the cumulative minted and burned amounts and the number of
+    /// holders of the token after the block in metrics_plt_token, for the
+    /// events changing balances.
+    ///
+    /// The holder count is updated by the accounts which got a non-zero balance
+    /// or whose balance became zero, comparing the balances after the event
+    /// with the balances before the changes of the event.
+    async fn update_metrics_plt_token(
+        &self,
+        tx: &mut sqlx::PgTransaction<'_>,
+        token_index: i64,
+        slot_time: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<()> {
+        let mut changes: Vec<(Vec<u8>, BigDecimal)> = Vec::new();
+        for (address, _, amount) in account_statement_changes(&self.event)? {
+            let canonical_address = canonical_address(&address)?;
+            match changes.iter_mut().find(|(existing, _)| *existing == canonical_address) {
+                Some((_, total)) => *total += amount,
+                None => changes.push((canonical_address, amount)),
+            }
+        }
+        if changes.is_empty() {
+            return Ok(());
+        }
+        let (addresses, amounts): (Vec<_>, Vec<_>) = changes.into_iter().unzip();
+        let holder_change = sqlx::query_scalar!(
+            r#"SELECT COALESCE(SUM(
+                    (plt_accounts.amount > 0)::INT
+                        - (plt_accounts.amount - changes.amount > 0)::INT
+                ), 0)::BIGINT AS "holder_change!"
+            FROM UNNEST($1::BYTEA[], $2::NUMERIC[]) AS changes(canonical_address, amount)
+                JOIN accounts ON accounts.canonical_address = changes.canonical_address
+                JOIN plt_accounts ON plt_accounts.account_index = accounts.index
+                    AND plt_accounts.token_index = $3"#,
+            &addresses,
+            &amounts,
+            token_index
+        )
+        .fetch_one(tx.as_mut())
+        .await?;
+        // A later event of the token in the same block updates the row of the block,
+        // building on the holder count already recorded for it.
+        sqlx::query!(
+            "INSERT INTO metrics_plt_token (
+                event_timestamp,
+                token_index,
+                cumulative_minted,
+                cumulative_burned,
+                holder_count
+            )
+            SELECT
+                $1,
+                plt_tokens.index,
+                COALESCE(plt_tokens.total_minted, 0),
+                COALESCE(plt_tokens.total_burned, 0),
+                COALESCE((
+                    SELECT holder_count FROM metrics_plt_token
+                    WHERE token_index = plt_tokens.index
+                    ORDER BY event_timestamp DESC
+                    LIMIT 1
+                ), 0) + $3
+            FROM plt_tokens
+            WHERE plt_tokens.index = $2
+            ON CONFLICT (token_index, event_timestamp) DO UPDATE SET
+                cumulative_minted = EXCLUDED.cumulative_minted,
+                cumulative_burned = EXCLUDED.cumulative_burned,
+                holder_count = EXCLUDED.holder_count",
+            slot_time,
+            token_index,
+            holder_change
+        )
+        .execute(tx.as_mut())
+        .await?;
+        Ok(())
+    }
+
     async fn plt_amount_accross_tokens_by_account(
         &self,
         tx: &mut sqlx::PgTransaction<'_>,
@@ -1033,3 +1357,23 @@ impl PreparedTokenUpdate {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_module_event_target() {
+        let address = "3kBx2h5Y2veb4hZgAJWPrr8RyQESKm5TjzF3ti1QQ4VSYLwK1G";
+        let add_allow_list = serde_json::json!({
+            "addAllowList": {"target": {"type": "account", "address": address}}
+        });
+        assert_eq!(module_event_target(&add_allow_list), Some(address.to_string()));
+
+        // Addresses nested elsewhere in the details are not a target.
+        let pause = serde_json::json!({"pause": {}});
+        assert_eq!(module_event_target(&pause), None);
+        let unknown = serde_json::json!({"other": {"address": address}});
+        assert_eq!(module_event_target(&unknown), None);
+    }
+}
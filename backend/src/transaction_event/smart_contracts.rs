@@ -1,14 +1,11 @@
 use crate::{
     address::{Address, ContractAddress},
-    graphql_api::{get_pool, ApiError, ApiResult, InternalError},
+    graphql_api::{get_pool, get_schema_cache, ApiError, ApiResult, InternalError},
     scalar_types::Amount,
 };
 use async_graphql::{connection, ComplexObject, Context, Enum, SimpleObject};
 use concordium_rust_sdk::base::{
-    contracts_common::{
-        schema::{Type, VersionedModuleSchema},
-        Cursor,
-    },
+    contracts_common::{schema::Type, Cursor},
     smart_contracts::ReceiveName,
 };
 use serde::Serialize;
@@ -78,7 +75,8 @@ impl ContractInitialized {
             "
             SELECT
                 name as contract_name,
-                schema as display_schema
+                contracts.module_reference,
+                schema_uploaded_at
             FROM contracts
             JOIN smart_contract_modules ON smart_contract_modules.module_reference = \
              contracts.module_reference
@@ -92,17 +90,11 @@ impl ContractInitialized {
         .ok_or(ApiError::NotFound)?;
 
         // Get the init param schema if it exists.
-        let opt_init_param_schema = if let Some(schema) = row.display_schema.as_ref() {
-            let versioned_schema = VersionedModuleSchema::new(schema, &None).map_err(|_| {
-                InternalError::InternalError(
-                    "Database bytes should be a valid VersionedModuleSchema".to_string(),
-                )
-            })?;
-
+        let schema =
+            get_schema_cache(ctx)?.get(pool, &row.module_reference, row.schema_uploaded_at).await?;
+        let opt_init_param_schema = schema.and_then(|versioned_schema| {
             versioned_schema.get_init_param_schema(&row.contract_name).ok()
-        } else {
-            None
-        };
+        });
 
         let decoded_input_parameter = decode_value_with_schema(
             opt_init_param_schema.as_ref(),
@@ -135,7 +127,8 @@ impl ContractInitialized {
             "
             SELECT
                 name as contract_name,
-                schema as display_schema
+                contracts.module_reference,
+                schema_uploaded_at
             FROM contracts
             JOIN smart_contract_modules ON smart_contract_modules.module_reference = \
              contracts.module_reference
@@ -149,17 +142,11 @@ impl ContractInitialized {
         .ok_or(ApiError::NotFound)?;
 
         // Get the event schema if it exists.
-        let opt_event_schema = if let Some(schema) = row.display_schema.as_ref() {
-            let versioned_schema = VersionedModuleSchema::new(schema, &None).map_err(|_| {
-                InternalError::InternalError(
-                    "Database bytes should be a valid VersionedModuleSchema".to_string(),
-                )
-            })?;
-
+        let schema =
+            get_schema_cache(ctx)?.get(pool, &row.module_reference, row.schema_uploaded_at).await?;
+        let opt_event_schema = schema.and_then(|versioned_schema| {
             versioned_schema.get_event_schema(&row.contract_name).ok()
-        } else {
-            None
-        };
+        });
 
         let mut connection = connection::Connection::new(true, true);
 
@@ -205,7 +192,8 @@ impl ContractUpdated {
             "
             SELECT
                 name as contract_name,
-                schema as display_schema
+                contracts.module_reference,
+                schema_uploaded_at
             FROM contracts
             JOIN smart_contract_modules ON smart_contract_modules.module_reference = \
              contracts.module_reference
@@ -219,22 +207,16 @@ impl ContractUpdated {
         .ok_or(ApiError::NotFound)?;
 
         // Get the receive param schema if it exists.
-        let opt_receive_param_schema = if let Some(schema) = row.display_schema.as_ref() {
-            let versioned_schema = VersionedModuleSchema::new(schema, &None).map_err(|_| {
-                InternalError::InternalError(
-                    "Database bytes should be a valid VersionedModuleSchema".to_string(),
-                )
-            })?;
-
+        let schema =
+            get_schema_cache(ctx)?.get(pool, &row.module_reference, row.schema_uploaded_at).await?;
+        let opt_receive_param_schema = schema.and_then(|versioned_schema| {
             versioned_schema
                 .get_receive_param_schema(
                     &row.contract_name,
                     ReceiveName::new_unchecked(&self.receive_name).entrypoint_name().into(),
                 )
                 .ok()
-        } else {
-            None
-        };
+        });
 
         let decoded_input_parameter = decode_value_with_schema(
             opt_receive_param_schema.as_ref(),
@@ -267,7 +249,8 @@ impl ContractUpdated {
             "
             SELECT
                 name as contract_name,
-                schema as display_schema
+                contracts.module_reference,
+                schema_uploaded_at
             FROM contracts
             JOIN smart_contract_modules ON smart_contract_modules.module_reference = \
              contracts.module_reference
@@ -281,17 +264,11 @@ impl ContractUpdated {
         .ok_or(ApiError::NotFound)?;
 
         // Get the event schema if it exists.
-        let opt_event_schema = if let Some(schema) = row.display_schema.as_ref() {
-            let versioned_schema = VersionedModuleSchema::new(schema, &None).map_err(|_| {
-                InternalError::InternalError(
-                    "Database bytes should be a valid VersionedModuleSchema".to_string(),
-                )
-            })?;
-
+        let schema =
+            get_schema_cache(ctx)?.get(pool, &row.module_reference, row.schema_uploaded_at).await?;
+        let opt_event_schema = schema.and_then(|versioned_schema| {
             versioned_schema.get_event_schema(&row.contract_name).ok()
-        } else {
-            None
-        };
+        });
 
         let mut connection = connection::Connection::new(true, true);
 
@@ -348,7 +325,8 @@ impl ContractInterrupted {
             "
             SELECT
                 name as contract_name,
-                schema as display_schema
+                contracts.module_reference,
+                schema_uploaded_at
             FROM contracts
             JOIN smart_contract_modules ON smart_contract_modules.module_reference = \
              contracts.module_reference
@@ -362,17 +340,11 @@ impl ContractInterrupted {
         .ok_or(ApiError::NotFound)?;
 
         // Get the event schema if it exists.
-        let opt_event_schema = if let Some(schema) = row.display_schema.as_ref() {
-            let versioned_schema = VersionedModuleSchema::new(schema, &None).map_err(|_| {
-                InternalError::InternalError(
-                    "Database bytes should be a valid VersionedModuleSchema".to_string(),
-                )
-            })?;
-
+        let schema =
+            get_schema_cache(ctx)?.get(pool, &row.module_reference, row.schema_uploaded_at).await?;
+        let opt_event_schema = schema.and_then(|versioned_schema| {
             versioned_schema.get_event_schema(&row.contract_name).ok()
-        } else {
-            None
-        };
+        });
 
         let mut connection = connection::Connection::new(true, true);
 
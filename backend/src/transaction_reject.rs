@@ -1,17 +1,20 @@
 use crate::{
     address::{AccountAddress, Address, ContractAddress},
+    graphql_api::{get_pool, get_schema_cache, ApiResult, InternalError},
     scalar_types::{Amount, BakerId},
     transaction_event::protocol_level_tokens::TokenModuleRejectReasonType,
 };
 use anyhow::Context;
-use async_graphql::{Enum, SimpleObject, Union};
+use async_graphql::{ComplexObject, Enum, SimpleObject, Union};
 use concordium_rust_sdk::{
     base::{
-        contracts_common::schema::{VersionedModuleSchema, VersionedSchemaError},
+        contracts_common::schema::{Type, VersionedModuleSchema, VersionedSchemaError},
         smart_contracts::ReceiveName,
+        transactions::{BlockItem, EncodedPayload, Payload},
     },
     protocol_level_tokens::TokenModuleRejectReason,
 };
+use std::sync::Arc;
 
 #[derive(Union, Clone, serde::Serialize, serde::Deserialize)]
 pub enum TransactionRejectReason {
@@ -153,13 +156,85 @@ pub struct OutOfEnergy {
     dummy: bool,
 }
 
+/// Transaction initializing a smart contract instance was rejected.
 #[derive(SimpleObject, serde::Serialize, serde::Deserialize, Clone)]
+#[graphql(complex)]
 pub struct RejectedInit {
-    reject_reason: i32,
+    /// Reject reason code produced by the init function.
+    reject_reason:    i32,
+    #[graphql(skip)]
+    #[serde(default)]
+    module_reference: Option<String>,
+    #[graphql(skip)]
+    #[serde(default)]
+    contract_name:    Option<String>,
+    /// The HEX representation of the parameter provided to the init function.
+    /// Null for transactions indexed before the parameter was recorded.
+    #[serde(default)]
+    message_as_hex:   Option<String>,
+}
+
+#[ComplexObject]
+impl RejectedInit {
+    /// The JSON representation of the parameter provided to the init
+    /// function, decoded using the smart contract module schema.
+    async fn message(&self, ctx: &async_graphql::Context<'_>) -> ApiResult<Option<String>> {
+        Ok(self.decode_message(ctx).await?.0)
+    }
+
+    /// The status of parsing `message` into its JSON representation using the
+    /// smart contract module schema. Null for transactions indexed before the
+    /// parameter was recorded.
+    async fn message_parsing_status(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+    ) -> ApiResult<Option<InstanceMessageParsingStatus>> {
+        Ok(self.decode_message(ctx).await?.1)
+    }
+
+    /// The name of the error matching the reject reason code in the error
+    /// schema of the init function. Null if the module has no error schema
+    /// or the code does not match an error.
+    async fn decoded_reject_reason(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+    ) -> ApiResult<Option<String>> {
+        let (Some(module_reference), Some(contract_name)) =
+            (&self.module_reference, &self.contract_name)
+        else {
+            return Ok(None);
+        };
+        let Some(schema) = module_schema(ctx, module_reference).await? else {
+            return Ok(None);
+        };
+        Ok(schema
+            .get_init_error_schema(contract_name)
+            .ok()
+            .and_then(|error_schema| decode_reject_reason(&error_schema, self.reject_reason)))
+    }
+}
+
+impl RejectedInit {
+    async fn decode_message(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+    ) -> ApiResult<(Option<String>, Option<InstanceMessageParsingStatus>)> {
+        let (Some(module_reference), Some(contract_name), Some(message_as_hex)) =
+            (&self.module_reference, &self.contract_name, &self.message_as_hex)
+        else {
+            return Ok((None, None));
+        };
+        let schema = module_schema(ctx, module_reference).await?;
+        let (message, status) =
+            decode_message(schema.as_deref(), contract_name, None, message_as_hex)
+                .map_err(|err| InternalError::InternalError(err.to_string()))?;
+        Ok((message, Some(status)))
+    }
 }
 
 /// Transaction updating a smart contract instance was rejected.
 #[derive(SimpleObject, serde::Serialize, serde::Deserialize, Clone)]
+#[graphql(complex)]
 pub struct RejectedReceive {
     /// Reject reason code produced by the smart contract instance.
     reject_reason:          i32,
@@ -171,15 +246,165 @@ pub struct RejectedReceive {
     /// The HEX representation of the message provided for the smart contract
     /// instance as parameter.
     message_as_hex:         String,
+    /// The message decoded when indexing, which is decoded again when queried
+    /// to use schemas uploaded later.
+    #[graphql(skip)]
+    message:                Option<String>,
+    #[graphql(skip)]
+    message_parsing_status: InstanceMessageParsingStatus,
+    /// The module of the contract instance at the time of the transaction.
+    /// Missing for rejects indexed before the module got stored, in which case
+    /// the current module of the contract is used.
+    #[graphql(skip)]
+    #[serde(default)]
+    module_reference:       Option<String>,
+}
+
+#[ComplexObject]
+impl RejectedReceive {
     /// The JSON representation of the message provided for the smart contract
     /// instance as parameter. Decoded using the smart contract module
     /// schema if present otherwise undefined. Failing to parse the message
     /// will result in this being undefined and `message_parsing_status`
     /// representing the error.
-    message:                Option<String>,
+    async fn message(&self, ctx: &async_graphql::Context<'_>) -> ApiResult<Option<String>> {
+        Ok(self.decode_message(ctx).await?.0)
+    }
+
     /// The status of parsing `message` into its JSON representation using the
     /// smart contract module schema.
-    message_parsing_status: InstanceMessageParsingStatus,
+    async fn message_parsing_status(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+    ) -> ApiResult<InstanceMessageParsingStatus> {
+        Ok(self.decode_message(ctx).await?.1)
+    }
+
+    /// The name of the error matching the reject reason code in the error
+    /// schema of the entrypoint. Null if the module has no error schema or the
+    /// code does not match an error.
+    async fn decoded_reject_reason(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+    ) -> ApiResult<Option<String>> {
+        let Some(schema) = self.contract_module_schema(ctx).await? else {
+            return Ok(None);
+        };
+        let Ok(receive_name) = ReceiveName::new(&self.receive_name) else {
+            return Ok(None);
+        };
+        Ok(schema
+            .get_receive_error_schema(
+                receive_name.contract_name(),
+                receive_name.entrypoint_name().into(),
+            )
+            .ok()
+            .and_then(|error_schema| decode_reject_reason(&error_schema, self.reject_reason)))
+    }
+}
+
+impl RejectedReceive {
+    /// The schema of the module of the contract at the time of the
+    /// transaction.
+    async fn contract_module_schema(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+    ) -> ApiResult<Option<Arc<VersionedModuleSchema>>> {
+        if let Some(module_reference) = &self.module_reference {
+            return module_schema(ctx, module_reference).await;
+        }
+        let pool = get_pool(ctx)?;
+        let row = sqlx::query!(
+            "SELECT contracts.module_reference, schema_uploaded_at
+            FROM contracts
+                JOIN smart_contract_modules
+                    ON smart_contract_modules.module_reference = contracts.module_reference
+            WHERE index = $1 AND sub_index = $2",
+            self.contract_address.index.0 as i64,
+            self.contract_address.sub_index.0 as i64
+        )
+        .fetch_one(pool)
+        .await?;
+        get_schema_cache(ctx)?.get(pool, &row.module_reference, row.schema_uploaded_at).await
+    }
+
+    async fn decode_message(
+        &self,
+        ctx: &async_graphql::Context<'_>,
+    ) -> ApiResult<(Option<String>, InstanceMessageParsingStatus)> {
+        let schema = self.contract_module_schema(ctx).await?;
+        let receive_name = ReceiveName::new(&self.receive_name)
+            .map_err(|_| InternalError::InternalError("Invalid receive name".to_string()))?;
+        decode_message(
+            schema.as_deref(),
+            receive_name.contract_name(),
+            Some(receive_name.entrypoint_name().into()),
+            &self.message_as_hex,
+        )
+        .map_err(|err| InternalError::InternalError(err.to_string()).into())
+    }
+}
+
+/// The schema of a module, `None` if the module has no schema.
+async fn module_schema(
+    ctx: &async_graphql::Context<'_>,
+    module_reference: &str,
+) -> ApiResult<Option<Arc<VersionedModuleSchema>>> {
+    get_schema_cache(ctx)?.get_by_module(get_pool(ctx)?, module_reference).await
+}
+
+/// Decode the parameter of an init function, or a receive function when the
+/// entrypoint is provided, using the schema of the module.
+fn decode_message(
+    schema: Option<&VersionedModuleSchema>,
+    contract_name: &str,
+    entrypoint: Option<&str>,
+    message_as_hex: &str,
+) -> anyhow::Result<(Option<String>, InstanceMessageParsingStatus)> {
+    use InstanceMessageParsingStatus as Status;
+    if message_as_hex.is_empty() {
+        return Ok((None, Status::EmptyMessage));
+    }
+    let Some(schema) = schema else {
+        // No schema found in the smart contract module.
+        return Ok((None, Status::ModuleSchemaNotFound));
+    };
+    let schema_type = match entrypoint {
+        Some(entrypoint) => schema.get_receive_param_schema(contract_name, entrypoint),
+        None => schema.get_init_param_schema(contract_name),
+    };
+    let schema_type = match schema_type {
+        Ok(t) => t,
+        Err(VersionedSchemaError::NoContractInModule) => {
+            return Ok((None, Status::ContractNotFound))
+        }
+        Err(VersionedSchemaError::NoReceiveInContract | VersionedSchemaError::NoInitInContract) => {
+            return Ok((None, Status::FunctionNotFound))
+        }
+        Err(VersionedSchemaError::NoParamsInReceive | VersionedSchemaError::NoParamsInInit) => {
+            return Ok((None, Status::ParamNotFound))
+        }
+        // Schemas failing to parse are treated as missing.
+        Err(_) => return Ok((None, Status::ModuleSchemaNotFound)),
+    };
+    let message = hex::decode(message_as_hex).context("Failed hex decoding of message")?;
+    let Ok(message) = schema_type.to_json_string_pretty(&message) else {
+        return Ok((None, Status::Failed));
+    };
+    Ok((Some(message), Status::Success))
+}
+
+/// Decode a reject reason code using the error schema of a function. Contracts
+/// deriving `Reject` for an error enum reject with `-1` for the first variant,
+/// `-2` for the second and so on. The error value itself is not part of the
+/// transaction outcome, so only the name of the variant is decoded.
+fn decode_reject_reason(error_schema: &Type, reject_reason: i32) -> Option<String> {
+    let index = usize::try_from(-i64::from(reject_reason) - 1).ok()?;
+    match error_schema {
+        Type::Enum(variants) => variants.get(index).map(|(name, _)| name.clone()),
+        Type::TaggedEnum(variants) => variants.values().nth(index).map(|(name, _)| name.clone()),
+        _ => None,
+    }
 }
 
 /// The status of parsing `message` into its JSON representation using the
@@ -608,6 +833,7 @@ pub enum PreparedTransactionRejectReason {
 impl PreparedTransactionRejectReason {
     pub fn prepare(
         sdk_reject_reason: concordium_rust_sdk::types::RejectReason,
+        item: &BlockItem<EncodedPayload>,
     ) -> anyhow::Result<Self> {
         use concordium_rust_sdk::types::RejectReason;
         if let RejectReason::RejectedReceive {
@@ -687,9 +913,29 @@ impl PreparedTransactionRejectReason {
             }),
             RejectReason::RejectedInit {
                 reject_reason,
-            } => TransactionRejectReason::RejectedInit(RejectedInit {
-                reject_reason,
-            }),
+            } => {
+                // The module, contract and parameter are only part of the transaction.
+                let BlockItem::AccountTransaction(account_transaction) = item else {
+                    anyhow::bail!("Block item was expected to be an account transaction")
+                };
+                let Payload::InitContract {
+                    payload,
+                } = account_transaction
+                    .payload
+                    .decode()
+                    .context("Failed decoding account transaction payload")?
+                else {
+                    anyhow::bail!("Payload did not match InitContract as expected")
+                };
+                TransactionRejectReason::RejectedInit(RejectedInit {
+                    reject_reason,
+                    module_reference: Some(payload.mod_ref.to_string()),
+                    contract_name: Some(
+                        payload.init_name.as_contract_name().contract_name().to_string(),
+                    ),
+                    message_as_hex: Some(hex::encode(payload.param.as_ref())),
+                })
+            }
             RejectReason::InvalidProof => TransactionRejectReason::InvalidProof(InvalidProof {
                 dummy: true,
             }),
@@ -980,8 +1226,21 @@ impl PreparedRejectedReceive {
         &self,
         tx: &mut sqlx::PgTransaction<'_>,
     ) -> anyhow::Result<RejectedReceive> {
+        let module = sqlx::query!(
+            "SELECT
+                contracts.module_reference,
+                schema
+            FROM contracts
+                JOIN smart_contract_modules
+                    ON smart_contract_modules.module_reference = contracts.module_reference
+            WHERE index = $1 AND sub_index = $2",
+            i64::try_from(self.contract_address.index.0)?,
+            i64::try_from(self.contract_address.sub_index.0)?
+        )
+        .fetch_one(tx.as_mut())
+        .await?;
         // Handle and store errors
-        let (message, message_parsing_status) = self.process_message(tx).await?;
+        let (message, message_parsing_status) = self.process_message(module.schema.as_deref())?;
         Ok(RejectedReceive {
             reject_reason: self.reject_reason,
             contract_address: self.contract_address,
@@ -989,62 +1248,47 @@ impl PreparedRejectedReceive {
             message_as_hex: self.message_as_hex.clone(),
             message,
             message_parsing_status,
+            module_reference: Some(module.module_reference),
         })
     }
 
-    /// Parse the message using the smart contract module of the smart contract
-    /// instance.
-    async fn process_message(
+    /// Parse the message using the smart contract module schema of the smart
+    /// contract instance. Schemas failing to parse are treated as missing.
+    fn process_message(
         &self,
-        tx: &mut sqlx::PgTransaction<'_>,
+        schema: Option<&[u8]>,
     ) -> anyhow::Result<(Option<String>, InstanceMessageParsingStatus)> {
-        use InstanceMessageParsingStatus as Status;
         if self.message_as_hex.is_empty() {
-            return Ok((None, Status::EmptyMessage));
+            return Ok((None, InstanceMessageParsingStatus::EmptyMessage));
         }
-        let schema = sqlx::query_scalar!(
-            "SELECT
-                schema
-            FROM contracts
-                JOIN smart_contract_modules
-                    ON smart_contract_modules.module_reference = contracts.module_reference
-            WHERE index = $1 AND sub_index = $2",
-            i64::try_from(self.contract_address.index.0)?,
-            i64::try_from(self.contract_address.sub_index.0)?
-        )
-        .fetch_one(tx.as_mut())
-        .await?;
-        let Some(schema) = schema else {
-            // No schema found in the smart contract module.
-            return Ok((None, Status::ModuleSchemaNotFound));
-        };
-        let schema = VersionedModuleSchema::new(&schema, &None)
-            .context("Failed to parse smart contract module schema")?;
+        let schema = schema.and_then(|schema| VersionedModuleSchema::new(schema, &None).ok());
         let receive_name = ReceiveName::new(&self.receive_name)
             .context("Invalid receive name for RejectedReceive")?;
-        let schema_type = match schema.get_receive_param_schema(
+        decode_message(
+            schema.as_ref(),
             receive_name.contract_name(),
-            receive_name.entrypoint_name().into(),
-        ) {
-            Ok(t) => t,
-            Err(VersionedSchemaError::NoContractInModule) => {
-                return Ok((None, Status::ContractNotFound))
-            }
-            Err(VersionedSchemaError::NoReceiveInContract) => {
-                return Ok((None, Status::FunctionNotFound))
-            }
-            Err(VersionedSchemaError::NoParamsInReceive) => {
-                return Ok((None, Status::ParamNotFound))
-            }
-            Err(err) => {
-                anyhow::bail!("Database bytes should be a valid VersionedModuleSchema: {}", err);
-            }
-        };
-        let message = hex::decode(&self.message_as_hex)
-            .context("Failed hex decoding of RejectedReceive message")?;
-        let Ok(message) = schema_type.to_json_string_pretty(&message) else {
-            return Ok((None, Status::Failed));
-        };
-        Ok((Some(message), Status::Success))
+            Some(receive_name.entrypoint_name().into()),
+            &self.message_as_hex,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use concordium_rust_sdk::base::contracts_common::schema::Fields;
+
+    #[test]
+    fn test_decode_reject_reason() {
+        let error_schema = Type::Enum(vec![
+            ("ParseParams".to_string(), Fields::None),
+            ("InsufficientFunds".to_string(), Fields::None),
+        ]);
+        assert_eq!(decode_reject_reason(&error_schema, -1), Some("ParseParams".to_string()));
+        assert_eq!(decode_reject_reason(&error_schema, -2), Some("InsufficientFunds".to_string()));
+        // Codes outside the variants, such as the codes of the standard library.
+        assert_eq!(decode_reject_reason(&error_schema, -3), None);
+        assert_eq!(decode_reject_reason(&error_schema, i32::MIN + 1), None);
+        assert_eq!(decode_reject_reason(&error_schema, 1), None);
     }
 }
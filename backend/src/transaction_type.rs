@@ -179,3 +179,26 @@ impl From<concordium_rust_sdk::types::UpdateType> for UpdateTransactionType {
         }
     }
 }
+
+impl From<&concordium_rust_sdk::types::UpdatePayload> for UpdateTransactionType {
+    /// The type of update given by the payload. Unlike
+    /// `UpdatePayload::update_type`, which only gives the level of the keys
+    /// signing updates of the governance keys, this is the key set being
+    /// updated.
+    fn from(payload: &concordium_rust_sdk::types::UpdatePayload) -> Self {
+        use concordium_rust_sdk::types::{Level1Update, RootUpdate, UpdatePayload};
+        match payload {
+            UpdatePayload::Root(RootUpdate::RootKeysUpdate(_)) => {
+                UpdateTransactionType::UpdateRootKeys
+            }
+            UpdatePayload::Root(RootUpdate::Level1KeysUpdate(_))
+            | UpdatePayload::Level1(Level1Update::Level1KeysUpdate(_)) => {
+                UpdateTransactionType::UpdateLevel1Keys
+            }
+            UpdatePayload::Root(_) | UpdatePayload::Level1(_) => {
+                UpdateTransactionType::UpdateLevel2Keys
+            }
+            payload => UpdateTransactionType::from(payload.update_type()),
+        }
+    }
+}
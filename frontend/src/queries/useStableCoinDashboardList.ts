@@ -16,9 +16,6 @@ const STABLECOIN_DASHBOARD_LIST_QUERY = gql<StableCoinDashboardListResponse>`
 			symbol
 			totalSupply
 			circulatingSupply
-			metadata {
-				iconUrl
-			}
 			transactions {
 				from
 				to
@@ -28,11 +25,6 @@ const STABLECOIN_DASHBOARD_LIST_QUERY = gql<StableCoinDashboardListResponse>`
 				transactionHash
 				value
 			}
-			transfers {
-				assetName
-				dateTime
-				amount
-			}
 			holdings {
 				address
 				assetName
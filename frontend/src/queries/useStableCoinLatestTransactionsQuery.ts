@@ -1,8 +1,8 @@
 import { useQuery, gql } from '@urql/vue'
-import type { LatestTransactionResponse } from '~/types/generated'
+import type { StableCoinTransfer } from '~/types/generated'
 
 export type stableCoinLatestTransactionsResponse = {
-	latestTransactions: LatestTransactionResponse[]
+	latestTransactions: StableCoinTransfer[]
 }
 
 const STABLECOIN_LATEST_TRANSACTIONS = gql<stableCoinLatestTransactionsResponse>`
@@ -14,9 +14,6 @@ const STABLECOIN_LATEST_TRANSACTIONS = gql<stableCoinLatestTransactionsResponse>
 			transactionHash
 			amount
 			value
-			assetMetadata {
-				iconUrl
-			}
 		}
 	}
 `
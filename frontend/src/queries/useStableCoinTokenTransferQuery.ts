@@ -20,9 +20,6 @@ const STABLECOIN_TOKEN_TRANSFER = gql<StableCoinTokenTransferResponse>`
 			decimal
 			symbol
 			issuer
-			metadata {
-				iconUrl
-			}
 		}
 
 		transferSummary(assetName: $symbol, days: $days) {
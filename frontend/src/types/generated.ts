@@ -1511,6 +1511,7 @@ export type HoldingResponse = {
   __typename?: 'HoldingResponse';
   address: Scalars['String'];
   assetName: Scalars['String'];
+  /** Percentage of the total supply held by the account. */
   percentage: Scalars['Float'];
   quantity: Scalars['Float'];
 };
@@ -1675,18 +1676,6 @@ export type KeyIndexAlreadyInUse = {
 
 export type LatestChainParameters = ChainParametersV1;
 
-export type LatestTransactionResponse = {
-  __typename?: 'LatestTransactionResponse';
-  amount: Scalars['Float'];
-  assetMetadata?: Maybe<Metadata>;
-  assetName: Scalars['String'];
-  dateTime: Scalars['DateTime'];
-  from: Scalars['String'];
-  to: Scalars['String'];
-  transactionHash: Scalars['String'];
-  value: Scalars['Float'];
-};
-
 export type Level1KeysChainUpdatePayload = {
   __typename?: 'Level1KeysChainUpdatePayload';
   /** @deprecated Don't use! This field is only in the schema to make this a valid GraphQL type (which does not allow types without any fields) */
@@ -1718,11 +1707,6 @@ export type Memo = {
   bytes: Scalars['String'];
 };
 
-export type Metadata = {
-  __typename?: 'Metadata';
-  iconUrl: Scalars['String'];
-};
-
 export type MetadataUrl = {
   __typename?: 'MetadataUrl';
   additional?: Maybe<Scalars['JSON']>;
@@ -2475,7 +2459,8 @@ export type Query = {
   globalPltMetrics: GlobalPltMetrics;
   importState: ImportState;
   latestChainParameters: LatestChainParameters;
-  latestTransactions?: Maybe<Array<LatestTransactionResponse>>;
+  /** The latest transfers across the stablecoins. */
+  latestTransactions: Array<StableCoinTransfer>;
   moduleReferenceEvent: ModuleReferenceEvent;
   nodeStatus?: Maybe<NodeStatus>;
   nodeStatuses: NodeStatusConnection;
@@ -2507,6 +2492,7 @@ export type Query = {
   transactionByTransactionHash: Transaction;
   transactionMetrics: TransactionMetrics;
   transactions: TransactionConnection;
+  /** Daily transfers of a stablecoin over the last days, including today. */
   transferSummary: TransferSummaryResponse;
   versions: Versions;
 };
@@ -2770,6 +2756,7 @@ export type QueryTransactionsArgs = {
 
 export type QueryTransferSummaryArgs = {
   assetName: Scalars['String'];
+  /** Number of days to summarize, 7 by default. */
   days?: InputMaybe<Scalars['Int']>;
 };
 
@@ -3001,30 +2988,64 @@ export enum SpecialEventTypeFilter {
 
 export type StableCoin = {
   __typename?: 'StableCoin';
-  circulatingSupply: Scalars['Int'];
+  /** Circulating supply = Total supply - Tokens held by the issuer. */
+  circulatingSupply: Scalars['Float'];
   decimal: Scalars['Int'];
-  holdings?: Maybe<Array<HoldingResponse>>;
+  /** The accounts holding the most of the token. */
+  holdings: Array<HoldingResponse>;
   issuer: Scalars['String'];
-  metadata?: Maybe<Metadata>;
+  /** URL of the token metadata. */
+  metadataUrl?: Maybe<Scalars['String']>;
   name: Scalars['String'];
+  /** ISO 4217 code of the fiat currency the token is pegged to. */
+  peggedCurrency: Scalars['String'];
+  /** The token id of the PLT. */
   symbol: Scalars['String'];
-  totalSupply: Scalars['Int'];
-  totalUniqueHolders?: Maybe<Scalars['Int']>;
-  transactions?: Maybe<Array<TransactionMResponse>>;
-  transfers?: Maybe<Array<Transfer>>;
-  valueInDollar: Scalars['Float'];
+  /** Total supply = Minted tokens - Burned tokens. */
+  totalSupply: Scalars['Float'];
+  /** Number of accounts holding a non-zero amount of the token. */
+  totalUniqueHolders: Scalars['Int'];
+  /** The latest transfers of the token. */
+  transactions: Array<StableCoinTransfer>;
+  /** The value of one token in US dollars, `null` when unknown. */
+  valueInDollar?: Maybe<Scalars['Float']>;
 };
 
 export type StableCoinOverview = {
   __typename?: 'StableCoinOverview';
   noOfTxn: Scalars['Int'];
   noOfTxnLast24H: Scalars['Int'];
+  /** Number of unique addresses holding any of the stablecoins. */
   numberOfUniqueHolders: Scalars['Int'];
+  /**
+   * Total market cap = Total supply * Value in dollar, for the stablecoins
+   * with a known value in dollar.
+   */
   totalMarketcap: Scalars['Float'];
+  /**
+   * The value in dollar transferred, for the stablecoins with a known value
+   * in dollar.
+   */
   valuesTransferred: Scalars['Float'];
   valuesTransferredLast24H: Scalars['Float'];
 };
 
+/** A transfer of a stablecoin. */
+export type StableCoinTransfer = {
+  __typename?: 'StableCoinTransfer';
+  amount: Scalars['Float'];
+  assetName: Scalars['String'];
+  dateTime: Scalars['DateTime'];
+  from: Scalars['String'];
+  to: Scalars['String'];
+  transactionHash: Scalars['String'];
+  /**
+   * The value of the transferred amount in US dollars, `null` when
+   * unknown.
+   */
+  value?: Maybe<Scalars['Float']>;
+};
+
 export type StakeOverMaximumThresholdForPool = {
   __typename?: 'StakeOverMaximumThresholdForPool';
   /** @deprecated Don't use! This field is only in the schema to make this a valid GraphQL type (which does not allow types without any fields) */
@@ -3319,17 +3340,6 @@ export type TransactionFeeDistributionChainUpdatePayload = {
   gasAccount: Scalars['Decimal'];
 };
 
-export type TransactionMResponse = {
-  __typename?: 'TransactionMResponse';
-  amount: Scalars['Float'];
-  assetName: Scalars['String'];
-  dateTime: Scalars['DateTime'];
-  from: Scalars['String'];
-  to: Scalars['String'];
-  transactionHash: Scalars['String'];
-  value: Scalars['Float'];
-};
-
 export type TransactionMetrics = {
   __typename?: 'TransactionMetrics';
   buckets: TransactionMetricsBuckets;
@@ -3363,15 +3373,6 @@ export type TransactionResult = Rejected | Success;
 
 export type TransactionType = AccountTransaction | CredentialDeploymentTransaction | UpdateTransaction;
 
-export type Transfer = {
-  __typename?: 'Transfer';
-  amount: Scalars['Float'];
-  assetName: Scalars['String'];
-  dateTime: Scalars['DateTime'];
-  from: AccountAddress;
-  to: AccountAddress;
-};
-
 export type TransferMemo = {
   __typename?: 'TransferMemo';
   decoded: DecodedText;